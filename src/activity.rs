@@ -1,5 +1,6 @@
 use enum_iterator::IntoEnumIterator;
 use serde::{Deserialize, Serialize};
+use serenity::utils::Color;
 
 macro_rules! with_activity_types {
     ($macro:ident) => {
@@ -44,6 +45,21 @@ macro_rules! define_activity_types {
 with_activity_types! { define_activity_types }
 static_assertions::const_assert!(ActivityType::VARIANT_COUNT <= 25);
 
+impl ActivityType {
+    /// The color of this type's event embeds, unless the guild picks its own.
+    pub fn color(&self) -> Color {
+        match self {
+            ActivityType::Raid => Color::DARK_GOLD,
+            ActivityType::Dungeon => Color::PURPLE,
+            ActivityType::Crucible => Color::RED,
+            ActivityType::Gambit => Color::DARK_GREEN,
+            ActivityType::PvE => Color::BLUE,
+            ActivityType::Seasonal => Color::TEAL,
+            ActivityType::Custom => Color::ORANGE,
+        }
+    }
+}
+
 macro_rules! define_activities {
     ($($enum_name:ident: ($name:literal, $prefix:literal, $activity_type:ident, $group_size:literal)),+ $(,)?) => {
         /// All supported Destiny 2 activities.
@@ -127,6 +143,25 @@ define_activities! {
     Custom: ("Custom", "cust", Custom, 6),
 }
 
+impl Activity {
+    /// Description template for new events of this activity when the creator doesn't give one, if
+    /// it has one. "{activity}" is replaced with the activity's name. Guilds can set their own.
+    pub fn default_description(&self) -> Option<&'static str> {
+        match self.activity_type() {
+            ActivityType::Raid => Some("Full {activity} clear, all encounters."),
+            ActivityType::Dungeon => Some("Full {activity} run."),
+            _ => None,
+        }
+    }
+
+    /// The activity with the given ID prefix or name, ignoring case and surrounding whitespace.
+    pub fn activity_named(name: &str) -> Option<Activity> {
+        let name = name.trim();
+        Self::activity_with_id_prefix(name.to_ascii_lowercase())
+            .or_else(|| Self::into_enum_iter().find(|a| a.name().eq_ignore_ascii_case(name)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,6 +190,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn activity_named_matches_prefix_or_name() {
+        assert_eq!(
+            Activity::activity_named("VOG"),
+            Some(Activity::VaultOfGlass)
+        );
+        assert_eq!(
+            Activity::activity_named(" last wish "),
+            Some(Activity::LastWish)
+        );
+        assert_eq!(Activity::activity_named("Crota's End"), None);
+    }
+
     #[test]
     fn activity_prefixes_alphabetic() {
         Activity::into_enum_iter().for_each(|a| {
@@ -0,0 +1,59 @@
+use super::check_admin;
+use crate::util::*;
+use anyhow::Result;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    utils::MessageBuilder,
+};
+
+// Discord messages are limited to 2000 characters, so only list this many entries.
+const MAX_LISTED: usize = 20;
+
+define_leaf_command!(
+    AdminAuditLog,
+    "audit-log",
+    "Show recent commands that changed events",
+    admin_audit_log,
+    options: [],
+);
+
+#[command_attr::hook]
+async fn admin_audit_log(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    _options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let entries = event_manager.audit_log(MAX_LISTED).await;
+    if entries.is_empty() {
+        let content = "Nobody's changed any events yet. *Quiet around here...*";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line("Most recent event changes:");
+    for entry in entries {
+        builder
+            .push(format!("<t:{}:R> ", entry.at.timestamp()))
+            .mention(&entry.user)
+            .push(" used ")
+            .push_bold_safe(format!("/{}", entry.command.replace('.', " ")));
+        if let Some(event_id) = &entry.event_id {
+            builder.push(" on ").push_mono_safe(event_id);
+        }
+        builder.push("\n");
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use super::check_admin;
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    prelude::*,
+    utils::MessageBuilder,
+};
+
+define_command_option!(
+    id: ChannelOpt,
+    name: "channel",
+    description: "Event channel to show",
+    required: true,
+    option_type: OptionType::Channel,
+);
+
+define_leaf_command!(
+    AdminChannelEvents,
+    "channel-events",
+    "Show the events an event channel currently shows, in order",
+    admin_channel_events,
+    options: [ChannelOpt],
+);
+
+#[command_attr::hook]
+async fn admin_channel_events(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let channel_id = match options.get_resolved("channel")? {
+        Some(OptionValue::Channel(channel)) => Ok(channel.id),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required channel value")),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let events = match event_manager.channel_events(channel_id).await {
+        Some(events) => events,
+        None => {
+            let content = format!("{} isn't an event channel.", channel_id.mention());
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if events.is_empty() {
+        let content = format!("{} isn't showing any events.", channel_id.mention());
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line(format!(
+        "{} is showing {} events:",
+        channel_id.mention(),
+        events.len()
+    ));
+    for event in events {
+        builder.push_bold(event.id.to_string()).push_line(format!(
+            " | {} | {}",
+            event.activity,
+            event.timestamp()
+        ));
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use super::check_admin;
+use crate::util::*;
+use anyhow::Result;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    prelude::*,
+    utils::MessageBuilder,
+};
+
+define_leaf_command!(
+    AdminChannels,
+    "channels",
+    "Show the event channels and which events each one posts",
+    admin_channels,
+    options: [],
+);
+
+#[command_attr::hook]
+async fn admin_channels(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    _options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let channels = event_manager.event_channels().await;
+    if channels.is_empty() {
+        let content = "No event channels are configured.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line(format!("{} event channels:", channels.len()));
+    for channel in channels {
+        let mut settings = Vec::new();
+        if !channel.delete_user_messages {
+            settings.push("keeps user messages");
+        }
+        if channel.show_summary {
+            settings.push("summary");
+        }
+        if channel.create_threads {
+            settings.push("threads");
+        }
+        builder
+            .push(format!(
+                "{} ({}): ",
+                channel.channel_id.mention(),
+                channel.channel_id
+            ))
+            .push_bold(channel.filter_label);
+        if !settings.is_empty() {
+            builder.push(format!(" [{}]", settings.join(", ")));
+        }
+        if let Some(role) = channel.new_event_role {
+            builder.push(format!(" pings {} for new events", role.mention()));
+        }
+        builder.push_line("");
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
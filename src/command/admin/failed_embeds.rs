@@ -0,0 +1,104 @@
+use super::check_admin;
+use crate::util::*;
+use anyhow::Result;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    utils::MessageBuilder,
+};
+use tracing::error;
+
+// Discord messages are limited to 2000 characters, so only list this many failures.
+const MAX_LISTED: usize = 10;
+
+define_command_group!(
+    AdminFailedEmbeds,
+    "failed-embeds",
+    "Inspect and retry event embed updates that failed",
+    subcommands: [AdminFailedEmbedsList, AdminFailedEmbedsRetry]
+);
+
+define_leaf_command!(
+    AdminFailedEmbedsList,
+    "list",
+    "List event embed updates that failed",
+    admin_failed_embeds_list,
+    options: [],
+);
+
+define_leaf_command!(
+    AdminFailedEmbedsRetry,
+    "retry",
+    "Retry all event embed updates that failed",
+    admin_failed_embeds_retry,
+    options: [],
+);
+
+#[command_attr::hook]
+async fn admin_failed_embeds_list(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    _options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let ops = event_manager.failed_embed_ops().await;
+    if ops.is_empty() {
+        let content = "No failed embed updates. *Everything's running smoothly, for once.*";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line(format!("{} failed embed updates:", ops.len()));
+    // Show the most recent failures.
+    for op in ops.iter().rev().take(MAX_LISTED) {
+        let error = op.error.lines().next().unwrap_or_default();
+        builder
+            .push(format!("<t:{}:R> ", op.failed_at.timestamp()))
+            .push_bold(format!("{} {}", op.operation, op.event_id))
+            .push(": ")
+            .push_mono_line_safe(error);
+    }
+    if ops.len() > MAX_LISTED {
+        builder.push_italic_line(format!("...and {} more", ops.len() - MAX_LISTED));
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
+
+#[command_attr::hook]
+async fn admin_failed_embeds_retry(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    _options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = match event_manager.retry_failed_embed_ops().await {
+        Ok((0, 0)) => "Nothing to retry.".to_owned(),
+        Ok((succeeded, 0)) => format!("Retried {} failed embed updates successfully!", succeeded),
+        Ok((succeeded, failed)) => format!(
+            "Retried failed embed updates: {} succeeded, {} failed again.",
+            succeeded, failed
+        ),
+        Err(err) => {
+            error!("Failed to retry failed embed updates: {:?}", err);
+            "Sorry Captain, I seem to be having trouble retrying those...".to_owned()
+        }
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,150 @@
+use super::check_admin;
+use crate::{command::OptionType, event::parse_ics, util::*};
+use anyhow::{format_err, Context as _, Result};
+use chrono::Utc;
+use serenity::{
+    client::Context,
+    model::{
+        id::MessageId,
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+    },
+    utils::MessageBuilder,
+};
+use tracing::error;
+
+// Keeps a single import from flooding the event channels.
+const MAX_IMPORTED_EVENTS: usize = 25;
+
+define_command_option!(
+    id: MessageOpt,
+    name: "message",
+    description: "ID or link of a message in this channel with an .ics file attached",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_leaf_command!(
+    AdminImportIcs,
+    "import-ics",
+    "Create events from an iCalendar (.ics) file attached to a message",
+    admin_import_ics,
+    options: [MessageOpt],
+);
+
+#[command_attr::hook]
+async fn admin_import_ics(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let message = match options.get_resolved("message")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required message value")),
+    }?;
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction not in a guild"))?;
+
+    // Message links end with the message's ID.
+    let message_id = match message.trim().rsplit('/').next().unwrap_or("").parse() {
+        Ok(id) => MessageId(id),
+        Err(_) => {
+            let content = format!("'{}' isn't a message ID or link, Captain.", message);
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    let message = match interaction.channel_id.message(&ctx, message_id).await {
+        Ok(message) => message,
+        Err(_) => {
+            let content = "I couldn't find that message in this channel, Captain.";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    let attachment = match message
+        .attachments
+        .iter()
+        .find(|a| a.filename.to_ascii_lowercase().ends_with(".ics"))
+    {
+        Some(attachment) => attachment,
+        None => {
+            let content = "That message doesn't have an .ics file attached, Captain.";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let bytes = attachment
+        .download()
+        .await
+        .context("Failed to download .ics attachment")?;
+    let default_timezone = event_manager.user_timezone(interaction.user.id).await;
+    let parsed = String::from_utf8(bytes)
+        .map_err(|_| format_err!("The file isn't valid UTF-8"))
+        .and_then(|ics| parse_ics(&ics, default_timezone));
+    let parsed = match parsed {
+        Ok(parsed) => parsed,
+        Err(err) => {
+            let content = format!("I couldn't read that calendar, Captain: {:#}", err);
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let now = Utc::now();
+    let total = parsed.len();
+    let upcoming: Vec<_> = parsed.into_iter().filter(|e| e.datetime > now).collect();
+    let skipped = total - upcoming.len();
+    if upcoming.is_empty() {
+        let content = "That calendar doesn't have any upcoming events, Captain.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+    if upcoming.len() > MAX_IMPORTED_EVENTS {
+        let content = format!(
+            "That calendar has {} upcoming events, Captain. I can only import up to {} at once.",
+            upcoming.len(),
+            MAX_IMPORTED_EVENTS
+        );
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let events = match event_manager.import_events(member, upcoming).await {
+        Ok(events) => events,
+        Err(err) => {
+            error!("Failed to import events: {:?}", err);
+            let content = "Sorry Captain, I seem to be having trouble importing those events...";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line(format!("Imported {} events:", events.len()));
+    for event in &events {
+        builder.push_bold(event.id.to_string()).push_line(format!(
+            " | {} | {}",
+            event.activity,
+            event.timestamp()
+        ));
+    }
+    if skipped > 0 {
+        builder.push_line(format!("Skipped {} events that already happened.", skipped));
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
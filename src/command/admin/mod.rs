@@ -0,0 +1,50 @@
+use crate::util::*;
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context, model::interactions::application_command::ApplicationCommandInteraction,
+};
+
+mod audit_log;
+mod channel_events;
+mod channels;
+mod failed_embeds;
+mod import_ics;
+mod new_event_role;
+mod scheduler;
+
+define_command_group!(
+    Admin,
+    "admin",
+    "Server administration (admin only)",
+    subcommands: [
+        audit_log::AdminAuditLog,
+        channel_events::AdminChannelEvents,
+        channels::AdminChannels,
+        failed_embeds::AdminFailedEmbeds,
+        import_ics::AdminImportIcs,
+        new_event_role::AdminNewEventRole,
+        scheduler::AdminScheduler,
+    ]
+);
+
+/// Checks that the member issuing the command is an admin, responding to the interaction and
+/// returning false if they aren't.
+pub(super) async fn check_admin(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+) -> Result<bool> {
+    let perms = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+    if perms.administrator() {
+        return Ok(true);
+    }
+
+    let content = "Only an admin can do that, Captain.";
+    interaction.create_response(&ctx, content, true).await?;
+    Ok(false)
+}
@@ -0,0 +1,83 @@
+use super::check_admin;
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    prelude::*,
+};
+use tracing::error;
+
+define_command_option!(
+    id: ChannelOpt,
+    name: "channel",
+    description: "Event channel to set the role for",
+    required: true,
+    option_type: OptionType::Channel,
+);
+
+define_command_option!(
+    id: RoleOpt,
+    name: "role",
+    description: "Role to mention when new events are posted. Leave out to stop mentioning one.",
+    required: false,
+    option_type: OptionType::Role,
+);
+
+define_leaf_command!(
+    AdminNewEventRole,
+    "new-event-role",
+    "Set or clear the role an event channel mentions when it posts new events",
+    admin_new_event_role,
+    options: [ChannelOpt, RoleOpt],
+);
+
+#[command_attr::hook]
+async fn admin_new_event_role(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let channel_id = match options.get_resolved("channel")? {
+        Some(OptionValue::Channel(channel)) => Ok(channel.id),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required channel value")),
+    }?;
+    let role = match options.get_resolved("role")? {
+        Some(OptionValue::Role(role)) => Ok(Some(role.id)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(None),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = match event_manager.set_new_event_role(channel_id, role).await {
+        Ok(true) => match role {
+            Some(role) => format!(
+                "{} will mention {} when it posts new events.",
+                channel_id.mention(),
+                role.mention()
+            ),
+            None => format!(
+                "{} won't mention a role when it posts new events.",
+                channel_id.mention()
+            ),
+        },
+        Ok(false) => format!("{} isn't an event channel.", channel_id.mention()),
+        Err(err) => {
+            error!(
+                "Failed to set new event role for channel {}: {:?}",
+                channel_id, err
+            );
+            "Sorry Captain, I seem to be having trouble saving that...".to_owned()
+        }
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::check_admin;
+use crate::util::*;
+use anyhow::Result;
+use itertools::Itertools;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    utils::MessageBuilder,
+};
+use tracing::info;
+
+// Discord messages are limited to 2000 characters, so only list this many actions.
+const MAX_LISTED: usize = 20;
+
+define_leaf_command!(
+    AdminScheduler,
+    "scheduler",
+    "Show the event scheduler's pending actions",
+    admin_scheduler,
+    options: [],
+);
+
+#[command_attr::hook]
+async fn admin_scheduler(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    _options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let actions = event_manager.scheduled_actions().await;
+    // The full set goes to the log, since it may not fit in a message.
+    info!(
+        "Scheduled actions for guild {:?}: [{}]",
+        interaction.guild_id,
+        actions.iter().join(", ")
+    );
+    if actions.is_empty() {
+        let content = "No actions scheduled.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut builder = MessageBuilder::new();
+    builder.push_line(format!("{} scheduled actions:", actions.len()));
+    for action in actions.iter().take(MAX_LISTED) {
+        builder
+            .push(format!("<t:{}:R> ", action.action_datetime().timestamp()))
+            .push_bold_line(format!("{} {}", action.action, action.id));
+    }
+    if actions.len() > MAX_LISTED {
+        builder.push_italic_line(format!("...and {} more", actions.len() - MAX_LISTED));
+    }
+    interaction
+        .create_response(&ctx, builder.build(), true)
+        .await?;
+
+    Ok(())
+}
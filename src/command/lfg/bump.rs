@@ -0,0 +1,71 @@
+use super::{get_event_from_str, opts};
+use crate::{event::BumpError, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+
+define_leaf_command!(
+    LfgBump,
+    "bump",
+    "Repost an event at the bottom of its channel (creator or admin only)",
+    lfg_bump,
+    options: [opts::EventId],
+);
+
+#[command_attr::hook]
+async fn lfg_bump(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let event = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if member.user.id != event.creator.id && !perms.administrator() {
+        let content = "Only the event creator or an admin can bump an event";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let content = match event_manager.bump_event(event.id).await {
+        Ok(()) => format!(
+            "Bumped event **{}** to the bottom of its channel!",
+            event.id
+        ),
+        Err(BumpError::NotPosted) => {
+            format!("Event {} isn't posted in any event channels.", event.id)
+        }
+        Err(BumpError::CoolingDown(until)) => format!(
+            "Event **{}** was bumped recently, Captain. You can bump it again <t:{}:R>.",
+            event.id,
+            until.timestamp()
+        ),
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
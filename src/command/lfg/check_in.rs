@@ -0,0 +1,44 @@
+use super::edit_event_from_str;
+use crate::util::*;
+use anyhow::Result;
+use serenity::client::Context;
+use tracing::error;
+
+/// Handles the Check In button, which is only shown once an event's check-in window opens.
+pub async fn check_in(
+    ctx: &Context,
+    interaction: &impl InteractionExt,
+    event_id: impl AsRef<str>,
+    member: &dyn MemberLike,
+) -> Result<()> {
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let edit_result = edit_event_from_str(&event_manager, &event_id, |event| {
+        match event.check_in(member.id()) {
+            Ok(()) => format!(
+                "Checked you in for the {} event. See you there!",
+                event.activity
+            ),
+            Err(_) if event.checked_in.contains(&member.id()) => {
+                "You've already checked in, Guardian.".to_owned()
+            }
+            Err(_) if !event.check_in_open() => {
+                "Check-in for that event isn't open yet.".to_owned()
+            }
+            Err(_) => "Only members of the event's groups need to check in.".to_owned(),
+        }
+    })
+    .await;
+
+    // Unlike the other event buttons, respond even on success, since nothing else tells the member
+    // that they're checked in besides a small mark in the embed.
+    let content = match edit_result {
+        Ok(content) => content,
+        Err(err) => {
+            error!("Failed to check in to event: {:?}", err);
+            "Sorry Captain, I seem to be having trouble checking you in...".to_owned()
+        }
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
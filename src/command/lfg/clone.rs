@@ -0,0 +1,292 @@
+use super::{get_event_from_str, opts};
+use crate::{
+    command::OptionType,
+    event::{Event, EventEmbedMessage, EventManager, EventSettings},
+    util::*,
+};
+use anyhow::{format_err, Result};
+use chrono::DateTime;
+use chrono_tz::Tz;
+use serenity::{
+    client::Context,
+    http::CacheHttp,
+    model::{
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        prelude::*,
+    },
+};
+use std::sync::Arc;
+use tracing::error;
+
+define_command_option!(
+    id: KeepRosterOpt,
+    name: "keep-roster",
+    description: "Keep the roster and creator rather than starting fresh (creator or admin only)",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_command_option!(
+    id: DeleteOriginalOpt,
+    name: "delete-original",
+    description: "Delete the original event after cloning it with its roster (default false)",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_leaf_command!(
+    LfgClone,
+    "clone",
+    "Copy an event to a new time, with you as the creator and a fresh roster",
+    lfg_clone,
+    options: [opts::EventId, opts::time::Datetime, KeepRosterOpt, DeleteOriginalOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_clone(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let keep_roster = match options.get_resolved("keep-roster")? {
+        None => Ok(false),
+        Some(OptionValue::Boolean(keep)) => Ok(*keep),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let delete_original = match options.get_resolved("delete-original")? {
+        None => Ok(false),
+        Some(OptionValue::Boolean(delete)) => Ok(*delete),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    if delete_original && !keep_roster {
+        let content = "The original event can only be deleted if its roster is kept, Captain.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let original = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if keep_roster && member.user.id != original.creator.id && !perms.administrator() {
+        let content = "Only the event creator or an admin can clone an event with its roster";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let default_timezone = event_manager.user_timezone(interaction.user.id).await;
+    let datetime = match opts::time::parse_datetime_options(
+        options,
+        event_manager.date_format(),
+        event_manager.weekly_reset(),
+        default_timezone,
+    ) {
+        Ok(datetime) => datetime,
+        Err(err) => {
+            let content = match err.user_error() {
+                Some(descr) => descr,
+                None => {
+                    error!("Error parsing datetime options: {:?}", err);
+                    "Sorry Captain, something went wrong with my internal chronometers..."
+                        .to_owned()
+                }
+            };
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let clone = if keep_roster {
+        event_manager
+            .clone_event(&original.id, datetime, delete_original)
+            .await
+    } else {
+        clone_fresh(&event_manager, &original, member, datetime)
+            .await
+            .map(Some)
+    };
+    let clone = match clone {
+        Ok(Some(clone)) => clone,
+        Ok(None) => {
+            let content = format!("I couldn't find an event with ID '{}'", original.id);
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+        Err(err) => {
+            error!("Failed to clone event {}: {:?}", original.id, err);
+            let content = "Sorry Captain, I seem to be having trouble cloning that event...";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let mut content = format!(
+        "Event **{}** has been cloned to **{}**, Captain!",
+        original.id, clone.id
+    );
+    if delete_original {
+        content.push_str(&format!(" {} was deleted.", original.id));
+    }
+    interaction
+        .create_embed_response(
+            &ctx,
+            &content,
+            clone.as_embed(event_manager.embed_style()),
+            clone.event_buttons(event_manager.button_labels()),
+            true,
+        )
+        .await?;
+    event_manager
+        .keep_embed_updated(
+            clone.id,
+            EventEmbedMessage::EphemeralResponse(interaction.clone(), content),
+        )
+        .await?;
+    if !keep_roster {
+        return Ok(());
+    }
+
+    // Let everyone else that's signed up know where the event went.
+    let dm = format!(
+        "The {} event **{}** has a new time: you're now signed up for **{}** at {}.",
+        clone.activity,
+        original.id,
+        clone.id,
+        clone.timestamp()
+    );
+    let mut failed = Vec::new();
+    for (target, _) in clone.join_order() {
+        if target.id == member.user.id {
+            continue;
+        }
+        if let Err(err) = event_manager.dm_event(target.id, &clone, &dm).await {
+            error!(
+                "Failed to notify {} about clone of event {}: {:?}",
+                target.id, original.id, err
+            );
+            failed.push(target.id.mention().to_string());
+        }
+    }
+    if !failed.is_empty() {
+        let content = format!(
+            "I couldn't let {} know about the new time. *Maybe they have DMs turned off?*",
+            failed.join(", ")
+        );
+        interaction.create_followup(&ctx, content, true).await?;
+    }
+
+    Ok(())
+}
+
+/// Creates a new event at the given time with the original's activity, description, and group
+/// size, but none of its members, so that the given creator starts it over.
+async fn clone_fresh<C: CacheHttp>(
+    event_manager: &EventManager<C>,
+    original: &Event,
+    creator: &dyn MemberLike,
+    datetime: DateTime<Tz>,
+) -> Result<Arc<Event>> {
+    let settings = EventSettings {
+        group_size: Some(original.group_size),
+        ..Default::default()
+    };
+    event_manager
+        .create_event(
+            creator,
+            original.activity,
+            datetime,
+            original.description.clone(),
+            settings,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{activity::Activity, event::JoinKind};
+    use chrono::Utc;
+    use serenity::model::user::User;
+    use test_env_log::test;
+
+    #[test(tokio::test)]
+    async fn fresh_clone_resets_roster() {
+        let event_manager = EventManager::default().await;
+        let datetime = (Utc::now() + chrono::Duration::days(1)).with_timezone(&Tz::PST8PDT);
+        let settings = EventSettings {
+            group_size: Some(3),
+            ..Default::default()
+        };
+        let original = event_manager
+            .create_event(
+                &User::default(),
+                Activity::VaultOfGlass,
+                datetime,
+                "Sherpa run",
+                settings,
+            )
+            .await
+            .unwrap();
+        let mut joiner = User::default();
+        joiner.id = 2.into();
+        event_manager
+            .edit_event(&original.id, |event| {
+                event.unwrap().join(&joiner, JoinKind::Confirmed).unwrap();
+            })
+            .await
+            .unwrap();
+
+        let mut cloner = User::default();
+        cloner.id = 3.into();
+        let later = datetime + chrono::Duration::days(7);
+        let original = event_manager.get_event(&original.id).await.unwrap();
+        let clone = clone_fresh(&event_manager, &original, &cloner, later)
+            .await
+            .unwrap();
+        assert_ne!(clone.id, original.id);
+        assert_eq!(clone.activity, Activity::VaultOfGlass);
+        assert_eq!(clone.description, "Sherpa run");
+        assert_eq!(clone.group_size, 3);
+        assert_eq!(clone.datetime(), later);
+        assert_eq!(clone.creator.id, cloner.id);
+        assert_eq!(
+            clone
+                .join_order()
+                .iter()
+                .map(|(m, _)| m.id)
+                .collect::<Vec<_>>(),
+            vec![cloner.id]
+        );
+
+        // The original is left as it was.
+        assert_eq!(
+            event_manager
+                .get_event(&original.id)
+                .await
+                .unwrap()
+                .confirmed()
+                .len(),
+            2
+        );
+    }
+}
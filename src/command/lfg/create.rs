@@ -1,27 +1,69 @@
-use super::{ask_for_description, opts};
+use super::{
+    ask_for_description, opts,
+    session::{CommandSession, InteractionSession},
+};
 use crate::{
     activity::{Activity, ActivityType},
     command::OptionType,
-    event::EventEmbedMessage,
+    custom_id::CustomId,
+    event::{
+        normalize_tags, EventId, EventManager, EventSettings, Platform, RecurKind, MAX_GROUP_SIZE,
+    },
     util::*,
 };
 use anyhow::{format_err, Context as _, Result};
+use chrono::DateTime;
+use chrono_tz::Tz;
 use lazy_static::lazy_static;
 use paste::paste;
 use serenity::{
+    builder::{CreateActionRow, CreateButton, CreateComponents},
     client::Context,
-    model::interactions::application_command::{
-        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    http::CacheHttp,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        message_component::{ButtonStyle, MessageComponentInteraction},
     },
 };
+use std::{convert::TryFrom, str::FromStr, time::Duration};
 use tracing::{debug, error};
 
 define_command_option!(
     id: RecurOpt,
     name: "recur",
-    description: "Enable weekly recurrence for this event?",
+    description: "How often the event recurs",
     required: false,
-    option_type: OptionType::Boolean,
+    option_type: OptionType::String(opts::RECUR_CHOICES),
+);
+
+define_command_option!(
+    id: GroupSizeOpt,
+    name: "group-size",
+    description: "Number of guardians per group, if not the activity's usual",
+    required: false,
+    option_type: OptionType::Integer(opts::GROUP_SIZE_CHOICES),
+);
+
+define_command_option!(
+    id: MaxPlayersOpt,
+    name: "max-players",
+    description: "Most guardians that can join as confirmed, leave out for no limit",
+    required: false,
+    option_type: OptionType::Integer(&[]),
+);
+
+// Reply to the description prompt with this to use the activity's default description.
+const SKIP_DESCRIPTION: &str = "skip";
+
+// How long a previewed event waits to be confirmed before it's scrapped.
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+// Options for guild-configured shortcut commands, which create events for a single activity.
+define_command_option_group!(
+    id: CreateShortcutOptions,
+    options: [opts::time::Datetime, RecurOpt, opts::Platform, MaxPlayersOpt, opts::Tags, GroupSizeOpt],
 );
 
 // Macro to create the individual leaf commands for each ActivityType. An "activity" option is added
@@ -34,7 +76,7 @@ macro_rules! define_create_command {
                 $cmd,
                 concat!("Create a new ", $name, " event"),
                 lfg_create,
-                options: [ [<ActivityOpt $enum_name>], opts::time::Datetime, RecurOpt ],
+                options: [ [<ActivityOpt $enum_name>], opts::time::Datetime, RecurOpt, opts::Platform, MaxPlayersOpt, opts::Tags, GroupSizeOpt ],
             );
 
             define_command_option!(
@@ -61,7 +103,7 @@ macro_rules! define_create_command {
                 $cmd,
                 concat!("Create a new ", $name, " event"),
                 [<lfg_create_ $enum_name:lower>],
-                options: [ opts::time::Datetime, RecurOpt ],
+                options: [ opts::time::Datetime, RecurOpt, opts::Platform, MaxPlayersOpt, opts::Tags, GroupSizeOpt ],
             );
 
             // For ActivityTypes with a single Activity, create a command handler that just passes
@@ -115,7 +157,7 @@ async fn lfg_create(
     create(ctx, interaction, options, activity).await
 }
 
-async fn create(
+pub async fn create(
     ctx: &Context,
     interaction: &ApplicationCommandInteraction,
     options: &Vec<ApplicationCommandInteractionDataOption>,
@@ -127,13 +169,46 @@ async fn create(
         .ok_or_else(|| format_err!("Interaction not in a guild"))?;
 
     let recur = match options.get_resolved("recur")? {
-        None => Ok(false),
-        Some(OptionValue::Boolean(recur)) => Ok(*recur),
+        None => Ok(RecurKind::None),
+        Some(OptionValue::String(s)) => RecurKind::from_str(s),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let platform = match options.get_resolved("platform")? {
+        None => Ok(None),
+        Some(OptionValue::String(s)) => Platform::from_str(s).map(Some),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let max_players = match options.get_resolved("max-players")? {
+        None => Ok(None),
+        Some(OptionValue::Integer(max)) => u16::try_from(*max)
+            .map(Some)
+            .context("Invalid max players value"),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let group_size = match options.get_resolved("group-size")? {
+        None => Ok(None),
+        Some(OptionValue::Integer(size)) => u8::try_from(*size)
+            .ok()
+            .filter(|size| (1..=MAX_GROUP_SIZE).contains(size))
+            .map(Some)
+            .ok_or_else(|| format_err!("Invalid group size: {}", size)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let tags = match options.get_resolved("tags")? {
+        None => Ok(vec![]),
+        Some(OptionValue::String(s)) => Ok(normalize_tags(s)),
         Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
     }?;
 
     // Parse the datetime options.
-    let datetime = match opts::time::parse_datetime_options(options) {
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let default_timezone = event_manager.user_timezone(interaction.user.id).await;
+    let datetime = match opts::time::parse_datetime_options(
+        options,
+        event_manager.date_format(),
+        event_manager.weekly_reset(),
+        default_timezone,
+    ) {
         Ok(datetime) => datetime,
         Err(err) => {
             let content = match err.user_error() {
@@ -149,33 +224,68 @@ async fn create(
         }
     };
 
+    let session = CommandSession::new(ctx, interaction);
+    let settings = EventSettings {
+        recur,
+        platform,
+        max_players,
+        tags,
+        group_size,
+    };
+    create_with_session(
+        &session,
+        &event_manager,
+        member,
+        activity,
+        datetime,
+        settings,
+    )
+    .await
+}
+
+/// Asks for the new event's description, previews the event, and creates it once confirmed,
+/// responding through the session. This is the part of create that converses with the user, split
+/// out so that tests can drive it.
+async fn create_with_session<C: CacheHttp>(
+    session: &impl InteractionSession,
+    event_manager: &EventManager<C>,
+    member: &dyn MemberLike,
+    activity: Activity,
+    datetime: DateTime<Tz>,
+    settings: EventSettings,
+) -> Result<()> {
     // Ask for the event description in the main response.
-    let content = format!(
+    let mut content = format!(
         "What's so special about this... *uhhh, \"{}\"?*  ...event?\n\
                     **Reply with a description.** *(In simple terms, like for a Guardi...errr, nevermind...)*",
         activity
     );
-    let description = match ask_for_description(ctx, interaction, content).await? {
+    let default_description = event_manager.default_description(activity);
+    if let Some(default) = &default_description {
+        content.push_str(&format!(
+            "\nOr reply `{}` to go with: *{}*",
+            SKIP_DESCRIPTION, default
+        ));
+    }
+    let mut description = match ask_for_description(session, content).await? {
         Some(str) => str,
         None => return Ok(()),
     };
     debug!("Got event description: {:?}", description);
+    // An empty description makes create_event use the default.
+    if default_description.is_some() && description.trim().eq_ignore_ascii_case(SKIP_DESCRIPTION) {
+        description.clear();
+    }
 
-    // Create the event!
-    let event_manager = ctx.get_event_manager(interaction).await?;
-    let event = match event_manager
-        .create_event(member, activity, datetime, description, recur)
+    // Show the event as it'll be posted, and only create it once it's confirmed.
+    let (event, mut resolved) = match event_manager
+        .preview_event(member, activity, datetime, description, settings)
         .await
     {
-        Ok(event) => event,
+        Ok(preview) => preview,
         Err(err) => {
-            if let Err(edit_err) = interaction
-                .edit_response(
-                    &ctx,
-                    "Sorry Captain, I seem to be having trouble creating your event...",
-                )
-                .await
-            {
+            let content = "Sorry Captain, I seem to be having trouble creating your event...";
+            if let Err(edit_err) = session.edit_response(content.to_owned()).await {
                 error!(
                     "Failed to edit response to indicate an error: {:?}",
                     edit_err
@@ -184,19 +294,379 @@ async fn create(
             return Err(err.context("Failed to create event"));
         }
     };
+    let content = format!(
+        "Here's how your event will look, Captain. **Confirm** within {} minutes to post it.",
+        PREVIEW_TIMEOUT.as_secs() / 60
+    );
+    if let Err(err) = session
+        .edit_embed_response(
+            content,
+            event.as_embed(event_manager.embed_style()),
+            preview_buttons(event.id),
+        )
+        .await
+    {
+        // Without the preview, there's no way to confirm the event.
+        event_manager
+            .discard_pending_event(event.id, event.creator.id)
+            .await;
+        return Err(err.context("Failed to edit response with event preview"));
+    }
+
+    let created = match tokio::time::timeout(PREVIEW_TIMEOUT, &mut resolved).await {
+        Ok(created) => created,
+        Err(_) => {
+            if event_manager
+                .discard_pending_event(event.id, event.creator.id)
+                .await
+            {
+                let content =
+                    "You didn't confirm your event in time, Captain, so I've scrapped it.";
+                session
+                    .edit_embed_response(
+                        content.to_owned(),
+                        event.as_embed(event_manager.embed_style()),
+                        CreateComponents::default(),
+                    )
+                    .await?;
+                return Ok(());
+            }
+            // Confirmed or cancelled just as the preview timed out.
+            resolved.await
+        }
+    };
+    let event = match created {
+        Ok(Some(event)) => event,
+        Ok(None) => {
+            let content = "Alright Captain, I won't create that event.";
+            session
+                .edit_embed_response(
+                    content.to_owned(),
+                    event.as_embed(event_manager.embed_style()),
+                    CreateComponents::default(),
+                )
+                .await?;
+            return Ok(());
+        }
+        // Confirming failed, which the Confirm button's handler reports.
+        Err(_) => {
+            let content = "Sorry Captain, I seem to be having trouble creating your event...";
+            session.edit_response(content.to_owned()).await?;
+            return Ok(());
+        }
+    };
 
     let event_id = event.id;
     let content = format!("Your event **{}** has been created, Captain!", event_id);
-    interaction
-        .edit_embed_response(&ctx, &content, event.as_embed(), event.event_buttons())
+    session
+        .edit_embed_response(
+            content.clone(),
+            event.as_embed(event_manager.embed_style()),
+            event.event_buttons(event_manager.button_labels()),
+        )
         .await
         .context("Failed to edit response after creating event")?;
-    event_manager
-        .keep_embed_updated(
-            event_id,
-            EventEmbedMessage::EphemeralResponse(interaction.clone(), content),
-        )
-        .await?;
+    if let Some(msg) = session.embed_message(content) {
+        event_manager.keep_embed_updated(event_id, msg).await?;
+    }
+
+    Ok(())
+}
 
+fn preview_buttons(event_id: EventId) -> CreateComponents {
+    let mut row = CreateActionRow::default();
+    for (confirm, label, style) in [
+        (true, "Confirm", ButtonStyle::Success),
+        (false, "Cancel", ButtonStyle::Secondary),
+    ] {
+        let id = CustomId::PendingEvent { confirm, event_id };
+        let mut button = CreateButton::default();
+        button.label(label).style(style).custom_id(id.to_string());
+        row.add_button(button);
+    }
+    let mut components = CreateComponents::default();
+    components.add_action_row(row);
+    components
+}
+
+/// Handles the Confirm and Cancel buttons on an event preview. The preview's own flow updates the
+/// preview once it hears which was pressed.
+pub async fn resolve_preview(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    confirm: bool,
+    event_id: EventId,
+) -> Result<()> {
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let user = interaction.user.id;
+    let resolved = if confirm {
+        event_manager
+            .confirm_pending_event(event_id, user)
+            .await?
+            .is_some()
+    } else {
+        event_manager.discard_pending_event(event_id, user).await
+    };
+    if resolved {
+        interaction.create_ack_response(&ctx).await?;
+    } else {
+        let content = "That event preview has expired, Captain.";
+        interaction.create_response(&ctx, content, true).await?;
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::lfg::session::FakeSession;
+    use chrono::Utc;
+    use serenity::model::id::UserId;
+    use test_env_log::test;
+
+    /// Presses one of the preview's buttons a moment after it's shown, like its creator would.
+    async fn press_preview_button<C: CacheHttp>(
+        event_manager: &EventManager<C>,
+        event_id: &str,
+        user: UserId,
+        confirm: bool,
+    ) {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let event_id = EventId::from_str(event_id).unwrap();
+        if confirm {
+            let event = event_manager.confirm_pending_event(event_id, user).await;
+            assert!(event.unwrap().is_some());
+        } else {
+            assert!(event_manager.discard_pending_event(event_id, user).await);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn create_flow_creates_event() {
+        let event_manager = EventManager::default().await;
+        let mut session = FakeSession::with_replies(&["Flawless run, bring your A game"]);
+        session.user.id = UserId(7);
+        session.user.name = "creator".to_owned();
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let settings = EventSettings {
+            recur: RecurKind::Monthly,
+            max_players: Some(6),
+            tags: vec!["sherpa".to_owned()],
+            ..Default::default()
+        };
+
+        let user = session.user.clone();
+        let (created, _) = tokio::join!(
+            create_with_session(
+                &session,
+                &event_manager,
+                &user,
+                Activity::VaultOfGlass,
+                datetime,
+                settings,
+            ),
+            press_preview_button(&event_manager, "vog1", user.id, true),
+        );
+        created.unwrap();
+
+        let events = event_manager.list(None).await;
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.activity, Activity::VaultOfGlass);
+        assert_eq!(event.datetime, datetime);
+        assert_eq!(event.description, "Flawless run, bring your A game");
+        assert_eq!(event.creator.id, UserId(7));
+        assert_eq!(event.recur, RecurKind::Monthly);
+        assert_eq!(event.max_players, Some(6));
+        assert_eq!(event.tags, vec!["sherpa"]);
+
+        let sent = session.sent();
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        assert!(sent[0].starts_with("response: What's so special about this"));
+        assert!(sent[1].starts_with("edit with embed: Here's how your event will look"));
+        assert_eq!(
+            sent[2],
+            format!(
+                "edit with embed: Your event **{}** has been created, Captain!",
+                event.id
+            )
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn create_flow_skip_uses_default_description() {
+        let event_manager = EventManager::default().await;
+        let session = FakeSession::with_replies(&["Skip"]);
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let user = session.user.clone();
+        let (created, _) = tokio::join!(
+            create_with_session(
+                &session,
+                &event_manager,
+                &user,
+                Activity::VaultOfGlass,
+                datetime,
+                Default::default(),
+            ),
+            press_preview_button(&event_manager, "vog1", user.id, true),
+        );
+        created.unwrap();
+
+        let events = event_manager.list(None).await;
+        assert_eq!(
+            events[0].description,
+            event_manager
+                .default_description(Activity::VaultOfGlass)
+                .unwrap()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn create_flow_cancelled_creates_nothing() {
+        let event_manager = EventManager::default().await;
+        let session = FakeSession::with_replies(&["Never mind"]);
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let user = session.user.clone();
+        let (created, _) = tokio::join!(
+            create_with_session(
+                &session,
+                &event_manager,
+                &user,
+                Activity::VaultOfGlass,
+                datetime,
+                Default::default(),
+            ),
+            press_preview_button(&event_manager, "vog1", user.id, false),
+        );
+        created.unwrap();
+
+        assert!(event_manager.list(None).await.is_empty());
+        let sent = session.sent();
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        assert_eq!(
+            sent[2],
+            "edit with embed: Alright Captain, I won't create that event."
+        );
+
+        // The cancelled event's ID is free again.
+        let vog1 = EventId::from_str("vog1").unwrap();
+        assert_eq!(
+            event_manager.next_id(Activity::VaultOfGlass).await.unwrap(),
+            vog1
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn create_flow_unconfirmed_preview_times_out() {
+        let event_manager = EventManager::default().await;
+        let mut session = FakeSession::with_replies(&["Flawless run"]);
+        session.user.id = UserId(7);
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let user = session.user.clone();
+        create_with_session(
+            &session,
+            &event_manager,
+            &user,
+            Activity::VaultOfGlass,
+            datetime,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(event_manager.list(None).await.is_empty());
+        let sent = session.sent();
+        assert_eq!(sent.len(), 3, "{:?}", sent);
+        assert!(sent[2].starts_with("edit with embed: You didn't confirm your event in time"));
+
+        // Pressing Confirm on the stale preview does nothing.
+        let vog1 = EventId::from_str("vog1").unwrap();
+        let event = event_manager.confirm_pending_event(vog1, UserId(7)).await;
+        assert!(event.unwrap().is_none());
+        assert!(event_manager.list(None).await.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn failed_preview_discards_event() {
+        let event_manager = EventManager::default().await;
+        let mut session = FakeSession::with_replies(&["Flawless run"]);
+        session.user.id = UserId(7);
+        session.fail_embed_edits = true;
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let user = session.user.clone();
+        let result = create_with_session(
+            &session,
+            &event_manager,
+            &user,
+            Activity::VaultOfGlass,
+            datetime,
+            Default::default(),
+        )
+        .await;
+        assert!(result.is_err());
+
+        // The event can't be confirmed without its preview, and its ID is free again.
+        let vog1 = EventId::from_str("vog1").unwrap();
+        let event = event_manager.confirm_pending_event(vog1, UserId(7)).await;
+        assert!(event.unwrap().is_none());
+        assert_eq!(
+            event_manager.next_id(Activity::VaultOfGlass).await.unwrap(),
+            vog1
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn only_creator_can_confirm_preview() {
+        let event_manager = EventManager::default().await;
+        let mut creator = serenity::model::user::User::default();
+        creator.id = UserId(7);
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let (event, _resolved) = event_manager
+            .preview_event(
+                &creator,
+                Activity::VaultOfGlass,
+                datetime,
+                "",
+                Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let other = event_manager
+            .confirm_pending_event(event.id, UserId(8))
+            .await;
+        assert!(other.unwrap().is_none());
+        assert!(
+            !event_manager
+                .discard_pending_event(event.id, UserId(8))
+                .await
+        );
+        let created = event_manager
+            .confirm_pending_event(event.id, UserId(7))
+            .await;
+        assert_eq!(created.unwrap().unwrap().id, event.id);
+    }
+
+    #[test(tokio::test)]
+    async fn create_flow_without_reply_creates_nothing() {
+        let event_manager = EventManager::default().await;
+        let session = FakeSession::with_replies(&[]);
+        let datetime = (Utc::now() + chrono::Duration::days(2)).with_timezone(&Tz::PST8PDT);
+        let user = session.user.clone();
+        create_with_session(
+            &session,
+            &event_manager,
+            &user,
+            Activity::VaultOfGlass,
+            datetime,
+            Default::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(event_manager.list(None).await.is_empty());
+        let sent = session.sent();
+        assert_eq!(sent.len(), 2, "{:?}", sent);
+        assert!(sent[1].starts_with("followup: **Yoohoo, "));
+    }
+}
@@ -0,0 +1,135 @@
+use super::{
+    get_event_from_str, opts,
+    session::{CommandSession, InteractionSession},
+};
+use crate::{
+    event::{EventEmbedMessage, EventId, EventManager},
+    util::*,
+};
+use anyhow::{format_err, Context as _, Result};
+use serenity::{
+    client::Context,
+    http::CacheHttp,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+
+define_leaf_command!(
+    LfgDm,
+    "dm",
+    "DM yourself an existing event, with buttons to join or leave",
+    lfg_dm,
+    options: [opts::EventId],
+);
+
+#[command_attr::hook]
+async fn lfg_dm(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let session = CommandSession::new(ctx, interaction);
+    let (event_id, msg) = match dm_with_session(&session, &event_manager, event_id).await? {
+        Some(dm) => dm,
+        None => return Ok(()),
+    };
+    event_manager
+        .keep_embed_updated(event_id, msg)
+        .await
+        .context("Failed to keep DMed event updated")?;
+
+    let content = format!("Sent event **{}** to your DMs!", event_id);
+    session.create_response(content).await
+}
+
+/// DMs the session's user the event's embed and buttons, returning the event's ID along with the
+/// DM to keep updated as the event changes. If there's no such event, responds saying so and
+/// returns None.
+async fn dm_with_session<C: CacheHttp>(
+    session: &impl InteractionSession,
+    event_manager: &EventManager<C>,
+    event_id: &str,
+) -> Result<Option<(EventId, EventEmbedMessage)>> {
+    let event = match get_event_from_str(event_manager, event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            session.create_response(content).await?;
+            return Ok(None);
+        }
+    };
+
+    let content = format!("Here's event **{}**, Captain.", event.id);
+    let dm = session
+        .dm_embed(
+            content,
+            event.as_embed(event_manager.embed_style()),
+            event.dm_event_buttons(event_manager.button_labels(), event_manager.guild_id()),
+        )
+        .await;
+    match dm {
+        Ok(msg) => Ok(Some((event.id, msg))),
+        Err(err) => {
+            let content =
+                "I couldn't DM you, Captain. Do you have DMs from server members turned off?";
+            session.create_response(content.to_owned()).await?;
+            Err(err.context("Failed to DM event"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{activity::Activity, command::lfg::session::FakeSession};
+    use chrono::Utc;
+    use chrono_tz::Tz;
+    use serenity::model::{id::*, user::User};
+    use test_env_log::test;
+
+    #[test(tokio::test)]
+    async fn dm_flow_returns_dm_to_track() {
+        let event_manager = EventManager::default().await;
+        let datetime = (Utc::now() + chrono::Duration::days(1)).with_timezone(&Tz::PST8PDT);
+        let event = event_manager
+            .create_event(
+                &User::default(),
+                Activity::VaultOfGlass,
+                datetime,
+                "",
+                Default::default(),
+            )
+            .await
+            .unwrap();
+
+        let session = FakeSession::default();
+        let dm = dm_with_session(&session, &event_manager, "vog1")
+            .await
+            .unwrap();
+        assert_eq!(session.sent(), vec!["dm: Here's event **vog1**, Captain."]);
+        match dm {
+            Some((id, EventEmbedMessage::Dm(ChannelId(1), MessageId(1)))) => {
+                assert_eq!(id, event.id)
+            }
+            dm => panic!("Unexpected DM {:?}", dm),
+        }
+
+        // Nothing is DMed for events that don't exist.
+        let session = FakeSession::default();
+        let dm = dm_with_session(&session, &event_manager, "vog2")
+            .await
+            .unwrap();
+        assert_eq!(
+            session.sent(),
+            vec!["response: I couldn't find an event with ID 'vog2'"]
+        );
+        assert!(dm.is_none());
+    }
+}
@@ -1,29 +1,47 @@
 use super::{
     ask_for_description, edit_event_from_str, get_event_from_str,
     opts::{self},
+    session::CommandSession,
 };
 use crate::{
+    activity::Activity,
     command::{CommandHandler, OptionType},
-    event::Event,
+    event::{
+        normalize_tags, parse_group_labels, CarryRoster, Event, GroupRole, Platform, RecurKind,
+        RoleRequirements,
+    },
     util::*,
 };
 use anyhow::{format_err, Context as _, Error, Result};
-use chrono::DateTime;
+use chrono::{DateTime, Duration, Utc};
 use chrono_tz::Tz;
+use enum_iterator::IntoEnumIterator;
+use itertools::Itertools;
 use serenity::{
     client::Context,
     model::interactions::application_command::{
         ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
     },
 };
-use std::convert::TryFrom;
+use std::{convert::TryFrom, str::FromStr};
 use tracing::error;
 
 define_command_group!(LfgEdit, "edit", "Edit an existing event", subcommands: [
+    LfgEditActivity,
+    LfgEditCarryRoster,
     LfgEditDatetime,
+    LfgEditDatetimeRelative,
     LfgEditDescription,
+    super::extend::LfgEditExtend,
+    LfgEditGroupLabels,
     LfgEditGroupSize,
+    LfgEditLocked,
+    LfgEditNotifyCreator,
+    LfgEditPlatform,
     LfgEditRecur,
+    LfgEditRoles,
+    LfgEditTags,
+    LfgEditUnlisted,
 ]);
 
 macro_rules! define_edit_command {
@@ -42,6 +60,23 @@ macro_rules! define_edit_command {
     };
 }
 
+define_command_option!(
+    id: ActivityOpt,
+    name: "activity",
+    description: "Name or ID prefix of the new activity, e.g. \"Last Wish\" or \"lw\"",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+// The event keeps its ID, even though the ID's prefix then names the old activity. Reassigning it
+// would break every message, button, and command that already refers to the event by ID.
+define_edit_command!(
+    LfgEditActivity,
+    "activity",
+    "Change which activity an existing event is for",
+    lfg_edit,
+    options: [opts::EventId, ActivityOpt],
+);
+
 define_edit_command!(
     LfgEditDatetime,
     "datetime",
@@ -50,6 +85,28 @@ define_edit_command!(
     options: [opts::EventId, opts::time::Datetime],
 );
 
+define_command_option!(
+    id: DeltaHoursOpt,
+    name: "hours",
+    description: "Hours to move the event by, negative to move it earlier",
+    required: false,
+    option_type: OptionType::Integer(&[]),
+);
+define_command_option!(
+    id: DeltaMinutesOpt,
+    name: "minutes",
+    description: "Minutes to move the event by, negative to move it earlier",
+    required: false,
+    option_type: OptionType::Integer(&[]),
+);
+define_edit_command!(
+    LfgEditDatetimeRelative,
+    "datetime-relative",
+    "Move an existing event earlier or later",
+    lfg_edit,
+    options: [opts::EventId, DeltaHoursOpt, DeltaMinutesOpt],
+);
+
 define_edit_command!(
     LfgEditDescription,
     "description",
@@ -59,49 +116,180 @@ define_edit_command!(
 );
 
 define_command_option!(
-    id: GroupSizeOpt,
-    name: "group-size",
-    description: "Number of guardians per group",
-    required: true,
-    option_type: OptionType::Integer(&[("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5), ("6", 6), ("12", 12)]),
+    id: GroupLabelsOpt,
+    name: "labels",
+    description: "Comma-separated names for the groups, in order, e.g. \"Atheon team, Relic team\"",
+    required: false,
+    option_type: OptionType::String(&[]),
 );
+define_edit_command!(
+    LfgEditGroupLabels,
+    "group-labels",
+    "Name an existing event's groups, or leave the labels out to number them",
+    lfg_edit,
+    options: [opts::EventId, GroupLabelsOpt],
+);
+
 define_edit_command!(
     LfgEditGroupSize,
     "group-size",
     "Edit an existing event's group size",
     lfg_edit,
-    options: [opts::EventId, GroupSizeOpt],
+    options: [opts::EventId, opts::GroupSize],
+);
+
+define_command_option!(
+    id: LockedOpt,
+    name: "locked",
+    description: "Lock the roster so that no one else can join?",
+    required: true,
+    option_type: OptionType::Boolean,
+);
+define_edit_command!(
+    LfgEditLocked,
+    "locked",
+    "Lock/unlock the roster of an existing event",
+    lfg_edit,
+    options: [opts::EventId, LockedOpt],
+);
+
+define_command_option!(
+    id: NotifyCreatorOpt,
+    name: "notify-creator",
+    description: "DM the creator when someone joins or leaves?",
+    required: true,
+    option_type: OptionType::Boolean,
+);
+define_edit_command!(
+    LfgEditNotifyCreator,
+    "notify-creator",
+    "Enable/disable DMs to the creator when someone joins or leaves an existing event",
+    lfg_edit,
+    options: [opts::EventId, NotifyCreatorOpt],
+);
+
+define_command_option!(
+    id: UnlistedOpt,
+    name: "unlisted",
+    description: "Keep the event out of the event channels?",
+    required: true,
+    option_type: OptionType::Boolean,
+);
+define_edit_command!(
+    LfgEditUnlisted,
+    "unlisted",
+    "Hide/show an existing event in the event channels",
+    lfg_edit,
+    options: [opts::EventId, UnlistedOpt],
+);
+
+define_edit_command!(
+    LfgEditPlatform,
+    "platform",
+    "Edit an existing event's platform, or leave it out to clear it",
+    lfg_edit,
+    options: [opts::EventId, opts::Platform],
+);
+
+define_edit_command!(
+    LfgEditTags,
+    "tags",
+    "Edit an existing event's tags, or leave them out to clear them",
+    lfg_edit,
+    options: [opts::EventId, opts::Tags],
 );
 
 define_command_option!(
     id: RecurOpt,
     name: "recur",
-    description: "Enable weekly recurrence for this event?",
+    description: "How often the event recurs",
     required: true,
-    option_type: OptionType::Boolean,
+    option_type: OptionType::String(opts::RECUR_CHOICES),
 );
 define_edit_command!(
     LfgEditRecur,
     "recur",
-    "Enable/disable weekly recurrence for an existing event",
+    "Change how often an existing event recurs",
     lfg_edit,
     options: [opts::EventId, RecurOpt],
 );
 
+define_command_option!(
+    id: DpsCountOpt,
+    name: "dps",
+    description: "Number of DPS needed per group",
+    required: false,
+    option_type: OptionType::Integer(&[("0", 0), ("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5), ("6", 6)]),
+);
+define_command_option!(
+    id: AddClearCountOpt,
+    name: "adds",
+    description: "Number of add clear needed per group",
+    required: false,
+    option_type: OptionType::Integer(&[("0", 0), ("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5), ("6", 6)]),
+);
+define_command_option!(
+    id: SupportCountOpt,
+    name: "support",
+    description: "Number of support needed per group",
+    required: false,
+    option_type: OptionType::Integer(&[("0", 0), ("1", 1), ("2", 2), ("3", 3), ("4", 4), ("5", 5), ("6", 6)]),
+);
+define_edit_command!(
+    LfgEditRoles,
+    "roles",
+    "Edit the roles each group of an existing event needs",
+    lfg_edit,
+    options: [opts::EventId, DpsCountOpt, AddClearCountOpt, SupportCountOpt],
+);
+
+define_command_option!(
+    id: CarryRosterOpt,
+    name: "carry-roster",
+    description: "Who to sign up for the next recurrence automatically",
+    required: true,
+    option_type: OptionType::String(&[
+        ("Nobody", "nobody"),
+        ("Confirmed", "confirmed"),
+        ("Confirmed & Alternates", "all"),
+    ]),
+);
+define_edit_command!(
+    LfgEditCarryRoster,
+    "carry-roster",
+    "Edit who gets carried over when an existing event recurs",
+    lfg_edit,
+    options: [opts::EventId, CarryRosterOpt],
+);
+
 enum EditType {
+    // The activity, or the name given if it isn't one.
+    Activity(Result<Activity, String>),
     // TODO: This is a pretty gnarly type...find a way to improve the flow here.
     Datetime(Result<DateTime<Tz>, (String, Error)>),
+    DatetimeRelative(Duration),
     // Description is unique in that the value doesn't come from an option, but from a separate
     // query & response with the user.
     Description(Option<String>),
     GroupSize(u8),
-    Recur(bool),
+    GroupLabels(Vec<String>),
+    Recur(RecurKind),
+    Locked(bool),
+    NotifyCreator(bool),
+    Unlisted(bool),
+    Platform(Option<Platform>),
+    Tags(Vec<String>),
+    Roles(RoleRequirements),
+    CarryRoster(CarryRoster),
 }
 
 impl EditType {
     pub fn from_option(
         options: &Vec<ApplicationCommandInteractionDataOption>,
         option_name: &str,
+        date_format: DateFormat,
+        weekly_reset: WeeklyReset,
+        default_timezone: Option<Tz>,
     ) -> Result<Self> {
         match option_name {
             "description" => {
@@ -109,7 +297,12 @@ impl EditType {
             }
             "datetime" => {
                 // Parse the datetime options.
-                let datetime = match opts::time::parse_datetime_options(options) {
+                let datetime = match opts::time::parse_datetime_options(
+                    options,
+                    date_format,
+                    weekly_reset,
+                    default_timezone,
+                ) {
                     Ok(datetime) => Ok(datetime),
                     Err(err) => {
                         let content = match err.user_error() {
@@ -126,6 +319,68 @@ impl EditType {
 
                 return Ok(EditType::Datetime(datetime));
             }
+            "datetime-relative" => {
+                let mut delta = Duration::zero();
+                for (name, unit) in [
+                    ("hours", Duration::hours(1)),
+                    ("minutes", Duration::minutes(1)),
+                ] {
+                    match options.get_resolved(name)? {
+                        None => {}
+                        Some(OptionValue::Integer(count)) => {
+                            let count = i32::try_from(*count)
+                                .with_context(|| format!("Too many {}", name))?;
+                            delta = delta + unit * count;
+                        }
+                        Some(v) => return Err(format_err!("Unexpected value type: {:?}", v)),
+                    }
+                }
+                return Ok(EditType::DatetimeRelative(delta));
+            }
+            "roles" => {
+                // Each role has its own option, and omitted roles aren't required.
+                let mut requirements = RoleRequirements::new();
+                for role in GroupRole::into_enum_iter() {
+                    match options.get_resolved(role.option_value())? {
+                        None => {}
+                        Some(OptionValue::Integer(0)) => {}
+                        Some(OptionValue::Integer(count)) => {
+                            let count = u8::try_from(*count).context("Role count too large")?;
+                            requirements.insert(role, count);
+                        }
+                        Some(v) => return Err(format_err!("Unexpected value type: {:?}", v)),
+                    }
+                }
+                return Ok(EditType::Roles(requirements));
+            }
+            "platform" => {
+                // Leaving out the platform clears it.
+                return match options.get_resolved(option_name)? {
+                    None => Ok(EditType::Platform(None)),
+                    Some(OptionValue::String(s)) => {
+                        Ok(EditType::Platform(Some(Platform::from_str(s)?)))
+                    }
+                    Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+                };
+            }
+            "group-labels" => {
+                // Leaving out the labels goes back to numbered groups.
+                return match options.get_resolved("labels")? {
+                    None => Ok(EditType::GroupLabels(vec![])),
+                    Some(OptionValue::String(s)) => {
+                        Ok(EditType::GroupLabels(parse_group_labels(s)))
+                    }
+                    Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+                };
+            }
+            "tags" => {
+                // Leaving out the tags clears them.
+                return match options.get_resolved(option_name)? {
+                    None => Ok(EditType::Tags(vec![])),
+                    Some(OptionValue::String(s)) => Ok(EditType::Tags(normalize_tags(s))),
+                    Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+                };
+            }
             _ => {}
         }
 
@@ -141,7 +396,29 @@ impl EditType {
                 _ => Err(format_err!("Wrong {} value type", option_name)),
             },
             "recur" => match value {
-                OptionValue::Boolean(recur) => Ok(EditType::Recur(*recur)),
+                OptionValue::String(s) => Ok(EditType::Recur(RecurKind::from_str(s)?)),
+                _ => Err(format_err!("Wrong {} value type", option_name)),
+            },
+            "locked" => match value {
+                OptionValue::Boolean(locked) => Ok(EditType::Locked(*locked)),
+                _ => Err(format_err!("Wrong {} value type", option_name)),
+            },
+            "notify-creator" => match value {
+                OptionValue::Boolean(notify) => Ok(EditType::NotifyCreator(*notify)),
+                _ => Err(format_err!("Wrong {} value type", option_name)),
+            },
+            "unlisted" => match value {
+                OptionValue::Boolean(unlisted) => Ok(EditType::Unlisted(*unlisted)),
+                _ => Err(format_err!("Wrong {} value type", option_name)),
+            },
+            "carry-roster" => match value {
+                OptionValue::String(s) => Ok(EditType::CarryRoster(CarryRoster::from_str(s)?)),
+                _ => Err(format_err!("Wrong {} value type", option_name)),
+            },
+            "activity" => match value {
+                OptionValue::String(s) => Ok(EditType::Activity(
+                    Activity::activity_named(s).ok_or_else(|| s.clone()),
+                )),
                 _ => Err(format_err!("Wrong {} value type", option_name)),
             },
             _ => unreachable!("Unknown edit option name"),
@@ -150,10 +427,34 @@ impl EditType {
 
     pub fn apply_edit(self, event: &mut Event) -> String {
         match self {
+            EditType::Activity(Ok(activity)) => {
+                if activity == event.activity {
+                    return format!("Event **{}** is already for {}", event.id, activity);
+                }
+                // Event channels pick this up from the edit, moving the event to the new
+                // activity's channel.
+                event.activity = activity;
+                format!("Event **{}** is now for {}", event.id, activity)
+            }
+            EditType::Activity(Err(name)) => {
+                format!(
+                    "*Uhhh, Captain?* I don't know of an activity called '{}'...",
+                    name
+                )
+            }
             EditType::Datetime(Ok(datetime)) => {
                 event.set_datetime(datetime);
                 format!("Event **{}** updated to {}", event.id, event.timestamp())
             }
+            EditType::DatetimeRelative(delta) => {
+                match shift_datetime(event.datetime(), delta, Utc::now()) {
+                    Ok(datetime) => {
+                        event.set_datetime(datetime);
+                        format!("Event **{}** updated to {}", event.id, event.timestamp())
+                    }
+                    Err(content) => content,
+                }
+            }
             EditType::Description(Some(descr)) => {
                 event.description = descr;
                 format!("Event **{}** description updated", event.id)
@@ -162,20 +463,128 @@ impl EditType {
                 event.group_size = size;
                 format!("Event **{}** group size is now {}", event.id, size)
             }
+            EditType::GroupLabels(labels) => match event.set_group_labels(labels) {
+                Ok(()) if event.group_labels.is_empty() => {
+                    format!("Event **{}** groups are numbered again", event.id)
+                }
+                Ok(()) => format!(
+                    "Event **{}** groups are now {}",
+                    event.id,
+                    event.group_labels.join(", ")
+                ),
+                Err(err) => format!("*Uhhh, Captain?* {}...", err),
+            },
             EditType::Recur(recur) => {
                 event.recur = recur;
+                if recur.is_recurring() {
+                    format!("Event **{}** will now recur {}", event.id, recur)
+                } else {
+                    format!("Event **{}** will no longer recur", event.id)
+                }
+            }
+            EditType::Roles(requirements) => {
+                let total: usize = requirements.values().map(|c| *c as usize).sum();
+                if total > event.group_size as usize {
+                    return format!(
+                        "*Uhhh, Captain?* That's {} roles for a group size of {}...",
+                        total, event.group_size
+                    );
+                }
+                event.role_requirements = requirements;
+                if event.role_requirements.is_empty() {
+                    format!("Event **{}** no longer requires any roles", event.id)
+                } else {
+                    format!("Event **{}** role requirements updated", event.id)
+                }
+            }
+            EditType::Locked(locked) => {
+                event.set_locked(locked);
                 format!(
-                    "Event **{}** will {} recur weekly",
+                    "Event **{}** roster is now {}",
                     event.id,
-                    if recur { "now" } else { "no longer" }
+                    if locked { "locked" } else { "unlocked" }
                 )
             }
+            EditType::Platform(platform) => {
+                event.platform = platform;
+                match platform {
+                    Some(platform) => {
+                        format!("Event **{}** is now on {}", event.id, platform)
+                    }
+                    None => format!("Event **{}** no longer has a platform", event.id),
+                }
+            }
+            EditType::Tags(tags) => {
+                let content = if tags.is_empty() {
+                    format!("Event **{}** no longer has any tags", event.id)
+                } else {
+                    format!(
+                        "Event **{}** is now tagged {}",
+                        event.id,
+                        tags.iter().map(|t| format!("#{}", t)).join(" ")
+                    )
+                };
+                event.tags = tags;
+                content
+            }
+            EditType::NotifyCreator(notify) => {
+                event.notify_creator = notify;
+                format!(
+                    "I'll {} DM the creator of event **{}** when someone joins or leaves",
+                    if notify { "now" } else { "no longer" },
+                    event.id
+                )
+            }
+            EditType::Unlisted(unlisted) => {
+                event.unlisted = unlisted;
+                if unlisted {
+                    format!(
+                        "Event **{}** is now unlisted, it won't be shown in any channels",
+                        event.id
+                    )
+                } else {
+                    format!("Event **{}** will now be shown in the channels", event.id)
+                }
+            }
+            EditType::CarryRoster(carry) => {
+                event.carry_roster = carry;
+                let mut content = format!(
+                    "Event **{}** will carry over {} to its next recurrence",
+                    event.id, carry
+                );
+                if !event.recur.is_recurring() {
+                    content.push_str(", once it's set to recur");
+                }
+                content
+            }
             EditType::Datetime(Err(_)) => unreachable!("Tried to apply invalid datetime"),
             EditType::Description(None) => unreachable!("Tried to apply empty description"),
         }
     }
 }
 
+/// Moves an event's datetime by the given delta, which must be nonzero and must not move the event
+/// into the past. Returns the user-facing error message otherwise.
+fn shift_datetime(
+    datetime: DateTime<Tz>,
+    delta: Duration,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Tz>, String> {
+    if delta == Duration::zero() {
+        return Err(
+            "*You want me to move it by... nothing?* Give me some hours or minutes.".to_owned(),
+        );
+    }
+    let shifted = datetime + delta;
+    if shifted <= now {
+        return Err(format!(
+            "I can't do that, that would move the event to <t:{}:F>, which is in the past.",
+            shifted.timestamp()
+        ));
+    }
+    Ok(shifted)
+}
+
 #[command_attr::hook]
 async fn lfg_edit(
     ctx: &Context,
@@ -216,7 +625,14 @@ async fn lfg_edit(
         return Ok(());
     }
 
-    let mut edit = EditType::from_option(options, option_name)?;
+    let default_timezone = event_manager.user_timezone(interaction.user.id).await;
+    let mut edit = EditType::from_option(
+        options,
+        option_name,
+        event_manager.date_format(),
+        event_manager.weekly_reset(),
+        default_timezone,
+    )?;
     let mut response_created = false;
     match edit {
         EditType::Datetime(Err((content, err))) => {
@@ -226,7 +642,8 @@ async fn lfg_edit(
         EditType::Description(None) => {
             // Ask the user for a new event description.
             let content = "What's the new description? *And try to get it right this time...*";
-            match ask_for_description(ctx, interaction, content).await? {
+            let session = CommandSession::new(ctx, interaction);
+            match ask_for_description(&session, content).await? {
                 Some(str) => edit = EditType::Description(Some(str)),
                 None => return Ok(()),
             };
@@ -252,3 +669,54 @@ async fn lfg_edit(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventId;
+
+    fn datetime(rfc3339: &str) -> DateTime<Tz> {
+        DateTime::parse_from_rfc3339(rfc3339)
+            .unwrap()
+            .with_timezone(&Tz::America__New_York)
+    }
+
+    #[test]
+    fn activity_edit_keeps_event_id() {
+        let mut event = Event::default();
+        event.id = EventId::from_str("vog1").unwrap();
+        event.activity = Activity::VaultOfGlass;
+
+        let content = EditType::Activity(Ok(Activity::LastWish)).apply_edit(&mut event);
+        assert_eq!(content, "Event **vog1** is now for Last Wish");
+        assert_eq!(event.activity, Activity::LastWish);
+        assert_eq!(event.id, EventId::from_str("vog1").unwrap());
+
+        let content = EditType::Activity(Err("Crota".to_owned())).apply_edit(&mut event);
+        assert!(content.contains("'Crota'"), "{}", content);
+        assert_eq!(event.activity, Activity::LastWish);
+    }
+
+    #[test]
+    fn shift_datetime_later_and_earlier() {
+        let now = datetime("2021-04-20T12:00:00-04:00").with_timezone(&Utc);
+        let event = datetime("2021-04-20T20:00:00-04:00");
+
+        let later = shift_datetime(event, Duration::minutes(30), now);
+        assert_eq!(later, Ok(datetime("2021-04-20T20:30:00-04:00")));
+
+        let earlier = shift_datetime(event, Duration::hours(-2) + Duration::minutes(-15), now);
+        assert_eq!(earlier, Ok(datetime("2021-04-20T17:45:00-04:00")));
+    }
+
+    #[test]
+    fn shift_datetime_rejects_past() {
+        let now = datetime("2021-04-20T12:00:00-04:00").with_timezone(&Utc);
+        let event = datetime("2021-04-20T13:00:00-04:00");
+
+        assert!(shift_datetime(event, Duration::hours(-1), now).is_err());
+        assert!(shift_datetime(event, Duration::hours(-3), now).is_err());
+        assert!(shift_datetime(event, Duration::minutes(-59), now).is_ok());
+        assert!(shift_datetime(event, Duration::zero(), now).is_err());
+    }
+}
@@ -0,0 +1,98 @@
+use super::{get_event_from_str, opts};
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use chrono::Duration;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+use tracing::error;
+
+// Long enough for any activity that overruns, short enough that a typo doesn't keep an event
+// around for days.
+const MAX_EXTEND_MINUTES: i64 = 6 * 60;
+
+define_command_option!(
+    id: MinutesOpt,
+    name: "minutes",
+    description: "Minutes to push the event's cleanup back by",
+    required: true,
+    option_type: OptionType::Integer(&[]),
+);
+
+define_leaf_command!(
+    LfgEditExtend,
+    "extend",
+    "Keep an event around longer without changing its start time (creator or admin only)",
+    lfg_edit_extend,
+    options: [opts::EventId, MinutesOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_edit_extend(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let minutes = match options.get_resolved("minutes")? {
+        Some(OptionValue::Integer(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required minutes value")),
+    }?;
+    if minutes <= 0 || minutes > MAX_EXTEND_MINUTES {
+        let content = format!(
+            "Events can be extended by between 1 and {} minutes, Captain.",
+            MAX_EXTEND_MINUTES
+        );
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let event = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if member.user.id != event.creator.id && !perms.administrator() {
+        let content = "Only the event creator or an admin can extend an event";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let content = match event_manager
+        .extend_cleanup(event.id, Duration::minutes(minutes))
+        .await
+    {
+        Ok(true) => format!(
+            "Event **{}** will stick around for another {} minutes.",
+            event.id, minutes
+        ),
+        Ok(false) => format!("Event {} has already been cleaned up.", event.id),
+        Err(err) => {
+            error!("Failed to extend cleanup of event {}: {:?}", event.id, err);
+            "Sorry Captain, I seem to be having trouble saving that...".to_owned()
+        }
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use super::{event_list_embed, opts, parse_tag_filter};
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    builder::CreateComponents,
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+
+define_command_option!(
+    id: QueryOpt,
+    name: "query",
+    description: "Text to look for in event descriptions and activity names",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_leaf_command!(
+    LfgFind,
+    "find",
+    "Search for events by description or activity, optionally only those with a tag",
+    lfg_find,
+    options: [QueryOpt, opts::Tag],
+);
+
+#[command_attr::hook]
+async fn lfg_find(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let query = match options.get_resolved("query")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required query value")),
+    }?;
+
+    let tag = match options.get_resolved("tag")? {
+        None => None,
+        Some(OptionValue::String(s)) => match parse_tag_filter(s) {
+            Ok(tag) => Some(tag),
+            Err(content) => {
+                interaction.create_response(&ctx, content, true).await?;
+                return Ok(());
+            }
+        },
+        Some(v) => return Err(format_err!("Unexpected value type: {:?}", v)),
+    };
+    let tagged = tag
+        .as_ref()
+        .map_or(String::new(), |t| format!(" tagged #{}", t));
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let mut events = event_manager.search(query).await;
+    if let Some(tag) = &tag {
+        events.retain(|e| e.has_tag(tag));
+    }
+    if events.is_empty() {
+        let content = format!("I couldn't find any events{} matching '{}'", tagged, query);
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let content = format!(
+        "Found {} events{} matching '{}':",
+        events.len(),
+        tagged,
+        query
+    );
+    interaction
+        .create_embed_response(
+            &ctx,
+            content,
+            event_list_embed(&events),
+            CreateComponents::default(),
+            true,
+        )
+        .await?;
+
+    Ok(())
+}
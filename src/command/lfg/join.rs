@@ -1,7 +1,9 @@
-use super::{edit_event_from_str, get_event_from_str, opts};
+use super::{edit_membership_from_str, get_event_from_str, notify_creator, opts};
 use crate::{
     command::OptionType,
-    event::{EventEmbedMessage, JoinKind},
+    event::{
+        normalize_note, ConflictCheck, Event, EventEmbedMessage, GroupRole, JoinError, JoinKind,
+    },
     util::*,
 };
 use anyhow::{format_err, Context as _, Result};
@@ -15,7 +17,7 @@ use serenity::{
     },
     utils::MessageBuilder,
 };
-use std::str::FromStr;
+use std::{str::FromStr, sync::Arc};
 use tracing::error;
 
 define_command_option!(
@@ -38,6 +40,26 @@ define_command_option!(
     ]),
 );
 
+define_command_option!(
+    id: RoleOpt,
+    name: "role",
+    description: "Role you'll fill in your group",
+    required: false,
+    option_type: OptionType::String(&[
+        ("DPS", "dps"),
+        ("Add Clear", "adds"),
+        ("Support", "support"),
+    ]),
+);
+
+define_command_option!(
+    id: NoteOpt,
+    name: "note",
+    description: "Short note shown next to your name, like \"can only stay 1hr\"",
+    required: false,
+    option_type: OptionType::String(&[]),
+);
+
 define_leaf_command!(
     LfgJoin,
     "join",
@@ -47,6 +69,8 @@ define_leaf_command!(
         opts::EventId,
         UserOpt,
         JoinKindOpt,
+        RoleOpt,
+        NoteOpt,
     ],
 );
 
@@ -82,6 +106,16 @@ async fn lfg_join(
         Some(OptionValue::String(s)) => JoinKind::from_str(s),
         Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
     }?;
+    let role = match options.get_resolved("role")? {
+        None => Ok(None),
+        Some(OptionValue::String(s)) => GroupRole::from_str(s).map(Some),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let note = match options.get_resolved("note")? {
+        None => Ok(None),
+        Some(OptionValue::String(s)) => Ok(normalize_note(s)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
 
     join(
         ctx,
@@ -90,12 +124,20 @@ async fn lfg_join(
         command_member,
         Some(target_member),
         kind,
+        JoinDetails { role, note },
     )
     .await?;
 
     Ok(())
 }
 
+/// What a member can add about themselves when joining, beyond how they're joining.
+#[derive(Debug, Default)]
+pub struct JoinDetails {
+    pub role: Option<GroupRole>,
+    pub note: Option<String>,
+}
+
 pub async fn join(
     ctx: &Context,
     interaction: &impl InteractionExt,
@@ -103,30 +145,73 @@ pub async fn join(
     command_member: &dyn MemberLike,
     target_member: Option<&dyn MemberLike>,
     kind: JoinKind,
+    details: JoinDetails,
 ) -> Result<()> {
+    let JoinDetails { role, note } = details;
     let event_id = event_id.as_ref();
     let target_member = target_member.unwrap_or(command_member);
 
-    let user_str = if command_member.user() != target_member.user() {
-        target_member.user().mention().to_string()
+    let (user_str, subject) = if command_member.user() != target_member.user() {
+        let mention = target_member.user().mention().to_string();
+        (mention.clone(), format!("{} is", mention))
     } else {
-        "you".to_owned()
+        ("you".to_owned(), "You're".to_owned())
     };
 
     let event_manager = ctx.get_event_manager(interaction).await?;
-    let edit_result = edit_event_from_str(&event_manager, &event_id, |event| {
-        match event.join(target_member, kind) {
-            Ok(()) => format!(
-                "Added {} to the {} event at {} as **{}**!",
-                user_str,
-                event.activity,
-                event.timestamp(),
-                kind,
-            ),
-            Err(_) => "You're already in that event!".to_owned(),
+    let auto_alt = event_manager.auto_alt();
+
+    // Look for conflicts before joining, so that a blocked join doesn't change anything.
+    let (conflict_check, window) = event_manager.conflict_check();
+    let mut conflicts = Vec::new();
+    if conflict_check != ConflictCheck::Off && kind == JoinKind::Confirmed {
+        if let Ok(event) = get_event_from_str(&event_manager, event_id).await {
+            conflicts = event_manager
+                .conflicts_for(target_member.id(), event.datetime, window)
+                .await;
+            conflicts.retain(|e| e.id != event.id);
         }
-    })
-    .await;
+    }
+    if conflict_check == ConflictCheck::Block && !conflicts.is_empty() {
+        let content = format!(
+            "Can't join that event, Captain. {}",
+            conflicts_content(&subject, &conflicts)
+        );
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let mut joined_kind = None;
+    let edit_result =
+        edit_membership_from_str(&event_manager, &event_id, target_member.id(), |event| {
+            if event.ended() {
+                return "That event has already ended, Captain.".to_owned();
+            }
+            let joined = if auto_alt {
+                event.join_or_alternate(target_member, kind)
+            } else {
+                event.join(target_member, kind).map(|()| kind)
+            };
+            joined_kind = joined.as_ref().ok().copied();
+            // A locked roster is locked to role and note changes too.
+            let locked = matches!(joined, Err(JoinError::Locked));
+            if !locked && role.is_some() {
+                // Also applies if the user was already in the event, to let them change roles.
+                event.set_role(target_member.id(), role).ok();
+            }
+            if !locked && note.is_some() {
+                // Likewise, this lets members already in the event change their note.
+                event.set_note(target_member.id(), note.clone()).ok();
+            }
+            match joined {
+                Ok(joined) => joined_content(&user_str, event, kind, joined),
+                Err(err) => join_error_content(&user_str, err, role, note.is_some()),
+            }
+        })
+        .await;
+    // Only joining as confirmed can conflict, which an automatic alternate didn't end up doing.
+    let warning = (joined_kind == Some(JoinKind::Confirmed) && !conflicts.is_empty())
+        .then(|| format!("Heads up: {}", conflicts_content(&subject, &conflicts)));
 
     match (edit_result, interaction.kind()) {
         (Err(err), _) => {
@@ -140,17 +225,33 @@ pub async fn join(
             interaction.create_response(&ctx, content, true).await?;
         }
         (Ok(content), InteractionType::ApplicationCommand) => {
+            let content = match &warning {
+                Some(warning) => format!("{}\n{}", content, warning),
+                None => content,
+            };
             interaction.create_response(&ctx, content, true).await?;
         }
-        (Ok(_), InteractionType::MessageComponent) => {
-            // Just ACK component interactions.
-            interaction.create_ack_response(&ctx).await?;
-        }
+        (Ok(_), InteractionType::MessageComponent) => match warning {
+            // Component interactions are just ACKed, unless there's a warning to show.
+            Some(warning) => interaction.create_response(&ctx, warning, true).await?,
+            None => interaction.create_ack_response(&ctx).await?,
+        },
         (_, kind) => error!("Unexpected interaction kind {:?}", kind),
     }
 
+    let kind = match joined_kind {
+        Some(joined) => {
+            notify_creator(ctx, &event_manager, event_id, target_member, Some(joined)).await;
+            joined
+        }
+        None => kind,
+    };
+
     // If the command's issuer was adding someone else to an event, notify the added user over DM.
     if command_member.user() != target_member.user() {
+        let guild_id = interaction
+            .guild_id()
+            .ok_or_else(|| format_err!("Interaction not in a guild"))?;
         let event = get_event_from_str(&event_manager, &event_id)
             .await
             .map_err(|_| format_err!("Unable to get just-joined event to send notification DM"))?;
@@ -168,18 +269,154 @@ pub async fn join(
             .user()
             .direct_message(&ctx, |msg| {
                 msg.content(content)
-                    .set_embed(event.as_embed())
+                    .set_embed(event.as_embed(event_manager.embed_style()))
                     .components(|c| {
-                        *c = event.event_buttons();
+                        *c = event.dm_event_buttons(event_manager.button_labels(), guild_id);
                         c
                     })
             })
             .await
             .context("Error sending added user a DM notification")?;
         event_manager
-            .keep_embed_updated(event.id, EventEmbedMessage::Normal(dm.channel_id, dm.id))
+            .keep_embed_updated(event.id, EventEmbedMessage::Dm(dm.channel_id, dm.id))
             .await?;
     }
 
     Ok(())
 }
+
+/// The response to a successful join, noting when the event was full and the member was added as
+/// an alternate rather than how they asked to join.
+fn joined_content(user_str: &str, event: &Event, requested: JoinKind, joined: JoinKind) -> String {
+    let content = format!(
+        "Added {} to the {} event at {} as **{}**!",
+        user_str,
+        event.activity,
+        event.timestamp(),
+        joined,
+    );
+    if joined == requested {
+        content
+    } else {
+        format!(
+            "{} The event is full, so that's instead of as **{}**.",
+            content, requested
+        )
+    }
+}
+
+/// Lists the other events that a member joining an event is already confirmed for around then.
+fn conflicts_content(subject: &str, conflicts: &[Arc<Event>]) -> String {
+    let events: Vec<_> = conflicts
+        .iter()
+        .map(|e| format!("**{}** ({}, {})", e.id, e.activity, e.timestamp()))
+        .collect();
+    format!(
+        "{} already confirmed for {} around the same time.",
+        subject,
+        events.join(", ")
+    )
+}
+
+/// The response to a join that was turned away. A member who's already in the event but gave a
+/// role or note has still had it updated.
+fn join_error_content(
+    user_str: &str,
+    err: JoinError,
+    role: Option<GroupRole>,
+    noted: bool,
+) -> String {
+    match (err, role) {
+        (JoinError::Ended, _) => "That event has already ended, Captain.".to_owned(),
+        (JoinError::Locked, _) => "That event's roster is locked, Captain.".to_owned(),
+        (JoinError::Full, _) => {
+            "That event is full, Captain. There's still room as an alt or a maybe.".to_owned()
+        }
+        (JoinError::AlreadyJoined, Some(role)) => {
+            format!("Updated {} to fill the **{}** role!", user_str, role)
+        }
+        (JoinError::AlreadyJoined, None) if noted => format!("Updated the note for {}!", user_str),
+        (JoinError::AlreadyJoined, None) => "You're already in that event!".to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn joined_content_notes_alternate_fallback() {
+        let event = Event::default();
+        let content = joined_content("you", &event, JoinKind::Confirmed, JoinKind::Confirmed);
+        assert_eq!(
+            content,
+            format!(
+                "Added you to the {} event at {} as **confirmed**!",
+                event.activity,
+                event.timestamp()
+            )
+        );
+
+        let content = joined_content("you", &event, JoinKind::Confirmed, JoinKind::Alternate);
+        assert_eq!(
+            content,
+            format!(
+                "Added you to the {} event at {} as **a confirmed alt**! The event is full, so \
+                 that's instead of as **confirmed**.",
+                event.activity,
+                event.timestamp()
+            )
+        );
+    }
+
+    #[test]
+    fn conflicts_content_lists_events() {
+        let event = Arc::new(Event::default());
+        let other = Arc::new(Event {
+            id: "gos2".parse().unwrap(),
+            ..Default::default()
+        });
+        assert_eq!(
+            conflicts_content("You're", &[event.clone(), other.clone()]),
+            format!(
+                "You're already confirmed for **{}** ({}, {}), **gos2** ({}, {}) around the same \
+                 time.",
+                event.id,
+                event.activity,
+                event.timestamp(),
+                other.activity,
+                other.timestamp()
+            )
+        );
+    }
+
+    #[test]
+    fn join_error_content_matches_error() {
+        let support = Some(GroupRole::Support);
+        assert_eq!(
+            join_error_content("you", JoinError::Ended, None, false),
+            "That event has already ended, Captain."
+        );
+        assert_eq!(
+            join_error_content("you", JoinError::Locked, support, false),
+            "That event's roster is locked, Captain."
+        );
+        assert_eq!(
+            join_error_content("you", JoinError::Full, support, false),
+            "That event is full, Captain. There's still room as an alt or a maybe."
+        );
+        assert_eq!(
+            join_error_content("you", JoinError::AlreadyJoined, None, false),
+            "You're already in that event!"
+        );
+        assert_eq!(
+            join_error_content("you", JoinError::AlreadyJoined, support, false),
+            format!("Updated you to fill the **{}** role!", GroupRole::Support)
+        );
+        assert_eq!(
+            join_error_content("you", JoinError::AlreadyJoined, None, true),
+            "Updated the note for you!"
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use super::{edit_event_from_str, opts};
+use super::{edit_event_from_str, get_event_from_str, notify_promoted, opts};
 use crate::{command::OptionType, util::*};
 use anyhow::{format_err, Result};
 use serenity::{
@@ -9,6 +9,7 @@ use serenity::{
         },
         prelude::*,
     },
+    utils::MessageBuilder,
 };
 use tracing::error;
 
@@ -23,7 +24,7 @@ define_command_option!(
 define_leaf_command!(
     LfgKick,
     "kick",
-    "Remove a user from an existing event",
+    "Remove a user from an existing event (creator or admin only)",
     lfg_kick,
     options: [
         opts::EventId,
@@ -52,14 +53,6 @@ async fn lfg_kick(
         .as_ref()
         .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
 
-    // First we need to check that the member issuing the command is an admin.
-    // TODO: Should event creators be able to kick? Maybe if the user is notified?
-    if !perms.administrator() {
-        let content = "Only admins can kick people out of events";
-        interaction.create_response(ctx, content, true).await?;
-        return Ok(());
-    }
-
     let target_member = match options.get_resolved("user")? {
         Some(OptionValue::User(user, Some(member))) => Ok((user, member)),
         Some(OptionValue::User(..)) => Err(format_err!(
@@ -69,16 +62,40 @@ async fn lfg_kick(
         None => Err(format_err!("Missing required user value")),
     }?;
 
-    let user_mention = target_member.user().mention();
+    // Only the event's creator or an admin can remove other people from it.
     let event_manager = ctx.get_event_manager(interaction).await?;
+    let err_msg = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => {
+            if member.user.id == event.creator.id || perms.administrator() {
+                None
+            } else {
+                Some(
+                    "Only the event creator or an admin can kick people out of an event".to_owned(),
+                )
+            }
+        }
+        Err(msg) => Some(msg),
+    };
+    if let Some(err_msg) = err_msg {
+        interaction.create_response(ctx, err_msg, true).await?;
+        return Ok(());
+    }
+
+    let user_mention = target_member.user().mention();
+    let mut did_kick = false;
+    let mut promoted = None;
     let edit_result = edit_event_from_str(&event_manager, &event_id, |event| {
         match event.leave(&target_member) {
-            Ok(()) => format!(
-                "Removed {} from the {} event at {}",
-                user_mention,
-                event.activity,
-                event.timestamp()
-            ),
+            Ok(promoted_alt) => {
+                did_kick = true;
+                promoted = promoted_alt;
+                format!(
+                    "Removed {} from the {} event at {}",
+                    user_mention,
+                    event.activity,
+                    event.timestamp()
+                )
+            }
             Err(_) => format!(
                 "*Errr, Captain, you can't kick {} because they aren't in that event...*",
                 user_mention
@@ -99,5 +116,34 @@ async fn lfg_kick(
     };
     interaction.create_response(&ctx, content, true).await?;
 
+    // Let the kicked user know what happened, unless they kicked themselves.
+    if did_kick && target_member.user() != &member.user {
+        if let Ok(event) = get_event_from_str(&event_manager, &event_id).await {
+            let content = MessageBuilder::new()
+                .push("Pssssst, ")
+                .mention(target_member.user())
+                .push(", just letting you know that ")
+                .mention(&member.user)
+                .push(" removed you from the ")
+                .push_bold_safe(event.activity.to_string())
+                .push(format!(" event {} at {}.", event.id, event.timestamp()))
+                .build();
+            if let Err(err) = target_member
+                .user()
+                .direct_message(&ctx, |msg| msg.content(content))
+                .await
+            {
+                error!(
+                    "Failed to notify {} about being kicked from {}: {:?}",
+                    user_mention, event.id, err
+                );
+            }
+        }
+    }
+
+    if let Some(promoted) = promoted {
+        notify_promoted(&event_manager, &event_id, &promoted).await;
+    }
+
     Ok(())
 }
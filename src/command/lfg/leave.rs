@@ -1,4 +1,4 @@
-use super::{edit_event_from_str, opts};
+use super::{edit_membership_from_str, notify_creator, notify_promoted, opts};
 use crate::util::*;
 use anyhow::{format_err, Result};
 use serenity::{
@@ -30,29 +30,40 @@ async fn lfg_leave(
         .member
         .as_ref()
         .ok_or_else(|| format_err!("Interaction not in a guild"))?;
-    let event_id = match options.get_resolved("event_id")? {
-        Some(OptionValue::String(v)) => Ok(v),
-        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
-        None => Err(format_err!("Missing required event_id value")),
-    }?;
+    let event_id = event_id_option(options)?;
 
     leave(ctx, interaction, event_id, member).await
 }
 
+fn event_id_option<O: OptionsExt>(options: O) -> Result<String> {
+    match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v.clone()),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }
+}
+
 pub async fn leave(
     ctx: &Context,
     interaction: &impl InteractionExt,
     event_id: impl AsRef<str>,
-    member: &Member,
+    member: &dyn MemberLike,
 ) -> Result<()> {
     let event_manager = ctx.get_event_manager(interaction).await?;
-    let edit_result = edit_event_from_str(&event_manager, &event_id, |event| {
-        match event.leave(member) {
-            Ok(()) => format!(
-                "Removed you from the {} event at {}",
-                event.activity,
-                event.timestamp()
-            ),
+    let mut did_leave = false;
+    let mut promoted = None;
+    let edit_result = edit_membership_from_str(&event_manager, &event_id, member.id(), |event| {
+        let left = event.leave(member);
+        did_leave = left.is_ok();
+        match left {
+            Ok(promoted_alt) => {
+                promoted = promoted_alt;
+                format!(
+                    "Removed you from the {} event at {}",
+                    event.activity,
+                    event.timestamp()
+                )
+            }
             Err(_) => {
                 "*Hey, you're not even in that event... did you think I'd forget?*".to_owned()
             }
@@ -77,5 +88,38 @@ pub async fn leave(
         (_, kind) => error!("Unexpected interaction kind {:?}", kind),
     }
 
+    if did_leave {
+        notify_creator(ctx, &event_manager, &event_id, member, None).await;
+    }
+    if let Some(promoted) = promoted {
+        notify_promoted(&event_manager, &event_id, &promoted).await;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn option(name: &str, value: OptionValue) -> ApplicationCommandInteractionDataOption {
+        let mut option: ApplicationCommandInteractionDataOption =
+            serde_json::from_value(json!({ "name": name, "type": 3 })).unwrap();
+        option.resolved = Some(value);
+        option
+    }
+
+    #[test]
+    fn event_id_option_parsed() {
+        let options = vec![option("event_id", OptionValue::String("vog1".to_owned()))];
+        assert_eq!(event_id_option(&options).unwrap(), "vog1");
+
+        let empty: Vec<ApplicationCommandInteractionDataOption> = vec![];
+        assert!(event_id_option(&empty).is_err());
+        let options = vec![option("event_id", OptionValue::Integer(1))];
+        assert!(event_id_option(&options).is_err());
+        let options = vec![option("other", OptionValue::String("vog1".to_owned()))];
+        assert!(event_id_option(&options).is_err());
+    }
+}
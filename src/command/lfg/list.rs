@@ -0,0 +1,308 @@
+use super::{event_list_embed, opts, parse_tag_filter};
+use crate::{
+    activity::ActivityType,
+    command::OptionType,
+    custom_id::{CustomId, ListFilters},
+    event::{Event, Platform},
+    util::*,
+};
+use anyhow::{format_err, Result};
+use enum_iterator::IntoEnumIterator;
+use lazy_static::lazy_static;
+use serenity::{
+    builder::{CreateActionRow, CreateButton, CreateComponents, CreateEmbed},
+    client::Context,
+    model::interactions::{
+        application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        message_component::{ButtonStyle, MessageComponentInteraction},
+        InteractionResponseType,
+    },
+};
+use std::{str::FromStr, sync::Arc};
+
+// Events shown on each page of the list.
+const PAGE_SIZE: usize = 10;
+
+define_command_option!(
+    id: ActivityTypeOpt,
+    name: "activity_type",
+    description: "Only list events of this type",
+    required: false,
+    option_type: OptionType::String(&*ACTIVITY_TYPES),
+);
+
+lazy_static! {
+    static ref ACTIVITY_TYPES: Vec<(&'static str, &'static str)> = ActivityType::into_enum_iter()
+        .map(|t| (t.name(), t.command_name()))
+        .collect();
+}
+
+define_leaf_command!(
+    LfgList,
+    "list",
+    "List upcoming events, optionally only those of a type, open to a platform, or with a tag",
+    lfg_list,
+    options: [ActivityTypeOpt, opts::Platform, opts::Tag],
+);
+
+impl ListFilters {
+    fn matches(&self, event: &Event) -> bool {
+        self.activity_type
+            .map_or(true, |t| event.activity.activity_type() == t)
+            && self.tag.as_ref().map_or(true, |t| event.has_tag(t))
+    }
+
+    /// Describes the filters for the response, e.g. " Raid" and " open to PC tagged #sherpa" in
+    /// "3 upcoming Raid events open to PC tagged #sherpa".
+    fn describe(&self) -> (String, String) {
+        let activity_type = self
+            .activity_type
+            .map_or(String::new(), |t| format!(" {}", t.name()));
+        let mut rest = self
+            .platform
+            .map_or(String::new(), |p| format!(" open to {}", p));
+        if let Some(tag) = &self.tag {
+            rest.push_str(&format!(" tagged #{}", tag));
+        }
+        (activity_type, rest)
+    }
+}
+
+#[command_attr::hook]
+async fn lfg_list(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let platform = match options.get_resolved("platform")? {
+        None => Ok(None),
+        Some(OptionValue::String(s)) => Platform::from_str(s).map(Some),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let activity_type = match options.get_resolved("activity_type")? {
+        None => Ok(None),
+        Some(OptionValue::String(s)) => ActivityType::into_enum_iter()
+            .find(|t| t.command_name() == s)
+            .map(Some)
+            .ok_or_else(|| format_err!("Unexpected activity type value: {}", s)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+    let tag = match options.get_resolved("tag")? {
+        None => None,
+        Some(OptionValue::String(s)) => match parse_tag_filter(s) {
+            Ok(tag) => Some(tag),
+            Err(content) => {
+                interaction.create_response(&ctx, content, true).await?;
+                return Ok(());
+            }
+        },
+        Some(v) => return Err(format_err!("Unexpected value type: {:?}", v)),
+    };
+    let filters = ListFilters {
+        activity_type,
+        platform,
+        tag,
+    };
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let mut events = event_manager.list(platform).await;
+    events.retain(|e| filters.matches(e));
+    if events.is_empty() {
+        let (activity_type, platform) = filters.describe();
+        let content = format!("There are no upcoming{} events{}", activity_type, platform);
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let (content, embed, components) = list_page(&events, &filters, 0);
+    interaction
+        .create_embed_response(&ctx, content, embed, components, true)
+        .await?;
+
+    Ok(())
+}
+
+/// Handles a paging button on a list response, replacing it with the requested page. The events
+/// are listed again, so the page reflects any changes since the list was first shown.
+pub async fn change_page(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    page: usize,
+    filters: &ListFilters,
+) -> Result<()> {
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let mut events = event_manager.list(filters.platform).await;
+    events.retain(|e| filters.matches(e));
+    let (content, embed, components) = list_page(&events, filters, page);
+    interaction
+        .create_interaction_response(&ctx, |resp| {
+            resp.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|msg| {
+                    msg.content(content).add_embed(embed).components(|c| {
+                        *c = components;
+                        c
+                    })
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// The response content, embed, and paging buttons for the given page of events. Pages past the
+/// end show the last page instead, since events may have been removed since the list was shown.
+fn list_page(
+    events: &[Arc<Event>],
+    filters: &ListFilters,
+    page: usize,
+) -> (String, CreateEmbed, CreateComponents) {
+    let page_count = ((events.len() + PAGE_SIZE - 1) / PAGE_SIZE).max(1);
+    let page = page.min(page_count - 1);
+    let start = page * PAGE_SIZE;
+    let page_events = &events[start..events.len().min(start + PAGE_SIZE)];
+
+    let (activity_type, platform) = filters.describe();
+    let content = format!(
+        "{} upcoming{} events{}:",
+        events.len(),
+        activity_type,
+        platform
+    );
+    let mut embed = event_list_embed(page_events);
+    let mut components = CreateComponents::default();
+    if page_count > 1 {
+        embed.footer(|f| f.text(format!("Page {} of {}", page + 1, page_count)));
+
+        let mut row = CreateActionRow::default();
+        for (label, target, disabled) in [
+            ("Previous", page.saturating_sub(1), page == 0),
+            ("Next", page + 1, page + 1 == page_count),
+        ] {
+            let custom_id = CustomId::ListPage {
+                page: target,
+                filters: filters.clone(),
+            };
+            let mut button = CreateButton::default();
+            button
+                .label(label)
+                .style(ButtonStyle::Secondary)
+                .custom_id(custom_id.to_string())
+                .disabled(disabled);
+            row.add_button(button);
+        }
+        components.add_action_row(row);
+    }
+    (content, embed, components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{activity::Activity, event::EventId};
+
+    fn test_events(count: u8) -> Vec<Arc<Event>> {
+        (1..=count)
+            .map(|idx| {
+                let mut event = Event::default();
+                event.id = EventId {
+                    activity: Activity::Custom,
+                    idx,
+                };
+                Arc::new(event)
+            })
+            .collect()
+    }
+
+    fn field_names(embed: &CreateEmbed) -> Vec<String> {
+        embed.0["fields"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|f| f["name"].as_str().unwrap().to_owned())
+            .collect()
+    }
+
+    fn buttons(components: &CreateComponents) -> Vec<(String, bool)> {
+        components
+            .0
+            .iter()
+            .flat_map(|row| row["components"].as_array().unwrap().iter())
+            .map(|button| {
+                let id = button["custom_id"].as_str().unwrap().to_owned();
+                (id, button["disabled"].as_bool().unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pages_through_events() {
+        let filters = ListFilters {
+            activity_type: Some(ActivityType::Raid),
+            platform: Some(Platform::PlayStation),
+            tag: None,
+        };
+        let events = test_events(23);
+
+        let (content, embed, components) = list_page(&events, &filters, 0);
+        assert_eq!(content, "23 upcoming Raid events open to PlayStation:");
+        assert_eq!(field_names(&embed).len(), PAGE_SIZE);
+        assert_eq!(embed.0["footer"]["text"], "Page 1 of 3");
+        assert_eq!(
+            buttons(&components),
+            vec![
+                ("p:0:playstation:raid:".to_owned(), true),
+                ("p:1:playstation:raid:".to_owned(), false),
+            ]
+        );
+
+        // Pages past the end show the last page.
+        for page in [2, 5] {
+            let (_, embed, components) = list_page(&events, &filters, page);
+            assert_eq!(field_names(&embed).len(), 3);
+            assert!(field_names(&embed)[0].starts_with("cust21 |"));
+            assert_eq!(
+                buttons(&components),
+                vec![
+                    ("p:1:playstation:raid:".to_owned(), false),
+                    ("p:3:playstation:raid:".to_owned(), true),
+                ]
+            );
+        }
+
+        // No paging when everything fits on one page.
+        let (_, embed, components) = list_page(&events[..PAGE_SIZE], &filters, 0);
+        assert_eq!(field_names(&embed).len(), PAGE_SIZE);
+        assert!(components.0.is_empty());
+    }
+
+    #[test]
+    fn filters_by_tag() {
+        let mut events = test_events(3);
+        Arc::make_mut(&mut events[0]).tags = vec!["sherpa".to_owned(), "chill".to_owned()];
+        Arc::make_mut(&mut events[2]).tags = vec!["chill".to_owned()];
+        let matching = |tag: &str| {
+            let filters = ListFilters {
+                tag: parse_tag_filter(tag).ok(),
+                ..Default::default()
+            };
+            events
+                .iter()
+                .filter(|e| filters.matches(e))
+                .map(|e| e.id.idx)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(matching("chill"), vec![1, 3]);
+        assert_eq!(matching("#Sherpa"), vec![1]);
+        assert!(matching("flawless").is_empty());
+
+        let filters = ListFilters {
+            platform: Some(Platform::Pc),
+            tag: Some("chill".to_owned()),
+            ..Default::default()
+        };
+        let (content, _, _) = list_page(&events, &filters, 0);
+        assert_eq!(content, "3 upcoming events open to PC tagged #chill:");
+    }
+}
@@ -0,0 +1,125 @@
+use super::get_event_from_str;
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        ApplicationCommandInteractionDataOptionValue as OptionValue,
+    },
+};
+use tracing::error;
+
+define_command_option!(
+    id: IntoOpt,
+    name: "into_event_id",
+    description: "ID of the event to keep",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_command_option!(
+    id: FromOpt,
+    name: "from_event_id",
+    description: "ID of the event to merge in and delete",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_leaf_command!(
+    LfgMerge,
+    "merge",
+    "Merge one event's members into another and delete it (creator of both or admin only)",
+    lfg_merge,
+    options: [IntoOpt, FromOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_merge(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let into_id = match options.get_resolved("into_event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required into_event_id value")),
+    }?;
+    let from_id = match options.get_resolved("from_event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required from_event_id value")),
+    }?;
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let (into, from) = match (
+        get_event_from_str(&event_manager, &into_id).await,
+        get_event_from_str(&event_manager, &from_id).await,
+    ) {
+        (Ok(into), Ok(from)) => (into, from),
+        (Err(content), _) | (_, Err(content)) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if into.id == from.id {
+        let content = "Those are the same event, Captain. It's already as merged as it gets.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+    let is_creator = member.user.id == into.creator.id && member.user.id == from.creator.id;
+    if !is_creator && !perms.administrator() {
+        let content = "Only the creator of both events or an admin can merge them";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let (merged_event, merged) = match event_manager.merge_events(&into.id, &from.id).await {
+        Ok(result) => result,
+        Err(err) => {
+            error!("Failed to merge {} into {}: {:?}", from.id, into.id, err);
+            let content = "Sorry Captain, I seem to be having trouble merging those events...";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let content = format!(
+        "Merged {} into {}, bringing over {} members.",
+        from.id,
+        into.id,
+        merged.len()
+    );
+    interaction.create_response(&ctx, content, true).await?;
+
+    for (moved, kind) in merged {
+        // The command issuer already knows about the merge.
+        if moved.id == member.user.id {
+            continue;
+        }
+        let content = format!(
+            "The {} event {} you signed up for was merged into {}, where you're now {}.",
+            from.activity, from.id, merged_event.id, kind
+        );
+        if let Err(err) = event_manager
+            .dm_event(moved.id, &merged_event, &content)
+            .await
+        {
+            error!(
+                "Failed to notify {} about merge into {}: {:?}",
+                moved.id, merged_event.id, err
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use super::MAX_EMBED_FIELDS;
+use crate::{
+    command::OptionType,
+    event::{role_label, Event},
+    util::*,
+};
+use anyhow::{format_err, Result};
+use serenity::{
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    model::{
+        id::UserId,
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+    },
+    utils::Color,
+};
+use std::sync::Arc;
+use tracing::error;
+
+define_command_option!(
+    id: DmOpt,
+    name: "dm",
+    description: "Send you the list in a DM instead?",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_command_option!(
+    id: DailyOpt,
+    name: "daily",
+    description: "Get the list in a DM every day? Set to false to stop.",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_leaf_command!(
+    LfgMine,
+    "mine",
+    "List upcoming events you created or joined",
+    lfg_mine,
+    options: [DmOpt, DailyOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_mine(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let dm = match options.get_resolved("dm")? {
+        Some(OptionValue::Boolean(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(false),
+    }?;
+    let daily = match options.get_resolved("daily")? {
+        Some(OptionValue::Boolean(v)) => Ok(Some(*v)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(None),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let user = interaction.user.id;
+    if let Some(daily) = daily {
+        let content = match event_manager.set_digest_subscribed(user, daily).await {
+            Ok(()) if daily => {
+                "I'll DM you a list of your upcoming events every day at 9am, in the timezone you \
+                 set with `/lfg timezone`, Guardian. Stop anytime with `/lfg mine daily:False`."
+            }
+            Ok(()) => "I'll stop DMing you a list of your upcoming events every day.",
+            Err(err) => {
+                error!("Failed to set daily digest for user {}: {:?}", user, err);
+                "Sorry Captain, I seem to be having trouble saving that..."
+            }
+        };
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    if dm {
+        let content = match event_manager.send_digest(user).await {
+            Ok(true) => "Sent you a DM with your upcoming events.",
+            Ok(false) => "You haven't created or joined any upcoming events, Guardian.",
+            Err(err) => {
+                error!("Failed to DM digest to user {}: {:?}", user, err);
+                "Sorry Guardian, I couldn't DM you. Check that you allow DMs from server members."
+            }
+        };
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let events = event_manager.events_for_user(user).await;
+    if events.is_empty() {
+        let content = "You haven't created or joined any upcoming events, Guardian.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let content = format!("You're in {} upcoming events:", events.len());
+    interaction
+        .create_embed_response(
+            &ctx,
+            content,
+            my_events_embed(&events, user),
+            CreateComponents::default(),
+            true,
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Like `event_list_embed`, but labeling the part the given user has in each event rather than
+/// showing the description.
+fn my_events_embed(events: &[Arc<Event>], user: UserId) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.color(Color::DARK_GOLD);
+    events.iter().take(MAX_EMBED_FIELDS).for_each(|event| {
+        embed.field(
+            format!("{} | {}", event.id, event.activity),
+            format!("{}\n{}", event.timestamp(), role_label(event, user)),
+            false,
+        );
+    });
+    if events.len() > MAX_EMBED_FIELDS {
+        embed.footer(|f| f.text(format!("...and {} more", events.len() - MAX_EMBED_FIELDS)));
+    }
+    embed
+}
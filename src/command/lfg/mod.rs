@@ -1,30 +1,54 @@
 use crate::{
-    event::{Event, EventId, EventManager, JoinKind},
+    custom_id::{CustomId, EventAction},
+    event::{normalize_tags, Event, EventId, EventManager, EventMember, JoinKind},
     util::*,
 };
 use anyhow::{format_err, Result};
 use serenity::{
+    builder::CreateEmbed,
     client::Context,
-    model::interactions::{
-        application_command::ApplicationCommandInteraction,
-        message_component::MessageComponentInteraction,
-    },
-    utils::MessageBuilder,
+    http::CacheHttp,
+    model::{id::UserId, interactions::message_component::MessageComponentInteraction},
+    utils::{Color, MessageBuilder},
 };
+use session::InteractionSession;
 use std::time::Duration;
 use std::{str::FromStr, sync::Arc};
 use tokio::time::sleep;
 use tracing::{debug, error};
 
 mod opts;
+mod session;
 
+mod bump;
+mod check_in;
+mod clone;
 mod create;
 mod delete;
+mod dm;
 mod edit;
+mod extend;
+mod find;
 mod join;
 mod kick;
 mod leave;
+mod list;
+mod merge;
+mod mine;
+mod move_members;
+mod mute_alert;
+mod ping_group;
+mod preview_groups;
+mod restore;
+mod series;
 mod show;
+mod soon;
+mod streak;
+mod timezone;
+mod who;
+mod whois;
+
+pub use create::{create, CreateShortcutOptions};
 
 // TODO: Reorder these so that join & leave appear first when typing `/lfg` in Discord. Need to
 // delete and recreate.
@@ -33,19 +57,37 @@ define_command_group!(
     "lfg",
     "Create and interact with scheduled events",
     subcommands: [
+        bump::LfgBump,
+        clone::LfgClone,
         create::LfgCreate,
         delete::LfgDelete,
+        dm::LfgDm,
         edit::LfgEdit,
+        find::LfgFind,
         join::LfgJoin,
         kick::LfgKick,
         leave::LfgLeave,
+        list::LfgList,
+        merge::LfgMerge,
+        mine::LfgMine,
+        move_members::LfgMoveMembers,
+        mute_alert::LfgMuteAlert,
+        ping_group::LfgPingGroup,
+        preview_groups::LfgPreviewGroups,
+        restore::LfgRestore,
+        series::LfgSeries,
         show::LfgShow,
+        soon::LfgSoon,
+        streak::LfgStreak,
+        timezone::LfgTimezone,
+        who::LfgWho,
+        whois::LfgWhois,
     ]
 );
 
 /// Returns the matching Event or else an error message to use in the interaction reponse.
-async fn get_event_from_str(
-    event_manager: &EventManager,
+async fn get_event_from_str<C: CacheHttp>(
+    event_manager: &EventManager<C>,
     id_str: impl AsRef<str>,
 ) -> Result<Arc<Event>, String> {
     let id_str = id_str.as_ref();
@@ -60,22 +102,54 @@ async fn get_event_from_str(
     }
 }
 
+/// Parses a tag to filter events by, normalized the same way as the tags given to events. Returns
+/// an error message to use in the interaction response unless it's exactly one valid tag.
+fn parse_tag_filter(tag: &str) -> Result<String, String> {
+    let mut tags = normalize_tags(tag);
+    match tags.len() {
+        0 => Err(format!("'{}' isn't a valid tag", tag)),
+        1 => Ok(tags.remove(0)),
+        _ => Err("I can only look for one tag at a time, Captain.".to_owned()),
+    }
+}
+
 /// Runs the given closure on the matching Event, returning the message it generates or else an
 /// error message to use in the interaction reponse.
 async fn edit_event_from_str(
     event_manager: &EventManager,
     id_str: impl AsRef<str>,
     edit_fn: impl FnOnce(&mut Event) -> String,
+) -> Result<String> {
+    edit_from_str(event_manager, id_str, None, edit_fn).await
+}
+
+/// Like edit_event_from_str, for the member joining or leaving the event.
+async fn edit_membership_from_str(
+    event_manager: &EventManager,
+    id_str: impl AsRef<str>,
+    member: UserId,
+    edit_fn: impl FnOnce(&mut Event) -> String,
+) -> Result<String> {
+    edit_from_str(event_manager, id_str, Some(member), edit_fn).await
+}
+
+async fn edit_from_str(
+    event_manager: &EventManager,
+    id_str: impl AsRef<str>,
+    member: Option<UserId>,
+    edit_fn: impl FnOnce(&mut Event) -> String,
 ) -> Result<String> {
     let id_str = id_str.as_ref();
     match EventId::from_str(&id_str) {
         Ok(event_id) => {
-            event_manager
-                .edit_event(&event_id, |event| match event {
-                    Some(event) => edit_fn(event),
-                    None => format!("I couldn't find an event with ID '{}'", event_id),
-                })
-                .await
+            let edit = |event: Option<&mut Event>| match event {
+                Some(event) => edit_fn(event),
+                None => format!("I couldn't find an event with ID '{}'", event_id),
+            };
+            match member {
+                Some(member) => event_manager.edit_membership(&event_id, member, edit).await,
+                None => event_manager.edit_event(&event_id, edit).await,
+            }
         }
         Err(_) => {
             Ok("That's not a valid event ID, Captain. They look like this: `dsc123`".to_owned())
@@ -83,6 +157,96 @@ async fn edit_event_from_str(
     }
 }
 
+/// DMs the event's creator that the given member joined (with Some kind) or left (with None), if
+/// the creator asked to be notified. Failures are only logged, since the join or leave itself
+/// already succeeded.
+async fn notify_creator(
+    ctx: &Context,
+    event_manager: &EventManager,
+    event_id: impl AsRef<str>,
+    member: &dyn MemberLike,
+    joined: Option<JoinKind>,
+) {
+    let event = match get_event_from_str(event_manager, event_id).await {
+        Ok(event) => event,
+        Err(_) => return,
+    };
+    let (creator, content) = match event.creator_notification(member, joined) {
+        Some(notification) => notification,
+        None => return,
+    };
+    let result = async {
+        creator
+            .create_dm_channel(ctx)
+            .await?
+            .say(ctx, content)
+            .await
+    };
+    if let Err(err) = result.await {
+        error!(
+            "Failed to notify creator of event {} about a roster change: {:?}",
+            event.id, err
+        );
+    }
+}
+
+/// DMs an alternate that was confirmed because someone left the event. Failures are only logged,
+/// since the promotion itself already happened.
+async fn notify_promoted(
+    event_manager: &EventManager,
+    event_id: impl AsRef<str>,
+    promoted: &EventMember,
+) {
+    let event = match get_event_from_str(event_manager, event_id).await {
+        Ok(event) => event,
+        Err(_) => return,
+    };
+    let content = "Good news, Guardian! A spot opened up, so you've been moved up from alt to \
+                   **confirmed** for this event.";
+    if let Err(err) = event_manager.dm_event(promoted.id, &event, content).await {
+        error!(
+            "Failed to notify {} about promotion in event {}: {:?}",
+            promoted.id, event.id, err
+        );
+    }
+}
+
+// Discord allows at most 25 fields in an embed.
+const MAX_EMBED_FIELDS: usize = 25;
+// Descriptions are truncated in event lists to stay within the overall embed size limit.
+const LIST_DESCRIPTION_MAX_CHARS: usize = 100;
+
+/// Creates an embed summarizing each of the given events. Events beyond what fits in a single embed
+/// are left out, with a count of them in the footer.
+fn event_list_embed(events: &[Arc<Event>]) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.color(Color::DARK_GOLD);
+    events.iter().take(MAX_EMBED_FIELDS).for_each(|event| {
+        let mut description: String = event
+            .description
+            .chars()
+            .take(LIST_DESCRIPTION_MAX_CHARS)
+            .collect();
+        if description.len() < event.description.len() {
+            description.push('…');
+        }
+        embed.field(
+            format!("{} | {}", event.id, event.activity),
+            format!(
+                "{}\n{} confirmed\n{}",
+                event.timestamp(),
+                event.confirmed().len(),
+                description
+            ),
+            false,
+        );
+    });
+    if events.len() > MAX_EMBED_FIELDS {
+        embed.footer(|f| f.text(format!("...and {} more", events.len() - MAX_EMBED_FIELDS)));
+    }
+    embed
+}
+
 // Nudge the user for a description after this timeout.
 const LFG_DESCRIPTION_NUDGE_SEC: u64 = 60;
 // Overall description timeout.
@@ -91,19 +255,14 @@ const LFG_DESCRIPTION_TIMEOUT_SEC: u64 = 3 * 60;
 // Note that this creates the original interaction response, so subsequent logic must take care to
 // edit that response or create followups, rather than trying to create it again (which will fail).
 pub async fn ask_for_description(
-    ctx: &Context,
-    interaction: &ApplicationCommandInteraction,
+    session: &impl InteractionSession,
     query_content: impl ToString,
 ) -> Result<Option<String>> {
-    let user = &interaction.user;
+    let user = session.user();
 
-    interaction
-        .create_response(&ctx, query_content.to_string(), true)
-        .await?;
+    session.create_response(query_content.to_string()).await?;
 
-    let mut reply_fut = user
-        .await_reply(&ctx)
-        .timeout(Duration::from_secs(LFG_DESCRIPTION_TIMEOUT_SEC));
+    let mut reply_fut = session.await_reply(Duration::from_secs(LFG_DESCRIPTION_TIMEOUT_SEC));
     let nudge_sleep = sleep(Duration::from_secs(LFG_DESCRIPTION_NUDGE_SEC));
     tokio::pin!(nudge_sleep);
 
@@ -117,34 +276,22 @@ pub async fn ask_for_description(
                         .mention(user)
                         .push(", still there?* Just send a message with the event description in this channel.")
                         .build();
-                nudge_followup.insert(interaction.create_followup(&ctx, content, true).await?);
+                nudge_followup.insert(session.create_followup(content).await?);
             }
 
             // Wait for the user to reply with the description.
             reply = &mut reply_fut => {
-                if let Some(reply) = reply {
-                    // Immediately delete the user's (public) message since the rest of the bot
-                    // interaction is ephemeral.
-                    if let Err(err) = reply.delete(&ctx).await {
-                        // If the user is doing this in an event channel, our delete will race with
-                        // ChannelUpdater's delete, so ignore "Unknown Message" errors.
-                        if !err.is_discord_json_error(DiscordJsonErrorCode::UnknownMessage) {
-                            Err(err)?;
-                        }
-                    }
-
+                if let Some(reply) = reply? {
                     if let Some(followup) = nudge_followup {
                         // We can't just delete the followup, since it's ephemeral, so just edit the
                         // message so the channel state doesn't look confusing.
-                        let edit_fut = interaction.edit_followup_message(&ctx, followup.id, |msg| {
-                            msg.content("*Good job human, you followed basic instructions!*")
-                        });
-                        if let Err(err) = edit_fut.await {
+                        let content = "*Good job human, you followed basic instructions!*";
+                        if let Err(err) = session.edit_followup(followup, content.to_owned()).await {
                             error!("Failed to edit nudge followup message: {:?}", err);
                         }
                     }
 
-                    return Ok(Some(reply.content.clone()));
+                    return Ok(Some(reply));
                 } else {
                     // Timed out waiting for the description, send a followup message so that the
                     // user can see the description request still and so the mention works.
@@ -153,7 +300,7 @@ pub async fn ask_for_description(
                             .mention(user)
                             .push("!** Are the Fallen dismantling *your* brain now? *Whatever, just ask me again...not like I'm going anywhere...*")
                             .build();
-                    interaction.create_followup(&ctx, content, true).await?;
+                    session.create_followup(content).await?;
                     return Ok(None);
                 }
             }
@@ -168,42 +315,139 @@ pub async fn handle_component_interaction(
     let custom_id = &interaction.data.custom_id;
     debug!("handling component interaction, id '{}'", custom_id);
 
-    let member = interaction
-        .member
-        .as_ref()
-        .ok_or_else(|| format_err!("Interaction not in a guild"))?;
-    let (action, event_id) = custom_id
-        .split_once(":")
-        .ok_or_else(|| format_err!("Received unexpected component custom_id: {}", custom_id))?;
-
-    match action {
-        "join" => {
-            join::join(
-                ctx,
-                interaction,
-                event_id,
-                member,
-                None,
-                JoinKind::Confirmed,
-            )
-            .await
+    let (action, event_id, guild_id) = match CustomId::parse(custom_id) {
+        Some(CustomId::ListPage { page, filters }) => {
+            return list::change_page(ctx, interaction, page, &filters).await;
         }
-        "alt" => {
-            join::join(
-                ctx,
-                interaction,
-                event_id,
-                member,
-                None,
-                JoinKind::Alternate,
-            )
-            .await
+        Some(CustomId::PendingEvent { confirm, event_id }) => {
+            return create::resolve_preview(ctx, interaction, confirm, event_id).await;
+        }
+        Some(CustomId::Event {
+            action,
+            event_id,
+            guild_id,
+        }) => (action, event_id.to_string(), guild_id),
+        None => {
+            return Err(format_err!(
+                "Received unexpected component custom_id: {}",
+                custom_id
+            ))
+        }
+    };
+
+    // Buttons on messages sent over DM include the guild ID, since the interaction won't have one.
+    let dm_interaction;
+    let (interaction, member) = match (&interaction.member, guild_id) {
+        (Some(member), _) => (interaction, member as &dyn MemberLike),
+        (None, Some(guild_id)) => {
+            let mut dm = interaction.clone();
+            dm.guild_id = Some(guild_id);
+            dm_interaction = dm;
+            (&dm_interaction, &interaction.user as &dyn MemberLike)
+        }
+        (None, None) => return Err(format_err!("Interaction not in a guild")),
+    };
+
+    let join_kind = match action {
+        EventAction::Join => JoinKind::Confirmed,
+        EventAction::Alt => JoinKind::Alternate,
+        EventAction::Maybe => JoinKind::Maybe,
+        EventAction::Leave => return leave::leave(ctx, interaction, &event_id, member).await,
+        EventAction::CheckIn => {
+            return check_in::check_in(ctx, interaction, &event_id, member).await
         }
-        "maybe" => join::join(ctx, interaction, event_id, member, None, JoinKind::Maybe).await,
-        "leave" => leave::leave(ctx, interaction, event_id, member).await,
-        _ => Err(format_err!(
-            "Received unexpected component custom_id: {}",
-            custom_id
-        )),
+    };
+    join::join(
+        ctx,
+        interaction,
+        &event_id,
+        member,
+        None,
+        join_kind,
+        Default::default(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::{ButtonLabel, ButtonLabels};
+    use serenity::{builder::CreateComponents, model::id::GuildId};
+
+    #[test]
+    fn tag_filters_normalized() {
+        assert_eq!(parse_tag_filter("#Sherpa Run"), Ok("sherpa-run".to_owned()));
+        assert_eq!(parse_tag_filter("chill, "), Ok("chill".to_owned()));
+        assert_eq!(parse_tag_filter("chill,#chill"), Ok("chill".to_owned()));
+        assert!(parse_tag_filter("#!").is_err());
+        assert!(parse_tag_filter("sherpa, chill").is_err());
+    }
+
+    fn custom_ids_and_labels(components: &CreateComponents) -> Vec<(String, String)> {
+        components
+            .0
+            .iter()
+            .flat_map(|row| row["components"].as_array().unwrap().iter())
+            .map(|button| {
+                let id = button["custom_id"].as_str().unwrap().to_owned();
+                let label = button["label"].as_str().unwrap().to_owned();
+                (id, label)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn custom_button_labels_keep_custom_ids() {
+        let event = Event::default();
+        let label = |label: &str, emoji: Option<&str>| ButtonLabel {
+            label: label.to_owned(),
+            emoji: emoji.map(str::to_owned),
+        };
+        let custom = ButtonLabels {
+            join: label("I'm in", Some("👍")),
+            leave: label("I'm out", None),
+            alt: label("Backup", Some("🔁")),
+            maybe: label("Possibly", None),
+            ..Default::default()
+        };
+
+        let default_buttons = custom_ids_and_labels(&event.event_buttons(&Default::default()));
+        let custom_buttons = custom_ids_and_labels(&event.event_buttons(&custom));
+        assert_eq!(
+            custom_buttons
+                .iter()
+                .map(|(_, l)| l.as_str())
+                .collect::<Vec<_>>(),
+            vec!["I'm in", "I'm out", "Backup", "Possibly"]
+        );
+        let ids = |buttons: &[(String, String)]| -> Vec<String> {
+            buttons.iter().map(|(id, _)| id.clone()).collect()
+        };
+        assert_eq!(ids(&custom_buttons), ids(&default_buttons));
+
+        let actions = [
+            EventAction::Join,
+            EventAction::Leave,
+            EventAction::Alt,
+            EventAction::Maybe,
+        ];
+        for ((id, _), action) in custom_buttons.iter().zip(actions) {
+            assert_eq!(
+                CustomId::parse(id),
+                Some(CustomId::event(action, event.id, None))
+            );
+        }
+
+        // Buttons sent over DM also carry the guild ID.
+        let dm_buttons = custom_ids_and_labels(&event.dm_event_buttons(&custom, GuildId(5)));
+        assert_eq!(
+            CustomId::parse(&dm_buttons[0].0),
+            Some(CustomId::event(
+                EventAction::Join,
+                event.id,
+                Some(GuildId(5))
+            ))
+        );
     }
 }
@@ -0,0 +1,187 @@
+use super::get_event_from_str;
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use itertools::Itertools;
+use serenity::{
+    client::Context,
+    model::{
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        prelude::*,
+    },
+};
+use tracing::error;
+
+define_command_option!(
+    id: FromOpt,
+    name: "from_event_id",
+    description: "ID of the event to move members out of",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_command_option!(
+    id: ToOpt,
+    name: "to_event_id",
+    description: "ID of the event to move members into",
+    required: true,
+    option_type: OptionType::String(&[]),
+);
+
+define_command_option!(
+    id: User1Opt,
+    name: "user1",
+    description: "User to move",
+    required: true,
+    option_type: OptionType::User,
+);
+
+define_command_option!(
+    id: User2Opt,
+    name: "user2",
+    description: "Another user to move",
+    required: false,
+    option_type: OptionType::User,
+);
+
+define_command_option!(
+    id: User3Opt,
+    name: "user3",
+    description: "Another user to move",
+    required: false,
+    option_type: OptionType::User,
+);
+
+define_command_option!(
+    id: User4Opt,
+    name: "user4",
+    description: "Another user to move",
+    required: false,
+    option_type: OptionType::User,
+);
+
+define_command_option!(
+    id: User5Opt,
+    name: "user5",
+    description: "Another user to move",
+    required: false,
+    option_type: OptionType::User,
+);
+
+const USER_OPTS: [&str; 5] = ["user1", "user2", "user3", "user4", "user5"];
+
+define_leaf_command!(
+    LfgMoveMembers,
+    "move-members",
+    "Move users from one event to another as confirmed members",
+    lfg_move_members,
+    options: [FromOpt, ToOpt, User1Opt, User2Opt, User3Opt, User4Opt, User5Opt],
+);
+
+#[command_attr::hook]
+async fn lfg_move_members(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let from_id = match options.get_resolved("from_event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required from_event_id value")),
+    }?;
+    let to_id = match options.get_resolved("to_event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required to_event_id value")),
+    }?;
+    let mut targets = Vec::new();
+    for name in USER_OPTS {
+        match options.get_resolved(name)? {
+            Some(OptionValue::User(user, Some(member))) => targets.push((user, member)),
+            Some(OptionValue::User(..)) => {
+                return Err(format_err!(
+                    "Missing PartialMember, interaction not in a guild"
+                ))
+            }
+            Some(v) => return Err(format_err!("Unexpected resolved value type: {:?}", v)),
+            None => {}
+        }
+    }
+    // The same user could be given more than once.
+    let targets = targets
+        .into_iter()
+        .unique_by(|(user, _)| user.id)
+        .collect_vec();
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let (from, to) = match (
+        get_event_from_str(&event_manager, &from_id).await,
+        get_event_from_str(&event_manager, &to_id).await,
+    ) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(content), _) | (_, Err(content)) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if from.id == to.id {
+        let content = "Those are the same event, Captain. Nobody needs to go anywhere.";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+    let is_creator = member.user.id == from.creator.id && member.user.id == to.creator.id;
+    if !is_creator && !perms.administrator() {
+        let content = "Only the creator of both events or an admin can move members between them";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let members = targets
+        .iter()
+        .map(|target| target as &dyn MemberLike)
+        .collect_vec();
+    let results = match event_manager.move_members(&from.id, &to.id, &members).await {
+        Ok(results) => results,
+        Err(err) => {
+            error!(
+                "Failed to move members from {} to {}: {:?}",
+                from.id, to.id, err
+            );
+            let content = "Sorry Captain, I seem to be having trouble moving those members...";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let (moved, failed): (Vec<_>, Vec<_>) =
+        results.into_iter().partition(|(_, result)| result.is_ok());
+    let mut lines = Vec::new();
+    if !moved.is_empty() {
+        lines.push(format!(
+            "Moved {} from {} to {}",
+            moved.iter().map(|(id, _)| id.mention()).join(", "),
+            from.id,
+            to.id
+        ));
+    }
+    for (id, result) in failed {
+        if let Err(err) = result {
+            lines.push(format!("Couldn't move {}: {}", id.mention(), err));
+        }
+    }
+    interaction
+        .create_response(&ctx, lines.join("\n"), true)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use super::{edit_event_from_str, opts};
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+
+define_command_option!(
+    id: MutedOpt,
+    name: "muted",
+    description: "Skip the alert DM for this event? Defaults to true",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_leaf_command!(
+    LfgMuteAlert,
+    "mute-alert",
+    "Mute/unmute the alert DM for an event you're in, without leaving its group",
+    lfg_mute_alert,
+    options: [opts::EventId, MutedOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_mute_alert(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let muted = match options.get_resolved("muted")? {
+        Some(OptionValue::Boolean(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(true),
+    }?;
+
+    let user_id = interaction.user.id;
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = edit_event_from_str(&event_manager, &event_id, |event| {
+        match event.set_alert_muted(user_id, muted) {
+            Ok(()) if muted => format!(
+                "Got it, I won't DM you when the {} event at {} is about to start.",
+                event.activity,
+                event.timestamp()
+            ),
+            Ok(()) => format!(
+                "Got it, I'll DM you when the {} event at {} is about to start.",
+                event.activity,
+                event.timestamp()
+            ),
+            Err(_) => {
+                "*Hey, you're not even in that event... did you think I'd forget?*".to_owned()
+            }
+        }
+    })
+    .await?;
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -9,3 +9,80 @@ define_command_option!(
     required: true,
     option_type: OptionType::String(&[]),
 );
+
+define_command_option!(
+    id: Platform,
+    name: "platform",
+    description: "Platform the group plays on",
+    required: false,
+    option_type: OptionType::String(&[
+        ("PC", "pc"),
+        ("Xbox", "xbox"),
+        ("PlayStation", "playstation"),
+        ("Crossplay", "crossplay"),
+    ]),
+);
+
+define_command_option!(
+    id: GroupSize,
+    name: "group-size",
+    description: "Number of guardians per group",
+    required: true,
+    option_type: OptionType::Integer(GROUP_SIZE_CHOICES),
+);
+
+/// Every group size from 1 to MAX_GROUP_SIZE.
+pub const GROUP_SIZE_CHOICES: &[(&str, i32)] = &[
+    ("1", 1),
+    ("2", 2),
+    ("3", 3),
+    ("4", 4),
+    ("5", 5),
+    ("6", 6),
+    ("7", 7),
+    ("8", 8),
+    ("9", 9),
+    ("10", 10),
+    ("11", 11),
+    ("12", 12),
+];
+
+/// Choices for how often an event recurs, parsed with RecurKind::from_str.
+pub const RECUR_CHOICES: &[(&str, &str)] = &[
+    ("Never", "none"),
+    ("Weekly", "weekly"),
+    ("Every other week", "biweekly"),
+    ("Monthly", "monthly"),
+];
+
+define_command_option!(
+    id: Tags,
+    name: "tags",
+    description: "Tags separated by commas, like \"sherpa, chill\"",
+    required: false,
+    option_type: OptionType::String(&[]),
+);
+
+define_command_option!(
+    id: Tag,
+    name: "tag",
+    description: "Only events with this tag",
+    required: false,
+    option_type: OptionType::String(&[]),
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::MAX_GROUP_SIZE;
+
+    #[test]
+    fn group_size_choices_cover_valid_sizes() {
+        let sizes: Vec<_> = GROUP_SIZE_CHOICES.iter().map(|(_, size)| *size).collect();
+        let valid: Vec<_> = (1..=MAX_GROUP_SIZE as i32).collect();
+        assert_eq!(sizes, valid);
+        for (name, size) in GROUP_SIZE_CHOICES {
+            assert_eq!(*name, size.to_string());
+        }
+    }
+}
@@ -1,30 +1,38 @@
 use crate::{command::OptionType, util::*};
 use chrono::{
     format::{self, StrftimeItems},
-    DateTime, Datelike, Duration, TimeZone, Utc,
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc,
+    Weekday,
 };
 use chrono_tz::Tz;
 use lazy_static::lazy_static;
-use std::fmt::Write;
 use std::{
     cmp::Ordering,
     collections::HashMap,
     convert::{TryFrom, TryInto},
+    str::FromStr,
 };
 use thiserror::Error;
 use tracing::warn;
 
+// The time options are only required when the date isn't relative to the weekly reset, which
+// parse_datetime_options checks, since Discord can't.
 define_command_option_group!(
     id: Datetime,
     options: [Date, TimeHour, TimeMinute, TimeAmPm, Timezone],
 );
 
+// For commands where giving a new datetime is optional. All of the options must be given together.
+define_command_option_group!(
+    id: OptionalDatetime,
+    options: [Date, TimeHour, TimeMinute, TimeAmPm, Timezone],
+    optional: true,
+);
+
 define_command_option!(
     id: Date,
     name: "date",
-    description: "Event date as \"mm/dd\" (e.g. \"4/20\")",
-    // TODO: Support relative dates
-    //description: "Event date, either \"mm/dd\" (e.g. \"4/20\") or a day name (e.g. \"Friday\" for the next Friday)",
+    description: "Event date: \"mm/dd\" or \"dd/mm\" per server, \"today\", \"tomorrow\", a day name, or \"reset+30m\" (no time)",
     required: true,
     option_type: OptionType::String(&[]),
 );
@@ -33,7 +41,7 @@ define_command_option!(
     id: TimeHour,
     name: "hour",
     description: "Hour",
-    required: true,
+    required: false,
     option_type: OptionType::Integer(&[
         ("1", 1),
         ("2", 2),
@@ -54,7 +62,7 @@ define_command_option!(
     id: TimeMinute,
     name: "minute",
     description: "Minute",
-    required: true,
+    required: false,
     option_type: OptionType::Integer(&[
         (":00", 0),
         (":15", 15),
@@ -67,30 +75,148 @@ define_command_option!(
     id: TimeAmPm,
     name: "ampm",
     description: "AM/PM",
-    required: true,
+    required: false,
     option_type: OptionType::String(&[("AM", "AM"), ("PM", "PM")]),
 );
 
 define_command_option!(
     id: Timezone,
     name: "timezone",
-    description: "Time Zone",
-    required: true,
-    option_type: OptionType::String(&[("ET", "ET"), ("CT", "CT"), ("MT", "MT"), ("PT", "PT")]),
+    description: "Time Zone, if not your default set with /lfg timezone",
+    required: false,
+    option_type: OptionType::String(&[
+        ("ET", "ET"),
+        ("CT", "CT"),
+        ("MT", "MT"),
+        ("PT", "PT"),
+        ("UTC", "UTC"),
+        ("UK (GMT/BST)", "UK"),
+        ("CET (Central Europe)", "CET"),
+        ("AET (Sydney)", "AET"),
+        ("JST (Japan)", "JST"),
+    ]),
 );
 
-// TODO: Expand list of supported timezones.
+// Supported timezones, by the abbreviation shown to users, along with other names that users
+// commonly type for them (lowercase, since they're matched ignoring case). Each is also a choice of
+// the timezone option, which Discord limits to 25 choices.
+const TIMEZONES: &[(&str, Tz, &[&str])] = &[
+    (
+        "ET",
+        Tz::EST5EDT,
+        &[
+            "est",
+            "edt",
+            "eastern",
+            "us/eastern",
+            "america/new_york",
+            "est5edt",
+        ],
+    ),
+    (
+        "CT",
+        Tz::CST6CDT,
+        &[
+            "cst",
+            "cdt",
+            "central",
+            "us/central",
+            "america/chicago",
+            "cst6cdt",
+        ],
+    ),
+    (
+        "MT",
+        Tz::MST7MDT,
+        &[
+            "mst",
+            "mdt",
+            "mountain",
+            "us/mountain",
+            "america/denver",
+            "mst7mdt",
+        ],
+    ),
+    (
+        "PT",
+        Tz::PST8PDT,
+        &[
+            "pst",
+            "pdt",
+            "pacific",
+            "us/pacific",
+            "america/los_angeles",
+            "pst8pdt",
+        ],
+    ),
+    ("UTC", Tz::UTC, &["etc/utc", "universal", "zulu"]),
+    (
+        "UK",
+        Tz::Europe__London,
+        &["gmt", "bst", "london", "europe/london", "united kingdom"],
+    ),
+    (
+        "CET",
+        Tz::Europe__Paris,
+        &[
+            "cest",
+            "central european",
+            "europe/paris",
+            "europe/berlin",
+            "paris",
+            "berlin",
+        ],
+    ),
+    (
+        "AET",
+        Tz::Australia__Sydney,
+        &[
+            "aest",
+            "aedt",
+            "australian eastern",
+            "australia/sydney",
+            "australia/melbourne",
+            "sydney",
+        ],
+    ),
+    ("JST", Tz::Asia__Tokyo, &["japan", "asia/tokyo", "tokyo"]),
+];
+
 lazy_static! {
-    static ref TIMEZONE_MAP: HashMap<&'static str, Tz> = {
-        vec![
-            ("ET", Tz::EST5EDT),
-            ("CT", Tz::CST6CDT),
-            ("MT", Tz::MST7MDT),
-            ("PT", Tz::PST8PDT),
-        ]
-        .into_iter()
-        .collect()
-    };
+    static ref TIMEZONE_MAP: HashMap<&'static str, Tz> = TIMEZONES
+        .iter()
+        .map(|(abbrev, tz, _)| (*abbrev, *tz))
+        .collect();
+
+    // Maps every lowercase abbreviation and alias to its abbreviation.
+    static ref TIMEZONE_ALIASES: HashMap<String, &'static str> = TIMEZONES
+        .iter()
+        .flat_map(|(abbrev, _, aliases)| {
+            aliases
+                .iter()
+                .map(|alias| alias.to_string())
+                .chain(std::iter::once(abbrev.to_lowercase()))
+                .map(move |alias| (alias, *abbrev))
+        })
+        .collect();
+}
+
+/// The abbreviation shown to users for a timezone, or its full name if it isn't one of the
+/// supported timezones.
+pub fn timezone_abbrev(timezone: Tz) -> &'static str {
+    TIMEZONES
+        .iter()
+        .find(|(_, tz, _)| *tz == timezone)
+        .map_or(timezone.name(), |(abbrev, _, _)| *abbrev)
+}
+
+/// Normalizes a timezone typed by a user, e.g. "EST", "Eastern", or "America/New_York", to the
+/// abbreviation and Tz of the matching supported timezone.
+pub fn normalize_timezone(input: &str) -> Result<(&'static str, Tz), DatetimeParseError> {
+    let abbrev = *TIMEZONE_ALIASES
+        .get(&input.trim().to_lowercase())
+        .ok_or_else(|| DatetimeParseError::UnknownTimezone(input.to_owned()))?;
+    Ok((abbrev, TIMEZONE_MAP[abbrev]))
 }
 
 #[derive(Error, Debug)]
@@ -109,10 +235,22 @@ pub enum DatetimeParseError {
     DstJumpedOver(String),
     #[error("{0} is ambiguous, could be DST or not")]
     DstAmbiguous(String),
+    #[error("Invalid weekly reset offset '{0}'")]
+    InvalidResetOffset(String),
+    #[error("Time options given along with the weekly reset date '{0}'")]
+    TimeWithResetDate(String),
+    #[error("No time given for the date '{0}'")]
+    MissingTime(String),
+    #[error("No timezone given and the user has no default")]
+    MissingTimezone,
+    #[error("Unknown timezone '{0}'")]
+    UnknownTimezone(String),
     #[error(transparent)]
     OptionError(#[from] OptionError),
     #[error("Missing required option '{0}'")]
     MissingRequiredOption(&'static str),
+    #[error("Only some of the optional datetime options were given")]
+    IncompleteDatetime,
     #[error("Unexpected value type for option '{0}': {1:?}")]
     UnexpectedValueType(&'static str, OptionValue),
     #[error("Unexpected value for option '{0}': {1:?}")]
@@ -134,7 +272,8 @@ impl DatetimeParseError {
         use DatetimeParseError::*;
         match self {
             InvalidDateFormat(date, _) => Some(format!(
-                "'{}' isn't a valid date format; I need the month and day in that order (e.g. '2/20')",
+                "'{}' isn't a valid date format; I need the month and day in this server's order (e.g. '2/20' \
+                 or '20/2'), 'today', 'tomorrow', a day of the week, or 'reset' plus an offset",
                 date
             )),
             DateOutOfRange(date, _) => Some(format!(
@@ -152,14 +291,106 @@ impl DatetimeParseError {
             MaybeRecentPast(date) => Some(format!("I can't do that, {} is in the past.", date)),
             DstJumpedOver(datetime) => Some(format!("I can't do that, '{}' doesn't exist, daylight savings time jumps over that time.", datetime)),
             DstAmbiguous(datetime) => Some(format!("I can't do that, '{}' is ambiguous; it could be either daylight savings time or not.", datetime)),
+            IncompleteDatetime => Some("I need the whole date and time, Captain, or none of it.".to_owned()),
+            InvalidResetOffset(date) => Some(format!(
+                "'{}' isn't a valid time relative to reset; I need an offset in hours and minutes \
+                 like 'reset+30m', 'reset+1h30m', or 'reset-2h'",
+                date
+            )),
+            TimeWithResetDate(date) => Some(format!(
+                "'{}' already says when the event is, Captain; leave out the hour, minute, and AM/PM.",
+                date
+            )),
+            MissingTime(date) => Some(format!(
+                "What time on '{}', Captain? I need the hour, minute, and AM/PM.",
+                date
+            )),
+            MissingTimezone => Some(
+                "What timezone is that in, Captain? Give me one, or set your default with `/lfg timezone`."
+                    .to_owned(),
+            ),
+            UnknownTimezone(timezone) => Some(format!(
+                "I don't know the timezone '{}'. Try one of {}, or a name like 'Eastern' or 'America/New_York'.",
+                timezone,
+                TIMEZONES.iter().map(|(abbrev, _, _)| *abbrev).collect::<Vec<_>>().join(", "),
+            )),
             // All other error types are bugs/internal errors.
             _ => None,
         }
     }
 }
 
+/// Parses the Datetime options. Dates in user-facing errors are written in the given format, and
+/// dates like "reset+30m" are relative to the guild's weekly reset. If the timezone option isn't
+/// given, the user's default timezone is used if they have one.
 pub fn parse_datetime_options<O: OptionsExt>(
     options: O,
+    date_format: DateFormat,
+    weekly_reset: WeeklyReset,
+    default_timezone: Option<Tz>,
+) -> Result<DateTime<Tz>, DatetimeParseError> {
+    parse_datetime_options_at(
+        options,
+        date_format,
+        weekly_reset,
+        default_timezone,
+        Utc::now(),
+    )
+}
+
+/// Like parse_datetime_options, but for the OptionalDatetime options. Returns None if none of the
+/// datetime options were given.
+pub fn parse_optional_datetime_options<O: OptionsExt>(
+    options: O,
+    date_format: DateFormat,
+    weekly_reset: WeeklyReset,
+    default_timezone: Option<Tz>,
+) -> Result<Option<DateTime<Tz>>, DatetimeParseError> {
+    parse_optional_datetime_options_at(
+        options,
+        date_format,
+        weekly_reset,
+        default_timezone,
+        Utc::now(),
+    )
+}
+
+fn parse_optional_datetime_options_at<O: OptionsExt>(
+    options: O,
+    date_format: DateFormat,
+    weekly_reset: WeeklyReset,
+    default_timezone: Option<Tz>,
+    now: DateTime<Utc>,
+) -> Result<Option<DateTime<Tz>>, DatetimeParseError> {
+    // A date relative to the weekly reset doesn't take the time options.
+    let reset_date = matches!(
+        options.get_resolved("date")?,
+        Some(OptionValue::String(date)) if parse_reset_offset(date).is_some()
+    );
+    // The timezone can be left out in favor of the user's default, but not given on its own.
+    let mut given = 0;
+    for name in ["date", "hour", "minute", "ampm"] {
+        if options.get_resolved(name)?.is_some() {
+            given += 1;
+        }
+    }
+    let timezone_given = options.get_resolved("timezone")?.is_some();
+    match given {
+        0 if !timezone_given => Ok(None),
+        _ if given == 4 || reset_date => {
+            parse_datetime_options_at(options, date_format, weekly_reset, default_timezone, now)
+                .map(Some)
+        }
+        _ => Err(DatetimeParseError::IncompleteDatetime),
+    }
+}
+
+fn parse_datetime_options_at<O: OptionsExt>(
+    options: O,
+    date_format: DateFormat,
+    weekly_reset: WeeklyReset,
+    default_timezone: Option<Tz>,
+    now: DateTime<Utc>,
 ) -> Result<DateTime<Tz>, DatetimeParseError> {
     use DatetimeParseError::*;
 
@@ -168,15 +399,33 @@ pub fn parse_datetime_options<O: OptionsExt>(
         Some(v) => Err(UnexpectedValueType("date", v.clone())),
         None => Err(MissingRequiredOption("date")),
     }?;
+    let (timezone_str, timezone) = match options.get_resolved("timezone")? {
+        Some(OptionValue::String(v)) => normalize_timezone(v),
+        Some(v) => Err(UnexpectedValueType("timezone", v.clone())),
+        None => default_timezone
+            .map(|tz| (timezone_abbrev(tz), tz))
+            .ok_or(MissingTimezone),
+    }?;
+    if let Some(offset) = parse_reset_offset(date) {
+        // Rather than silently ignoring a time that might not match what the offset says.
+        for name in ["hour", "minute", "ampm"] {
+            if options.get_resolved(name)?.is_some() {
+                return Err(TimeWithResetDate(date.to_owned()));
+            }
+        }
+        let datetime = weekly_reset.resolve_offset(offset?, now);
+        return Ok(datetime.with_timezone(&timezone));
+    }
+
     let hour = match options.get_resolved("hour")? {
         Some(OptionValue::Integer(num)) => Ok(*num),
         Some(v) => Err(UnexpectedValueType("hour", v.clone())),
-        None => Err(MissingRequiredOption("hour")),
+        None => Err(MissingTime(date.to_owned())),
     }?;
     let minute = match options.get_resolved("minute")? {
         Some(OptionValue::Integer(num)) => Ok(*num),
         Some(v) => Err(UnexpectedValueType("minute", v.clone())),
-        None => Err(MissingRequiredOption("minute")),
+        None => Err(MissingTime(date.to_owned())),
     }?;
     let pm = match options.get_resolved("ampm")? {
         Some(OptionValue::String(v)) => match v.as_str() {
@@ -185,25 +434,18 @@ pub fn parse_datetime_options<O: OptionsExt>(
             _ => Err(UnexpectedValue("ampm", v.to_owned())),
         },
         Some(v) => Err(UnexpectedValueType("ampm", v.clone())),
-        None => Err(MissingRequiredOption("ampm")),
+        None => Err(MissingTime(date.to_owned())),
     }?;
-    let timezone_str = match options.get_resolved("timezone")? {
-        Some(OptionValue::String(v)) => Ok(v),
-        Some(v) => Err(UnexpectedValueType("timezone", v.clone())),
-        None => Err(MissingRequiredOption("timezone")),
-    }?;
-    let timezone = *TIMEZONE_MAP
-        .get(timezone_str.as_str())
-        .ok_or_else(|| UnexpectedValue("timezone", timezone_str.to_owned()))?;
 
     DatetimeComponents {
-        now: Utc::now(),
+        now,
         date,
         hour,
         minute,
         pm,
         timezone_str,
         timezone,
+        date_format,
     }
     .try_into()
 }
@@ -216,6 +458,7 @@ struct DatetimeComponents<'a> {
     pm: bool,
     timezone_str: &'a str,
     timezone: Tz,
+    date_format: DateFormat,
 }
 
 impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
@@ -224,9 +467,8 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
     fn try_from(value: DatetimeComponents) -> Result<Self, Self::Error> {
         use DatetimeParseError::*;
 
+        let now = value.now.with_timezone(&value.timezone);
         let mut parsed = format::Parsed::new();
-        format::parse(&mut parsed, value.date, StrftimeItems::new("%m/%d"))
-            .map_err(|err| InvalidDateFormat(value.date.to_owned(), err))?;
         parsed
             .set_hour12(value.hour)
             .map_err(|err| ParsedRejectedValue("hour", value.hour.to_string(), err))?;
@@ -237,6 +479,26 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
             .set_ampm(value.pm)
             .map_err(|err| ParsedRejectedValue("ampm", value.pm.to_string(), err))?;
 
+        let time = parsed
+            .to_naive_time()
+            .map_err(|err| NaiveTimeCreationFailed(err, parsed.clone()))?;
+        match resolve_relative_date(value.date, now.naive_local(), time) {
+            Some(date) => {
+                parsed
+                    .set_month(date.month().into())
+                    .map_err(|err| ParsedRejectedValue("month", date.month().to_string(), err))?;
+                parsed
+                    .set_day(date.day().into())
+                    .map_err(|err| ParsedRejectedValue("day", date.day().to_string(), err))?;
+            }
+            None => format::parse(
+                &mut parsed,
+                value.date,
+                StrftimeItems::new(value.date_format.input_format()),
+            )
+            .map_err(|err| InvalidDateFormat(value.date.to_owned(), err))?,
+        }
+
         // Figure out the year to use based on relation to the current date and on the fact that dates
         // shouldn't be in the past.
         //
@@ -248,7 +510,8 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
         // year and an input of "1/10" will use 2022. This also means that "12/11" will use 2022, even
         // though the user may be mistakenly using the wrong date and intended the current year. This
         // will be caught later, e.g. by checking that the date is no more than X months away.
-        let now = value.now.with_timezone(&value.timezone);
+        //
+        // Relative dates are resolved to a calendar date first, and so go through the same checks.
         let month = parsed.month.ok_or_else(|| ParsedMissingValue("month"))?;
         let day = parsed.day.ok_or_else(|| ParsedMissingValue("day"))?;
         let next_year = match month.cmp(&now.month()) {
@@ -256,9 +519,6 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
             Ordering::Equal => match day.cmp(&now.day()) {
                 Ordering::Less => true,
                 Ordering::Equal => {
-                    let time = parsed
-                        .to_naive_time()
-                        .map_err(|err| NaiveTimeCreationFailed(err, parsed.clone()))?;
                     let now_time = now.time();
                     if time >= now_time {
                         false
@@ -277,8 +537,10 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
         const RECENT_PAST_DATE_DAYS: i64 = 30;
 
         let year = now.year() + if next_year { 1 } else { 0 };
-        let date_str = |dt: DateTime<Tz>| dt.format("%-m/%-d/%-Y").to_string();
-        datetime_with_timezone_for_year(parsed.clone(), value.timezone, year.into())
+        let date_format = value.date_format;
+        let date_str =
+            |dt: DateTime<Tz>| date_format.format_date(Some(dt.year()), dt.month(), dt.day());
+        datetime_with_timezone_for_year(parsed.clone(), value.timezone, date_format, year.into())
             .and_then(|datetime| {
                 // Check whether the resulting date is unreasonably far away (arbitrarily chosen as ~6 months or
                 // 26 weeks), and if so return an error.
@@ -293,8 +555,12 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
                 // (current year) is valid and in the recent past (less than a ~month, 30 days), and
                 // replace the error with that.
                 if next_year && err.user_error().is_some() {
-                    let alternate_datetime =
-                        datetime_with_timezone_for_year(parsed, value.timezone, now.year().into());
+                    let alternate_datetime = datetime_with_timezone_for_year(
+                        parsed,
+                        value.timezone,
+                        date_format,
+                        now.year().into(),
+                    );
                     match alternate_datetime {
                         Ok(alt) => {
                             if now - alt <= Duration::days(RECENT_PAST_DATE_DAYS) {
@@ -312,9 +578,76 @@ impl TryFrom<DatetimeComponents<'_>> for DateTime<Tz> {
     }
 }
 
+/// Resolves a relative date, i.e. "today", "tomorrow", or a day of the week like "Friday" or "fri",
+/// to the date it refers to as of `now`. A day of the week means the next such day, which is only
+/// today if `time` hasn't passed yet. Returns None if the date isn't relative.
+fn resolve_relative_date(date: &str, now: NaiveDateTime, time: NaiveTime) -> Option<NaiveDate> {
+    let today = now.date();
+    let days_ahead = match date.trim().to_lowercase().as_str() {
+        "today" => 0,
+        "tomorrow" => 1,
+        day => {
+            let weekday = Weekday::from_str(day).ok()?;
+            let days =
+                (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+            if days == 0 && time < now.time() {
+                7
+            } else {
+                days
+            }
+        }
+    };
+    Some(today + Duration::days(days_ahead.into()))
+}
+
+/// Parses a date given relative to the weekly reset, i.e. "reset" optionally followed by a signed
+/// offset in days, hours, and minutes like "+30m" or "-1h30m", returning the offset. Returns None if
+/// the date isn't relative to reset.
+fn parse_reset_offset(date: &str) -> Option<Result<Duration, DatetimeParseError>> {
+    let normalized = date.trim().to_lowercase().replace(' ', "");
+    let offset = normalized.strip_prefix("reset")?;
+    let invalid = || DatetimeParseError::InvalidResetOffset(date.to_owned());
+    if offset.is_empty() {
+        return Some(Ok(Duration::zero()));
+    }
+
+    let parse = || {
+        let (negative, mut rest) = match (offset.strip_prefix('+'), offset.strip_prefix('-')) {
+            (Some(rest), _) => (false, rest),
+            (_, Some(rest)) => (true, rest),
+            _ => return Err(invalid()),
+        };
+        if rest.is_empty() {
+            return Err(invalid());
+        }
+        let mut total = Duration::zero();
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let amount: i64 = rest[..digits].parse::<u16>().map_err(|_| invalid())?.into();
+            let unit = match rest[digits..].chars().next() {
+                Some('d') => Duration::days(amount),
+                Some('h') => Duration::hours(amount),
+                Some('m') => Duration::minutes(amount),
+                _ => return Err(invalid()),
+            };
+            total = total + unit;
+            rest = &rest[digits + 1..];
+        }
+        // Anything a week or more away from reset would be better given relative to another reset.
+        if total >= Duration::weeks(1) {
+            return Err(invalid());
+        }
+        Ok(if negative { -total } else { total })
+    };
+    Some(parse())
+}
+
 fn datetime_with_timezone_for_year<Tz: TimeZone>(
     mut parsed: format::Parsed,
     timezone: Tz,
+    date_format: DateFormat,
     year: i64,
 ) -> Result<DateTime<Tz>, DatetimeParseError> {
     use DatetimeParseError::*;
@@ -333,20 +666,27 @@ fn datetime_with_timezone_for_year<Tz: TimeZone>(
                     .to_naive_time()
                     .map_err(|err| NaiveTimeCreationFailed(err, parsed.clone()))?;
                 let mut datetime_str = time.format("%-I:%M %p ").to_string();
-                write!(&mut datetime_str, "{}/{}", month, day).unwrap();
+                datetime_str.push_str(&date_format.format_date(None, month, day));
                 Ok(datetime_str)
             };
-            Err(match err.kind() {
-                format::ParseErrorKind::OutOfRange => {
-                    let year = parsed.year.ok_or_else(|| ParsedMissingValue("year"))?;
-                    DateOutOfRange(format!("{}/{}/{}", month, day, year), err)
-                }
-                format::ParseErrorKind::Impossible if month == 3 && day == 14 => {
-                    DstJumpedOver(datetime_str(&parsed)?)
-                }
-                format::ParseErrorKind::NotEnough if month == 11 && day == 7 => {
-                    DstAmbiguous(datetime_str(&parsed)?)
-                }
+            if err.kind() == format::ParseErrorKind::OutOfRange {
+                let year = parsed.year.ok_or_else(|| ParsedMissingValue("year"))?;
+                return Err(DateOutOfRange(
+                    date_format.format_date(Some(year), month, day),
+                    err,
+                ));
+            }
+
+            // Otherwise the date and time are valid, so it's a problem if the local time doesn't
+            // map to exactly one instant in the timezone, i.e. it's during a DST transition.
+            let local = match (parsed.to_naive_date(), parsed.to_naive_time()) {
+                (Ok(date), Ok(time)) => Some(date.and_time(time)),
+                _ => None,
+            };
+            let mapped = local.map(|local| timezone.from_local_datetime(&local));
+            Err(match mapped {
+                Some(LocalResult::None) => DstJumpedOver(datetime_str(&parsed)?),
+                Some(LocalResult::Ambiguous(..)) => DstAmbiguous(datetime_str(&parsed)?),
                 _ => DatetimeCreationFailed(err, parsed),
             })
         }
@@ -356,10 +696,486 @@ fn datetime_with_timezone_for_year<Tz: TimeZone>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::util::OptionError;
     use assert_matches::assert_matches;
     use test_env_log::test;
     use DatetimeParseError::*;
 
+    /// Vec-backed OptionsExt, to test the full option parsing path. A None value is an option
+    /// that's present but is missing its resolved value.
+    struct FakeOptions(Vec<(&'static str, Option<OptionValue>)>);
+
+    impl FakeOptions {
+        fn valid() -> Self {
+            FakeOptions(vec![
+                ("date", Some(OptionValue::String("4/22".to_owned()))),
+                ("hour", Some(OptionValue::Integer(2))),
+                ("minute", Some(OptionValue::Integer(15))),
+                ("ampm", Some(OptionValue::String("PM".to_owned()))),
+                ("timezone", Some(OptionValue::String("ET".to_owned()))),
+            ])
+        }
+
+        fn with(mut self, name: &'static str, value: Option<OptionValue>) -> Self {
+            self.0.retain(|(n, _)| *n != name);
+            self.0.push((name, value));
+            self
+        }
+
+        fn without(mut self, name: &'static str) -> Self {
+            self.0.retain(|(n, _)| *n != name);
+            self
+        }
+    }
+
+    impl OptionsExt for FakeOptions {
+        fn get_resolved(&self, name: impl AsRef<str>) -> Result<Option<&OptionValue>, OptionError> {
+            let name = name.as_ref();
+            match self.0.iter().find(|(n, _)| *n == name) {
+                Some((_, Some(value))) => Ok(Some(value)),
+                Some((_, None)) => Err(OptionError::MissingResolvedValue(name.to_owned())),
+                None => Ok(None),
+            }
+        }
+    }
+
+    fn parse_at(options: FakeOptions) -> Result<DateTime<Tz>, DatetimeParseError> {
+        let now = DateTime::parse_from_rfc3339("2021-04-20T00:00:00Z").unwrap();
+        parse_datetime_options_at(
+            options,
+            DateFormat::Us,
+            Default::default(),
+            None,
+            now.with_timezone(&Utc),
+        )
+    }
+
+    #[test]
+    fn options_valid() {
+        let expected = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-04:00").unwrap();
+        assert_matches!(parse_at(FakeOptions::valid()), Ok(dt) if dt == expected);
+    }
+
+    #[test]
+    fn options_missing() {
+        assert_matches!(
+            parse_at(FakeOptions::valid().without("date")),
+            Err(MissingRequiredOption("date"))
+        );
+        for name in ["hour", "minute", "ampm"] {
+            let err = parse_at(FakeOptions::valid().without(name)).unwrap_err();
+            assert_matches!(&err, MissingTime(date) if date == "4/22", "{}", name);
+            assert!(err.user_error().is_some());
+        }
+        assert_matches!(
+            parse_at(FakeOptions::valid().without("timezone")),
+            Err(MissingTimezone)
+        );
+    }
+
+    #[test]
+    fn default_timezone_fallback() {
+        let now = DateTime::parse_from_rfc3339("2021-04-20T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let parse = |options, default_timezone| {
+            parse_datetime_options_at(
+                options,
+                DateFormat::Us,
+                Default::default(),
+                default_timezone,
+                now,
+            )
+        };
+
+        // A given timezone wins over the default.
+        let eastern = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-04:00").unwrap();
+        assert_matches!(
+            parse(FakeOptions::valid(), Some(Tz::PST8PDT)),
+            Ok(dt) if dt == eastern && dt.timezone() == Tz::EST5EDT
+        );
+
+        let pacific = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-07:00").unwrap();
+        assert_matches!(
+            parse(FakeOptions::valid().without("timezone"), Some(Tz::PST8PDT)),
+            Ok(dt) if dt == pacific && dt.timezone() == Tz::PST8PDT
+        );
+        let err = parse(FakeOptions::valid().without("timezone"), None).unwrap_err();
+        assert_matches!(err, MissingTimezone);
+        assert!(err.user_error().unwrap().contains("/lfg timezone"));
+
+        // Errors name the default timezone like a given one.
+        let options = FakeOptions::valid()
+            .without("timezone")
+            .with("date", Some(OptionValue::String("4/19".to_owned())))
+            .with("ampm", Some(OptionValue::String("AM".to_owned())));
+        let now = DateTime::parse_from_rfc3339("2021-04-19T12:00:00-05:00").unwrap();
+        assert_matches!(
+            parse_datetime_options_at(
+                options,
+                DateFormat::Us,
+                Default::default(),
+                Some(Tz::CST6CDT),
+                now.with_timezone(&Utc)
+            ),
+            Err(TimeHasPassed(time)) if time == "2:15 AM CT"
+        );
+        assert_eq!(timezone_abbrev(Tz::UTC), "UTC");
+    }
+
+    #[test]
+    fn options_missing_resolved_value() {
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("hour", None)),
+            Err(OptionError(OptionError::MissingResolvedValue(n))) if n == "hour"
+        );
+    }
+
+    #[test]
+    fn options_wrong_type() {
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("minute", Some(OptionValue::String("15".to_owned())))),
+            Err(UnexpectedValueType("minute", OptionValue::String(v))) if v == "15"
+        );
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("date", Some(OptionValue::Integer(4)))),
+            Err(UnexpectedValueType("date", OptionValue::Integer(4)))
+        );
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("ampm", Some(OptionValue::Boolean(true)))),
+            Err(UnexpectedValueType("ampm", OptionValue::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn optional_options() {
+        let now = DateTime::parse_from_rfc3339("2021-04-20T00:00:00Z").unwrap();
+        let parse = |options| {
+            parse_optional_datetime_options_at(
+                options,
+                DateFormat::Us,
+                Default::default(),
+                Some(Tz::PST8PDT),
+                now.with_timezone(&Utc),
+            )
+        };
+        let expected = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-04:00").unwrap();
+        assert_matches!(parse(FakeOptions::valid()), Ok(Some(dt)) if dt == expected);
+        assert_matches!(parse(FakeOptions(vec![])), Ok(None));
+        assert_matches!(
+            parse(FakeOptions::valid().without("hour")),
+            Err(IncompleteDatetime)
+        );
+        assert_matches!(
+            parse(FakeOptions(vec![(
+                "timezone",
+                Some(OptionValue::String("ET".to_owned()))
+            )])),
+            Err(IncompleteDatetime)
+        );
+        // The timezone falls back to the user's default.
+        let expected = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-07:00").unwrap();
+        assert_matches!(
+            parse(FakeOptions::valid().without("timezone")),
+            Ok(Some(dt)) if dt == expected
+        );
+    }
+
+    #[test]
+    fn options_unexpected_value() {
+        let string = |s: &str| Some(OptionValue::String(s.to_owned()));
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("ampm", string("noon"))),
+            Err(UnexpectedValue("ampm", v)) if v == "noon"
+        );
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("timezone", string("XT"))),
+            Err(UnknownTimezone(v)) if v == "XT"
+        );
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("date", string("4-22"))),
+            Err(InvalidDateFormat(date, _)) if date == "4-22"
+        );
+    }
+
+    #[test]
+    fn timezone_aliases_normalized() {
+        let cases = [
+            ("ET", "ET", Tz::EST5EDT),
+            ("est", "ET", Tz::EST5EDT),
+            (" Eastern ", "ET", Tz::EST5EDT),
+            ("America/New_York", "ET", Tz::EST5EDT),
+            ("CDT", "CT", Tz::CST6CDT),
+            ("US/Central", "CT", Tz::CST6CDT),
+            ("mountain", "MT", Tz::MST7MDT),
+            ("America/Denver", "MT", Tz::MST7MDT),
+            ("PST", "PT", Tz::PST8PDT),
+            ("america/los_angeles", "PT", Tz::PST8PDT),
+            ("utc", "UTC", Tz::UTC),
+            ("GMT", "UK", Tz::Europe__London),
+            ("Europe/London", "UK", Tz::Europe__London),
+            ("CEST", "CET", Tz::Europe__Paris),
+            ("Europe/Berlin", "CET", Tz::Europe__Paris),
+            ("AEST", "AET", Tz::Australia__Sydney),
+            ("jst", "JST", Tz::Asia__Tokyo),
+            ("Asia/Tokyo", "JST", Tz::Asia__Tokyo),
+        ];
+        for (input, abbrev, tz) in cases {
+            assert_eq!(
+                normalize_timezone(input).unwrap(),
+                (abbrev, tz),
+                "{}",
+                input
+            );
+        }
+
+        // Options given as an alias are normalized too, including in error messages.
+        let string = |s: &str| Some(OptionValue::String(s.to_owned()));
+        let expected = DateTime::parse_from_rfc3339("2021-04-22T14:15:00-04:00").unwrap();
+        assert_matches!(
+            parse_at(FakeOptions::valid().with("timezone", string("Eastern"))),
+            Ok(dt) if dt == expected
+        );
+    }
+
+    #[test]
+    fn unknown_timezone_rejected() {
+        for input in ["", "XT", "asdfgh", "America/Phoenix", "eastern standard"] {
+            let err = normalize_timezone(input).unwrap_err();
+            assert_matches!(&err, UnknownTimezone(v) if v == input);
+            let user_error = err.user_error().unwrap();
+            assert!(
+                user_error.contains("ET, CT, MT, PT, UTC, UK, CET, AET, JST"),
+                "{}",
+                user_error
+            );
+        }
+    }
+
+    #[test]
+    fn reset_offset_parsed() {
+        let ok_cases = [
+            ("reset", Duration::zero()),
+            ("Reset", Duration::zero()),
+            ("reset+30m", Duration::minutes(30)),
+            ("reset + 1h30m", Duration::minutes(90)),
+            ("reset-2h", Duration::hours(-2)),
+            ("reset+1d2h", Duration::hours(26)),
+        ];
+        for (date, expected) in ok_cases {
+            assert_matches!(parse_reset_offset(date), Some(Ok(d)) if d == expected, "{}", date);
+        }
+
+        for date in [
+            "reset+",
+            "reset30m",
+            "reset+30",
+            "reset+h",
+            "reset+30s",
+            "reset+7d",
+            "reset+99999m",
+        ] {
+            assert_matches!(
+                parse_reset_offset(date),
+                Some(Err(InvalidResetOffset(d))) if d == date
+            );
+        }
+        assert_matches!(parse_reset_offset("4/20"), None);
+        assert_matches!(parse_reset_offset("tuesday"), None);
+    }
+
+    #[test]
+    fn reset_offset_resolved_across_weeks() {
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let reset = WeeklyReset::default();
+        let cases = [
+            // Shortly before reset, an offset either way is relative to the coming reset, unless
+            // that would already have passed.
+            ("2021-04-20T15:00:00Z", "reset+30m", "2021-04-20T17:30:00Z"),
+            ("2021-04-20T15:00:00Z", "reset-1h", "2021-04-20T16:00:00Z"),
+            ("2021-04-20T16:30:00Z", "reset-1h", "2021-04-27T16:00:00Z"),
+            // Shortly after reset, a positive offset can still be relative to the reset that just
+            // happened.
+            ("2021-04-20T17:10:00Z", "reset+30m", "2021-04-20T17:30:00Z"),
+            ("2021-04-20T17:40:00Z", "reset+30m", "2021-04-27T17:30:00Z"),
+            ("2021-04-20T17:10:00Z", "reset", "2021-04-27T17:00:00Z"),
+            // Later in the week, relative to next week's reset.
+            ("2021-04-24T12:00:00Z", "reset+1d", "2021-04-28T17:00:00Z"),
+            ("2021-04-24T12:00:00Z", "reset-3d", "2021-04-24T17:00:00Z"),
+            ("2021-12-30T00:00:00Z", "reset+2h", "2022-01-04T19:00:00Z"),
+        ];
+        for (now, date, expected) in cases {
+            let offset = parse_reset_offset(date).unwrap().unwrap();
+            assert_eq!(
+                reset.resolve_offset(offset, utc(now)),
+                utc(expected),
+                "{} at {}",
+                date,
+                now
+            );
+        }
+    }
+
+    #[test]
+    fn reset_offset_options() {
+        let string = |s: &str| Some(OptionValue::String(s.to_owned()));
+        // A time given along with the date is turned away rather than ignored.
+        let err = parse_at(FakeOptions::valid().with("date", string("reset+30m"))).unwrap_err();
+        assert_matches!(&err, TimeWithResetDate(d) if d == "reset+30m");
+        assert!(err.user_error().is_some());
+
+        // Without one, the result is in the given timezone.
+        let expected = DateTime::parse_from_rfc3339("2021-04-20T13:30:00-04:00").unwrap();
+        let options = || {
+            FakeOptions::valid()
+                .with("date", string("reset+30m"))
+                .without("hour")
+                .without("minute")
+                .without("ampm")
+        };
+        assert_matches!(
+            parse_at(options()),
+            Ok(dt) if dt == expected && dt.timezone() == Tz::EST5EDT
+        );
+        assert_matches!(
+            parse_at(options().without("timezone")),
+            Err(MissingTimezone)
+        );
+        assert_matches!(
+            parse_at(options().with("date", string("reset+1x"))),
+            Err(InvalidResetOffset(d)) if d == "reset+1x"
+        );
+
+        let now = DateTime::parse_from_rfc3339("2021-04-20T00:00:00Z").unwrap();
+        assert_matches!(
+            parse_optional_datetime_options_at(
+                options(),
+                DateFormat::Us,
+                Default::default(),
+                None,
+                now.with_timezone(&Utc)
+            ),
+            Ok(Some(dt)) if dt == expected
+        );
+        assert_matches!(
+            parse_optional_datetime_options_at(
+                options().with("hour", Some(OptionValue::Integer(2))),
+                DateFormat::Us,
+                Default::default(),
+                None,
+                now.with_timezone(&Utc)
+            ),
+            Err(TimeWithResetDate(_))
+        );
+
+        // The offset is from the guild's reset.
+        let friday_morning = WeeklyReset {
+            weekday: Weekday::Fri,
+            time: NaiveTime::from_hms(9, 0, 0),
+        };
+        let expected = DateTime::parse_from_rfc3339("2021-04-23T09:30:00Z").unwrap();
+        assert_matches!(
+            parse_datetime_options_at(
+                options(),
+                DateFormat::Us,
+                friday_morning,
+                None,
+                now.with_timezone(&Utc)
+            ),
+            Ok(dt) if dt == expected
+        );
+    }
+
+    #[test]
+    fn date_format_in_errors() {
+        let parse = |date: &str, date_format, now: &str| {
+            let now = DateTime::parse_from_rfc3339(now).unwrap();
+            let options = FakeOptions::valid().with("date", Some(OptionValue::String(date.into())));
+            parse_datetime_options_at(
+                options,
+                date_format,
+                Default::default(),
+                None,
+                now.with_timezone(&Utc),
+            )
+        };
+
+        // Dates are typed in the same order they're shown in.
+        let cases = [
+            (
+                DateFormat::Us,
+                ["10/1", "4/31", "3/14"],
+                "10/1/2021",
+                "4/31/2021",
+                "2:00 AM 3/14",
+            ),
+            (
+                DateFormat::Iso,
+                ["10/1", "4/31", "3/14"],
+                "2021-10-01",
+                "2021-04-31",
+                "2:00 AM 03-14",
+            ),
+            (
+                DateFormat::DayMonth,
+                ["1/10", "31/4", "14/3"],
+                "1/10/2021",
+                "31/4/2021",
+                "2:00 AM 14/3",
+            ),
+        ];
+        for (date_format, [far_input, invalid_input, dst_input], far, invalid, dst) in cases {
+            assert_matches!(
+                parse(far_input, date_format, "2021-02-01T00:00:00Z"),
+                Err(TooFarAway(date)) if date == far
+            );
+            assert_matches!(
+                parse(invalid_input, date_format, "2021-02-01T00:00:00Z"),
+                Err(DateOutOfRange(date, _)) if date == invalid
+            );
+            let options = FakeOptions::valid()
+                .with("date", Some(OptionValue::String(dst_input.into())))
+                .with("hour", Some(OptionValue::Integer(2)))
+                .with("minute", Some(OptionValue::Integer(0)))
+                .with("ampm", Some(OptionValue::String("AM".into())))
+                .with("timezone", Some(OptionValue::String("PT".into())));
+            let now = DateTime::parse_from_rfc3339("2021-03-13T00:00:00-08:00").unwrap();
+            assert_matches!(
+                parse_datetime_options_at(
+                    options,
+                    date_format,
+                    Default::default(),
+                    None,
+                    now.with_timezone(&Utc)
+                ),
+                Err(DstJumpedOver(datetime)) if datetime == dst
+            );
+        }
+    }
+
+    #[test]
+    fn day_month_dates_parsed() {
+        let parse = |date: &str, date_format| {
+            let now = DateTime::parse_from_rfc3339("2021-04-20T00:00:00Z").unwrap();
+            let options = FakeOptions::valid().with("date", Some(OptionValue::String(date.into())));
+            parse_datetime_options_at(
+                options,
+                date_format,
+                Default::default(),
+                None,
+                now.with_timezone(&Utc),
+            )
+        };
+
+        let expected = DateTime::parse_from_rfc3339("2021-05-03T14:15:00-04:00").unwrap();
+        assert_matches!(parse("3/5", DateFormat::DayMonth), Ok(dt) if dt == expected);
+        assert_matches!(parse("5/3", DateFormat::Us), Ok(dt) if dt == expected);
+        assert_matches!(
+            parse("4/22", DateFormat::DayMonth),
+            Err(InvalidDateFormat(date, _)) if date == "4/22"
+        );
+    }
+
     macro_rules! test_parse {
         ($(
             $test_name:ident => {
@@ -386,6 +1202,7 @@ mod tests {
                         pm: $pm,
                         timezone_str: $timezone_str,
                         timezone,
+                        date_format: DateFormat::Us,
                     });
                     assert_matches!(result, $($pat)*);
                 }
@@ -529,6 +1346,191 @@ mod tests {
             timezone: "ET",
             expected: "2021-11-07T02:00:00-05:00",
         },
+        // European DST starts and ends on different dates than in the US, and at 1:00 AM UTC
+        // rather than at 2:00 AM local time.
+        eu_dst_started1 => {
+            now: "2021-03-27T00:00:00Z",
+            date: "3/28",
+            hour: 12,
+            minute: 0,
+            pm: true,
+            timezone: "UK",
+            expected: "2021-03-28T12:00:00+01:00",
+        },
+        // 2:00 AM 3/28 is unambiguously after DST starts in the UK
+        eu_dst_started2 => {
+            now: "2021-03-27T00:00:00Z",
+            date: "3/28",
+            hour: 2,
+            minute: 0,
+            pm: false,
+            timezone: "UK",
+            expected: "2021-03-28T02:00:00+01:00",
+        },
+        eu_dst_ended1 => {
+            now: "2021-10-30T00:00:00+02:00",
+            date: "10/31",
+            hour: 12,
+            minute: 0,
+            pm: true,
+            timezone: "CET",
+            expected: "2021-10-31T12:00:00+01:00",
+        },
+        // 3:00 AM 10/31 is unambiguously after DST ends in Central Europe
+        eu_dst_ended2 => {
+            now: "2021-10-30T00:00:00+02:00",
+            date: "10/31",
+            hour: 3,
+            minute: 0,
+            pm: false,
+            timezone: "CET",
+            expected: "2021-10-31T03:00:00+01:00",
+        },
+        // A US DST transition date is an ordinary day in Europe.
+        eu_on_us_dst_end => {
+            now: "2021-11-06T00:00:00Z",
+            date: "11/7",
+            hour: 1,
+            minute: 30,
+            pm: false,
+            timezone: "UK",
+            expected: "2021-11-07T01:30:00+00:00",
+        },
+        // Zones without DST never hit transition errors.
+        utc_on_dst_end => {
+            now: "2021-10-30T00:00:00Z",
+            date: "10/31",
+            hour: 1,
+            minute: 30,
+            pm: false,
+            timezone: "UTC",
+            expected: "2021-10-31T01:30:00+00:00",
+        },
+        jst_on_dst_start => {
+            now: "2021-03-13T00:00:00Z",
+            date: "3/14",
+            hour: 2,
+            minute: 30,
+            pm: false,
+            timezone: "JST",
+            expected: "2021-03-14T02:30:00+09:00",
+        },
+    }
+
+    // 4/20/2021 is a Tuesday.
+    test_parse_ok! {
+        today => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "today",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-20T14:15:00-04:00",
+        },
+        tomorrow => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "Tomorrow",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-21T14:15:00-04:00",
+        },
+        tomorrow_next_year => {
+            now: "2021-12-31T20:00:00-05:00",
+            date: "tomorrow",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2022-01-01T14:15:00-05:00",
+        },
+        day_name_later_this_week => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "Friday",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-23T14:15:00-04:00",
+        },
+        day_name_abbreviated => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "fri",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-23T14:15:00-04:00",
+        },
+        day_name_next_week => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "monday",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-26T14:15:00-04:00",
+        },
+        day_name_today_later => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "Tuesday",
+            hour: 2,
+            minute: 15,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-20T14:15:00-04:00",
+        },
+        day_name_today_passed => {
+            now: "2021-04-20T14:00:00-04:00",
+            date: "tue",
+            hour: 1,
+            minute: 0,
+            pm: true,
+            timezone: "ET",
+            expected: "2021-04-27T13:00:00-04:00",
+        },
+        // Still Tuesday in PT, though it's already Wednesday in UTC.
+        relative_date_in_timezone => {
+            now: "2021-04-21T02:00:00Z",
+            date: "today",
+            hour: 8,
+            minute: 0,
+            pm: true,
+            timezone: "PT",
+            expected: "2021-04-20T20:00:00-07:00",
+        },
+        day_name_in_timezone => {
+            now: "2021-04-21T02:00:00Z",
+            date: "Wednesday",
+            hour: 8,
+            minute: 0,
+            pm: true,
+            timezone: "PT",
+            expected: "2021-04-21T20:00:00-07:00",
+        },
+    }
+
+    test_parse! {
+        today_passed => {
+            now: "2021-04-20T15:00:00-04:00",
+            date: "today",
+            hour: 2,
+            minute: 30,
+            pm: true,
+            timezone: "ET",
+            pattern: Err(TimeHasPassed(time)) if time == "2:30 PM ET"
+        },
+        unknown_day_name => {
+            now: "2021-04-20T12:00:00-04:00",
+            date: "someday",
+            hour: 2,
+            minute: 30,
+            pm: true,
+            timezone: "ET",
+            pattern: Err(InvalidDateFormat(date, _)) if date == "someday"
+        },
     }
 
     test_parse! {
@@ -696,5 +1698,147 @@ mod tests {
             timezone: "ET",
             pattern: Err(DstAmbiguous(datetime)) if datetime == "1:30 AM 11/7"
         },
+        // [1:00, 2:00) AM 3/28 UK time doesn't exist, and [2:00, 3:00) AM 3/28 CET
+        eu_dst_start_doesnt_exist1 => {
+            now: "2021-03-27T00:00:00Z",
+            date: "3/28",
+            hour: 1,
+            minute: 0,
+            pm: false,
+            timezone: "UK",
+            pattern: Err(DstJumpedOver(datetime)) if datetime == "1:00 AM 3/28"
+        },
+        eu_dst_start_doesnt_exist2 => {
+            now: "2021-03-27T00:00:00Z",
+            date: "3/28",
+            hour: 2,
+            minute: 30,
+            pm: false,
+            timezone: "CET",
+            pattern: Err(DstJumpedOver(datetime)) if datetime == "2:30 AM 3/28"
+        },
+        // [1:00, 2:00) AM 10/31 UK time is ambiguous, and [2:00, 3:00) AM 10/31 CET
+        eu_dst_end_ambiguous1 => {
+            now: "2021-10-30T00:00:00Z",
+            date: "10/31",
+            hour: 1,
+            minute: 30,
+            pm: false,
+            timezone: "UK",
+            pattern: Err(DstAmbiguous(datetime)) if datetime == "1:30 AM 10/31"
+        },
+        eu_dst_end_ambiguous2 => {
+            now: "2021-10-30T00:00:00Z",
+            date: "10/31",
+            hour: 2,
+            minute: 0,
+            pm: false,
+            timezone: "CET",
+            pattern: Err(DstAmbiguous(datetime)) if datetime == "2:00 AM 10/31"
+        },
+        // Australia's DST is in the southern summer, so it ends in April.
+        aet_dst_end_ambiguous => {
+            now: "2021-04-01T00:00:00Z",
+            date: "4/4",
+            hour: 2,
+            minute: 30,
+            pm: false,
+            timezone: "AET",
+            pattern: Err(DstAmbiguous(datetime)) if datetime == "2:30 AM 4/4"
+        },
+    }
+
+    // Property tests for the year inference in DatetimeComponents::try_from, over random instants,
+    // timezones, and dates, checking what has to hold whatever the input.
+    mod year_inference {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn date_strategy() -> impl Strategy<Value = String> {
+            prop_oneof![
+                4 => (1u32..=12, 1u32..=31).prop_map(|(m, d)| format!("{}/{}", m, d)),
+                1 => prop::sample::select(vec!["today", "tomorrow", "fri", "Monday"])
+                    .prop_map(str::to_owned),
+            ]
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(2000))]
+
+            #[test]
+            fn parsed_datetimes_are_upcoming_and_round_trip(
+                // 2020 through 2033, which covers both leap and non-leap years.
+                now_secs in 1_577_836_800i64..2_019_686_400,
+                date in date_strategy(),
+                hour in 1i64..=12,
+                minute in 0i64..60,
+                pm: bool,
+                tz_idx in 0..TIMEZONES.len(),
+            ) {
+                let now = Utc.timestamp(now_secs, 0);
+                let (timezone_str, timezone, _) = TIMEZONES[tz_idx];
+                let result = <DateTime<Tz>>::try_from(DatetimeComponents {
+                    now,
+                    date: &date,
+                    hour,
+                    minute,
+                    pm,
+                    timezone_str,
+                    timezone,
+                    date_format: DateFormat::Us,
+                });
+
+                let local_now = now.with_timezone(&timezone);
+                let month_day = date
+                    .split_once('/')
+                    .map(|(m, d)| (m.parse::<u32>().unwrap(), d.parse::<u32>().unwrap()));
+                match result {
+                    Ok(datetime) => {
+                        prop_assert!(datetime >= now, "{} is before {}", datetime, now);
+                        prop_assert!(datetime - local_now < Duration::weeks(26));
+                        prop_assert_eq!(datetime.timezone(), timezone);
+
+                        // The local time is what was asked for, on the date asked for.
+                        let hour24 = (hour % 12) as u32 + if pm { 12 } else { 0 };
+                        let time = NaiveTime::from_hms(hour24, minute as u32, 0);
+                        prop_assert_eq!(datetime.naive_local().time(), time);
+                        if let Some((month, day)) = month_day {
+                            prop_assert_eq!((datetime.month(), datetime.day()), (month, day));
+                            let years = [local_now.year(), local_now.year() + 1];
+                            prop_assert!(years.contains(&datetime.year()));
+                        }
+                        let round_trip = timezone
+                            .from_local_datetime(&datetime.naive_local())
+                            .single();
+                        prop_assert_eq!(round_trip, Some(datetime));
+                    }
+                    Err(err) => {
+                        prop_assert!(
+                            err.user_error().is_some(),
+                            "Unexpected internal error: {:?}",
+                            err
+                        );
+                        match err {
+                            TimeHasPassed(_) => {
+                                if let Some(month_day) = month_day {
+                                    let today = (local_now.month(), local_now.day());
+                                    prop_assert_eq!(month_day, today);
+                                }
+                            }
+                            MaybeRecentPast(_) => {
+                                let (month, day) = month_day.unwrap();
+                                let this_year =
+                                    NaiveDate::from_ymd_opt(local_now.year(), month, day);
+                                let this_year = this_year.unwrap();
+                                let ago = local_now.naive_local().date() - this_year;
+                                prop_assert!(ago >= Duration::zero());
+                                prop_assert!(ago <= Duration::days(31));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
     }
 }
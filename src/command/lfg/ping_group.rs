@@ -0,0 +1,133 @@
+use super::{get_event_from_str, opts};
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::{
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        prelude::*,
+    },
+    utils::MessageBuilder,
+};
+use tracing::error;
+
+define_command_option!(
+    id: GroupOpt,
+    name: "group",
+    description: "Group number, as shown on the event",
+    required: true,
+    option_type: OptionType::Integer(&[]),
+);
+
+define_command_option!(
+    id: MessageOpt,
+    name: "message",
+    description: "Message to send along with the ping",
+    required: false,
+    option_type: OptionType::String(&[]),
+);
+
+define_leaf_command!(
+    LfgPingGroup,
+    "ping-group",
+    "DM the members of one of an event's groups (creator or admin only)",
+    lfg_ping_group,
+    options: [opts::EventId, GroupOpt, MessageOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_ping_group(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let group = match options.get_resolved("group")? {
+        Some(OptionValue::Integer(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required group value")),
+    }?;
+    let message = match options.get_resolved("message")? {
+        None => Ok(None),
+        Some(OptionValue::String(v)) => Ok(Some(v)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+    }?;
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let event = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+    if member.user.id != event.creator.id && !perms.administrator() {
+        let content = "Only the event creator or an admin can ping a group";
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    // Groups are numbered from 1 in the embed.
+    let (label, members) = match usize::try_from(group - 1)
+        .ok()
+        .and_then(|idx| Some((event.group_label(idx), event.group_members(idx)?)))
+    {
+        Some(group) => group,
+        None => {
+            let content = format!("Event {} doesn't have a group {}", event.id, group);
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let mut builder = MessageBuilder::new();
+    builder.mention(&member.user).push(format!(
+        " is rallying {} for the {} event at {}!",
+        label,
+        event.activity,
+        event.timestamp()
+    ));
+    if let Some(message) = message {
+        builder.push("\n> ").push_safe(message);
+    }
+    let content = builder.build();
+
+    let mut failed = Vec::new();
+    for target in &members {
+        if let Err(err) = event_manager.dm_event(target.id, &event, &content).await {
+            error!(
+                "Failed to ping {} for event {}: {:?}",
+                target.id, event.id, err
+            );
+            failed.push(target.id.mention().to_string());
+        }
+    }
+
+    let content = if failed.is_empty() {
+        format!("Pinged all {} members of {}!", members.len(), label)
+    } else {
+        format!(
+            "Pinged {}, but I couldn't reach {}. *Maybe they have DMs turned off?*",
+            label,
+            failed.join(", ")
+        )
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use super::opts;
+use crate::{command::OptionType, event::GroupLayout, util::*};
+use anyhow::{format_err, Context as _, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+use std::convert::TryFrom;
+
+define_command_option!(
+    id: CountOpt,
+    name: "count",
+    description: "Number of guardians signed up",
+    required: true,
+    option_type: OptionType::Integer(&[]),
+);
+
+define_leaf_command!(
+    LfgPreviewGroups,
+    "preview-groups",
+    "Preview how a number of guardians would be split into groups",
+    lfg_preview_groups,
+    options: [opts::GroupSize, CountOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_preview_groups(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let group_size = match options.get_resolved("group-size")? {
+        Some(OptionValue::Integer(v)) => Ok(u8::try_from(*v).context("Group size too large")?),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required group-size value")),
+    }?;
+    let count = match options.get_resolved("count")? {
+        Some(OptionValue::Integer(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required count value")),
+    }?;
+
+    let content = match usize::try_from(count) {
+        Ok(count) => format!(
+            "{} guardians in groups of {}: **{}**",
+            count,
+            group_size,
+            GroupLayout::new(group_size, count)
+        ),
+        Err(_) => "The count can't be negative, Captain.".to_owned(),
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
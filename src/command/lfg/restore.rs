@@ -0,0 +1,120 @@
+use super::opts;
+use crate::{
+    command::admin::check_admin,
+    event::{EventEmbedMessage, EventId, RestoreError},
+    util::*,
+};
+use anyhow::{format_err, Context as _, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        ApplicationCommandInteractionDataOptionValue as OptionValue,
+    },
+};
+use std::str::FromStr;
+use tracing::error;
+
+define_leaf_command!(
+    LfgRestore,
+    "restore",
+    "Restore a recently cleaned up event (admin only)",
+    lfg_restore,
+    options: [opts::EventId, opts::time::OptionalDatetime],
+);
+
+#[command_attr::hook]
+async fn lfg_restore(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+
+    if !check_admin(ctx, interaction).await? {
+        return Ok(());
+    }
+
+    let event_id = match EventId::from_str(event_id) {
+        Ok(id) => id,
+        Err(_) => {
+            let content = "That's not a valid event ID, Captain. They look like this: `dsc123`";
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let default_timezone = event_manager.user_timezone(interaction.user.id).await;
+    let datetime = match opts::time::parse_optional_datetime_options(
+        options,
+        event_manager.date_format(),
+        event_manager.weekly_reset(),
+        default_timezone,
+    ) {
+        Ok(datetime) => datetime,
+        Err(err) => {
+            let content = match err.user_error() {
+                Some(descr) => descr,
+                None => {
+                    error!("Error parsing datetime options: {:?}", err);
+                    "Sorry Captain, something went wrong with my internal chronometers..."
+                        .to_owned()
+                }
+            };
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let event = match event_manager.restore_event(event_id, datetime).await {
+        Ok(event) => event,
+        Err(err) => {
+            let content = match err {
+                RestoreError::NotFound(id) => {
+                    format!("I don't remember any recent event with ID '{}'", id)
+                }
+                RestoreError::NeedsDatetime(id) => format!(
+                    "Event {} already happened, so I need a new date and time for it.",
+                    id
+                ),
+                RestoreError::DatetimeInPast(_) => {
+                    "I can't do that, that time is in the past.".to_owned()
+                }
+                RestoreError::Other(err) => {
+                    error!("Failed to restore event {}: {:?}", event_id, err);
+                    "Sorry Captain, I seem to be having trouble restoring that event...".to_owned()
+                }
+            };
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let content = format!(
+        "Event {} has been restored as **{}**! *Back from the Infinite Forest...*",
+        event_id, event.id
+    );
+    interaction
+        .create_embed_response(
+            &ctx,
+            &content,
+            event.as_embed(event_manager.embed_style()),
+            event.event_buttons(event_manager.button_labels()),
+            true,
+        )
+        .await
+        .context("Failed to respond after restoring event")?;
+    event_manager
+        .keep_embed_updated(
+            event.id,
+            EventEmbedMessage::EphemeralResponse(interaction.clone(), content),
+        )
+        .await?;
+
+    Ok(())
+}
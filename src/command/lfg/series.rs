@@ -0,0 +1,180 @@
+use super::{get_event_from_str, opts};
+use crate::{command::OptionType, event::EventMember, util::*};
+use anyhow::{format_err, Result};
+use itertools::Itertools;
+use serenity::{
+    client::Context,
+    model::{
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        prelude::*,
+    },
+};
+use tracing::error;
+
+// Keeps the list short enough to read at a glance.
+const DEFAULT_RECURRENCES: i64 = 5;
+const MAX_RECURRENCES: i64 = 12;
+
+define_command_group!(
+    LfgSeries,
+    "series",
+    "Manage a recurring event and its future recurrences",
+    subcommands: [LfgSeriesRecurrences, LfgSeriesTransfer]
+);
+
+define_command_option!(
+    id: CountOpt,
+    name: "count",
+    description: "How many recurrences to show",
+    required: false,
+    option_type: OptionType::Integer(&[]),
+);
+
+define_leaf_command!(
+    LfgSeriesRecurrences,
+    "recurrences",
+    "Show when a recurring event's next recurrences will be",
+    lfg_series_recurrences,
+    options: [opts::EventId, CountOpt],
+);
+
+define_command_option!(
+    id: NewCreatorOpt,
+    name: "user",
+    description: "New creator of the event and its future recurrences",
+    required: true,
+    option_type: OptionType::User,
+);
+
+define_leaf_command!(
+    LfgSeriesTransfer,
+    "transfer",
+    "Give an event and its future recurrences to a new creator (creator or admin only)",
+    lfg_series_transfer,
+    options: [
+        opts::EventId,
+        NewCreatorOpt,
+    ],
+);
+
+#[command_attr::hook]
+async fn lfg_series_recurrences(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let count = match options.get_resolved("count")? {
+        Some(OptionValue::Integer(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(DEFAULT_RECURRENCES),
+    }?;
+    if !(1..=MAX_RECURRENCES).contains(&count) {
+        let content = format!(
+            "I can show between 1 and {} recurrences, Captain.",
+            MAX_RECURRENCES
+        );
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let event = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => event,
+        Err(content) => {
+            interaction.create_response(&ctx, content, true).await?;
+            return Ok(());
+        }
+    };
+
+    let recurrences = event.upcoming_recurrences(count as usize);
+    let content = if recurrences.is_empty() {
+        format!("Event **{}** doesn't recur.", event.id)
+    } else {
+        let mut content = format!(
+            "Event **{}** recurs {}. Its next {} recurrences are:",
+            event.id,
+            event.recur,
+            recurrences.len()
+        );
+        for datetime in recurrences {
+            content.push_str(&format!("\n• <t:{}:F>", datetime.timestamp()));
+        }
+        content
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
+
+#[command_attr::hook]
+async fn lfg_series_transfer(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let new_creator = match options.get_resolved("user")? {
+        Some(OptionValue::User(user, Some(member))) => Ok((user, member)),
+        Some(OptionValue::User(..)) => Err(format_err!(
+            "Missing PartialMember, interaction not in a guild"
+        )),
+        Some(v) => Err(format_err!("Unexpected resolved value type: {:?}", v)),
+        None => Err(format_err!("Missing required user value")),
+    }?;
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| format_err!("Guild interaction missing member data"))?;
+    let perms = member
+        .permissions
+        .as_ref()
+        .ok_or_else(|| format_err!("Interaction missing member permissions"))?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let check_result = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => {
+            if member.user.id == event.creator.id || perms.administrator() {
+                Ok(event.id)
+            } else {
+                Err("Only the event creator or an admin can transfer an event".to_owned())
+            }
+        }
+        Err(err) => Err(err),
+    };
+
+    let user_mention = new_creator.user().mention();
+    let content = match check_result {
+        Ok(event_id) => {
+            let new_creator = EventMember::from(&new_creator as &dyn MemberLike);
+            match event_manager.transfer_series(&event_id, new_creator).await {
+                Ok(Some(ids)) => format!(
+                    "{} is now the creator of {}, along with its future recurrences.",
+                    user_mention,
+                    ids.iter().join(", ")
+                ),
+                Ok(None) => format!("I couldn't find an event with ID '{}'", event_id),
+                Err(err) => {
+                    error!("Failed to transfer event {}: {:?}", event_id, err);
+                    "Sorry Captain, I seem to be having trouble transferring that event..."
+                        .to_owned()
+                }
+            }
+        }
+        Err(str) => str,
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
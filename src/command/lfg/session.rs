@@ -0,0 +1,248 @@
+use crate::{event::EventEmbedMessage, util::*};
+use anyhow::Result;
+use serenity::{
+    async_trait,
+    builder::{CreateComponents, CreateEmbed},
+    client::Context,
+    model::{interactions::application_command::ApplicationCommandInteraction, prelude::*},
+};
+use std::time::Duration;
+
+/// The interaction operations used by flows that converse with the user, like asking for an event's
+/// description, so that tests can drive those flows with a fake rather than Discord. Responses are
+/// all ephemeral.
+#[async_trait]
+pub trait InteractionSession: Send + Sync {
+    /// The user that issued the interaction.
+    fn user(&self) -> &User;
+
+    async fn create_response(&self, content: String) -> Result<()>;
+
+    async fn edit_response(&self, content: String) -> Result<()>;
+
+    async fn edit_embed_response(
+        &self,
+        content: String,
+        embed: CreateEmbed,
+        components: CreateComponents,
+    ) -> Result<()>;
+
+    async fn create_followup(&self, content: String) -> Result<MessageId>;
+
+    async fn edit_followup(&self, id: MessageId, content: String) -> Result<()>;
+
+    /// DMs the user, returning the DM as a message to keep updated with the event's embed.
+    async fn dm_embed(
+        &self,
+        content: String,
+        embed: CreateEmbed,
+        components: CreateComponents,
+    ) -> Result<EventEmbedMessage>;
+
+    /// Waits up to `timeout` for the user's next message in the channel, returning its content.
+    /// The message itself is deleted, since the rest of the conversation is ephemeral.
+    async fn await_reply(&self, timeout: Duration) -> Result<Option<String>>;
+
+    /// The response as a message to keep updated with an event's embed, once the response shows
+    /// one with the given content.
+    fn embed_message(&self, content: String) -> Option<EventEmbedMessage>;
+}
+
+/// An InteractionSession for a slash command interaction.
+pub struct CommandSession<'a> {
+    ctx: &'a Context,
+    interaction: &'a ApplicationCommandInteraction,
+}
+
+impl<'a> CommandSession<'a> {
+    pub fn new(ctx: &'a Context, interaction: &'a ApplicationCommandInteraction) -> Self {
+        CommandSession { ctx, interaction }
+    }
+}
+
+#[async_trait]
+impl InteractionSession for CommandSession<'_> {
+    fn user(&self) -> &User {
+        &self.interaction.user
+    }
+
+    async fn create_response(&self, content: String) -> Result<()> {
+        Ok(InteractionExt::create_response(self.interaction, self.ctx, content, true).await?)
+    }
+
+    async fn edit_response(&self, content: String) -> Result<()> {
+        InteractionExt::edit_response(self.interaction, self.ctx, content).await?;
+        Ok(())
+    }
+
+    async fn edit_embed_response(
+        &self,
+        content: String,
+        embed: CreateEmbed,
+        components: CreateComponents,
+    ) -> Result<()> {
+        InteractionExt::edit_embed_response(self.interaction, self.ctx, content, embed, components)
+            .await?;
+        Ok(())
+    }
+
+    async fn create_followup(&self, content: String) -> Result<MessageId> {
+        let msg =
+            InteractionExt::create_followup(self.interaction, self.ctx, content, true).await?;
+        Ok(msg.id)
+    }
+
+    async fn edit_followup(&self, id: MessageId, content: String) -> Result<()> {
+        self.interaction
+            .edit_followup_message(self.ctx, id, |msg| msg.content(content))
+            .await?;
+        Ok(())
+    }
+
+    async fn dm_embed(
+        &self,
+        content: String,
+        embed: CreateEmbed,
+        components: CreateComponents,
+    ) -> Result<EventEmbedMessage> {
+        let dm = self
+            .interaction
+            .user
+            .direct_message(self.ctx, |msg| {
+                msg.content(content).set_embed(embed).components(|c| {
+                    *c = components;
+                    c
+                })
+            })
+            .await?;
+        Ok(EventEmbedMessage::Dm(dm.channel_id, dm.id))
+    }
+
+    async fn await_reply(&self, timeout: Duration) -> Result<Option<String>> {
+        let reply = match self
+            .interaction
+            .user
+            .await_reply(self.ctx)
+            .timeout(timeout)
+            .await
+        {
+            Some(reply) => reply,
+            None => return Ok(None),
+        };
+        if let Err(err) = reply.delete(self.ctx).await {
+            // If the user is doing this in an event channel, our delete will race with
+            // ChannelUpdater's delete, so ignore "Unknown Message" errors.
+            if !err.is_discord_json_error(DiscordJsonErrorCode::UnknownMessage) {
+                return Err(err.into());
+            }
+        }
+        Ok(Some(reply.content.clone()))
+    }
+
+    fn embed_message(&self, content: String) -> Option<EventEmbedMessage> {
+        Some(EventEmbedMessage::EphemeralResponse(
+            self.interaction.clone(),
+            content,
+        ))
+    }
+}
+
+#[cfg(test)]
+pub use fake::FakeSession;
+
+#[cfg(test)]
+mod fake {
+    use super::*;
+    use std::{collections::VecDeque, sync::Mutex};
+
+    /// A session that replies with canned messages and records what was sent to the user.
+    #[derive(Debug, Default)]
+    pub struct FakeSession {
+        pub user: User,
+        /// Whether editing the response with an embed fails, as if Discord rejected it.
+        pub fail_embed_edits: bool,
+        replies: Mutex<VecDeque<String>>,
+        sent: Mutex<Vec<String>>,
+    }
+
+    impl FakeSession {
+        /// A session in which the user replies with each of the given messages in turn, and then
+        /// stops replying.
+        pub fn with_replies(replies: &[&str]) -> Self {
+            FakeSession {
+                replies: Mutex::new(replies.iter().map(|r| r.to_string()).collect()),
+                ..Default::default()
+            }
+        }
+
+        /// Everything sent to the user so far, each prefixed by how it was sent.
+        pub fn sent(&self) -> Vec<String> {
+            self.sent.lock().unwrap().clone()
+        }
+
+        fn send(&self, kind: &str, content: String) {
+            self.sent
+                .lock()
+                .unwrap()
+                .push(format!("{}: {}", kind, content));
+        }
+    }
+
+    #[async_trait]
+    impl InteractionSession for FakeSession {
+        fn user(&self) -> &User {
+            &self.user
+        }
+
+        async fn create_response(&self, content: String) -> Result<()> {
+            self.send("response", content);
+            Ok(())
+        }
+
+        async fn edit_response(&self, content: String) -> Result<()> {
+            self.send("edit", content);
+            Ok(())
+        }
+
+        async fn edit_embed_response(
+            &self,
+            content: String,
+            _embed: CreateEmbed,
+            _components: CreateComponents,
+        ) -> Result<()> {
+            if self.fail_embed_edits {
+                return Err(anyhow::format_err!("Simulated failure"));
+            }
+            self.send("edit with embed", content);
+            Ok(())
+        }
+
+        async fn create_followup(&self, content: String) -> Result<MessageId> {
+            self.send("followup", content);
+            Ok(MessageId(1))
+        }
+
+        async fn edit_followup(&self, _id: MessageId, content: String) -> Result<()> {
+            self.send("edit followup", content);
+            Ok(())
+        }
+
+        async fn dm_embed(
+            &self,
+            content: String,
+            _embed: CreateEmbed,
+            _components: CreateComponents,
+        ) -> Result<EventEmbedMessage> {
+            self.send("dm", content);
+            Ok(EventEmbedMessage::Dm(ChannelId(1), MessageId(1)))
+        }
+
+        async fn await_reply(&self, _timeout: Duration) -> Result<Option<String>> {
+            Ok(self.replies.lock().unwrap().pop_front())
+        }
+
+        fn embed_message(&self, _content: String) -> Option<EventEmbedMessage> {
+            None
+        }
+    }
+}
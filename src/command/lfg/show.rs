@@ -32,7 +32,13 @@ async fn lfg_show(
     match get_event_from_str(&event_manager, &event_id).await {
         Ok(event) => {
             interaction
-                .create_embed_response(&ctx, "", event.as_embed(), event.event_buttons(), false)
+                .create_embed_response(
+                    &ctx,
+                    "",
+                    event.as_embed(event_manager.embed_style()),
+                    event.event_buttons(event_manager.button_labels()),
+                    false,
+                )
                 .await?;
 
             let msg = interaction.get_interaction_response(&ctx).await?;
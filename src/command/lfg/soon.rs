@@ -0,0 +1,78 @@
+use super::event_list_embed;
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    builder::CreateComponents,
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+use std::time::Duration;
+
+const DEFAULT_HOURS: i64 = 12;
+// Anything longer than a week is better served by just scrolling the event channels.
+const MAX_HOURS: i64 = 7 * 24;
+
+define_command_option!(
+    id: HoursOpt,
+    name: "hours",
+    description: "How many hours ahead to look (default 12)",
+    required: false,
+    option_type: OptionType::Integer(&[]),
+);
+
+define_leaf_command!(
+    LfgSoon,
+    "soon",
+    "List events starting within the next few hours",
+    lfg_soon,
+    options: [HoursOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_soon(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let hours = match options.get_resolved("hours")? {
+        Some(OptionValue::Integer(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(DEFAULT_HOURS),
+    }?;
+    if !(1..=MAX_HOURS).contains(&hours) {
+        let content = format!(
+            "I can only look between 1 and {} hours ahead, Captain.",
+            MAX_HOURS
+        );
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let window = Duration::from_secs(hours as u64 * 60 * 60);
+    let events = event_manager.events_within(window).await;
+    if events.is_empty() {
+        let content = format!("There are no events in the next {} hours", hours);
+        interaction.create_response(&ctx, content, true).await?;
+        return Ok(());
+    }
+
+    let content = format!(
+        "{} events starting in the next {} hours:",
+        events.len(),
+        hours
+    );
+    interaction
+        .create_embed_response(
+            &ctx,
+            content,
+            event_list_embed(&events),
+            CreateComponents::default(),
+            true,
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    prelude::Mentionable,
+};
+
+define_command_option!(
+    id: UserOpt,
+    name: "user",
+    description: "User to show the streak for, if not yourself",
+    required: false,
+    option_type: OptionType::User,
+);
+
+define_leaf_command!(
+    LfgStreak,
+    "streak",
+    "Show how many weeks in a row someone has attended an event",
+    lfg_streak,
+    options: [UserOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_streak(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let user = match options.get_resolved("user")? {
+        None => Ok(&interaction.user),
+        Some(OptionValue::User(user, _)) => Ok(user),
+        Some(v) => Err(format_err!("Unexpected resolved value type: {:?}", v)),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let weeks = event_manager.attendance_streak(user.id).await;
+    let who = if user.id == interaction.user.id {
+        "You've".to_owned()
+    } else {
+        format!("{} has", user.mention())
+    };
+    let content = match weeks {
+        0 => format!(
+            "{} not attended an event in the last week. *Time to get back out there, Guardian.*",
+            who
+        ),
+        1 => format!("{} attended an event this week or last.", who),
+        weeks => format!("{} attended an event {} weeks in a row!", who, weeks),
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
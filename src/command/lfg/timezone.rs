@@ -0,0 +1,93 @@
+use super::opts::time::{normalize_timezone, timezone_abbrev};
+use crate::{command::OptionType, util::*};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+};
+use tracing::error;
+
+define_command_option!(
+    id: TimezoneOpt,
+    name: "timezone",
+    description: "Your timezone, e.g. \"ET\", \"Eastern\", or \"America/New_York\"",
+    required: false,
+    option_type: OptionType::String(&[]),
+);
+
+define_command_option!(
+    id: ClearOpt,
+    name: "clear",
+    description: "Clear your default timezone?",
+    required: false,
+    option_type: OptionType::Boolean,
+);
+
+define_leaf_command!(
+    LfgTimezone,
+    "timezone",
+    "Show or set your default timezone, used when you leave it out of a date and time",
+    lfg_timezone,
+    options: [TimezoneOpt, ClearOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_timezone(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let timezone = match options.get_resolved("timezone")? {
+        Some(OptionValue::String(v)) => Ok(Some(v)),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(None),
+    }?;
+    let clear = match options.get_resolved("clear")? {
+        Some(OptionValue::Boolean(v)) => Ok(*v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Ok(false),
+    }?;
+
+    let user = interaction.user.id;
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = match (timezone, clear) {
+        (Some(_), true) => {
+            "I can set your default timezone or clear it, Captain, not both.".to_owned()
+        }
+        (None, true) => match event_manager.set_user_timezone(user, None).await {
+            Ok(()) => "Cleared your default timezone. You'll need to give one with each date and \
+                       time."
+                .to_owned(),
+            Err(err) => {
+                error!("Failed to clear user timezone: {:?}", err);
+                "Sorry Captain, I seem to be having trouble clearing your timezone...".to_owned()
+            }
+        },
+        (Some(timezone), false) => match normalize_timezone(timezone) {
+            Ok((abbrev, tz)) => match event_manager.set_user_timezone(user, Some(tz)).await {
+                Ok(()) => format!(
+                    "Your default timezone is now {}. I'll use it whenever you leave out the \
+                     timezone.",
+                    abbrev
+                ),
+                Err(err) => {
+                    error!("Failed to set user timezone: {:?}", err);
+                    "Sorry Captain, I seem to be having trouble saving your timezone...".to_owned()
+                }
+            },
+            // Unknown timezones are the only possible error, which are always user errors.
+            Err(err) => err.user_error().unwrap_or_else(|| err.to_string()),
+        },
+        (None, false) => match event_manager.user_timezone(user).await {
+            Some(tz) => format!("Your default timezone is {}.", timezone_abbrev(tz)),
+            None => "You haven't set a default timezone. Set one with `/lfg timezone`, giving \
+                     the timezone."
+                .to_owned(),
+        },
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use super::{get_event_from_str, opts};
+use crate::{event::JoinKind, util::*};
+use anyhow::{format_err, Result};
+use itertools::Itertools;
+use serenity::{
+    client::Context,
+    model::interactions::application_command::{
+        ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    },
+    prelude::Mentionable,
+};
+
+define_leaf_command!(
+    LfgWho,
+    "who",
+    "List who has joined an event, in the order they joined",
+    lfg_who,
+    options: [opts::EventId],
+);
+
+#[command_attr::hook]
+async fn lfg_who(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => {
+            let order = event.join_order();
+            if order.is_empty() {
+                format!("Nobody has joined {} yet, Captain.", event.id)
+            } else {
+                let lines = order
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (member, kind))| {
+                        let kind = match kind {
+                            JoinKind::Confirmed => "confirmed",
+                            JoinKind::Alternate => "alt",
+                            JoinKind::Maybe => "maybe",
+                        };
+                        let joined = match member.joined_at {
+                            Some(joined_at) => format!("<t:{}:f>", joined_at.timestamp()),
+                            None => "join time unknown".to_owned(),
+                        };
+                        format!("{}. {} ({}) | {}", i + 1, member.id.mention(), kind, joined)
+                    })
+                    .join("\n");
+                format!("**Join order for {}**\n{}", event.id, lines)
+            }
+        }
+        Err(content) => content,
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use super::{get_event_from_str, opts};
+use crate::{
+    command::OptionType,
+    event::{Event, JoinKind},
+    util::*,
+};
+use anyhow::{format_err, Result};
+use serenity::{
+    client::Context,
+    model::{
+        interactions::application_command::{
+            ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+        },
+        prelude::*,
+    },
+};
+
+define_command_option!(
+    id: UserOpt,
+    name: "user",
+    description: "User to check on",
+    required: true,
+    option_type: OptionType::User,
+);
+
+define_leaf_command!(
+    LfgWhois,
+    "whois",
+    "Check whether a user is in an event, and how",
+    lfg_whois,
+    options: [opts::EventId, UserOpt],
+);
+
+#[command_attr::hook]
+async fn lfg_whois(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &Vec<ApplicationCommandInteractionDataOption>,
+) -> Result<()> {
+    let event_id = match options.get_resolved("event_id")? {
+        Some(OptionValue::String(v)) => Ok(v),
+        Some(v) => Err(format_err!("Unexpected value type: {:?}", v)),
+        None => Err(format_err!("Missing required event_id value")),
+    }?;
+    let user = match options.get_resolved("user")? {
+        Some(OptionValue::User(user, _)) => Ok(user),
+        Some(v) => Err(format_err!("Unexpected resolved value type: {:?}", v)),
+        None => Err(format_err!("Missing required user value")),
+    }?;
+
+    let event_manager = ctx.get_event_manager(interaction).await?;
+    let content = match get_event_from_str(&event_manager, &event_id).await {
+        Ok(event) => status_message(&event, user.id),
+        Err(content) => content,
+    };
+    interaction.create_response(&ctx, content, true).await?;
+
+    Ok(())
+}
+
+/// Describes how the user is part of the event, if at all.
+fn status_message(event: &Event, user: UserId) -> String {
+    let status = match event.kind_of(user) {
+        Some(JoinKind::Confirmed) => "is confirmed for",
+        Some(JoinKind::Alternate) => "is an alt for",
+        Some(JoinKind::Maybe) => "is a maybe for",
+        None => "isn't in",
+    };
+    let mut message = format!("{} {} {}", user.mention(), status, event.id);
+    if event.is_creator(user) {
+        message.push_str(match event.kind_of(user) {
+            Some(_) => ", and created it",
+            None => ", but created it",
+        });
+    }
+    message.push('.');
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(id: u64) -> User {
+        let mut user = User::default();
+        user.id = UserId(id);
+        user
+    }
+
+    #[test]
+    fn status_for_each_kind() {
+        // The default event was created by, and has confirmed, user 1.
+        let mut event = Event::default();
+        event.join(&user(2), JoinKind::Confirmed).unwrap();
+        event.join(&user(3), JoinKind::Alternate).unwrap();
+        event.join(&user(4), JoinKind::Maybe).unwrap();
+        let id = event.id;
+
+        assert_eq!(
+            status_message(&event, UserId(1)),
+            format!("<@1> is confirmed for {}, and created it.", id)
+        );
+        assert_eq!(
+            status_message(&event, UserId(2)),
+            format!("<@2> is confirmed for {}.", id)
+        );
+        assert_eq!(
+            status_message(&event, UserId(3)),
+            format!("<@3> is an alt for {}.", id)
+        );
+        assert_eq!(
+            status_message(&event, UserId(4)),
+            format!("<@4> is a maybe for {}.", id)
+        );
+        assert_eq!(
+            status_message(&event, UserId(5)),
+            format!("<@5> isn't in {}.", id)
+        );
+
+        // The creator may have left their own event.
+        event.leave(&user(1)).unwrap();
+        assert_eq!(
+            status_message(&event, UserId(1)),
+            format!("<@1> isn't in {}, but created it.", id)
+        );
+    }
+}
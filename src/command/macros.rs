@@ -45,7 +45,35 @@ macro_rules! define_command_option_group {
             }
 
         }
-    }
+    };
+    (
+        id: $id:ident,
+        options: [$($($opt_path:ident)::+),+ $(,)?],
+        optional: true $(,)?
+    ) => {
+        // Same as above, but with every option made optional.
+        #[allow(non_snake_case)]
+        pub mod $id {
+            #[allow(unused)]
+            use super::*;
+
+            lazy_static::lazy_static! {
+                static ref OWNED_OPTIONS: Vec<$crate::command::CommandOption> = {
+                    let mut vec = Vec::new();
+                    $(
+                        vec.extend($($opt_path)::+ ::OPTIONS.iter().map(|&opt| opt.clone()));
+                    )+
+                    vec.iter_mut().for_each(|opt: &mut $crate::command::CommandOption| {
+                        opt.required = false
+                    });
+                    vec
+                };
+
+                pub static ref OPTIONS: Vec<&'static $crate::command::CommandOption> =
+                    OWNED_OPTIONS.iter().collect();
+            }
+        }
+    };
 }
 
 macro_rules! define_leaf_command {
@@ -57,6 +85,7 @@ macro_rules! define_leaf_command {
 
             lazy_static::lazy_static! {
                 static ref OPTIONS: Vec<&'static $crate::command::CommandOption> = {
+                    #[allow(unused_mut)]
                     let mut vec = Vec::new();
                     $(
                         vec.extend_from_slice(&*$($opt_path)::+ ::OPTIONS);
@@ -1,12 +1,15 @@
+use crate::{activity::Activity, event::AuditEntry, util::*};
 use anyhow::{ensure, format_err, Context as _, Result};
-use futures::future::BoxFuture;
+use chrono::Utc;
+use futures::{future::BoxFuture, Future, FutureExt};
 use lazy_static::lazy_static;
+use serde_json::{json, Value};
 use serenity::{
     builder::{CreateApplicationCommand, CreateApplicationCommandOption},
     client::Context,
     http::Http,
     model::{
-        id::GuildId,
+        id::{GuildId, UserId},
         interactions::{
             application_command::{
                 ApplicationCommandInteraction, ApplicationCommandInteractionData,
@@ -16,12 +19,19 @@ use serenity::{
         },
     },
 };
-use tracing::debug;
+use std::{collections::HashMap, panic::AssertUnwindSafe};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{debug, error, warn};
 
 #[macro_use]
 mod macros;
 
+mod admin;
 mod lfg;
+mod shortcut;
+
+pub use shortcut::Shortcut;
 
 /// Definition of a command.
 pub struct Command {
@@ -91,28 +101,203 @@ impl OptionType {
 
 // List of all known top-level commands; add new commands here as they're created.
 lazy_static! {
-    static ref COMMANDS: Vec<&'static Command> = vec![&*lfg::Lfg::COMMAND];
+    static ref COMMANDS: Vec<&'static Command> = vec![&*lfg::Lfg::COMMAND, &*admin::Admin::COMMAND];
+}
+
+// Commands that change events or act on them for members, by full name with subcommands joined by
+// dots. Subcommands of these are included too.
+const AUDITED_COMMANDS: &[&str] = &[
+    "admin.import-ics",
+    "lfg.bump",
+    "lfg.clone",
+    "lfg.create",
+    "lfg.delete",
+    "lfg.dm",
+    "lfg.edit",
+    "lfg.join",
+    "lfg.kick",
+    "lfg.leave",
+    "lfg.merge",
+    "lfg.move-members",
+    "lfg.mute-alert",
+    "lfg.restore",
+    "lfg.series.transfer",
+];
+
+// Options that name the existing event a command acts on, in order of preference.
+const EVENT_ID_OPTIONS: &[&str] = &["event_id", "into_event_id", "to_event_id"];
+
+fn is_audited(cmd_name: &str) -> bool {
+    AUDITED_COMMANDS.iter().any(|audited| {
+        cmd_name
+            .strip_prefix(audited)
+            .map_or(false, |rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+fn audit_entry(
+    cmd_name: &str,
+    user: UserId,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> AuditEntry {
+    let event_id = EVENT_ID_OPTIONS.iter().find_map(|name| {
+        options
+            .iter()
+            .find(|opt| opt.name == *name)
+            .and_then(|opt| opt.value.as_ref()?.as_str())
+    });
+    AuditEntry {
+        at: Utc::now(),
+        command: cmd_name.to_owned(),
+        user,
+        event_id: event_id.map(str::to_owned),
+    }
+}
+
+/// Awaits the command's handler, returning its result along with the entry to record in the guild's
+/// audit log if the command is audited. Commands that fail aren't recorded, since they didn't change
+/// anything.
+async fn run_command(
+    cmd_name: &str,
+    audited: bool,
+    user: UserId,
+    options: &[ApplicationCommandInteractionDataOption],
+    handler: impl Future<Output = Result<()>>,
+) -> (Result<()>, Option<AuditEntry>) {
+    let result = handler.await;
+    let entry = (audited && result.is_ok()).then(|| audit_entry(cmd_name, user, options));
+    (result, entry)
+}
+
+/// Records the command in the guild's audit log. Failures are only logged, since the command itself
+/// has already been handled.
+async fn record_audit_entry(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    entry: AuditEntry,
+) {
+    let command = entry.command.clone();
+    let result = match ctx.get_event_manager(interaction).await {
+        Ok(event_manager) => event_manager.record_audit_entry(entry).await,
+        Err(err) => Err(err),
+    };
+    if let Err(err) = result {
+        error!("Failed to record '{}' in audit log: {:?}", command, err);
+    }
+}
+
+const PANIC_RESPONSE: &str =
+    "Sorry Captain, something went very wrong on my end. I've noted it for my maintainers...";
+
+/// A panic in an interaction handler, caught so that one bad interaction doesn't take down the task
+/// handling it.
+#[derive(Error, Debug)]
+#[error("Handler for '{handler}' panicked: {message}")]
+pub struct HandlerPanic {
+    handler: String,
+    message: String,
+}
+
+/// Awaits the handler, turning a panic into a HandlerPanic error.
+async fn catch_panic(handler: &str, fut: impl Future<Output = Result<()>>) -> Result<()> {
+    match AssertUnwindSafe(fut).catch_unwind().await {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "(non-string panic payload)".to_owned());
+            Err(HandlerPanic {
+                handler: handler.to_owned(),
+                message,
+            }
+            .into())
+        }
+    }
+}
+
+/// The response to send for a handler error, if the handler can't be relied on to have responded
+/// itself, i.e. if it panicked.
+fn error_response(err: &anyhow::Error) -> Option<&'static str> {
+    if err.is::<HandlerPanic>() {
+        Some(PANIC_RESPONSE)
+    } else {
+        None
+    }
+}
+
+/// Apologizes to the user if handling their interaction failed without a response. Failures are
+/// only logged.
+async fn respond_to_error(ctx: &Context, interaction: &impl InteractionExt, result: &Result<()>) {
+    let content = match result.as_ref().err().and_then(error_response) {
+        Some(content) => content,
+        None => return,
+    };
+    // The handler may have already responded before failing, in which case only a followup works.
+    if interaction
+        .create_response(ctx, content, true)
+        .await
+        .is_err()
+    {
+        if let Err(err) = interaction.create_followup(ctx, content, true).await {
+            error!("Failed to respond to interaction after error: {:?}", err);
+        }
+    }
 }
 
 /// Manages the bot's slash commands, handling creating the commands on startup and dispatching
 /// interactions as they're received.
-#[derive(Debug)]
-pub struct CommandManager;
+#[derive(Debug, Default)]
+pub struct CommandManager {
+    /// Each guild's shortcut commands, which are registered alongside the built-in commands.
+    shortcuts: RwLock<HashMap<GuildId, Vec<Shortcut>>>,
+}
 
 impl CommandManager {
     pub fn new() -> CommandManager {
-        Self
+        Default::default()
     }
 
     /// Set up a newly ready GuildId, creating guild application commands as needed.
-    pub async fn add_guild(&self, http: impl AsRef<Http>, guild: &GuildId) -> Result<()> {
+    pub async fn add_guild(
+        &self,
+        http: impl AsRef<Http>,
+        guild: &GuildId,
+        shortcuts: Vec<Shortcut>,
+    ) -> Result<()> {
+        let shortcuts = self.set_shortcuts(*guild, shortcuts).await;
+
+        let app_commands: Vec<_> = COMMANDS
+            .iter()
+            .map(|command| command.build())
+            .chain(shortcuts.iter().map(|shortcut| shortcut.build()))
+            .collect();
+
         // There's a rate limit on creating commands (200 per day per guild) that could get hit if
-        // restarting the bot frequently, unclear if replacing/updating commands counts against that
-        // limit.
+        // restarting the bot frequently, so leave the commands alone if they're already set. If
+        // they can't be fetched, setting them anyway is still safe.
         let http = http.as_ref();
+        let desired = app_commands
+            .iter()
+            .map(|command| serde_json::to_value(&command.0))
+            .collect::<Result<Vec<_>, _>>()?;
+        match guild.get_application_commands(http).await {
+            Ok(current) => {
+                let current = current
+                    .iter()
+                    .map(serde_json::to_value)
+                    .collect::<Result<Vec<_>, _>>()?;
+                if !commands_differ(&current, &desired) {
+                    debug!("Commands for guild {} are up to date", guild);
+                    return Ok(());
+                }
+            }
+            Err(err) => warn!("Failed to get commands for guild {}: {:?}", guild, err),
+        }
+
         guild
             .set_application_commands(http, |commands| {
-                let app_commands = COMMANDS.iter().map(|command| command.build()).collect();
                 commands.set_application_commands(app_commands)
             })
             .await
@@ -120,6 +305,35 @@ impl CommandManager {
         Ok(())
     }
 
+    /// Sets the guild's shortcut commands, leaving out any that would clash with a built-in
+    /// command. Returns the shortcuts that were kept.
+    async fn set_shortcuts(&self, guild: GuildId, shortcuts: Vec<Shortcut>) -> Vec<Shortcut> {
+        let (shortcuts, clashing): (Vec<_>, Vec<_>) = shortcuts
+            .into_iter()
+            .partition(|s| COMMANDS.iter().all(|cmd| cmd.name != s.name));
+        for shortcut in clashing {
+            warn!(
+                "Ignoring shortcut '{}' for guild {}, which clashes with a built-in command",
+                shortcut.name, guild
+            );
+        }
+        self.shortcuts
+            .write()
+            .await
+            .insert(guild, shortcuts.clone());
+        shortcuts
+    }
+
+    /// The activity that the guild's shortcut command with the given name creates events for.
+    async fn shortcut_activity(&self, guild: GuildId, name: &str) -> Option<Activity> {
+        let shortcuts = self.shortcuts.read().await;
+        shortcuts
+            .get(&guild)?
+            .iter()
+            .find(|s| s.name == name)
+            .map(|s| s.activity)
+    }
+
     /// Dispatch the given interaction to the appropriate command.
     pub async fn dispatch_interaction(
         &self,
@@ -128,21 +342,70 @@ impl CommandManager {
     ) -> Result<()> {
         debug!("Received interaction: {:?}", interaction);
 
+        // Handlers are run such that a panic is caught and reported like any other error, rather
+        // than leaving the user without a response.
         match interaction {
             Interaction::ApplicationCommand(interaction) => {
-                // TODO: Parse the options into an easier to consume form.
-                let (cmd_name, leaf, options) = self.find_leaf_command(&interaction.data)?;
-
-                debug!("'{}' handling command interaction", cmd_name);
-                (leaf.handler)(ctx, &interaction, options).await
+                let handler = self.dispatch_command(ctx, &interaction);
+                let result = catch_panic(&interaction.data.name, handler).await;
+                respond_to_error(ctx, &interaction, &result).await;
+                result
             }
             Interaction::MessageComponent(interaction) => {
-                lfg::handle_component_interaction(ctx, &interaction).await
+                let handler = lfg::handle_component_interaction(ctx, &interaction);
+                let result = catch_panic(&interaction.data.custom_id, handler).await;
+                respond_to_error(ctx, &interaction, &result).await;
+                result
             }
             Interaction::Ping(i) => Err(format_err!("Unexpected Ping interaction: {:?}", i)),
         }
     }
 
+    async fn dispatch_command(
+        &self,
+        ctx: &Context,
+        interaction: &ApplicationCommandInteraction,
+    ) -> Result<()> {
+        let shortcut = match interaction.guild_id {
+            Some(guild) => self.shortcut_activity(guild, &interaction.data.name).await,
+            None => None,
+        };
+        if let Some(activity) = shortcut {
+            debug!("'{}' shortcut handling command interaction", activity);
+            let options = &interaction.data.options;
+            // Shortcuts create events, so they're always audited.
+            let (result, entry) = run_command(
+                &interaction.data.name,
+                true,
+                interaction.user.id,
+                options,
+                lfg::create(ctx, interaction, options, activity),
+            )
+            .await;
+            if let Some(entry) = entry {
+                record_audit_entry(ctx, interaction, entry).await;
+            }
+            return result;
+        }
+
+        // TODO: Parse the options into an easier to consume form.
+        let (cmd_name, leaf, options) = self.find_leaf_command(&interaction.data)?;
+
+        debug!("'{}' handling command interaction", cmd_name);
+        let (result, entry) = run_command(
+            &cmd_name,
+            is_audited(&cmd_name),
+            interaction.user.id,
+            options,
+            (leaf.handler)(ctx, interaction, options),
+        )
+        .await;
+        if let Some(entry) = entry {
+            record_audit_entry(ctx, interaction, entry).await;
+        }
+        result
+    }
+
     fn find_leaf_command<'a>(
         &self,
         data: &'a ApplicationCommandInteractionData,
@@ -302,3 +565,246 @@ impl CommandOption {
         option
     }
 }
+
+/// A command as JSON, reduced to what's set when building it and normalized so that a command
+/// fetched from Discord, which fills in IDs and default values, matches the command it was built
+/// from.
+fn normalize_command(command: &Value) -> Value {
+    json!({
+        "name": command["name"],
+        "description": command["description"],
+        "options": normalize_options(&command["options"]),
+    })
+}
+
+fn normalize_options(options: &Value) -> Value {
+    let options = options
+        .as_array()
+        .map_or(&[][..], |options| options.as_slice());
+    options
+        .iter()
+        .map(|option| {
+            let choices = option["choices"]
+                .as_array()
+                .map_or(&[][..], |c| c.as_slice());
+            json!({
+                "type": option["type"],
+                "name": option["name"],
+                "description": option["description"],
+                "required": option["required"].as_bool().unwrap_or(false),
+                "choices": choices
+                    .iter()
+                    .map(|choice| json!({ "name": choice["name"], "value": choice["value"] }))
+                    .collect::<Vec<_>>(),
+                "options": normalize_options(&option["options"]),
+            })
+        })
+        .collect()
+}
+
+/// Whether a guild's current commands differ from the desired ones, so that they need to be set.
+/// The order of the commands doesn't matter, but the order of their options does.
+fn commands_differ(current: &[Value], desired: &[Value]) -> bool {
+    let normalized = |commands: &[Value]| {
+        let mut commands: Vec<_> = commands.iter().map(normalize_command).collect();
+        commands.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        commands
+    };
+    normalized(current) != normalized(desired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventManager;
+    use serde_json::json;
+    use test_env_log::test;
+
+    fn string_option(name: &str, value: &str) -> ApplicationCommandInteractionDataOption {
+        serde_json::from_value(json!({ "name": name, "type": 3, "value": value })).unwrap()
+    }
+
+    fn buggy_handler(events: &[u8]) -> Result<()> {
+        if events.is_empty() {
+            panic!("no events");
+        }
+        Ok(())
+    }
+
+    #[test(tokio::test)]
+    async fn panicking_handler_yields_error_response() {
+        let err = catch_panic("lfg.join", async { buggy_handler(&[]) })
+            .await
+            .unwrap_err();
+        let panic = err.downcast_ref::<HandlerPanic>().unwrap();
+        assert_eq!(panic.handler, "lfg.join");
+        assert_eq!(panic.message, "no events");
+        assert_eq!(error_response(&err), Some(PANIC_RESPONSE));
+
+        let message = format!("no events for {}", 1);
+        let err = catch_panic("lfg.join", async move { panic!("{}", message) })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Handler for 'lfg.join' panicked: no events for 1"
+        );
+
+        // Handlers that don't panic are unaffected, including their errors.
+        assert!(catch_panic("lfg.join", async { buggy_handler(&[1]) })
+            .await
+            .is_ok());
+        let err = catch_panic("lfg.join", async { Err(format_err!("oops")) })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "oops");
+        assert_eq!(error_response(&err), None);
+    }
+
+    #[test]
+    fn audited_commands() {
+        assert!(is_audited("lfg.join"));
+        assert!(is_audited("lfg.edit.datetime"));
+        assert!(is_audited("lfg.create.raid"));
+        assert!(!is_audited("lfg.list"));
+        assert!(is_audited("lfg.series.transfer"));
+        assert!(!is_audited("lfg.series.recurrences"));
+        assert!(!is_audited("lfg.join-waitlist"));
+        assert!(!is_audited("admin.audit-log"));
+        for name in ["lfg.bump", "lfg.mute-alert", "lfg.dm", "admin.import-ics"] {
+            assert!(is_audited(name), "{}", name);
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn dispatched_commands_audited_when_successful() {
+        let data = |subcommand: &str| -> ApplicationCommandInteractionData {
+            serde_json::from_value(json!({
+                "id": "1",
+                "name": "lfg",
+                "type": 1,
+                "options": [{
+                    "name": subcommand,
+                    "type": 1,
+                    "options": [{ "name": "event_id", "type": 3, "value": "vog1" }],
+                }],
+            }))
+            .unwrap()
+        };
+        // Like dispatch_command, with a stand-in for the handler.
+        let dispatch = |data: ApplicationCommandInteractionData, succeed: bool| async move {
+            let manager = CommandManager::new();
+            let (cmd_name, _, options) = manager.find_leaf_command(&data).unwrap();
+            let handler = async move {
+                if succeed {
+                    Ok(())
+                } else {
+                    Err(format_err!("failed"))
+                }
+            };
+            let audited = is_audited(&cmd_name);
+            run_command(&cmd_name, audited, UserId(5), options, handler)
+                .await
+                .1
+        };
+
+        let event_manager = EventManager::default().await;
+        for subcommand in ["bump", "mute-alert", "dm"] {
+            let entry = dispatch(data(subcommand), true).await.unwrap();
+            assert_eq!(entry.command, format!("lfg.{}", subcommand));
+            assert_eq!(entry.event_id.as_deref(), Some("vog1"));
+            event_manager.record_audit_entry(entry).await.unwrap();
+        }
+        assert_eq!(event_manager.audit_log(10).await.len(), 3);
+
+        // Failed commands and ones that don't change anything aren't recorded.
+        assert!(dispatch(data("bump"), false).await.is_none());
+        assert!(dispatch(data("show"), true).await.is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn mutating_command_appends_audit_entry() {
+        let event_manager = EventManager::default().await;
+        let options = vec![
+            string_option("join_kind", "alt"),
+            string_option("event_id", "vog12"),
+        ];
+        let entry = audit_entry("lfg.join", UserId(5), &options);
+        event_manager
+            .record_audit_entry(entry.clone())
+            .await
+            .unwrap();
+        assert_eq!(event_manager.audit_log(10).await, vec![entry]);
+        let entry = &event_manager.audit_log(10).await[0];
+        assert_eq!(entry.command, "lfg.join");
+        assert_eq!(entry.user, UserId(5));
+        assert_eq!(entry.event_id.as_deref(), Some("vog12"));
+
+        let options = vec![
+            string_option("from_event_id", "gos3"),
+            string_option("into_event_id", "gos2"),
+        ];
+        let entry = audit_entry("lfg.merge", UserId(5), &options);
+        assert_eq!(entry.event_id.as_deref(), Some("gos2"));
+
+        // Creating an event doesn't target an existing one.
+        let entry = audit_entry("lfg.create.raid", UserId(5), &[]);
+        assert_eq!(entry.event_id, None);
+    }
+
+    /// The command as Discord returns it, with IDs filled in and options that aren't required
+    /// leaving out "required".
+    fn as_fetched(command: &Value, id: u64) -> Value {
+        fn strip_required(options: &mut Value) {
+            for option in options.as_array_mut().into_iter().flatten() {
+                if option["required"] == json!(false) {
+                    option.as_object_mut().unwrap().remove("required");
+                }
+                strip_required(&mut option["options"]);
+            }
+        }
+        let mut command = command.clone();
+        strip_required(&mut command["options"]);
+        let fields = command.as_object_mut().unwrap();
+        fields.insert("id".to_owned(), json!(id.to_string()));
+        fields.insert("application_id".to_owned(), json!("1"));
+        fields.insert("version".to_owned(), json!("1"));
+        fields.insert("default_permission".to_owned(), json!(true));
+        command
+    }
+
+    #[test]
+    fn all_commands_build() {
+        // Discord allows at most 25 subcommands per command or group, which build() checks.
+        for command in COMMANDS.iter() {
+            let built = command.build();
+            assert_eq!(built.0["name"], command.name);
+        }
+    }
+
+    #[test]
+    fn commands_only_set_when_changed() {
+        let desired: Vec<_> = COMMANDS
+            .iter()
+            .map(|command| serde_json::to_value(&command.build().0).unwrap())
+            .collect();
+        let current: Vec<_> = desired
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(id, command)| as_fetched(command, id as u64))
+            .collect();
+        assert!(!commands_differ(&current, &desired));
+
+        let mut changed = current.clone();
+        changed[0]["description"] = json!("Something else");
+        assert!(commands_differ(&changed, &desired));
+
+        let mut changed = current.clone();
+        changed[0]["options"][0]["name"] = json!("renamed");
+        assert!(commands_differ(&changed, &desired));
+
+        assert!(commands_differ(&current[1..], &desired));
+        assert!(commands_differ(&[], &desired));
+    }
+}
@@ -0,0 +1,62 @@
+use super::lfg::CreateShortcutOptions;
+use crate::activity::Activity;
+use serenity::builder::CreateApplicationCommand;
+
+/// A guild-configured top-level command, like `/raid`, that creates an event for a single activity
+/// using the same flow as `/lfg create`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcut {
+    pub name: String,
+    pub activity: Activity,
+}
+
+impl Shortcut {
+    pub fn build(&self) -> CreateApplicationCommand {
+        let options = CreateShortcutOptions::OPTIONS
+            .iter()
+            .map(|opt| opt.build())
+            .collect();
+        let mut command = CreateApplicationCommand::default();
+        command
+            .name(&self.name)
+            .description(format!("Create a new {} event", self.activity))
+            .set_options(options);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::CommandManager;
+    use serenity::model::id::GuildId;
+    use test_env_log::test;
+
+    #[test(tokio::test)]
+    async fn shortcut_creates_configured_activity() {
+        let shortcut = Shortcut {
+            name: "vog".to_owned(),
+            activity: Activity::VaultOfGlass,
+        };
+        let command = shortcut.build();
+        assert_eq!(command.0["name"], "vog");
+        assert_eq!(
+            command.0["description"],
+            "Create a new Vault of Glass event"
+        );
+        let options = command.0["options"].as_array().unwrap();
+        // The activity comes from the shortcut rather than an option.
+        assert!(options.iter().all(|opt| opt["name"] != "activity"));
+        assert!(options.iter().any(|opt| opt["name"] == "recur"));
+
+        let manager = CommandManager::new();
+        let guild = GuildId(1);
+        manager.set_shortcuts(guild, vec![shortcut]).await;
+        assert_eq!(
+            manager.shortcut_activity(guild, "vog").await,
+            Some(Activity::VaultOfGlass)
+        );
+        assert_eq!(manager.shortcut_activity(guild, "raid").await, None);
+        assert_eq!(manager.shortcut_activity(GuildId(2), "vog").await, None);
+    }
+}
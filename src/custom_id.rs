@@ -0,0 +1,304 @@
+use crate::{
+    activity::ActivityType,
+    event::{normalize_tag, EventId, Platform},
+};
+use enum_iterator::IntoEnumIterator;
+use serenity::model::id::GuildId;
+use std::str::FromStr;
+
+// Discord rejects components whose custom_id is longer than this.
+pub const MAX_CUSTOM_ID_LEN: usize = 100;
+
+/// What pressing an event button does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoEnumIterator)]
+pub enum EventAction {
+    Join,
+    Leave,
+    Alt,
+    Maybe,
+    CheckIn,
+}
+
+impl EventAction {
+    /// The short code for the action used in custom_ids.
+    fn code(&self) -> &'static str {
+        match self {
+            EventAction::Join => "j",
+            EventAction::Leave => "l",
+            EventAction::Alt => "a",
+            EventAction::Maybe => "m",
+            EventAction::CheckIn => "c",
+        }
+    }
+
+    /// What the action was called in custom_ids before they were shortened. Buttons on older
+    /// messages still have these until the messages are next updated.
+    fn legacy_name(&self) -> &'static str {
+        match self {
+            EventAction::Join => "join",
+            EventAction::Leave => "leave",
+            EventAction::Alt => "alt",
+            EventAction::Maybe => "maybe",
+            EventAction::CheckIn => "checkin",
+        }
+    }
+
+    fn from_code(s: &str) -> Option<Self> {
+        EventAction::into_enum_iter().find(|a| a.code() == s || a.legacy_name() == s)
+    }
+}
+
+/// What a list of events was narrowed down to, which the list's paging buttons carry along.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListFilters {
+    pub activity_type: Option<ActivityType>,
+    pub platform: Option<Platform>,
+    /// A normalized tag.
+    pub tag: Option<String>,
+}
+
+/// The custom_id of a message component, which says what to do when it's used. All custom_ids are
+/// formatted and parsed here, so that they stay consistent and within Discord's length limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomId {
+    /// An event button, formatted like `j:vog12`. Buttons on messages sent over DM also carry the
+    /// guild ID, like `j:vog12:1234`, since the interaction won't have one.
+    Event {
+        action: EventAction,
+        event_id: EventId,
+        guild_id: Option<GuildId>,
+    },
+    /// A list paging button, formatted like `p:2:pc:raid:sherpa` with the page to show followed by
+    /// the list's filters. Filters that weren't given are left empty.
+    ListPage { page: usize, filters: ListFilters },
+    /// A button on the preview of an event that hasn't been created yet, formatted like `pc:vog3`
+    /// to confirm creating it or `px:vog3` to cancel.
+    PendingEvent { confirm: bool, event_id: EventId },
+}
+
+const LIST_PAGE_CODE: &str = "p";
+const CONFIRM_PENDING_CODE: &str = "pc";
+const CANCEL_PENDING_CODE: &str = "px";
+// What list paging buttons started with before custom_ids were shortened.
+const LEGACY_LIST_PAGE_PREFIX: &str = "list-page";
+
+impl CustomId {
+    pub fn event(action: EventAction, event_id: EventId, guild_id: Option<GuildId>) -> Self {
+        CustomId::Event {
+            action,
+            event_id,
+            guild_id,
+        }
+    }
+
+    /// Parses a custom_id, returning None if it isn't one of ours.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (code, args) = s.split_once(':')?;
+        if code == LIST_PAGE_CODE || code == LEGACY_LIST_PAGE_PREFIX {
+            return Self::parse_list_page(args);
+        }
+        if code == CONFIRM_PENDING_CODE || code == CANCEL_PENDING_CODE {
+            let event_id = EventId::from_str(args).ok()?;
+            let confirm = code == CONFIRM_PENDING_CODE;
+            return Some(CustomId::PendingEvent { confirm, event_id });
+        }
+
+        let action = EventAction::from_code(code)?;
+        let mut parts = args.split(':');
+        let event_id = EventId::from_str(parts.next()?).ok()?;
+        let guild_id = match parts.next() {
+            None => None,
+            Some(id) => Some(GuildId(id.parse().ok()?)),
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(CustomId::event(action, event_id, guild_id))
+    }
+
+    fn parse_list_page(args: &str) -> Option<Self> {
+        let mut parts = args.split(':');
+        let page = parts.next()?.parse().ok()?;
+        let platform = match parts.next()? {
+            "" => None,
+            p => Some(Platform::from_str(p).ok()?),
+        };
+        let activity_type = match parts.next()? {
+            "" => None,
+            t => Some(ActivityType::into_enum_iter().find(|at| at.command_name() == t)?),
+        };
+        // Buttons from before tags existed don't have the tag part.
+        let tag = match parts.next() {
+            None | Some("") => None,
+            Some(t) => Some(normalize_tag(t)?),
+        };
+        let filters = ListFilters {
+            activity_type,
+            platform,
+            tag,
+        };
+        Some(CustomId::ListPage { page, filters })
+    }
+}
+
+impl std::fmt::Display for CustomId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let id = match self {
+            CustomId::Event {
+                action,
+                event_id,
+                guild_id,
+            } => match guild_id {
+                Some(guild_id) => format!("{}:{}:{}", action.code(), event_id, guild_id),
+                None => format!("{}:{}", action.code(), event_id),
+            },
+            CustomId::ListPage { page, filters } => format!(
+                "{}:{}:{}:{}:{}",
+                LIST_PAGE_CODE,
+                page,
+                filters
+                    .platform
+                    .map_or(String::new(), |p| p.to_string().to_lowercase()),
+                filters.activity_type.map_or("", |t| t.command_name()),
+                filters.tag.as_deref().unwrap_or(""),
+            ),
+            CustomId::PendingEvent { confirm, event_id } => {
+                let code = if *confirm {
+                    CONFIRM_PENDING_CODE
+                } else {
+                    CANCEL_PENDING_CODE
+                };
+                format!("{}:{}", code, event_id)
+            }
+        };
+        // Every part has a bounded length, so this only fails if one of those bounds grows.
+        debug_assert!(id.len() <= MAX_CUSTOM_ID_LEN, "custom_id too long: {}", id);
+        f.write_str(&id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Activity;
+    use test_env_log::test;
+
+    fn longest_event_ids() -> Vec<EventId> {
+        Activity::into_enum_iter()
+            .map(|activity| EventId {
+                activity,
+                idx: u8::MAX,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn event_ids_round_trip() {
+        for action in EventAction::into_enum_iter() {
+            for event_id in longest_event_ids() {
+                for guild_id in [None, Some(GuildId(u64::MAX))] {
+                    let custom_id = CustomId::event(action, event_id, guild_id);
+                    let s = custom_id.to_string();
+                    assert!(s.len() <= MAX_CUSTOM_ID_LEN, "{}", s);
+                    assert_eq!(CustomId::parse(&s), Some(custom_id), "{}", s);
+                }
+            }
+        }
+        assert_eq!(
+            CustomId::event(EventAction::Join, longest_event_ids()[0], Some(GuildId(5)))
+                .to_string(),
+            format!("j:{}:5", longest_event_ids()[0])
+        );
+    }
+
+    #[test]
+    fn legacy_event_ids_parse() {
+        let vog1 = EventId::from_str("vog1").unwrap();
+        for (s, action) in [
+            ("join:vog1", EventAction::Join),
+            ("leave:vog1", EventAction::Leave),
+            ("alt:vog1", EventAction::Alt),
+            ("maybe:vog1", EventAction::Maybe),
+            ("checkin:vog1", EventAction::CheckIn),
+        ] {
+            assert_eq!(
+                CustomId::parse(s),
+                Some(CustomId::event(action, vog1, None))
+            );
+        }
+        assert_eq!(
+            CustomId::parse("leave:vog1:5"),
+            Some(CustomId::event(EventAction::Leave, vog1, Some(GuildId(5))))
+        );
+    }
+
+    #[test]
+    fn list_page_ids_round_trip() {
+        let longest_tag = "t".repeat(20);
+        let mut cases = vec![ListFilters::default()];
+        for activity_type in ActivityType::into_enum_iter() {
+            for platform in ["pc", "xbox", "playstation", "crossplay"] {
+                cases.push(ListFilters {
+                    activity_type: Some(activity_type),
+                    platform: Some(Platform::from_str(platform).unwrap()),
+                    tag: normalize_tag(&longest_tag),
+                });
+            }
+        }
+        for filters in cases {
+            let custom_id = CustomId::ListPage { page: 99, filters };
+            let s = custom_id.to_string();
+            assert!(s.len() <= MAX_CUSTOM_ID_LEN, "{}", s);
+            assert_eq!(CustomId::parse(&s), Some(custom_id), "{}", s);
+        }
+
+        // Buttons from before custom_ids were shortened, or before tags existed, still work.
+        assert_eq!(
+            CustomId::parse("list-page:1:pc:"),
+            Some(CustomId::ListPage {
+                page: 1,
+                filters: ListFilters {
+                    platform: Some(Platform::Pc),
+                    ..Default::default()
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn pending_event_ids_round_trip() {
+        for confirm in [true, false] {
+            for event_id in longest_event_ids() {
+                let custom_id = CustomId::PendingEvent { confirm, event_id };
+                let s = custom_id.to_string();
+                assert!(s.len() <= MAX_CUSTOM_ID_LEN, "{}", s);
+                assert_eq!(CustomId::parse(&s), Some(custom_id), "{}", s);
+            }
+        }
+        assert_eq!(
+            CustomId::parse("px:vog3"),
+            Some(CustomId::PendingEvent {
+                confirm: false,
+                event_id: EventId::from_str("vog3").unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn malformed_ids_rejected() {
+        for s in [
+            "",
+            "j",
+            "join",
+            "x:vog1",
+            "j:nope1",
+            "j:vog1:guild",
+            "j:vog1:5:6",
+            "p:x::",
+            "p:1:stadia:",
+            "pc:vog1:5",
+        ] {
+            assert_eq!(CustomId::parse(s), None, "{}", s);
+        }
+    }
+}
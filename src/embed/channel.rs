@@ -1,66 +1,424 @@
 use crate::{
-    event::{Event, EventChange},
+    custom_id::CustomId,
+    event::{ButtonLabels, EmbedStyle, Event, EventChange, EventId},
+    store::PersistentStore,
     util::*,
 };
 use anyhow::{format_err, Context as _, Result};
+use chrono::Utc;
 use derivative::Derivative;
 use futures::prelude::*;
+use itertools::Itertools;
 use serenity::{
     builder::CreateEmbed,
     collector::{EventCollector, EventCollectorBuilder},
     model::{
         channel::{Message, MessageFlags},
         event::{Event as DiscordEvent, EventType},
-        id::{ChannelId, GuildId},
+        id::{ChannelId, GuildId, RoleId, UserId},
+        interactions::message_component::ActionRowComponent,
     },
     prelude::*,
+    utils::Color,
+};
+use std::{
+    cmp,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::{
+    sync::{
+        mpsc::{self, error::TrySendError},
+        watch, Mutex,
+    },
+    task::JoinHandle,
 };
-use std::{cmp, collections::BTreeSet, sync::Arc, time::Duration};
-use tokio::sync::mpsc::{self, error::TrySendError};
 use tracing::{debug, error, warn};
 
 const CHANNEL_UPDATER_DELAY_PER_RETRY: u64 = 5;
 const CHANNEL_UPDATER_DELAY_CAP: u64 = 60;
 
+// How often the watchdog checks on the loop, and how long the loop can go without doing any work
+// before it's considered stalled. Each check also gives an idle loop a command to process, and the
+// timeout comfortably covers the longest retry delay.
+const WATCHDOG_INTERVAL: Duration = Duration::from_secs(60);
+const STALL_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+// Threads are archived after a day without messages, in minutes.
+const THREAD_AUTO_ARCHIVE_MINUTES: u16 = 24 * 60;
+
+// Number of upcoming events listed in a channel's summary message.
+const SUMMARY_EVENT_COUNT: usize = 5;
+
 pub type EventChannelFilterFn = Box<dyn Fn(&Event) -> bool + Send + Sync + 'static>;
 
+/// Configuration for a single event channel.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct EventChannelConfig {
+    /// Which events are posted to the channel.
+    #[derivative(Debug = "ignore")]
+    pub filter: EventChannelFilterFn,
+    /// Describes which events the filter lets through, e.g. "Raid events", since the filter itself
+    /// can't be inspected.
+    pub filter_label: String,
+    /// Whether messages from anyone other than the bot are deleted, keeping the channel clear for
+    /// event embeds.
+    pub delete_user_messages: bool,
+    /// Whether a summary message listing the channel's soonest events is kept at the top of the
+    /// channel.
+    pub show_summary: bool,
+    /// What the buttons on event messages say.
+    pub button_labels: ButtonLabels,
+    /// How event embeds look: their colors by activity type, and whether they show the creator.
+    pub embed_style: EmbedStyle,
+    /// Whether a discussion thread is started off each new event message.
+    pub create_threads: bool,
+    /// Role mentioned in a message posted to the channel when an event's alert protocol fires, if
+    /// any.
+    pub alert_role: Option<RoleId>,
+    /// Role mentioned in the message of each event newly posted to the channel, if any. Updates
+    /// and reposts of the message don't mention it again.
+    pub new_event_role: Option<RoleId>,
+    /// How many confirmed members an event needs before it's posted to the channel. Once posted,
+    /// it stays posted even if members leave.
+    pub min_confirmed: usize,
+}
+
+/// A description of an event channel's configuration, for admins.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventChannelInfo {
+    pub channel_id: ChannelId,
+    pub filter_label: String,
+    pub delete_user_messages: bool,
+    pub show_summary: bool,
+    pub create_threads: bool,
+    pub new_event_role: Option<RoleId>,
+}
+
+impl EventChannelConfig {
+    pub fn info(&self, channel_id: ChannelId) -> EventChannelInfo {
+        EventChannelInfo {
+            channel_id,
+            filter_label: self.filter_label.clone(),
+            delete_user_messages: self.delete_user_messages,
+            show_summary: self.show_summary,
+            create_threads: self.create_threads,
+            new_event_role: self.new_event_role,
+        }
+    }
+}
+
 /// Wraps a single "event channel", i.e. a channel that events are automatically posted to based on
 /// a filter.
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct EventChannel {
-    send: mpsc::Sender<EventChange>,
+    channel: ChannelId,
+    send: mpsc::Sender<ChannelCommand>,
+    // The events the channel shows, in order, as of the last change the processing loop applied.
+    #[derivative(Debug = "ignore")]
+    snapshot: watch::Receiver<Vec<Arc<Event>>>,
+    // Aborted on drop, which also stops the processing loop it runs, so a channel that's been
+    // replaced (e.g. its guild was removed and re-added) doesn't keep a loop fighting the new one.
+    #[derivative(Debug = "ignore")]
+    watchdog: JoinHandle<()>,
+}
+
+impl Drop for EventChannel {
+    fn drop(&mut self) {
+        self.watchdog.abort();
+    }
+}
+
+/// Aborts the task when dropped, so that a task's subtask doesn't outlive it when it's aborted.
+struct AbortOnDrop<T>(JoinHandle<T>);
+
+impl<T> Drop for AbortOnDrop<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// The threads started for events in each event channel, by event.
+type EventThreadIds = BTreeMap<ChannelId, BTreeMap<EventId, ChannelId>>;
+
+/// Keeps track of the discussion threads started for events. A thread can otherwise only be found
+/// off of the message it was started from, which an event no longer has once its message is
+/// reposted, e.g. by a bump.
+#[derive(Debug)]
+pub struct EventThreadStore {
+    threads: Mutex<EventThreadIds>,
+    store: PersistentStore<EventThreadIds>,
+}
+
+impl EventThreadStore {
+    pub async fn new(store: PersistentStore<EventThreadIds>) -> Result<Self> {
+        let threads = Mutex::new(store.load().await?);
+        Ok(EventThreadStore { threads, store })
+    }
+
+    async fn threads(&self, channel: ChannelId) -> BTreeMap<EventId, ChannelId> {
+        let threads = self.threads.lock().await;
+        threads.get(&channel).cloned().unwrap_or_default()
+    }
+
+    /// Records the event's thread in the channel, or forgets it with None.
+    async fn set(
+        &self,
+        channel: ChannelId,
+        event: EventId,
+        thread: Option<ChannelId>,
+    ) -> Result<()> {
+        let mut threads = self.threads.lock().await;
+        let channel_threads = threads.entry(channel).or_default();
+        let changed = match thread {
+            Some(thread) => channel_threads.insert(event, thread) != Some(thread),
+            None => channel_threads.remove(&event).is_some(),
+        };
+        if channel_threads.is_empty() {
+            threads.remove(&channel);
+        }
+        if !changed {
+            return Ok(());
+        }
+        self.store.store(&threads).await
+    }
+}
+
+/// State of an event channel's processing loop that outlives any single run of the loop, so that a
+/// stalled loop can be replaced without losing its events or queued changes.
+struct LoopState {
+    recv: mpsc::Receiver<ChannelCommand>,
+    events: ChannelEvents,
+    // Kept here rather than in ChannelLoop since the new event role can be changed at runtime.
+    roles: ChannelRoles,
+}
+
+/// Something for an event channel's processing loop to do.
+#[derive(Debug)]
+enum ChannelCommand {
+    /// Update the channel to reflect a change to an event.
+    Change(EventChange),
+    /// Repost the event's message at the bottom of the channel.
+    Bump(EventId),
+    /// Mention the given role, if any, in the messages of newly posted events from now on.
+    SetNewEventRole(Option<RoleId>),
+    /// Nothing to do. The watchdog sends these so that a healthy but idle loop still has work
+    /// to show progress with.
+    Probe,
+}
+
+/// Records when an event channel's processing loop last made progress, as milliseconds since the
+/// Unix epoch.
+#[derive(Debug, Default)]
+struct Heartbeat(AtomicI64);
+
+impl Heartbeat {
+    fn beat(&self) {
+        self.0
+            .store(Utc::now().timestamp_millis(), Ordering::Relaxed);
+    }
+
+    fn is_stalled(&self) -> bool {
+        is_stalled(
+            self.0.load(Ordering::Relaxed),
+            Utc::now().timestamp_millis(),
+            STALL_TIMEOUT,
+        )
+    }
+}
+
+/// Whether a loop whose last heartbeat was at `last_beat` has gone longer than `timeout` without
+/// progress as of `now`, both in milliseconds since the Unix epoch.
+fn is_stalled(last_beat: i64, now: i64, timeout: Duration) -> bool {
+    now.saturating_sub(last_beat) > timeout.as_millis() as i64
+}
+
+/// How event messages in a channel look, beyond what the event itself determines.
+#[derive(Debug, Clone, Default)]
+struct MessageStyle {
+    button_labels: ButtonLabels,
+    embed_style: EmbedStyle,
+}
+
+/// Roles mentioned in an event channel's messages.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChannelRoles {
+    /// Pinged when an event's alert protocol fires.
+    alert: Option<RoleId>,
+    /// Pinged in the message of each event newly posted to the channel.
+    new_event: Option<RoleId>,
+}
+
+/// Everything needed to (re)start an event channel's processing loop.
+#[derive(Clone)]
+struct ChannelLoop {
+    ctx: Context,
+    channel: ChannelId,
+    delete_user_messages: bool,
+    show_summary: bool,
+    style: MessageStyle,
+    state: Arc<Mutex<LoopState>>,
+    heartbeat: Arc<Heartbeat>,
+    snapshot: Arc<watch::Sender<Vec<Arc<Event>>>>,
+    thread_store: Arc<EventThreadStore>,
 }
 
 impl EventChannel {
     pub fn new<'a, I>(
         ctx: Context,
         channel: ChannelId,
-        filter: EventChannelFilterFn,
+        config: EventChannelConfig,
+        thread_store: Arc<EventThreadStore>,
         initial_events: I,
     ) -> Self
     where
         I: Iterator<Item = &'a Arc<Event>> + Clone,
     {
-        let events = ChannelEvents::new(filter, initial_events);
+        let mut events =
+            ChannelEvents::with_min_confirmed(config.filter, config.min_confirmed, initial_events);
+        events.create_threads = config.create_threads;
         let (send, recv) = mpsc::channel(EVENT_CHANGE_BUFFER_SIZE);
-        tokio::spawn(Self::event_processing_loop(ctx, channel, recv, events));
+        let (snapshot_send, snapshot) = watch::channel(events.snapshot());
+        let channel_loop = ChannelLoop {
+            ctx,
+            channel,
+            delete_user_messages: config.delete_user_messages,
+            show_summary: config.show_summary,
+            style: MessageStyle {
+                button_labels: config.button_labels,
+                embed_style: config.embed_style,
+            },
+            state: Arc::new(Mutex::new(LoopState {
+                recv,
+                events,
+                roles: ChannelRoles {
+                    alert: config.alert_role,
+                    new_event: config.new_event_role,
+                },
+            })),
+            heartbeat: Default::default(),
+            snapshot: Arc::new(snapshot_send),
+            thread_store,
+        };
+        let watchdog = tokio::spawn(Self::watchdog(channel_loop, send.clone()));
 
-        Self { send }
+        Self {
+            channel,
+            send,
+            snapshot,
+            watchdog,
+        }
     }
 
-    async fn event_processing_loop(
-        ctx: Context,
-        channel: ChannelId,
-        mut recv: mpsc::Receiver<EventChange>,
-        mut events: ChannelEvents,
-    ) -> ! {
+    pub fn channel_id(&self) -> ChannelId {
+        self.channel
+    }
+
+    /// The events the channel shows, in the order they're posted. Changes still waiting to be
+    /// processed aren't reflected yet.
+    pub fn events(&self) -> Vec<Arc<Event>> {
+        self.snapshot.borrow().clone()
+    }
+
+    /// Runs the event processing loop, replacing it if it stops making progress. The loop
+    /// recovers from errors on its own, but can't notice if it gets stuck, e.g. waiting on a
+    /// request or a collector that never finishes. The loop only beats once it's done a command
+    /// or a message event, so the watchdog probes it with a command to process each time it
+    /// checks; a loop that can't get to it in time is stalled. Runs until aborted, which stops
+    /// the loop too.
+    async fn watchdog(channel_loop: ChannelLoop, send: mpsc::Sender<ChannelCommand>) {
+        channel_loop.heartbeat.beat();
+        let mut handle = AbortOnDrop(tokio::spawn(channel_loop.clone().event_processing_loop()));
+        let mut interval = tokio::time::interval(WATCHDOG_INTERVAL);
+        loop {
+            interval.tick().await;
+            // A full queue means the loop already has work waiting that it should get to.
+            let _ = send.try_send(ChannelCommand::Probe);
+            if channel_loop.heartbeat.is_stalled() {
+                error!(
+                    "Event channel {} processing loop stalled, restarting it",
+                    channel_loop.channel
+                );
+                // Replacing the handle aborts the stalled loop, dropping its lock on the LoopState for the
+                // new loop.
+                handle = AbortOnDrop(tokio::spawn(channel_loop.clone().event_processing_loop()));
+                channel_loop.heartbeat.beat();
+            }
+        }
+    }
+
+    pub async fn handle_event_change(&self, change: EventChange) {
+        self.send_command(ChannelCommand::Change(change)).await
+    }
+
+    /// Changes the role mentioned when new events are posted, or stops mentioning one with None.
+    pub async fn set_new_event_role(&self, role: Option<RoleId>) {
+        self.send_command(ChannelCommand::SetNewEventRole(role))
+            .await
+    }
+
+    /// Reposts the event's message at the bottom of the channel, returning false if the channel
+    /// doesn't show the event. Messages are kept in event order, so the messages of any events
+    /// after it are reposted below it too. Nothing is reposted unless others' messages have buried
+    /// the event messages.
+    pub async fn bump(&self, id: EventId) -> bool {
+        if !self.snapshot.borrow().iter().any(|e| e.id == id) {
+            return false;
+        }
+        self.send_command(ChannelCommand::Bump(id)).await;
+        true
+    }
+
+    async fn send_command(&self, command: ChannelCommand) {
+        match self.send.try_send(command) {
+            Ok(()) => {}
+            Err(try_send_err) => match try_send_err {
+                TrySendError::Full(command) => {
+                    warn!("ChannelUpdater channel full when adding event change!");
+                    if let Err(_) = self.send.send(command).await {
+                        panic!("ChannelUpdater channel unexpectedly closed");
+                    }
+                }
+                TrySendError::Closed(_) => {
+                    panic!("ChannelUpdater channel unexpectedly closed");
+                }
+            },
+        }
+    }
+}
+
+impl ChannelLoop {
+    async fn event_processing_loop(self) -> ! {
+        let mut state = self.state.lock().await;
+        let LoopState {
+            recv,
+            events,
+            roles,
+        } = &mut *state;
         let mut retry = 0;
         loop {
+            self.heartbeat.beat();
+
             // Initialize a new ChannelUpdater. This gets the current messages in the channel
             // and compares them against the given events, updating as necessary to ensure our
             // state is consistent and ready to apply new event changes.
-            let mut updater = match ChannelUpdater::new(ctx.clone(), channel, &events).await {
+            let updater = ChannelUpdater::new(
+                self.ctx.clone(),
+                self.channel,
+                self.delete_user_messages,
+                self.show_summary,
+                self.style.clone(),
+                *roles,
+                self.thread_store.clone(),
+                events,
+            );
+            let mut updater = match updater.await {
                 Ok(updater) => updater,
                 Err(err) => {
                     error!("Error creating ChannelUpdater, retry {}: {}", retry, err);
@@ -73,15 +431,20 @@ impl EventChannel {
                 }
             };
             retry = 0;
+            // Changes applied before an error restarted the loop haven't been reported yet.
+            let _ = self.snapshot.send(events.snapshot());
 
             'restart_updater: loop {
+                // Each pass after the first finished processing a message event or a command,
+                // e.g. the watchdog's probes, which is what shows the loop is still working.
+                self.heartbeat.beat();
                 tokio::select! {
                     // Let ChannelUpdater handle Discord message events as they come in. This will only
                     // yield a value if an error occurs while handling events, otherwise the select
                     // polling will keep handling message events.
                     updater_event = updater.next_updater_event() => {
                         match updater_event {
-                            Ok(updater_event) => if let Err(err) = updater.process_updater_event(updater_event, &events).await {
+                            Ok(updater_event) => if let Err(err) = updater.process_updater_event(updater_event, events).await {
                                 error!("Error processing ChannelUpdaterEvent: {:?}", err);
                                 break 'restart_updater;
                             }
@@ -93,8 +456,30 @@ impl EventChannel {
                     }
 
                     // Process new event updates as they occur.
-                    Some(change) = recv.recv() => {
-                        let updates = events.apply_event_change(change);
+                    Some(command) = recv.recv() => {
+                        let updates = match command {
+                            ChannelCommand::Change(change) => {
+                                if let EventChange::Deleted(event) = &change {
+                                    // Its ID can be reused, but not its thread.
+                                    if let Err(err) = updater.forget_thread(event.id).await {
+                                        error!("Error forgetting event thread: {:?}", err);
+                                    }
+                                }
+                                events.apply_event_change(change).collect_vec()
+                            }
+                            // Without others' messages below them, the event messages are
+                            // already at the bottom of the channel.
+                            ChannelCommand::Bump(id) if updater.buried => {
+                                events.bump(id).collect_vec()
+                            }
+                            ChannelCommand::Bump(_) => Vec::new(),
+                            ChannelCommand::SetNewEventRole(role) => {
+                                roles.new_event = role;
+                                updater.roles.new_event = role;
+                                Vec::new()
+                            }
+                            ChannelCommand::Probe => continue 'restart_updater,
+                        };
                         for update in updates {
                             debug!("Applying event channel update: {:?}", update);
                             if let Err(err) = updater.apply_update(update).await {
@@ -102,6 +487,17 @@ impl EventChannel {
                                 break 'restart_updater;
                             }
                         }
+                        // This only fails if the EventChannel is gone, in which case nobody's
+                        // asking.
+                        let _ = self.snapshot.send(events.snapshot());
+                        if let Err(err) = updater.update_summary(events).await {
+                            error!("Error updating channel summary: {:?}", err);
+                            break 'restart_updater;
+                        }
+                        if let Err(err) = updater.prune_alert_pings(events).await {
+                            error!("Error pruning alert pings: {:?}", err);
+                            break 'restart_updater;
+                        }
                     }
                 };
             }
@@ -111,30 +507,20 @@ impl EventChannel {
             error!("ChannelUpdater error, restarting loop");
         }
     }
-
-    pub async fn handle_event_change(&self, change: EventChange) {
-        match self.send.try_send(change) {
-            Ok(()) => {}
-            Err(try_send_err) => match try_send_err {
-                TrySendError::Full(change) => {
-                    warn!("ChannelUpdater channel full when adding event change!");
-                    if let Err(_) = self.send.send(change).await {
-                        panic!("ChannelUpdater channel unexpectedly closed");
-                    }
-                }
-                TrySendError::Closed(_) => {
-                    panic!("ChannelUpdater channel unexpectedly closed");
-                }
-            },
-        }
-    }
 }
 
 /// A single update to an event channel.
 #[derive(Debug, Clone, PartialEq, Eq)]
 enum ChannelUpdate<'a> {
-    /// Create a new message at the end of this channel for the given event.
-    New { event: &'a Arc<Event> },
+    /// Create a new message at the end of this channel for the given event, starting a discussion
+    /// thread off of it if `thread` is set and mentioning the channel's new event role if `ping`
+    /// is set. Reposted messages don't get another thread, since the one started off the original
+    /// message is still around, and don't ping again either.
+    New {
+        event: &'a Arc<Event>,
+        thread: bool,
+        ping: bool,
+    },
     /// Update the channel's message at idx with the given event.
     Update { event: &'a Arc<Event>, idx: usize },
     /// Delete the channel's message at idx.
@@ -155,10 +541,27 @@ struct ChannelUpdaterEvent(Arc<DiscordEvent>);
 // new messages, since the message ID then won't be known until ChannelUpdater creates it. Instead,
 // ChannelEvents only keeps track of event ordering in a channel and then specifies actions in terms
 // of indexes, and ChannelUpdater turns that into a message ID.
+//
+// If the channel shows a summary message, that's always the bot's oldest message in the channel and
+// is kept separately, so that event message indexes aren't affected by it. The same goes for the
+// messages pinging the alert role, which are interleaved with the event messages.
 struct ChannelUpdater {
     ctx: Context,
     channel: ChannelId,
+    delete_user_messages: bool,
+    show_summary: bool,
+    style: MessageStyle,
+    roles: ChannelRoles,
     messages: Vec<Message>,
+    summary: Option<Message>,
+    alert_pings: HashMap<EventId, Message>,
+    // Whether others have posted since the bot's newest message, so that bumping events would
+    // bring them back into view. Channels that delete others' messages never get buried.
+    buried: bool,
+    // Threads started for events, including those recorded or found off of the events' messages
+    // when this ChannelUpdater was created.
+    threads: HashMap<EventId, EventThread>,
+    thread_store: Arc<EventThreadStore>,
 
     // Note that the "Event" in EventCollector is referring to Discord gateway events.
     collector: EventCollector,
@@ -168,7 +571,16 @@ impl ChannelUpdater {
     /// Creates a new ChannelUpdater, populating its state with the channel's current messages and
     /// updating those messages as needed to match the provided ChannelEvents, such that the
     /// ChannelUpdater is ready to apply updates for new event changes (through `apply_update`).
-    pub async fn new(ctx: Context, channel: ChannelId, events: &ChannelEvents) -> Result<Self> {
+    pub async fn new(
+        ctx: Context,
+        channel: ChannelId,
+        delete_user_messages: bool,
+        show_summary: bool,
+        style: MessageStyle,
+        roles: ChannelRoles,
+        thread_store: Arc<EventThreadStore>,
+        events: &mut ChannelEvents,
+    ) -> Result<Self> {
         // Set up a collector for any message change events in this channel that aren't from the bot.
         let own_id = ctx.cache.current_user_id().await;
         let collector = EventCollectorBuilder::new(&ctx)
@@ -180,7 +592,7 @@ impl ChannelUpdater {
             .filter(move |e| match e.as_ref() {
                 // Don't care about our own message create events. If we could filter out our own
                 // updates and deletes here we would, but the event doesn't say who performed the
-                // update/delete.
+                // update/delete. Others' messages either get deleted or bury the event messages.
                 DiscordEvent::MessageCreate(e) => e.message.author.id != own_id,
                 _ => true,
             })
@@ -190,7 +602,16 @@ impl ChannelUpdater {
         let mut updater = ChannelUpdater {
             ctx,
             channel,
+            delete_user_messages,
+            show_summary,
+            style,
+            roles,
             messages: Vec::new(),
+            summary: None,
+            alert_pings: HashMap::new(),
+            buried: false,
+            threads: HashMap::new(),
+            thread_store,
             collector,
         };
 
@@ -199,6 +620,21 @@ impl ChannelUpdater {
             "ChannelUpdater {}: Initial messages: {:?}",
             updater.channel, updater.messages
         );
+        let posted = updater
+            .messages
+            .iter()
+            .filter_map(|msg| message_event_id(msg))
+            .collect();
+        events.keep_posted(&posted);
+
+        // Events that already have a thread shouldn't get another one if their message is
+        // reposted below.
+        if events.create_threads {
+            updater.load_threads(events).await?;
+        }
+
+        // The summary goes first so that it stays at the top of the channel.
+        updater.update_summary(events).await?;
 
         let initial_updates = updater.updates_needed_to_match_events(events);
         debug!(
@@ -209,6 +645,10 @@ impl ChannelUpdater {
         for update in initial_updates {
             updater.apply_update(update).await?;
         }
+        updater.prune_alert_pings(events).await?;
+        if events.create_threads {
+            updater.find_threads(events).await;
+        }
 
         debug!("ChannelUpdater {} ready", updater.channel);
         Ok(updater)
@@ -228,11 +668,13 @@ impl ChannelUpdater {
         updater_event: ChannelUpdaterEvent,
         events: &ChannelEvents,
     ) -> Result<()> {
-        let prev_len = self.messages.len();
+        let prev_len = self.message_count();
         match updater_event.0.as_ref() {
+            DiscordEvent::MessageCreate(_) if !self.delete_user_messages => {
+                // The collector filter only lets through others' messages.
+                self.buried = true;
+            }
             DiscordEvent::MessageCreate(e) => {
-                // The collector filter already filtered out our own messages, so this is
-                // someone else; delete it.
                 if let Err(err) = e.message.delete(&self.ctx).await {
                     // This message could be from a user replying with a description for an /lfg
                     // command and ask_for_description() also deletes the message, so ignore
@@ -251,7 +693,12 @@ impl ChannelUpdater {
                 // Others can only suppress embeds, any other edits are from the bot.
                 if let Some(flags) = e.flags {
                     if flags.contains(MessageFlags::SUPPRESS_EMBEDS) {
-                        if let Some(existing) = self.messages.iter_mut().find(|m| m.id == e.id) {
+                        let existing = self
+                            .messages
+                            .iter_mut()
+                            .chain(self.summary.iter_mut())
+                            .find(|m| m.id == e.id);
+                        if let Some(existing) = existing {
                             existing
                                 .edit(&self.ctx, |msg| msg.suppress_embeds(false))
                                 .await
@@ -265,16 +712,23 @@ impl ChannelUpdater {
                 }
             }
             DiscordEvent::MessageDelete(e) => {
+                self.alert_pings.retain(|_, m| m.id != e.message_id);
                 self.messages.retain(|m| m.id != e.message_id);
+                self.summary = self.summary.take().filter(|m| m.id != e.message_id);
             }
             DiscordEvent::MessageDeleteBulk(e) => {
+                self.alert_pings.retain(|_, m| !e.ids.contains(&m.id));
                 self.messages.retain(|m| !e.ids.contains(&m.id));
+                self.summary = self.summary.take().filter(|m| !e.ids.contains(&m.id));
             }
             e => error!("Collector got unexpected event: {:?}", e),
         }
 
         // Repair channel if messages were deleted.
-        if prev_len != self.messages.len() {
+        if prev_len != self.message_count() {
+            self.update_summary(events)
+                .await
+                .context("Error while repairing channel summary after delete")?;
             let updates = self.updates_needed_to_match_events(events);
             for update in updates {
                 self.apply_update(update)
@@ -288,103 +742,293 @@ impl ChannelUpdater {
 
     async fn populate_current_messages(&mut self) -> Result<()> {
         let own_id = self.ctx.cache.current_user_id().await;
-        let mut messages: Vec<_> = self
+        let messages: Vec<_> = self
             .channel
             .messages_iter(&self.ctx)
-            .try_filter_map(|mut msg| async {
-                if msg.author.id != own_id {
-                    // Delete messages that aren't from the bot.
-                    // TODO(serenity-rs/serenity#1439): We set guild ID to something non-None
-                    // because guild_id is missing for messages acquired over the HTTP API, which
-                    // confuses delete() into thinking this is a private message we can't delete.
-                    // The guild id doesn't actually have to be correct.
-                    msg.guild_id = Some(GuildId(1));
-                    if let Err(err) = msg.delete(&self.ctx).await {
-                        error!("Failed to delete non-own message {}: {:?}", msg.id, err);
-                    }
-                    return Ok(None);
-                }
-                Ok(Some(msg))
-            })
             .try_collect()
             .await
             .context("Failed to get channel messages")?;
+        // The returned messages have the newest first.
+        self.buried = !self.delete_user_messages
+            && messages
+                .first()
+                .map_or(false, |msg| msg.author.id != own_id);
+
+        let (mut messages, others) =
+            partition_channel_messages(messages, own_id, self.delete_user_messages);
+        for mut msg in others {
+            // Delete messages that aren't from the bot.
+            // TODO(serenity-rs/serenity#1439): We set guild ID to something non-None because
+            // guild_id is missing for messages acquired over the HTTP API, which confuses delete()
+            // into thinking this is a private message we can't delete. The guild id doesn't
+            // actually have to be correct.
+            msg.guild_id = Some(GuildId(1));
+            if let Err(err) = msg.delete(&self.ctx).await {
+                error!("Failed to delete non-own message {}: {:?}", msg.id, err);
+            }
+        }
 
         // The returned messages have the newest first, so reverse the order.
         messages.reverse();
 
+        // Pings from before a restart are kept, so that their events' alerts don't ping again.
+        let (pings, mut messages): (Vec<_>, Vec<_>) = messages
+            .into_iter()
+            .partition(|msg| alert_ping_event_id(msg, self.roles.alert).is_some());
+        for ping in pings {
+            if let Some(id) = alert_ping_event_id(&ping, self.roles.alert) {
+                self.alert_pings.insert(id, ping);
+            }
+        }
+
+        if self.show_summary && !messages.is_empty() {
+            self.summary = Some(messages.remove(0));
+        }
         self.messages = messages;
         Ok(())
     }
 
+    /// The number of the bot's messages being managed, including the summary.
+    fn message_count(&self) -> usize {
+        self.messages.len() + self.summary.iter().len()
+    }
+
+    /// Creates or updates the channel's summary message, if enabled, to match the given events.
+    ///
+    /// A missing summary is sent as a new message only if the channel has no event messages yet.
+    /// Otherwise the first event message is repurposed as the summary so that it stays at the top,
+    /// and the event messages are expected to be reconciled afterwards.
+    pub async fn update_summary(&mut self, events: &ChannelEvents) -> Result<()> {
+        if !self.show_summary {
+            return Ok(());
+        }
+
+        if self.summary.is_none() && !self.messages.is_empty() {
+            self.summary = Some(self.messages.remove(0));
+        }
+        let embed = summary_embed(events);
+        match &mut self.summary {
+            Some(summary) => {
+                if !summary_update_needed(summary, &embed) {
+                    return Ok(());
+                }
+                summary
+                    .edit(&self.ctx, |msg| {
+                        msg.set_embed(embed)
+                            .components(|c| c)
+                            .suppress_embeds(false)
+                            .content("")
+                    })
+                    .await
+                    .context("Failed to edit summary message")?;
+            }
+            None => {
+                let message = self
+                    .channel
+                    .send_message(&self.ctx, |msg| msg.set_embed(embed))
+                    .await
+                    .context("Failed to send summary message to channel")?;
+                self.summary = Some(message);
+            }
+        }
+        Ok(())
+    }
+
     fn updates_needed_to_match_events<'a>(
         &self,
         events: &'a ChannelEvents,
     ) -> Vec<ChannelUpdate<'a>> {
-        let events = &events.events;
+        updates_needed_to_match_events(&self.messages, events, &self.style)
+    }
 
-        // Update existing messages as needed.
-        let updates = events
-            .iter()
-            .zip(self.messages.iter())
-            .enumerate()
-            .filter_map(|(idx, (event, message))| {
-                let update = Some(ChannelUpdate::Update { event, idx });
+    /// Starts a discussion thread for the event off of its just-sent message.
+    async fn start_thread(&mut self, event: &Event, message: &Message) -> Result<()> {
+        let thread = self
+            .channel
+            .create_public_thread(&self.ctx, message.id, |t| {
+                t.name(thread_name(event))
+                    .auto_archive_duration(THREAD_AUTO_ARCHIVE_MINUTES)
+            })
+            .await
+            .context("Failed to start event thread")?;
+        self.threads.insert(
+            event.id,
+            EventThread {
+                id: thread.id,
+                alert_posted: false,
+            },
+        );
+        self.thread_store
+            .set(self.channel, event.id, Some(thread.id))
+            .await
+            .context("Failed to record event thread")
+    }
 
-                // Check whether the current message has embeds suppressed or whether the embed
-                // isn't in sync with the correct event state and update if so.
-                if message
-                    .flags
-                    .map_or(false, |f| f.contains(MessageFlags::SUPPRESS_EMBEDS))
-                {
-                    return update;
+    /// Stops tracking the event's thread, e.g. once the event is deleted.
+    async fn forget_thread(&mut self, event_id: EventId) -> Result<()> {
+        self.threads.remove(&event_id);
+        self.thread_store.set(self.channel, event_id, None).await
+    }
+
+    /// Loads the threads recorded for the channel's events, e.g. before a restart. Recorded threads
+    /// that are gone, or whose events are, are forgotten.
+    async fn load_threads(&mut self, events: &ChannelEvents) -> Result<()> {
+        for (event_id, id) in self.thread_store.threads(self.channel).await {
+            let event = match events.events.iter().find(|e| e.id == event_id) {
+                Some(event) => event,
+                None => {
+                    self.thread_store.set(self.channel, event_id, None).await?;
+                    continue;
                 }
-                if message.content != event.alert_protocol_message().unwrap_or_default() {
-                    return update;
+            };
+            match id.to_channel(&self.ctx).await {
+                Ok(_) => {
+                    // An alert that's already out was posted to the thread before the restart.
+                    let alert_posted = event.alert_protocol_message().is_some();
+                    self.threads
+                        .insert(event_id, EventThread { id, alert_posted });
                 }
-                if message.embeds.len() != 1 {
-                    return update;
+                Err(err) if err.is_discord_json_error(DiscordJsonErrorCode::UnknownChannel) => {
+                    self.thread_store.set(self.channel, event_id, None).await?;
                 }
-                let current = CreateEmbed::from(message.embeds[0].clone());
-                let target = event.as_embed();
-                if current.0 != target.0 {
-                    return update;
+                Err(err) => warn!("Failed to look up thread for event {}: {:?}", event_id, err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the discussion threads started off of the events' existing messages that weren't
+    /// recorded, e.g. those started before threads were. A thread started off of a message shares
+    /// its ID.
+    async fn find_threads(&mut self, events: &ChannelEvents) {
+        for (event, message) in events.events.iter().zip(&self.messages) {
+            if self.threads.contains_key(&event.id) {
+                continue;
+            }
+            let id = ChannelId(message.id.0);
+            match id.to_channel(&self.ctx).await {
+                Ok(_) => {
+                    // An alert that's already out was posted to the thread before the restart.
+                    let alert_posted = event.alert_protocol_message().is_some();
+                    self.threads
+                        .insert(event.id, EventThread { id, alert_posted });
+                    if let Err(err) = self
+                        .thread_store
+                        .set(self.channel, event.id, Some(id))
+                        .await
+                    {
+                        warn!("Failed to record thread for event {}: {:?}", event.id, err);
+                    }
                 }
-                None
-            });
+                Err(err) if err.is_discord_json_error(DiscordJsonErrorCode::UnknownChannel) => {}
+                Err(err) => warn!("Failed to look up thread for event {}: {:?}", event.id, err),
+            }
+        }
+    }
 
-        // Only new or delete will yield any elements, not both, but this lets us simply chain the
-        // iterators together.
-        let delete_range = events.len()..self.messages.len();
-        let delete = std::iter::repeat(ChannelUpdate::Delete {
-            idx: delete_range.start,
-        })
-        .take(delete_range.len());
-        let new = events
-            .iter()
-            .skip(self.messages.len())
-            .map(|event| ChannelUpdate::New { event });
-        assert!(delete_range.len() == 0 || new.len() == 0);
+    /// Posts the event's alert protocol message to its thread, if it has one, once per alert.
+    async fn post_alert_to_thread(&mut self, event: &Event) -> Result<()> {
+        let thread = match self.threads.get_mut(&event.id) {
+            Some(thread) => thread,
+            None => return Ok(()),
+        };
+        match event.alert_protocol_message() {
+            Some(alert) if !thread.alert_posted => {
+                thread
+                    .id
+                    .say(&self.ctx, alert)
+                    .await
+                    .context("Failed to post alert to event thread")?;
+                thread.alert_posted = true;
+            }
+            Some(_) => {}
+            // Alerts get reset when the event's time changes, so be ready to post again.
+            None => thread.alert_posted = false,
+        }
+        Ok(())
+    }
+
+    /// Posts a message mentioning the alert role along with the event's alert protocol message,
+    /// once per alert, if the channel has an alert role. Alerts without any full groups don't
+    /// have anyone to ping for.
+    async fn ping_alert_role(&mut self, event: &Event) -> Result<()> {
+        let role = match self.roles.alert {
+            Some(role) => role,
+            None => return Ok(()),
+        };
+        match event.alert_protocol_message() {
+            Some(alert) if !alert.is_empty() && !self.alert_pings.contains_key(&event.id) => {
+                let message = self
+                    .channel
+                    .send_message(&self.ctx, |msg| {
+                        msg.content(alert_ping_content(role, &alert))
+                            // Only ping the role, since the groups' members were already DMed.
+                            .allowed_mentions(|m| m.roles(vec![role]))
+                    })
+                    .await
+                    .context("Failed to ping alert role")?;
+                self.alert_pings.insert(event.id, message);
+            }
+            Some(_) => {}
+            // Alerts get reset when the event's time changes, so be ready to ping again.
+            None => self.delete_alert_ping(event.id).await?,
+        }
+        Ok(())
+    }
+
+    async fn delete_alert_ping(&mut self, id: EventId) -> Result<()> {
+        if let Some(ping) = self.alert_pings.remove(&id) {
+            if let Err(err) = ping.delete(&self.ctx).await {
+                if !err.is_discord_json_error(DiscordJsonErrorCode::UnknownMessage) {
+                    return Err(err).context("Failed to delete alert ping");
+                }
+            }
+        }
+        Ok(())
+    }
 
-        updates.chain(delete).chain(new).collect()
+    /// Deletes the alert pings of events that are no longer in the channel.
+    pub async fn prune_alert_pings(&mut self, events: &ChannelEvents) -> Result<()> {
+        let stale = self
+            .alert_pings
+            .keys()
+            .filter(|id| !events.events.iter().any(|e| e.id == **id))
+            .copied()
+            .collect_vec();
+        for id in stale {
+            self.delete_alert_ping(id).await?;
+        }
+        Ok(())
     }
 
     pub async fn apply_update(&mut self, update: ChannelUpdate<'_>) -> Result<()> {
         match update {
-            ChannelUpdate::New { event } => {
+            ChannelUpdate::New {
+                event,
+                thread,
+                ping,
+            } => {
+                let role = self.roles.new_event.filter(|_| ping);
                 let message = self
                     .channel
                     .send_message(&self.ctx, |msg| {
-                        msg.set_embed(event.as_embed())
+                        msg.set_embed(event.as_embed(&self.style.embed_style))
                             .components(|c| {
-                                *c = event.event_buttons();
+                                *c = event.event_buttons(&self.style.button_labels);
                                 c
                             })
-                            .content(event.alert_protocol_message().unwrap_or_default())
+                            .content(new_message_content(event, role))
                     })
                     .await
                     .context("Failed to send new message to channel")?;
+                // An event whose message was reposted still has the thread it started with.
+                if thread && !self.threads.contains_key(&event.id) {
+                    self.start_thread(event, &message).await?;
+                }
                 self.messages.push(message);
+                self.buried = false;
+                self.post_alert_to_thread(event).await?;
+                self.ping_alert_role(event).await?;
             }
             ChannelUpdate::Update { event, idx } => {
                 let message = self
@@ -393,9 +1037,9 @@ impl ChannelUpdater {
                     .expect("Message index OOB, state inconsistent");
                 message
                     .edit(&self.ctx, |msg| {
-                        msg.set_embed(event.as_embed())
+                        msg.set_embed(event.as_embed(&self.style.embed_style))
                             .components(|c| {
-                                *c = event.event_buttons();
+                                *c = event.event_buttons(&self.style.button_labels);
                                 c
                             })
                             .suppress_embeds(false)
@@ -403,6 +1047,8 @@ impl ChannelUpdater {
                     })
                     .await
                     .context("Failed to edit message")?;
+                self.post_alert_to_thread(event).await?;
+                self.ping_alert_role(event).await?;
             }
             ChannelUpdate::Delete { idx } => {
                 let message = self.messages.remove(idx);
@@ -416,8 +1062,243 @@ impl ChannelUpdater {
     }
 }
 
+/// The parts of a channel message that are compared against an event to determine whether the
+/// message needs to be updated.
+trait ManagedMessage {
+    fn author_id(&self) -> UserId;
+    fn embeds_suppressed(&self) -> bool;
+    fn content(&self) -> &str;
+    fn embeds(&self) -> Vec<CreateEmbed>;
+    /// The (custom_id, disabled) state of each button.
+    fn buttons(&self) -> Vec<(String, bool)>;
+}
+
+impl ManagedMessage for Message {
+    fn author_id(&self) -> UserId {
+        self.author.id
+    }
+
+    fn embeds_suppressed(&self) -> bool {
+        self.flags
+            .map_or(false, |f| f.contains(MessageFlags::SUPPRESS_EMBEDS))
+    }
+
+    fn content(&self) -> &str {
+        &self.content
+    }
+
+    fn embeds(&self) -> Vec<CreateEmbed> {
+        self.embeds.iter().cloned().map(CreateEmbed::from).collect()
+    }
+
+    fn buttons(&self) -> Vec<(String, bool)> {
+        self.components
+            .iter()
+            .flat_map(|row| row.components.iter())
+            .filter_map(|component| match component {
+                ActionRowComponent::Button(button) => {
+                    Some((button.custom_id.clone()?, button.disabled))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// Creates the embed for a channel's summary message, listing the soonest of the channel's events.
+fn summary_embed(events: &ChannelEvents) -> CreateEmbed {
+    let mut embed = CreateEmbed::default();
+    embed.title("Upcoming Events").color(Color::DARK_GOLD);
+
+    let events = &events.events;
+    if events.is_empty() {
+        embed.description("Nothing scheduled yet. Create an event with `/lfg create`!");
+        return embed;
+    }
+    let lines = events
+        .iter()
+        .take(SUMMARY_EVENT_COUNT)
+        .map(|event| {
+            format!(
+                "**{}** | `{}` | {} | {} joined",
+                event.activity,
+                event.id,
+                event.timestamp(),
+                event.confirmed().len()
+            )
+        })
+        .join("\n");
+    embed.description(lines);
+    if events.len() > SUMMARY_EVENT_COUNT {
+        embed.footer(|f| {
+            f.text(format!(
+                "...and {} more",
+                events.len() - SUMMARY_EVENT_COUNT
+            ))
+        });
+    }
+    embed
+}
+
+/// Whether the given summary message differs from the target summary embed.
+fn summary_update_needed<M: ManagedMessage>(message: &M, target: &CreateEmbed) -> bool {
+    if message.embeds_suppressed() || !message.content().is_empty() {
+        return true;
+    }
+    if !message.buttons().is_empty() {
+        return true;
+    }
+    let embeds = message.embeds();
+    embeds.len() != 1 || embeds[0].0 != target.0
+}
+
+/// Split a channel's messages into the bot's own messages, which ChannelUpdater manages, and the
+/// messages from others that should be deleted. Messages from others are ignored entirely if the
+/// channel doesn't delete user messages.
+fn partition_channel_messages<M: ManagedMessage>(
+    messages: Vec<M>,
+    own_id: UserId,
+    delete_user_messages: bool,
+) -> (Vec<M>, Vec<M>) {
+    let (own, others): (Vec<_>, Vec<_>) = messages
+        .into_iter()
+        .partition(|msg| msg.author_id() == own_id);
+    if delete_user_messages {
+        (own, others)
+    } else {
+        (own, Vec::new())
+    }
+}
+
+/// Determine the updates needed to make the given channel messages match the channel's events.
+///
+/// This is used on startup (and after repairing deletes), so it also covers messages whose embeds
+/// were suppressed while the bot wasn't around to see the MessageUpdate event.
+fn updates_needed_to_match_events<'a, M: ManagedMessage>(
+    messages: &[M],
+    events: &'a ChannelEvents,
+    style: &MessageStyle,
+) -> Vec<ChannelUpdate<'a>> {
+    let create_threads = events.create_threads;
+    let events = &events.events;
+
+    // Update existing messages as needed.
+    let updates =
+        events
+            .iter()
+            .zip(messages.iter())
+            .enumerate()
+            .filter_map(|(idx, (event, message))| {
+                let update = Some(ChannelUpdate::Update { event, idx });
+
+                // Check whether the current message has embeds suppressed or whether the embed isn't in
+                // sync with the correct event state and update if so.
+                if message.embeds_suppressed() {
+                    return update;
+                }
+                if message.content() != event.alert_protocol_message().unwrap_or_default() {
+                    return update;
+                }
+                let embeds = message.embeds();
+                if embeds.len() != 1 {
+                    return update;
+                }
+                let target = event.as_embed(&style.embed_style);
+                if embeds[0].0 != target.0 {
+                    return update;
+                }
+                // Buttons get disabled/enabled as the event fills up or is locked.
+                if message.buttons() != button_states(&event.event_buttons(&style.button_labels)) {
+                    return update;
+                }
+                None
+            });
+
+    // Only new or delete will yield any elements, not both, but this lets us simply chain the
+    // iterators together.
+    let delete_range = events.len()..messages.len();
+    let delete = std::iter::repeat(ChannelUpdate::Delete {
+        idx: delete_range.start,
+    })
+    .take(delete_range.len());
+    let new = events
+        .iter()
+        .skip(messages.len())
+        .map(|event| ChannelUpdate::New {
+            event,
+            thread: create_threads,
+            ping: false,
+        });
+    assert!(delete_range.len() == 0 || new.len() == 0);
+
+    updates.chain(delete).chain(new).collect()
+}
+
+/// The content of an event's message when it's posted, mentioning the given role ahead of the
+/// event's alert protocol message, if any.
+fn new_message_content(event: &Event, role: Option<RoleId>) -> String {
+    let alert = event.alert_protocol_message().unwrap_or_default();
+    match role {
+        Some(role) if alert.is_empty() => role.mention().to_string(),
+        Some(role) => alert_ping_content(role, &alert),
+        None => alert,
+    }
+}
+
+fn alert_ping_content(role: RoleId, alert: &str) -> String {
+    format!("{} {}", role.mention(), alert)
+}
+
+/// The ID of the event that the message is for, going by its buttons, if it's an event message.
+fn message_event_id<M: ManagedMessage>(message: &M) -> Option<EventId> {
+    message
+        .buttons()
+        .iter()
+        .find_map(|(id, _)| match CustomId::parse(id)? {
+            CustomId::Event { event_id, .. } => Some(event_id),
+            _ => None,
+        })
+}
+
+/// The ID of the event that the message pinged the alert role for, if it's an alert ping.
+fn alert_ping_event_id<M: ManagedMessage>(message: &M, role: Option<RoleId>) -> Option<EventId> {
+    let alert = message
+        .content()
+        .strip_prefix(&format!("{} ", role?.mention()))?;
+    if !message.embeds().is_empty() {
+        return None;
+    }
+    // The alert protocol message names the event as "LFG **<id>**".
+    let (_, rest) = alert.split_once("LFG **")?;
+    let (id, _) = rest.split_once("**")?;
+    id.parse().ok()
+}
+
+/// A discussion thread started off of an event's message.
+#[derive(Debug)]
+struct EventThread {
+    id: ChannelId,
+    /// Whether the event's current alert protocol message has been posted to the thread.
+    alert_posted: bool,
+}
+
+/// Name of the discussion thread for an event. Thread names are limited to 100 characters.
+fn thread_name(event: &Event) -> String {
+    format!("{} | {}", event.id, event.activity)
+        .chars()
+        .take(100)
+        .collect()
+}
+
 struct ChannelEvents {
     filter: EventChannelFilterFn,
+    /// Whether new event messages get a discussion thread.
+    create_threads: bool,
+    /// Confirmed members an event needs before it's first posted.
+    min_confirmed: usize,
+    /// Initial events that were held back for having fewer than `min_confirmed` confirmed
+    /// members. Any of them that were posted before a restart are put back by keep_posted().
+    held_back: Vec<Arc<Event>>,
 
     // Note that this relies on Event's Ord implementation that orders by event datetime.
     events: BTreeSet<Arc<Event>>,
@@ -428,8 +1309,51 @@ impl ChannelEvents {
     where
         I: Iterator<Item = &'a Arc<Event>> + Clone,
     {
-        let events = initial_events.filter(|e| filter(e)).cloned().collect();
-        Self { filter, events }
+        Self::with_min_confirmed(filter, 0, initial_events)
+    }
+
+    /// Like new(), but holding back events with fewer than `min_confirmed` confirmed members
+    /// until they reach it.
+    pub fn with_min_confirmed<'a, I>(
+        filter: EventChannelFilterFn,
+        min_confirmed: usize,
+        initial_events: I,
+    ) -> Self
+    where
+        I: Iterator<Item = &'a Arc<Event>> + Clone,
+    {
+        let (events, held_back) = initial_events
+            .filter(|e| Self::shown(&filter, e))
+            .cloned()
+            .partition(|e| e.confirmed().len() >= min_confirmed);
+        Self {
+            filter,
+            create_threads: false,
+            min_confirmed,
+            held_back,
+            events,
+        }
+    }
+
+    /// Puts back the held back initial events that already have messages in the channel, since
+    /// events stay posted once they are, even if they've since dropped below `min_confirmed`.
+    /// Only the first call does anything, once the channel's messages are known.
+    fn keep_posted(&mut self, posted: &HashSet<EventId>) {
+        for event in self.held_back.drain(..) {
+            if posted.contains(&event.id) {
+                self.events.insert(event);
+            }
+        }
+    }
+
+    /// The channel's events, in the order they're posted.
+    pub fn snapshot(&self) -> Vec<Arc<Event>> {
+        self.events.iter().cloned().collect()
+    }
+
+    /// Whether the event belongs in this channel. Unlisted events never get posted to any channel.
+    fn shown(filter: &EventChannelFilterFn, event: &Event) -> bool {
+        !event.unlisted && filter(event)
     }
 
     pub fn apply_event_change(
@@ -441,7 +1365,8 @@ impl ChannelEvents {
         let old_idx = match &change {
             EventChange::Deleted(change)
             | EventChange::Edited(change)
-            | EventChange::Alert(change) => {
+            | EventChange::Alert(change)
+            | EventChange::CheckIn(change) => {
                 // This might be better with drain_filter() once that is stabilized.
                 let old = self
                     .events
@@ -460,12 +1385,17 @@ impl ChannelEvents {
             EventChange::Added(_) => None,
         };
 
-        // Insert only if event still meets filter.
+        // Insert only if event still meets filter and is listed.
         let new_idx = match change {
             EventChange::Added(change)
             | EventChange::Edited(change)
-            | EventChange::Alert(change) => {
-                if (self.filter)(&change) {
+            | EventChange::Alert(change)
+            | EventChange::CheckIn(change) => {
+                // Events already in the channel stay there, so that they don't disappear and
+                // reappear as members come and go.
+                let enough_confirmed =
+                    old_idx.is_some() || change.confirmed().len() >= self.min_confirmed;
+                if Self::shown(&self.filter, &change) && enough_confirmed {
                     let id = change.id;
                     self.events.insert(change);
                     Some(
@@ -484,6 +1414,31 @@ impl ChannelEvents {
         self.create_updates(old_idx, new_idx)
     }
 
+    /// Create the channel updates that repost the given event's message at the bottom of the
+    /// channel. To keep the messages in event order, the messages of the events after it are
+    /// deleted and reposted after it too.
+    pub fn bump(&self, id: EventId) -> impl Iterator<Item = ChannelUpdate<'_>> + '_ {
+        let start = self
+            .events
+            .iter()
+            .position(|e| e.id == id)
+            .unwrap_or_else(|| self.events.len());
+        // Delete from the end so that the indexes of the messages still to delete don't change.
+        let deletes = (start..self.events.len())
+            .rev()
+            .map(|idx| ChannelUpdate::Delete { idx });
+        let reposts = self
+            .events
+            .iter()
+            .skip(start)
+            .map(|event| ChannelUpdate::New {
+                event,
+                thread: false,
+                ping: false,
+            });
+        deletes.chain(reposts)
+    }
+
     /// Create all the channel updates to apply given an "old_idx" where an event was removed or
     /// replaced and a "new_idx" where an event was added or replaced.
     fn create_updates(
@@ -501,7 +1456,11 @@ impl ChannelEvents {
                     .iter()
                     .last()
                     .expect("Events shouldn't be empty");
-                last_action = Some(ChannelUpdate::New { event });
+                last_action = Some(ChannelUpdate::New {
+                    event,
+                    thread: self.create_threads,
+                    ping: true,
+                });
                 new..self.events.len() - 1
             }
             (Some(old), None) => {
@@ -529,9 +1488,11 @@ impl ChannelEvents {
 mod test {
     use super::*;
     use crate::activity::{Activity, ActivityType};
-    use crate::event::{Event, EventId};
+    use crate::custom_id::{CustomId, EventAction};
+    use crate::event::{Event, EventId, JoinKind};
     use chrono::{Duration, Utc};
     use chrono_tz::Tz;
+    use serenity::model::user::User;
     use std::iter;
 
     fn test_event(activity: Activity, idx: u8, hours_away: i64) -> Arc<Event> {
@@ -543,7 +1504,11 @@ mod test {
     }
 
     fn new_action(event: &Arc<Event>) -> ChannelUpdate {
-        ChannelUpdate::New { event }
+        ChannelUpdate::New {
+            event,
+            thread: false,
+            ping: true,
+        }
     }
 
     fn update_action(event: &Arc<Event>, idx: usize) -> ChannelUpdate {
@@ -554,6 +1519,121 @@ mod test {
         ChannelUpdate::Delete { idx }
     }
 
+    const OWN_ID: UserId = UserId(1);
+
+    struct FakeMessage {
+        author: UserId,
+        suppressed: bool,
+        content: String,
+        embeds: Vec<CreateEmbed>,
+        buttons: Vec<(String, bool)>,
+    }
+
+    impl FakeMessage {
+        fn for_event(event: &Event) -> Self {
+            FakeMessage {
+                author: OWN_ID,
+                suppressed: false,
+                content: event.alert_protocol_message().unwrap_or_default(),
+                embeds: vec![event.as_embed(&Default::default())],
+                buttons: button_states(&event.event_buttons(&ButtonLabels::default())),
+            }
+        }
+    }
+
+    impl ManagedMessage for FakeMessage {
+        fn author_id(&self) -> UserId {
+            self.author
+        }
+
+        fn embeds_suppressed(&self) -> bool {
+            self.suppressed
+        }
+
+        fn content(&self) -> &str {
+            &self.content
+        }
+
+        fn embeds(&self) -> Vec<CreateEmbed> {
+            self.embeds.clone()
+        }
+
+        fn buttons(&self) -> Vec<(String, bool)> {
+            self.buttons.clone()
+        }
+    }
+
+    #[test]
+    fn stalled_loop_detected() {
+        let timeout = Duration::from_secs(60);
+        let last_beat = 1_000_000;
+        assert!(!is_stalled(last_beat, last_beat, timeout));
+        assert!(!is_stalled(last_beat, last_beat + 60_000, timeout));
+        assert!(is_stalled(last_beat, last_beat + 60_001, timeout));
+        // A beat that looks like it's from the future (e.g. the clock moved) isn't a stall.
+        assert!(!is_stalled(last_beat, last_beat - 5_000, timeout));
+
+        let heartbeat = Heartbeat::default();
+        assert!(heartbeat.is_stalled());
+        heartbeat.beat();
+        assert!(!heartbeat.is_stalled());
+    }
+
+    #[test]
+    fn suppressed_message_updated_on_startup() {
+        let event1 = test_event(Activity::DeepStoneCrypt, 1, 0);
+        let event2 = test_event(Activity::VaultOfGlass, 2, 1);
+        let events = [event1.clone(), event2.clone()];
+        let chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+
+        let mut messages = vec![
+            FakeMessage::for_event(&event1),
+            FakeMessage::for_event(&event2),
+        ];
+        assert!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()).is_empty()
+        );
+
+        messages[1].suppressed = true;
+        assert_eq!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()),
+            vec![update_action(&event2, 1)]
+        );
+    }
+
+    #[test]
+    fn stale_buttons_updated_on_startup() {
+        let event1 = test_event(Activity::DeepStoneCrypt, 1, 0);
+        let event2 = test_event(Activity::VaultOfGlass, 2, 1);
+        let messages = vec![
+            FakeMessage::for_event(&event1),
+            FakeMessage::for_event(&event2),
+        ];
+
+        // The event filled up while the bot wasn't around, so its Join button should be disabled.
+        let mut full_event2 = (*event2).clone();
+        full_event2.max_players = Some(1);
+        let full_event2 = Arc::new(full_event2);
+        let events = [event1.clone(), full_event2.clone()];
+        let chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+        assert_eq!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()),
+            vec![update_action(&full_event2, 1)]
+        );
+
+        let messages = vec![
+            FakeMessage::for_event(&event1),
+            FakeMessage::for_event(&full_event2),
+        ];
+        assert!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()).is_empty()
+        );
+        assert!(messages[1].buttons.contains(&(
+            CustomId::event(EventAction::Join, event2.id, None).to_string(),
+            true
+        )));
+    }
+
     #[test]
     fn add_update_delete_matching_event() {
         let mut chan = ChannelEvents::new(
@@ -583,6 +1663,136 @@ mod test {
         );
     }
 
+    #[test]
+    fn snapshot_reflects_applied_changes() {
+        let mut chan = ChannelEvents::new(
+            Box::new(|event: &Event| event.activity.activity_type() == ActivityType::Raid),
+            iter::empty(),
+        );
+        let ids = |chan: &ChannelEvents| chan.snapshot().iter().map(|e| e.id).collect::<Vec<_>>();
+        assert!(chan.snapshot().is_empty());
+
+        let later = test_event(Activity::DeepStoneCrypt, 1, 2);
+        let sooner = test_event(Activity::VaultOfGlass, 1, 1);
+        let filtered = test_event(Activity::Prophecy, 1, 0);
+        for event in [&later, &sooner, &filtered] {
+            chan.apply_event_change(EventChange::Added(event.clone()))
+                .for_each(drop);
+        }
+        assert_eq!(ids(&chan), vec![sooner.id, later.id]);
+
+        // Moving an event reorders it.
+        let mut moved = later.clone();
+        Arc::make_mut(&mut moved).set_datetime(sooner.datetime() - Duration::hours(1));
+        chan.apply_event_change(EventChange::Edited(moved.clone()))
+            .for_each(drop);
+        assert_eq!(ids(&chan), vec![moved.id, sooner.id]);
+        assert_eq!(chan.snapshot()[0].datetime(), moved.datetime());
+
+        chan.apply_event_change(EventChange::Deleted(sooner.clone()))
+            .for_each(drop);
+        assert_eq!(ids(&chan), vec![moved.id]);
+    }
+
+    #[test]
+    fn bump_reposts_event_and_later_ones() {
+        let event1 = test_event(Activity::DeepStoneCrypt, 1, 0);
+        let event2 = test_event(Activity::VaultOfGlass, 2, 1);
+        let event3 = test_event(Activity::LastWish, 3, 2);
+        let events = [event1.clone(), event2.clone(), event3.clone()];
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+        chan.create_threads = true;
+
+        let updates = chan.bump(event2.id).collect::<Vec<_>>();
+        assert_eq!(
+            updates,
+            vec![
+                delete_action(2),
+                delete_action(1),
+                ChannelUpdate::New {
+                    event: &event2,
+                    thread: false,
+                    ping: false,
+                },
+                ChannelUpdate::New {
+                    event: &event3,
+                    thread: false,
+                    ping: false,
+                },
+            ]
+        );
+
+        // Applying the updates leaves the messages in event order, with nothing left to fix up.
+        let mut messages = events
+            .iter()
+            .map(|e| FakeMessage::for_event(e))
+            .collect_vec();
+        for update in updates {
+            match update {
+                ChannelUpdate::Delete { idx } => {
+                    messages.remove(idx);
+                }
+                ChannelUpdate::New { event, .. } => messages.push(FakeMessage::for_event(event)),
+                ChannelUpdate::Update { .. } => panic!("Unexpected update"),
+            }
+        }
+        assert_eq!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()),
+            vec![]
+        );
+        assert_eq!(chan.snapshot(), events.to_vec());
+
+        assert_eq!(
+            chan.bump(event3.id).collect::<Vec<_>>(),
+            vec![
+                delete_action(2),
+                ChannelUpdate::New {
+                    event: &event3,
+                    thread: false,
+                    ping: false,
+                },
+            ]
+        );
+        let other = test_event(Activity::GardenOfSalvation, 4, 3);
+        assert_eq!(chan.bump(other.id).count(), 0);
+    }
+
+    #[test]
+    fn new_event_starts_thread_when_enabled() {
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), iter::empty());
+        chan.create_threads = true;
+
+        let event1 = test_event(Activity::DeepStoneCrypt, 1, 0);
+        assert_eq!(
+            chan.apply_event_change(EventChange::Added(event1.clone()))
+                .collect::<Vec<_>>(),
+            vec![ChannelUpdate::New {
+                event: &event1,
+                thread: true,
+                ping: true,
+            }]
+        );
+        assert_eq!(
+            thread_name(&event1),
+            format!("{} | Deep Stone Crypt", event1.id)
+        );
+
+        // Messages created on startup get threads too, while existing ones are just updated.
+        let event2 = test_event(Activity::VaultOfGlass, 2, 1);
+        let events = [event1.clone(), event2.clone()];
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+        chan.create_threads = true;
+        let messages = vec![FakeMessage::for_event(&event1)];
+        assert_eq!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()),
+            vec![ChannelUpdate::New {
+                event: &event2,
+                thread: true,
+                ping: false,
+            }]
+        );
+    }
+
     #[test]
     fn add_edit_delete_earlier_events_test() {
         let mut chan = ChannelEvents::new(
@@ -644,4 +1854,359 @@ mod test {
             vec![delete_action(0)],
         );
     }
+
+    #[test]
+    fn unlisted_events_not_posted() {
+        let mut unlisted = test_event(Activity::VaultOfGlass, 1, 0);
+        Arc::make_mut(&mut unlisted).unlisted = true;
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), [unlisted.clone()].iter());
+        assert!(chan.events.is_empty());
+        assert_eq!(
+            chan.apply_event_change(EventChange::Added(unlisted.clone()))
+                .count(),
+            0
+        );
+
+        // Unlisting an already posted event removes it from the channel.
+        let event = test_event(Activity::LastWish, 2, 1);
+        chan.apply_event_change(EventChange::Added(event.clone()))
+            .for_each(drop);
+        let mut event = event.clone();
+        Arc::make_mut(&mut event).unlisted = true;
+        assert_eq!(
+            chan.apply_event_change(EventChange::Edited(event.clone()))
+                .collect::<Vec<_>>(),
+            vec![delete_action(0)],
+        );
+    }
+
+    #[test]
+    fn activity_edit_moves_event_between_channels() {
+        let type_filter = |ty: ActivityType| -> EventChannelFilterFn {
+            Box::new(move |e: &Event| e.activity.activity_type() == ty)
+        };
+        let event = test_event(Activity::VaultOfGlass, 1, 1);
+        let mut raids = ChannelEvents::new(type_filter(ActivityType::Raid), iter::once(&event));
+        let mut crucible =
+            ChannelEvents::new(type_filter(ActivityType::Crucible), iter::once(&event));
+        assert_eq!(raids.snapshot(), vec![event.clone()]);
+        assert!(crucible.snapshot().is_empty());
+
+        // The event keeps its ID, so the edit finds and removes its old message.
+        let mut edited = event.clone();
+        Arc::make_mut(&mut edited).activity = Activity::TrialsOfOsiris;
+        assert_eq!(
+            raids
+                .apply_event_change(EventChange::Edited(edited.clone()))
+                .collect::<Vec<_>>(),
+            vec![delete_action(0)],
+        );
+        assert_eq!(
+            crucible
+                .apply_event_change(EventChange::Edited(edited.clone()))
+                .collect::<Vec<_>>(),
+            vec![new_action(&edited)],
+        );
+        assert!(raids.snapshot().is_empty());
+        assert_eq!(crucible.snapshot(), vec![edited]);
+    }
+
+    #[test]
+    fn events_posted_once_min_confirmed_reached() {
+        let mut chan =
+            ChannelEvents::with_min_confirmed(Box::new(|_: &Event| true), 2, iter::empty());
+        // The creator is the only confirmed member at first.
+        let mut event = test_event(Activity::VaultOfGlass, 1, 1);
+        assert_eq!(event.confirmed().len(), 1);
+        assert_eq!(
+            chan.apply_event_change(EventChange::Added(event.clone()))
+                .count(),
+            0
+        );
+        assert!(chan.events.is_empty());
+
+        // A maybe doesn't count towards the threshold.
+        let mut maybe = User::default();
+        maybe.id = UserId(11);
+        Arc::make_mut(&mut event)
+            .join(&maybe, JoinKind::Maybe)
+            .unwrap();
+        assert_eq!(
+            chan.apply_event_change(EventChange::Edited(event.clone()))
+                .count(),
+            0
+        );
+
+        let mut joiner = User::default();
+        joiner.id = UserId(12);
+        Arc::make_mut(&mut event)
+            .join(&joiner, JoinKind::Confirmed)
+            .unwrap();
+        assert_eq!(
+            chan.apply_event_change(EventChange::Edited(event.clone()))
+                .collect::<Vec<_>>(),
+            vec![new_action(&event)],
+        );
+
+        // Dropping back below the threshold doesn't take it down again.
+        Arc::make_mut(&mut event).leave(&joiner).unwrap();
+        assert_eq!(
+            chan.apply_event_change(EventChange::Edited(event.clone()))
+                .collect::<Vec<_>>(),
+            vec![update_action(&event, 0)],
+        );
+    }
+
+    #[test]
+    fn posted_events_stay_posted_after_restart() {
+        let posted = test_event(Activity::VaultOfGlass, 1, 1);
+        let unposted = test_event(Activity::VaultOfGlass, 2, 2);
+        let mut chan = ChannelEvents::with_min_confirmed(
+            Box::new(|_: &Event| true),
+            2,
+            [&posted, &unposted].into_iter(),
+        );
+        assert!(chan.events.is_empty());
+
+        // Only events whose messages are already in the channel are put back.
+        let messages = [FakeMessage::for_event(&posted)];
+        let ids = messages.iter().filter_map(message_event_id).collect();
+        chan.keep_posted(&ids);
+        assert_eq!(chan.snapshot(), vec![posted.clone()]);
+        assert!(
+            updates_needed_to_match_events(&messages, &chan, &MessageStyle::default()).is_empty()
+        );
+    }
+
+    fn summary_lines(chan: &ChannelEvents) -> Vec<String> {
+        let embed = summary_embed(chan);
+        embed.0["description"]
+            .as_str()
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn summary_lists_soonest_events_in_order() {
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), iter::empty());
+        assert!(summary_lines(&chan)[0].contains("Nothing scheduled"));
+
+        let later = test_event(Activity::LastWish, 3, 5);
+        let sooner = test_event(Activity::VaultOfGlass, 2, 1);
+        chan.apply_event_change(EventChange::Added(later.clone()))
+            .for_each(drop);
+        chan.apply_event_change(EventChange::Added(sooner.clone()))
+            .for_each(drop);
+        let lines = summary_lines(&chan);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains(&format!("`{}`", sooner.id)));
+        assert!(lines[1].contains(&format!("`{}`", later.id)));
+
+        // Moving an event earlier reorders the summary.
+        let mut soonest = later.clone();
+        Arc::make_mut(&mut soonest).set_datetime(sooner.datetime() - Duration::hours(1));
+        chan.apply_event_change(EventChange::Edited(soonest.clone()))
+            .for_each(drop);
+        let lines = summary_lines(&chan);
+        assert!(lines[0].contains(&format!("`{}`", soonest.id)));
+        assert!(lines[1].contains(&format!("`{}`", sooner.id)));
+
+        chan.apply_event_change(EventChange::Deleted(sooner.clone()))
+            .for_each(drop);
+        assert_eq!(summary_lines(&chan).len(), 1);
+    }
+
+    #[test]
+    fn summary_capped_at_soonest_events() {
+        let events: Vec<_> = (0..SUMMARY_EVENT_COUNT + 2)
+            .map(|i| test_event(Activity::VaultOfGlass, i as u8, i as i64))
+            .collect();
+        let chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+
+        let lines = summary_lines(&chan);
+        assert_eq!(lines.len(), SUMMARY_EVENT_COUNT);
+        for (line, event) in lines.iter().zip(&events) {
+            assert!(line.contains(&format!("`{}`", event.id)));
+        }
+        let embed = summary_embed(&chan);
+        assert_eq!(embed.0["footer"]["text"], "...and 2 more");
+    }
+
+    #[test]
+    fn summary_updated_when_stale() {
+        let event = test_event(Activity::DeepStoneCrypt, 1, 0);
+        let chan = ChannelEvents::new(Box::new(|_: &Event| true), iter::once(&event));
+        let target = summary_embed(&chan);
+
+        let mut summary = FakeMessage {
+            author: OWN_ID,
+            suppressed: false,
+            content: String::new(),
+            embeds: vec![target.clone()],
+            buttons: vec![],
+        };
+        assert!(!summary_update_needed(&summary, &target));
+
+        // A repurposed event message needs its embed and buttons replaced.
+        assert!(summary_update_needed(
+            &FakeMessage::for_event(&event),
+            &target
+        ));
+
+        summary.suppressed = true;
+        assert!(summary_update_needed(&summary, &target));
+
+        let empty = ChannelEvents::new(Box::new(|_: &Event| true), iter::empty());
+        summary.suppressed = false;
+        assert!(summary_update_needed(&summary, &summary_embed(&empty)));
+    }
+
+    #[test]
+    fn user_messages_kept_when_deletion_disabled() {
+        let event1 = test_event(Activity::DeepStoneCrypt, 1, 0);
+        let event2 = test_event(Activity::VaultOfGlass, 2, 1);
+        let events = [event1.clone(), event2.clone()];
+        let chan = ChannelEvents::new(Box::new(|_: &Event| true), events.iter());
+
+        let user_message = || FakeMessage {
+            author: UserId(2),
+            suppressed: false,
+            content: "anyone up for a raid?".to_owned(),
+            embeds: vec![],
+            buttons: vec![],
+        };
+        let mut stale = FakeMessage::for_event(&event1);
+        stale.content = "outdated".to_owned();
+        let messages = || {
+            vec![
+                FakeMessage::for_event(&event1),
+                user_message(),
+                FakeMessage::for_event(&event2),
+            ]
+        };
+
+        let (own, others) = partition_channel_messages(messages(), OWN_ID, false);
+        assert_eq!(own.len(), 2);
+        assert!(others.is_empty());
+        assert!(updates_needed_to_match_events(&own, &chan, &MessageStyle::default()).is_empty());
+
+        // The bot's own messages are still reconciled.
+        let (own, _) = partition_channel_messages(vec![stale, user_message()], OWN_ID, false);
+        assert_eq!(
+            updates_needed_to_match_events(&own, &chan, &MessageStyle::default()),
+            vec![
+                update_action(&event1, 0),
+                ChannelUpdate::New {
+                    event: &event2,
+                    thread: false,
+                    ping: false,
+                },
+            ]
+        );
+
+        let (own, others) = partition_channel_messages(messages(), OWN_ID, true);
+        assert_eq!(own.len(), 2);
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].author, UserId(2));
+    }
+
+    #[test]
+    fn alert_pings_recognized() {
+        let role = RoleId(9);
+        let event = test_event(Activity::VaultOfGlass, 2, 0);
+        let alert = format!(
+            "Alert Protocol initiated for LFG **{}** ({})\nGroup 1: <@1>",
+            event.id, event.activity
+        );
+        let ping = FakeMessage {
+            author: OWN_ID,
+            suppressed: false,
+            content: alert_ping_content(role, &alert),
+            embeds: vec![],
+            buttons: vec![],
+        };
+        assert_eq!(alert_ping_event_id(&ping, Some(role)), Some(event.id));
+        assert_eq!(alert_ping_event_id(&ping, Some(RoleId(8))), None);
+        assert_eq!(alert_ping_event_id(&ping, None), None);
+
+        // Event messages show the alert too, but aren't pings.
+        let mut message = FakeMessage::for_event(&event);
+        message.content = alert;
+        assert_eq!(alert_ping_event_id(&message, Some(role)), None);
+    }
+
+    #[test]
+    fn new_event_role_mentioned_only_when_posted() {
+        let role = RoleId(7);
+        let mut chan = ChannelEvents::new(Box::new(|_: &Event| true), iter::empty());
+        let event = test_event(Activity::VaultOfGlass, 1, 0);
+        let updates = chan
+            .apply_event_change(EventChange::Added(event.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(updates, vec![new_action(&event)]);
+        assert_eq!(new_message_content(&event, Some(role)), "<@&7>");
+        assert_eq!(new_message_content(&event, None), "");
+
+        // Edits update the message, which keeps only the alert as its content.
+        let mut edited = event.clone();
+        Arc::make_mut(&mut edited).description = "edited".to_owned();
+        assert_eq!(
+            chan.apply_event_change(EventChange::Edited(edited.clone()))
+                .collect::<Vec<_>>(),
+            vec![update_action(&edited, 0)]
+        );
+
+        // Reposts don't ping again.
+        assert!(chan.bump(edited.id).all(|update| match update {
+            ChannelUpdate::New { ping, .. } => !ping,
+            _ => true,
+        }));
+    }
+
+    #[tokio::test]
+    async fn event_threads_round_trip() {
+        let tempdir = tempdir::TempDir::new("event_threads").unwrap();
+        let store_builder = crate::store::PersistentStoreBuilder::new(tempdir.path())
+            .await
+            .unwrap();
+        let load = || async {
+            let store = store_builder.build("event_threads.json").await.unwrap();
+            EventThreadStore::new(store).await.unwrap()
+        };
+        let vog1 = EventId {
+            activity: Activity::VaultOfGlass,
+            idx: 1,
+        };
+        let vog2 = EventId {
+            activity: Activity::VaultOfGlass,
+            idx: 2,
+        };
+
+        let threads = load().await;
+        threads
+            .set(ChannelId(1), vog1, Some(ChannelId(11)))
+            .await
+            .unwrap();
+        threads
+            .set(ChannelId(1), vog2, Some(ChannelId(12)))
+            .await
+            .unwrap();
+        threads
+            .set(ChannelId(2), vog1, Some(ChannelId(21)))
+            .await
+            .unwrap();
+        threads.set(ChannelId(2), vog1, None).await.unwrap();
+        drop(threads);
+
+        // Threads survive a restart, and forgetting a channel's last thread forgets the channel.
+        let threads = load().await;
+        assert_eq!(
+            threads.threads(ChannelId(1)).await,
+            BTreeMap::from_iter([(vog1, ChannelId(11)), (vog2, ChannelId(12))])
+        );
+        assert!(threads.threads(ChannelId(2)).await.is_empty());
+        assert!(!threads.threads.lock().await.contains_key(&ChannelId(2)));
+    }
 }
@@ -0,0 +1,226 @@
+use super::fixed::{self, EmbedContent, EventEmbedMessage};
+use crate::{
+    event::EventId,
+    store::{PersistentStore, PersistentStoreBuilder},
+};
+use anyhow::{format_err, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::http::Http;
+use tokio::sync::Mutex;
+
+const STORE_NAME: &str = "dead_letters.json";
+
+// Keep the log from growing without bound if, say, Discord is down for a while.
+const MAX_ENTRIES: usize = 100;
+
+/// An operation on a tracked embed message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmbedOperation {
+    Update,
+    Delete,
+}
+
+impl std::fmt::Display for EmbedOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbedOperation::Update => f.write_str("update"),
+            EmbedOperation::Delete => f.write_str("delete"),
+        }
+    }
+}
+
+/// An embed operation that failed, kept so that an admin can inspect and retry it later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedOp {
+    pub event_id: EventId,
+    pub operation: EmbedOperation,
+    pub message: EventEmbedMessage,
+    pub error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl FailedOp {
+    /// Whether retrying is pointless, because the message can no longer be modified or because
+    /// it's an update to an event that no longer exists.
+    pub fn obsolete(&self, event_exists: bool) -> bool {
+        self.message.expired() || (self.operation == EmbedOperation::Update && !event_exists)
+    }
+
+    fn is_same(&self, other: &FailedOp) -> bool {
+        self.event_id == other.event_id
+            && self.operation == other.operation
+            && self.message == other.message
+            && self.failed_at == other.failed_at
+    }
+
+    /// Retry the operation. Updates need the event's current content, so if the event no longer
+    /// exists then there's nothing left to update.
+    pub async fn retry(&self, http: &Http, content: Option<&EmbedContent>) -> Result<()> {
+        match (self.operation, content) {
+            (EmbedOperation::Update, Some(content)) => {
+                fixed::update_message(http, &self.message, content).await
+            }
+            (EmbedOperation::Update, None) => Err(format_err!(
+                "Event {} no longer exists to update",
+                self.event_id
+            )),
+            (EmbedOperation::Delete, _) => fixed::delete_message(http, &self.message).await,
+        }
+    }
+}
+
+/// Collects the failures from a set of embed operation results.
+pub fn failed_ops(
+    event_id: EventId,
+    operation: EmbedOperation,
+    results: Vec<(EventEmbedMessage, Result<()>)>,
+) -> Vec<FailedOp> {
+    let now = Utc::now();
+    results
+        .into_iter()
+        .filter_map(|(message, result)| {
+            let err = result.err()?;
+            Some(FailedOp {
+                event_id,
+                operation,
+                message,
+                error: format!("{:?}", err),
+                failed_at: now,
+            })
+        })
+        .collect()
+}
+
+/// Persistent log of embed operations that failed.
+#[derive(Debug)]
+pub struct DeadLetterLog {
+    ops: Mutex<Vec<FailedOp>>,
+    store: PersistentStore<Vec<FailedOp>>,
+}
+
+impl DeadLetterLog {
+    pub async fn new(store_builder: &PersistentStoreBuilder) -> Result<Self> {
+        let store = store_builder.build(STORE_NAME).await?;
+        let ops = Mutex::new(store.load().await?);
+        Ok(DeadLetterLog { ops, store })
+    }
+
+    pub async fn record(&self, failed: Vec<FailedOp>) -> Result<()> {
+        if failed.is_empty() {
+            return Ok(());
+        }
+        let mut ops = self.ops.lock().await;
+        ops.extend(failed);
+        if ops.len() > MAX_ENTRIES {
+            let excess = ops.len() - MAX_ENTRIES;
+            ops.drain(..excess);
+        }
+        self.store.store(&ops).await
+    }
+
+    pub async fn list(&self) -> Vec<FailedOp> {
+        self.ops.lock().await.clone()
+    }
+
+    /// Removes the entry, e.g. once a retry of it has succeeded. Does nothing if it's no longer in
+    /// the log.
+    pub async fn remove(&self, op: &FailedOp) -> Result<()> {
+        let mut ops = self.ops.lock().await;
+        let len = ops.len();
+        ops.retain(|o| !o.is_same(op));
+        if ops.len() == len {
+            return Ok(());
+        }
+        self.store.store(&ops).await
+    }
+
+    /// Replaces the entry with an updated one, e.g. with the error from a failed retry. Does nothing
+    /// if it's no longer in the log.
+    pub async fn replace(&self, op: &FailedOp, updated: FailedOp) -> Result<()> {
+        let mut ops = self.ops.lock().await;
+        match ops.iter_mut().find(|o| o.is_same(op)) {
+            Some(o) => *o = updated,
+            None => return Ok(()),
+        }
+        self.store.store(&ops).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Activity;
+    use serenity::model::id::{ChannelId, MessageId};
+    use std::str::FromStr;
+    use test_env_log::test;
+
+    #[test(tokio::test)]
+    async fn failure_recorded_in_dead_letters() {
+        let tempdir = tempdir::TempDir::new("dead_letter").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let event_id = EventId::from_str("vog1").unwrap();
+        let results = vec![
+            (
+                EventEmbedMessage::Normal(ChannelId(1), MessageId(10)),
+                Ok(()),
+            ),
+            (
+                EventEmbedMessage::Normal(ChannelId(1), MessageId(11)),
+                Err(format_err!("Simulated failure")),
+            ),
+        ];
+
+        {
+            let log = DeadLetterLog::new(&store_builder).await.unwrap();
+            assert!(log.list().await.is_empty());
+            log.record(failed_ops(event_id, EmbedOperation::Delete, results))
+                .await
+                .unwrap();
+        }
+
+        // Entries persist across restarts.
+        let log = DeadLetterLog::new(&store_builder).await.unwrap();
+        let ops = log.list().await;
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].event_id.activity, Activity::VaultOfGlass);
+        assert_eq!(ops[0].operation, EmbedOperation::Delete);
+        assert_eq!(
+            ops[0].message,
+            EventEmbedMessage::Normal(ChannelId(1), MessageId(11))
+        );
+        assert!(ops[0].error.contains("Simulated failure"));
+
+        let mut retried = ops[0].clone();
+        retried.error = "Simulated failure again".to_owned();
+        log.replace(&ops[0], retried.clone()).await.unwrap();
+        let ops = log.list().await;
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].error, "Simulated failure again");
+
+        log.remove(&retried).await.unwrap();
+        assert!(log.list().await.is_empty());
+        // Removals persist too.
+        let log = DeadLetterLog::new(&store_builder).await.unwrap();
+        assert!(log.list().await.is_empty());
+    }
+
+    #[test]
+    fn updates_to_deleted_events_obsolete() {
+        let event_id = EventId::from_str("vog1").unwrap();
+        let results = vec![(
+            EventEmbedMessage::Normal(ChannelId(1), MessageId(10)),
+            Err(format_err!("Simulated failure")),
+        )];
+        let update = failed_ops(event_id, EmbedOperation::Update, results).remove(0);
+        assert!(!update.obsolete(true));
+        assert!(update.obsolete(false));
+
+        // Deleting the event's messages doesn't need the event.
+        let delete = FailedOp {
+            operation: EmbedOperation::Delete,
+            ..update
+        };
+        assert!(!delete.obsolete(false));
+    }
+}
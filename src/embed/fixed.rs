@@ -1,23 +1,37 @@
-use crate::event::{Event, EventId};
+use super::dead_letter::{failed_ops, DeadLetterLog, EmbedOperation};
+use crate::{
+    event::{ButtonLabels, EmbedStyle, Event, EventId},
+    store::PersistentStore,
+    util::{DiscordJsonErrorCode, SerenityErrorExt},
+};
 use anyhow::{Context as _, Result};
 use chrono::{Duration, Utc};
-use futures::prelude::*;
+use futures::{
+    future::{BoxFuture, Shared},
+    prelude::*,
+};
 use lazy_static::lazy_static;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serenity::{
+    builder::{CreateComponents, CreateEmbed},
     http::Http,
     model::{
-        id::{ChannelId, MessageId},
+        id::{ChannelId, GuildId, MessageId},
         interactions::application_command::ApplicationCommandInteraction,
     },
+    prelude::SerenityError,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
 };
-use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 use tracing::{debug, error, info, warn};
 
 #[derive(Debug, Default)]
 pub struct EmbedMessages {
     messages: Arc<RwLock<HashMap<EventId, Vec<EventEmbedMessage>>>>,
+    op_queue: EventOpQueue,
 }
 
 impl EmbedMessages {
@@ -39,11 +53,46 @@ impl EmbedMessages {
             .for_each(|vec| vec.retain(|m| !m.expired()));
     }
 
-    /// Asychronously (in a spawned task) update the embeds in tracked messages.
-    pub fn start_updating_embeds(&self, http: impl AsRef<Arc<Http>>, event: &Event) {
-        let embed = event.as_embed();
-        let alert_message = event.alert_protocol_message().unwrap_or_default();
-        let event_id = event.id;
+    /// Stops tracking the messages whose updates failed because they no longer exist, e.g. because
+    /// an admin or another bot deleted them, since they'd only keep failing. Returns the remaining
+    /// results and whether any messages were dropped.
+    async fn prune_unknown_messages(
+        &self,
+        event_id: EventId,
+        results: Vec<(EventEmbedMessage, Result<()>)>,
+    ) -> (Vec<(EventEmbedMessage, Result<()>)>, bool) {
+        let (unknown, results): (Vec<_>, Vec<_>) = results
+            .into_iter()
+            .partition(|(_, result)| matches!(result, Err(err) if is_unknown_message(err)));
+        if unknown.is_empty() {
+            return (results, false);
+        }
+
+        warn!(
+            "No longer updating {} deleted embed messages for event {}",
+            unknown.len(),
+            event_id
+        );
+        let mut msgs = self.messages.write().await;
+        if let Some(event_msgs) = msgs.get_mut(&event_id) {
+            event_msgs.retain(|m| !unknown.iter().any(|(gone, _)| gone == m));
+            if event_msgs.is_empty() {
+                msgs.remove(&event_id);
+            }
+        }
+        (results, true)
+    }
+
+    /// Asychronously (in a spawned task) update the embeds in tracked messages. Messages that no
+    /// longer exist stop being tracked, and other failures are recorded in the dead-letter log.
+    pub fn start_updating_embeds(
+        self: &Arc<Self>,
+        http: impl AsRef<Arc<Http>>,
+        event_id: EventId,
+        content: EmbedContent,
+        dead_letters: Arc<DeadLetterLog>,
+        store: Arc<PersistentStore<EmbedMessages>>,
+    ) {
         let http = http.as_ref().clone();
         let messages = self.messages.clone();
         let update_fut = async move {
@@ -52,51 +101,44 @@ impl EmbedMessages {
             let event_messages = messages.get(&event_id).unwrap_or(&empty);
 
             future::join_all(event_messages.iter().filter(|m| !m.expired()).map(|msg| {
-                let (http, embed, alert_message) = (&http, &embed, &alert_message);
+                let (http, content) = (&http, &content);
                 async move {
-                    match msg {
-                        EventEmbedMessage::Normal(chan_id, msg_id) => {
-                            chan_id
-                                .edit_message(http, msg_id, |edit| {
-                                    edit.embed(|e| {
-                                        *e = embed.clone();
-                                        e
-                                    })
-                                    .content(alert_message.clone())
-                                })
-                                .await
-                        }
-                        EventEmbedMessage::EphemeralResponse(interaction, ..) => {
-                            interaction
-                                .edit_original_interaction_response(&http, |resp| {
-                                    resp.set_embeds(vec![embed.clone()])
-                                })
-                                .await
-                        }
-                    }
-                    .context("Failed to edit message")
+                    let result = update_message(http, msg, content).await;
+                    (msg.clone(), result)
                 }
             }))
             .await
         };
 
-        tokio::spawn(async move {
+        let embed_messages = self.clone();
+        self.op_queue.spawn(event_id, async move {
             let results = update_fut.await;
             if results.is_empty() {
                 return;
             }
 
-            let (successes, failures): (Vec<_>, Vec<_>) =
-                results.into_iter().partition(Result::is_ok);
-            let count = successes.len() + failures.len();
+            let (results, pruned) = embed_messages
+                .prune_unknown_messages(event_id, results)
+                .await;
+            if pruned {
+                if let Err(err) = store.store(&embed_messages).await {
+                    error!("Failed to store pruned embed messages: {:?}", err);
+                }
+            }
+            if results.is_empty() {
+                return;
+            }
+
+            let count = results.len();
+            let failures = failed_ops(event_id, EmbedOperation::Update, results);
             if failures.is_empty() {
                 info!("Successfully updated fixed embeds for event {}", event_id);
-            } else if successes.is_empty() {
+                return;
+            } else if failures.len() == count {
                 error!(
                     "All ({}) embeds failed to update for event {}",
                     count, event_id
                 );
-                failures.into_iter().for_each(|f| error!("{:?}", f));
             } else {
                 error!(
                     "Some ({}/{}) embeds failed to update for event {}",
@@ -104,12 +146,20 @@ impl EmbedMessages {
                     count,
                     event_id
                 );
-                failures.into_iter().for_each(|f| error!("{:?}", f));
+            }
+            failures.iter().for_each(|f| error!("{}", f.error));
+            if let Err(err) = dead_letters.record(failures).await {
+                error!("Failed to record failed embed updates: {:?}", err);
             }
         });
     }
 
-    pub async fn start_deleting_embeds(&self, http: impl AsRef<Arc<Http>>, event: &Event) {
+    pub async fn start_deleting_embeds(
+        &self,
+        http: impl AsRef<Arc<Http>>,
+        event: &Event,
+        dead_letters: Arc<DeadLetterLog>,
+    ) {
         let event_id = event.id;
         let http = http.as_ref().clone();
         let mut messages = self.messages.write().await;
@@ -127,49 +177,29 @@ impl EmbedMessages {
                     .map(|msg| {
                         let http = &http;
                         async move {
-                            match msg {
-                                EventEmbedMessage::Normal(chan_id, msg_id) => {
-                                    chan_id.delete_message(http, msg_id).await
-                                }
-                                EventEmbedMessage::EphemeralResponse(interaction, ..) => {
-                                    interaction
-                                        .edit_original_interaction_response(http, |resp| {
-                                            // set_embeds(vec![]) does nothing, rather than removing
-                                            // existing embeds, so set embeds empty explicity
-                                            resp.0
-                                                .insert("embeds", serde_json::Value::Array(vec![]));
-                                            resp.components(|c| {
-                                                *c = Default::default();
-                                                c
-                                            })
-                                        })
-                                        .await
-                                        .and(Ok(()))
-                                }
-                            }
-                            .context("Failed to delete message")
+                            let result = delete_message(http, &msg).await;
+                            (msg, result)
                         }
                     }),
             )
             .await
         };
 
-        tokio::spawn(async move {
+        self.op_queue.spawn(event_id, async move {
             let results = update_fut.await;
-            let (successes, failures): (Vec<_>, Vec<_>) =
-                results.into_iter().partition(Result::is_ok);
-            let count = successes.len() + failures.len();
+            let count = results.len();
+            let failures = failed_ops(event_id, EmbedOperation::Delete, results);
             if failures.is_empty() {
                 info!(
                     "Successfully deleted fixed embed messages for event {}",
                     event_id
                 );
-            } else if successes.is_empty() {
+                return;
+            } else if failures.len() == count {
                 error!(
                     "All ({}) embed messages failed to delete for event {}",
                     count, event_id
                 );
-                failures.into_iter().for_each(|f| error!("{:?}", f));
             } else {
                 error!(
                     "Some ({}/{}) embed messages failed to delete for event {}",
@@ -177,12 +207,168 @@ impl EmbedMessages {
                     count,
                     event_id
                 );
-                failures.into_iter().for_each(|f| error!("{:?}", f));
+            }
+            failures.iter().for_each(|f| error!("{}", f.error));
+            if let Err(err) = dead_letters.record(failures).await {
+                error!("Failed to record failed embed deletes: {:?}", err);
+            }
+        });
+    }
+}
+
+type QueuedOp = Shared<BoxFuture<'static, ()>>;
+
+/// Runs each event's embed operations one at a time, in the order they were started, so that e.g.
+/// deleting an event's embeds can't be overtaken by an update that was started before it.
+#[derive(Default)]
+struct EventOpQueue {
+    // The most recently started operation for each event that hasn't finished yet, along with a
+    // sequence number to tell whether it's still the most recent one once it finishes.
+    last_ops: Arc<Mutex<HashMap<EventId, (u64, QueuedOp)>>>,
+    next_seq: Mutex<u64>,
+}
+
+impl std::fmt::Debug for EventOpQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pending = self.last_ops.lock().unwrap().len();
+        f.debug_struct("EventOpQueue")
+            .field("pending_events", &pending)
+            .finish()
+    }
+}
+
+impl EventOpQueue {
+    /// Spawns a task that runs op once the event's previously started operations have finished.
+    fn spawn<F>(&self, event_id: EventId, op: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let seq = {
+            let mut next_seq = self.next_seq.lock().unwrap();
+            *next_seq += 1;
+            *next_seq
+        };
+        let queued = {
+            let mut last_ops = self.last_ops.lock().unwrap();
+            let prev = last_ops.get(&event_id).map(|(_, op)| op.clone());
+            let queued = async move {
+                if let Some(prev) = prev {
+                    prev.await;
+                }
+                op.await;
+            }
+            .boxed()
+            .shared();
+            last_ops.insert(event_id, (seq, queued.clone()));
+            queued
+        };
+
+        let last_ops = self.last_ops.clone();
+        tokio::spawn(async move {
+            queued.await;
+            let mut last_ops = last_ops.lock().unwrap();
+            if matches!(last_ops.get(&event_id), Some((last, _)) if *last == seq) {
+                last_ops.remove(&event_id);
             }
         });
     }
 }
 
+/// Whether the operation failed because the message no longer exists.
+fn is_unknown_message(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<SerenityError>().map_or(false, |err| {
+        err.is_discord_json_error(DiscordJsonErrorCode::UnknownMessage)
+    })
+}
+
+/// What an event's tracked messages are updated to show.
+pub struct EmbedContent {
+    embed: CreateEmbed,
+    alert_message: String,
+    buttons: CreateComponents,
+    dm_buttons: CreateComponents,
+}
+
+impl EmbedContent {
+    pub fn new(
+        event: &Event,
+        style: &EmbedStyle,
+        labels: &ButtonLabels,
+        guild_id: GuildId,
+    ) -> Self {
+        EmbedContent {
+            embed: event.as_embed(style),
+            alert_message: event.alert_protocol_message().unwrap_or_default(),
+            buttons: event.event_buttons(labels),
+            dm_buttons: event.dm_event_buttons(labels, guild_id),
+        }
+    }
+}
+
+/// Update the embed and buttons (and alert message content) in a single tracked message.
+pub(super) async fn update_message(
+    http: &Http,
+    msg: &EventEmbedMessage,
+    content: &EmbedContent,
+) -> Result<()> {
+    match msg {
+        EventEmbedMessage::Normal(chan_id, msg_id) | EventEmbedMessage::Dm(chan_id, msg_id) => {
+            let buttons = match msg {
+                EventEmbedMessage::Dm(..) => &content.dm_buttons,
+                _ => &content.buttons,
+            };
+            chan_id
+                .edit_message(http, msg_id, |edit| {
+                    edit.embed(|e| {
+                        *e = content.embed.clone();
+                        e
+                    })
+                    .components(|c| {
+                        *c = buttons.clone();
+                        c
+                    })
+                    .content(&content.alert_message)
+                })
+                .await
+                .and(Ok(()))
+        }
+        EventEmbedMessage::EphemeralResponse(interaction, ..) => interaction
+            .edit_original_interaction_response(&http, |resp| {
+                resp.set_embeds(vec![content.embed.clone()])
+                    .components(|c| {
+                        *c = content.buttons.clone();
+                        c
+                    })
+            })
+            .await
+            .and(Ok(())),
+    }
+    .context("Failed to edit message")
+}
+
+/// Delete a single tracked message, or for ephemeral responses (which can't be deleted) remove
+/// the embed from it.
+pub(super) async fn delete_message(http: &Http, msg: &EventEmbedMessage) -> Result<()> {
+    match msg {
+        EventEmbedMessage::Normal(chan_id, msg_id) | EventEmbedMessage::Dm(chan_id, msg_id) => {
+            chan_id.delete_message(http, msg_id).await
+        }
+        EventEmbedMessage::EphemeralResponse(interaction, ..) => interaction
+            .edit_original_interaction_response(http, |resp| {
+                // set_embeds(vec![]) does nothing, rather than removing
+                // existing embeds, so set embeds empty explicity
+                resp.0.insert("embeds", serde_json::Value::Array(vec![]));
+                resp.components(|c| {
+                    *c = Default::default();
+                    c
+                })
+            })
+            .await
+            .and(Ok(())),
+    }
+    .context("Failed to delete message")
+}
+
 impl Serialize for EmbedMessages {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
     where
@@ -211,15 +397,19 @@ impl<'de> Deserialize<'de> for EmbedMessages {
 
         Ok(EmbedMessages {
             messages: Arc::new(RwLock::new(value)),
+            op_queue: Default::default(),
         })
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum EventEmbedMessage {
     // A "normal" message in a channel, either posted directly by the bot or a non-ephemeral
     // interaction response.
     Normal(ChannelId, MessageId),
+    // A message DMed to a member, which needs the buttons meant for DMs since interactions there
+    // don't say which guild the event belongs to.
+    Dm(ChannelId, MessageId),
     // An ephemeral interaction response and the text of the response.
     // These cannot be edited by message ID, only through the Edit Original Interaction Response
     // endpoint, and then only within the 15 minute lifetime of the Interaction's token.
@@ -252,9 +442,9 @@ impl EventEmbedMessage {
         }
     }
 
-    fn expired(&self) -> bool {
+    pub(super) fn expired(&self) -> bool {
         match self {
-            EventEmbedMessage::Normal(..) => false,
+            EventEmbedMessage::Normal(..) | EventEmbedMessage::Dm(..) => false,
             EventEmbedMessage::EphemeralResponse(interaction, _) => {
                 Utc::now().signed_duration_since(interaction.id.created_at())
                     >= *INTERACTION_LIFETIME
@@ -312,7 +502,7 @@ impl PartialEq for EventEmbedMessage {
     fn eq(&self, other: &Self) -> bool {
         use EventEmbedMessage::*;
         match (self, other) {
-            (Normal(a1, a2), Normal(b1, b2)) => a1 == b1 && a2 == b2,
+            (Normal(a1, a2), Normal(b1, b2)) | (Dm(a1, a2), Dm(b1, b2)) => a1 == b1 && a2 == b2,
             (EphemeralResponse(a, ..), EphemeralResponse(b, ..)) => a.id == b.id,
             _ => false,
         }
@@ -320,3 +510,119 @@ impl PartialEq for EventEmbedMessage {
 }
 
 impl Eq for EventEmbedMessage {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{activity::Activity, util::button_states};
+    use anyhow::format_err;
+    use serenity::{http::error::Error as HttpError, model::id::MessageId};
+    use std::time::Duration as StdDuration;
+
+    #[tokio::test(start_paused = true)]
+    async fn delete_waits_for_earlier_update() {
+        let queue = EventOpQueue::default();
+        let id = EventId {
+            activity: Activity::Custom,
+            idx: 1,
+        };
+        let other_id = EventId { idx: 2, ..id };
+        // Stands in for the event's message: Some(embed) while it's posted, None once deleted.
+        let message = Arc::new(Mutex::new(Some("original")));
+        let log = Arc::new(Mutex::new(vec![]));
+
+        // A slow update is started before the delete, and would re-add the embed if it finished
+        // after it.
+        let (msg, l) = (message.clone(), log.clone());
+        queue.spawn(id, async move {
+            tokio::time::sleep(StdDuration::from_secs(5)).await;
+            *msg.lock().unwrap() = Some("edited");
+            l.lock().unwrap().push("update");
+        });
+        let (msg, l) = (message.clone(), log.clone());
+        queue.spawn(id, async move {
+            *msg.lock().unwrap() = None;
+            l.lock().unwrap().push("delete");
+        });
+        // Other events' operations don't wait on this one.
+        let l = log.clone();
+        queue.spawn(other_id, async move {
+            l.lock().unwrap().push("other");
+        });
+
+        tokio::time::sleep(StdDuration::from_secs(1)).await;
+        assert_eq!(*log.lock().unwrap(), vec!["other"]);
+        assert_eq!(*message.lock().unwrap(), Some("original"));
+
+        tokio::time::sleep(StdDuration::from_secs(5)).await;
+        assert_eq!(*log.lock().unwrap(), vec!["other", "update", "delete"]);
+        assert_eq!(*message.lock().unwrap(), None);
+        assert!(queue.last_ops.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn embed_content_has_current_buttons() {
+        let event = Event {
+            locked: true,
+            ..Default::default()
+        };
+        let labels = ButtonLabels::default();
+        let content = EmbedContent::new(&event, &Default::default(), &labels, GuildId(5));
+        assert_eq!(
+            button_states(&content.buttons),
+            button_states(&event.event_buttons(&labels))
+        );
+        assert_eq!(
+            button_states(&content.dm_buttons),
+            button_states(&event.dm_event_buttons(&labels, GuildId(5)))
+        );
+        // Buttons for joining are disabled while the roster is locked.
+        assert!(button_states(&content.buttons)[0].1);
+    }
+
+    async fn unknown_message_error() -> anyhow::Error {
+        let response = http::Response::builder()
+            .status(404)
+            .body(r#"{"message": "Unknown Message", "code": 10008}"#)
+            .unwrap();
+        let err = HttpError::from_response(reqwest::Response::from(response)).await;
+        anyhow::Error::new(SerenityError::Http(Box::new(err))).context("Failed to edit message")
+    }
+
+    #[tokio::test]
+    async fn unknown_messages_pruned() {
+        let id = EventId {
+            activity: Activity::Custom,
+            idx: 1,
+        };
+        let deleted = EventEmbedMessage::Normal(ChannelId(1), MessageId(1));
+        let flaky = EventEmbedMessage::Normal(ChannelId(1), MessageId(2));
+        let fine = EventEmbedMessage::Normal(ChannelId(2), MessageId(3));
+        let embed_messages = EmbedMessages::default();
+        for msg in [&deleted, &flaky, &fine] {
+            embed_messages.keep_embed_updated(id, msg.clone()).await;
+        }
+
+        let results = vec![
+            (deleted.clone(), Err(unknown_message_error().await)),
+            (flaky.clone(), Err(format_err!("Internal Server Error"))),
+            (fine.clone(), Ok(())),
+        ];
+        let (results, pruned) = embed_messages.prune_unknown_messages(id, results).await;
+        assert!(pruned);
+        assert_eq!(
+            results.iter().map(|(msg, _)| msg).collect::<Vec<_>>(),
+            vec![&flaky, &fine]
+        );
+        assert_eq!(embed_messages.messages.read().await[&id], vec![flaky, fine]);
+
+        // Once the deleted message is gone, other failures don't prune anything.
+        let results = vec![(
+            EventEmbedMessage::Normal(ChannelId(2), MessageId(3)),
+            Err(format_err!("Internal Server Error")),
+        )];
+        let (results, pruned) = embed_messages.prune_unknown_messages(id, results).await;
+        assert!(!pruned);
+        assert_eq!(results.len(), 1);
+    }
+}
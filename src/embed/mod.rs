@@ -1,87 +1,152 @@
 use crate::{
-    event::{Event, EventChange, EventId},
+    event::{ButtonLabels, EmbedStyle, Event, EventChange, EventId},
     store::{PersistentStore, PersistentStoreBuilder},
 };
 use anyhow::Result;
 use derivative::Derivative;
-use serenity::{model::id::ChannelId, prelude::*};
-use std::{collections::HashMap, sync::Arc};
+use itertools::Itertools;
+use serenity::{
+    model::id::{ChannelId, GuildId, RoleId},
+    prelude::*,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
 
 mod channel;
+mod dead_letter;
 mod fixed;
 
-use channel::EventChannel;
-pub use channel::EventChannelFilterFn;
+use channel::{EventChannel, EventThreadStore};
+pub use channel::{EventChannelConfig, EventChannelFilterFn, EventChannelInfo};
+use dead_letter::DeadLetterLog;
+pub use dead_letter::FailedOp;
+use fixed::EmbedContent;
 pub use fixed::EventEmbedMessage;
 
-#[derive(Default)]
+#[derive(Debug, Default)]
 pub struct EmbedManagerConfig {
-    pub event_channels: HashMap<ChannelId, EventChannelFilterFn>,
+    pub event_channels: HashMap<ChannelId, EventChannelConfig>,
+    /// How event embeds look outside of event channels.
+    pub embed_style: EmbedStyle,
+    /// What the buttons on event messages outside of event channels say.
+    pub button_labels: ButtonLabels,
 }
 
-impl std::fmt::Debug for EmbedManagerConfig {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_map()
-            .entries(
-                self.event_channels
-                    .keys()
-                    .zip(std::iter::repeat("EventChannelFilterFn")),
-            )
-            .finish()
+impl EmbedManagerConfig {
+    /// Applies the new event roles that admins have set, which take precedence over the configured
+    /// ones. A None role means it was cleared.
+    fn apply_new_event_roles(&mut self, roles: &NewEventRoles) {
+        for (channel, role) in roles {
+            if let Some(config) = self.event_channels.get_mut(channel) {
+                config.new_event_role = *role;
+            }
+        }
     }
-}
 
-impl EmbedManagerConfig {
-    fn create_event_channels<'a, I>(self, ctx: &Context, initial_events: I) -> Vec<EventChannel>
+    fn create_event_channels<'a, I>(
+        self,
+        ctx: &Context,
+        thread_store: Arc<EventThreadStore>,
+        initial_events: I,
+    ) -> Vec<EventChannel>
     where
         I: Iterator<Item = &'a Arc<Event>> + Clone,
     {
         self.event_channels
             .into_iter()
-            .map(|(chan_id, filter)| {
-                EventChannel::new(ctx.clone(), chan_id, filter, initial_events.clone())
+            .map(|(chan_id, config)| {
+                EventChannel::new(
+                    ctx.clone(),
+                    chan_id,
+                    config,
+                    thread_store.clone(),
+                    initial_events.clone(),
+                )
             })
             .collect()
     }
 }
 
 const STORE_NAME: &str = "embeds.json";
+const NEW_EVENT_ROLES_STORE_NAME: &str = "new_event_roles.json";
+const THREADS_STORE_NAME: &str = "event_threads.json";
+
+/// The new event role admins have set or cleared for each event channel.
+type NewEventRoles = BTreeMap<ChannelId, Option<RoleId>>;
 
 #[derive(Derivative)]
 #[derivative(Debug)]
 pub struct EmbedManager {
     #[derivative(Debug = "ignore")]
     ctx: Context,
+    guild_id: GuildId,
 
     event_channels: Vec<EventChannel>,
+    // How the event channels are configured, ordered by channel ID.
+    channel_info: Vec<EventChannelInfo>,
+    embed_style: EmbedStyle,
+    button_labels: ButtonLabels,
 
     // Messages that this event's embed has been added to, and which need to be updated when the
     // event is updated.
     // TODO: Could we keep track of a hash of the last embed's data, so we can update on restart if
     // the embed content has changed (say through a code change)?
-    embed_messages: fixed::EmbedMessages,
-    store: PersistentStore<fixed::EmbedMessages>,
+    embed_messages: Arc<fixed::EmbedMessages>,
+    store: Arc<PersistentStore<fixed::EmbedMessages>>,
+
+    // Embed operations that failed, for an admin to retry.
+    dead_letters: Arc<DeadLetterLog>,
+
+    new_event_roles: NewEventRoles,
+    new_event_roles_store: PersistentStore<NewEventRoles>,
 }
 
 impl EmbedManager {
     pub async fn new<'a, I>(
         ctx: Context,
+        guild_id: GuildId,
         store_builder: &PersistentStoreBuilder,
-        config: EmbedManagerConfig,
+        mut config: EmbedManagerConfig,
         initial_events: I,
     ) -> Result<Self>
     where
         I: Iterator<Item = &'a Arc<Event>> + Clone,
     {
         let store = store_builder.build(STORE_NAME).await?;
-        let embed_messages = store.load().await?;
+        let embed_messages = Arc::new(store.load().await?);
 
-        let event_channels = config.create_event_channels(&ctx, initial_events);
+        let dead_letters = Arc::new(DeadLetterLog::new(store_builder).await?);
+
+        let new_event_roles_store = store_builder.build(NEW_EVENT_ROLES_STORE_NAME).await?;
+        let new_event_roles = new_event_roles_store.load().await?;
+        config.apply_new_event_roles(&new_event_roles);
+
+        let thread_store = store_builder.build(THREADS_STORE_NAME).await?;
+        let thread_store = Arc::new(EventThreadStore::new(thread_store).await?);
+
+        let channel_info = config
+            .event_channels
+            .iter()
+            .map(|(id, config)| config.info(*id))
+            .sorted_by_key(|info| info.channel_id)
+            .collect();
+        let embed_style = config.embed_style.clone();
+        let button_labels = config.button_labels.clone();
+        let event_channels = config.create_event_channels(&ctx, thread_store, initial_events);
         Ok(EmbedManager {
             ctx,
+            guild_id,
             event_channels,
+            channel_info,
+            embed_style,
+            button_labels,
             embed_messages,
-            store,
+            store: Arc::new(store),
+            dead_letters,
+            new_event_roles,
+            new_event_roles_store,
         })
     }
 
@@ -92,12 +157,18 @@ impl EmbedManager {
 
         match change {
             EventChange::Added(_) => {}
-            EventChange::Edited(event) | EventChange::Alert(event) => {
-                self.embed_messages.start_updating_embeds(&self.ctx, &event)
-            }
+            EventChange::Edited(event)
+            | EventChange::Alert(event)
+            | EventChange::CheckIn(event) => self.embed_messages.start_updating_embeds(
+                &self.ctx,
+                event.id,
+                self.embed_content(&event),
+                self.dead_letters.clone(),
+                self.store.clone(),
+            ),
             EventChange::Deleted(event) => {
                 self.embed_messages
-                    .start_deleting_embeds(&self.ctx, &event)
+                    .start_deleting_embeds(&self.ctx, &event, self.dead_letters.clone())
                     .await;
                 self.store.store(&self.embed_messages).await?;
             }
@@ -105,6 +176,10 @@ impl EmbedManager {
         Ok(())
     }
 
+    fn embed_content(&self, event: &Event) -> EmbedContent {
+        EmbedContent::new(event, &self.embed_style, &self.button_labels, self.guild_id)
+    }
+
     pub async fn keep_embed_updated(
         &self,
         event_id: EventId,
@@ -115,4 +190,103 @@ impl EmbedManager {
             .await;
         self.store.store(&self.embed_messages).await
     }
+
+    /// Stores the tracked messages, e.g. before shutting down.
+    pub async fn flush(&self) -> Result<()> {
+        self.store.store(&self.embed_messages).await
+    }
+
+    pub fn event_channels(&self) -> Vec<EventChannelInfo> {
+        self.channel_info.clone()
+    }
+
+    /// Sets the role mentioned when new events are posted to the given event channel, or clears it
+    /// with None, overriding the configured role. Returns false if it isn't an event channel.
+    pub async fn set_new_event_role(
+        &mut self,
+        channel_id: ChannelId,
+        role: Option<RoleId>,
+    ) -> Result<bool> {
+        let channel = match self
+            .event_channels
+            .iter()
+            .find(|chan| chan.channel_id() == channel_id)
+        {
+            Some(channel) => channel,
+            None => return Ok(false),
+        };
+        self.new_event_roles.insert(channel_id, role);
+        self.new_event_roles_store
+            .store(&self.new_event_roles)
+            .await?;
+        channel.set_new_event_role(role).await;
+        if let Some(info) = self
+            .channel_info
+            .iter_mut()
+            .find(|info| info.channel_id == channel_id)
+        {
+            info.new_event_role = role;
+        }
+        Ok(true)
+    }
+
+    /// The events the given event channel shows, in order, or None if it isn't an event channel.
+    pub fn channel_events(&self, channel_id: ChannelId) -> Option<Vec<Arc<Event>>> {
+        self.event_channels
+            .iter()
+            .find(|chan| chan.channel_id() == channel_id)
+            .map(EventChannel::events)
+    }
+
+    /// Reposts the event's message at the bottom of each event channel that shows it, returning how
+    /// many channels do.
+    pub async fn bump_event(&self, id: EventId) -> usize {
+        let mut bumped = 0;
+        for channel in &self.event_channels {
+            if channel.bump(id).await {
+                bumped += 1;
+            }
+        }
+        bumped
+    }
+
+    pub async fn failed_ops(&self) -> Vec<FailedOp> {
+        self.dead_letters.list().await
+    }
+
+    /// Retries all failed embed operations, looking up events' current state with the given
+    /// function. Each operation stays in the log until its retry succeeds, and those that fail
+    /// again are updated with the new error. Operations on messages that can no longer be modified
+    /// or on events that no longer exist are dropped. Returns the number of operations that
+    /// succeeded and failed, respectively.
+    pub async fn retry_failed_ops<F>(&self, get_event: F) -> Result<(usize, usize)>
+    where
+        F: Fn(EventId) -> Option<Arc<Event>>,
+    {
+        let mut failed = 0;
+        let mut succeeded = 0;
+        for op in self.dead_letters.list().await {
+            let content = get_event(op.event_id).map(|event| self.embed_content(&event));
+            if op.obsolete(content.is_some()) {
+                self.dead_letters.remove(&op).await?;
+                continue;
+            }
+            match op.retry(&self.ctx.http, content.as_ref()).await {
+                Ok(()) => {
+                    succeeded += 1;
+                    self.dead_letters.remove(&op).await?;
+                }
+                Err(err) => {
+                    failed += 1;
+                    let retried = FailedOp {
+                        error: format!("{:?}", err),
+                        failed_at: chrono::Utc::now(),
+                        ..op.clone()
+                    };
+                    self.dead_letters.replace(&op, retried).await?;
+                }
+            }
+        }
+        Ok((succeeded, failed))
+    }
 }
@@ -9,7 +9,10 @@ use std::{
     sync::{Arc, Weak},
     time::Duration,
 };
-use tokio::{sync::Mutex, time::sleep};
+use tokio::{
+    sync::{Mutex, Notify},
+    time::sleep,
+};
 use tracing::{debug, error, info, warn};
 
 /// Trait used to perform scheduled actions. Primarily this is implemented by EventManager, but this
@@ -50,6 +53,10 @@ impl ScheduledAction {
     pub fn expired<T: TimeZone>(&self, now: &DateTime<T>) -> bool {
         &self.action_datetime <= now
     }
+
+    pub fn action_datetime(&self) -> DateTime<Tz> {
+        self.action_datetime
+    }
 }
 
 impl std::fmt::Display for ScheduledAction {
@@ -63,29 +70,63 @@ impl std::fmt::Display for ScheduledAction {
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum EventAction {
+    /// Open the check-in window, asking event participants to confirm that they're coming.
+    CheckIn,
+
     /// Alert event participants that the event is about to start.
     Alert,
 
+    /// Mark the event as in progress at its start time.
+    Start,
+
     /// Clean up a past event, deleting it and (if needed) creating the next event for recurring
-    /// events.
+    /// events. If messages linger after cleanup, the event is only marked as ended instead.
     Cleanup,
+
+    /// Delete an ended event once its messages have lingered for long enough.
+    Expire,
 }
 
 impl std::fmt::Display for EventAction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            EventAction::CheckIn => f.write_str("CheckIn"),
             EventAction::Alert => f.write_str("Alert"),
+            EventAction::Start => f.write_str("Start"),
             EventAction::Cleanup => f.write_str("Cleanup"),
+            EventAction::Expire => f.write_str("Expire"),
         }
     }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct EventSchedulerConfig {
+    // Duration before an event's scheduled time to open check-in, if check-in is enabled.
+    pub check_in: Option<Duration>,
     // Duration before an event's scheduled time to trigger Alert Protocol.
     pub alert: Duration,
     // Duration after an event's scheduled time to clean up the event.
     pub cleanup: Duration,
+    // Duration after cleanup to keep an ended event's messages around before deleting them, if
+    // they should linger at all.
+    pub message_linger: Option<Duration>,
+}
+
+impl Default for EventSchedulerConfig {
+    fn default() -> Self {
+        EventSchedulerConfig {
+            check_in: None,
+            alert: Duration::from_secs(10 * 60),
+            cleanup: Duration::from_secs(30 * 60),
+            message_linger: None,
+        }
+    }
+}
+
+/// Converts one of the config's timings, which the guild config keeps to at most a year, far short
+/// of where the conversion would fail.
+fn signed(duration: Duration) -> SignedDuration {
+    SignedDuration::from_std(duration).expect("Scheduler timings should be validated")
 }
 
 impl EventSchedulerConfig {
@@ -94,24 +135,51 @@ impl EventSchedulerConfig {
         event: &Event,
         now: &DateTime<Tz>,
     ) -> Vec<ScheduledAction> {
+        // Events whose cleanup was extended are cleaned up later than usual.
+        let cleanup = event.cleanup_time(signed(self.cleanup)) - event.datetime();
+        // Ended events have already been cleaned up, so all that's left is removing their messages.
+        // If lingering was turned off since, they're removed right away.
+        if event.ended() {
+            let linger = signed(self.message_linger.unwrap_or_default());
+            return vec![ScheduledAction::new(
+                event,
+                cleanup + linger,
+                EventAction::Expire,
+            )];
+        }
+
         // Only create an alert action if the event is in the future and hasn't already alerted. This ensures that:
         // - We don't alert multiple times for an event that changes, say because someone
         //   joins/leaves, unless its time changes (which resets 'alerted').
         // - We don't alert for events that just need to be cleaned up, say if the bot was down when
         //   the event occurred.
-        let mut actions = Vec::with_capacity(2);
+        let mut actions = Vec::with_capacity(4);
+        // Same goes for opening check-in.
+        if let Some(check_in) = self.check_in {
+            if !event.check_in_open() && &event.datetime() >= now {
+                actions.push(ScheduledAction::new(
+                    event,
+                    -signed(check_in),
+                    EventAction::CheckIn,
+                ));
+            }
+        }
         if !event.alerted() && &event.datetime() >= now {
             actions.push(ScheduledAction::new(
                 event,
-                -SignedDuration::from_std(self.alert).unwrap(),
+                -signed(self.alert),
                 EventAction::Alert,
             ));
         }
-        actions.push(ScheduledAction::new(
-            event,
-            SignedDuration::from_std(self.cleanup).unwrap(),
-            EventAction::Cleanup,
-        ));
+        // Likewise, events that start while the bot is down are left as they are until cleanup.
+        if !event.started() && &event.datetime() >= now {
+            actions.push(ScheduledAction::new(
+                event,
+                SignedDuration::zero(),
+                EventAction::Start,
+            ));
+        }
+        actions.push(ScheduledAction::new(event, cleanup, EventAction::Cleanup));
         actions
     }
 }
@@ -135,6 +203,7 @@ impl TimeSource for RealTimeSource {
 pub struct EventScheduler<T: TimeSource = RealTimeSource> {
     state: Arc<Mutex<EventSchedulerState<T>>>,
     config: EventSchedulerConfig,
+    shutdown: Arc<Notify>,
 }
 
 impl EventScheduler {
@@ -167,6 +236,7 @@ impl<T: TimeSource> EventScheduler<T> {
                 time_source,
             })),
             config,
+            shutdown: Default::default(),
         }
     }
 
@@ -180,7 +250,7 @@ impl<T: TimeSource> EventScheduler<T> {
             EventChange::Edited(event) | EventChange::Deleted(event) => {
                 state.actions.retain(|action| action.id != event.id);
             }
-            EventChange::Added(_) | EventChange::Alert(_) => {}
+            EventChange::Added(_) | EventChange::Alert(_) | EventChange::CheckIn(_) => {}
         }
         match change {
             EventChange::Added(event) | EventChange::Edited(event) => {
@@ -189,21 +259,30 @@ impl<T: TimeSource> EventScheduler<T> {
                     .actions
                     .extend(self.config.actions_for_event(event, &now));
             }
-            EventChange::Deleted(_) | EventChange::Alert(_) => {}
+            EventChange::Deleted(_) | EventChange::Alert(_) | EventChange::CheckIn(_) => {}
         }
 
         // Kick the scheduler loop so it can check for new actions.
         state.sleep_handle.take().map(|a| a.abort());
     }
 
+    /// How long after events start they're usually cleaned up, unless their cleanup was extended.
+    pub fn cleanup_delay(&self) -> SignedDuration {
+        signed(self.config.cleanup)
+    }
+
+    /// A copy of all currently scheduled actions, soonest first, e.g. for diagnosing missed actions.
+    pub async fn snapshot(&self) -> Vec<ScheduledAction> {
+        self.state.lock().await.actions.iter().cloned().collect()
+    }
+
     pub fn start<H: ScheduledActionHandler>(&self, handler: Weak<H>) {
         let state = self.state.clone();
+        let shutdown = self.shutdown.clone();
         tokio::spawn(async move {
             loop {
                 // This scope ensures the MutexGuard is dropped before sleeping.
-                // We don't care about the reason for the sleep future finishing (duration reached
-                // vs abort) so the return value is ignored.
-                let _ = {
+                let sleep = {
                     let mut state = state.lock().await;
                     let sleep_duration = match handler.upgrade() {
                         Some(handler) => state.perform_actions(handler).await,
@@ -216,11 +295,29 @@ impl<T: TimeSource> EventScheduler<T> {
                     let (sleep, sleep_handle) = abortable(sleep(sleep_duration));
                     state.sleep_handle = Some(sleep_handle);
                     sleep
+                };
+
+                // We don't care about the reason for the sleep future finishing (duration reached
+                // vs abort) so its return value is ignored.
+                tokio::select! {
+                    biased;
+                    _ = shutdown.notified() => {
+                        info!("EventScheduler shut down, stopping its loop");
+                        return;
+                    }
+                    _ = sleep => {}
                 }
-                .await;
             }
         });
     }
+
+    /// Stops the loop started by start, so that no more actions are performed. Actions that were
+    /// already started aren't interrupted.
+    pub fn shutdown(&self) {
+        // notify_one keeps the notification around if the loop is busy performing actions rather
+        // than waiting, so it's not missed.
+        self.shutdown.notify_one();
+    }
 }
 
 #[derive(Debug)]
@@ -310,6 +407,7 @@ mod test {
     use super::*;
     use crate::activity::Activity;
     use anyhow::format_err;
+    use itertools::Itertools;
     use parking_lot::Mutex as SyncMutex;
     use std::collections::{hash_map::Entry, HashMap};
     use tokio::time::Instant;
@@ -453,18 +551,41 @@ mod test {
         }
 
         async fn perform_action(&self, action: &ScheduledAction) -> Result<()> {
-            let mut events = self.events.lock();
-            let mut entry = match events.entry(action.id) {
-                Entry::Occupied(entry) => Ok(entry),
-                Entry::Vacant(_) => Err(format_err!("No event with id {} exists", action.id)),
-            }?;
-            match action.action {
-                EventAction::Alert => {
-                    Arc::make_mut(entry.get_mut()).trigger_alert_protocol();
-                }
-                EventAction::Cleanup => {
-                    entry.remove();
+            let edited = {
+                let mut events = self.events.lock();
+                let mut entry = match events.entry(action.id) {
+                    Entry::Occupied(entry) => Ok(entry),
+                    Entry::Vacant(_) => Err(format_err!("No event with id {} exists", action.id)),
+                }?;
+                match action.action {
+                    EventAction::CheckIn => {
+                        Arc::make_mut(entry.get_mut()).open_check_in();
+                        None
+                    }
+                    EventAction::Alert => {
+                        Arc::make_mut(entry.get_mut()).trigger_alert_protocol();
+                        None
+                    }
+                    EventAction::Start => {
+                        Arc::make_mut(entry.get_mut()).start(false);
+                        Some(entry.get().clone())
+                    }
+                    EventAction::Cleanup if self.scheduler.config.message_linger.is_some() => {
+                        Arc::make_mut(entry.get_mut()).end();
+                        Some(entry.get().clone())
+                    }
+                    EventAction::Cleanup | EventAction::Expire => {
+                        entry.remove();
+                        None
+                    }
                 }
+            };
+            // Like EventManager, starting or ending the event is an edit, which reschedules its
+            // remaining actions (e.g. Expire once it's ended).
+            if let Some(event) = edited {
+                self.scheduler
+                    .event_changed(&EventChange::Edited(event))
+                    .await;
             }
             self.last_actions
                 .lock()
@@ -519,8 +640,10 @@ mod test {
     async fn test_scheduler_with_initial_events() {
         let time_source = TestTimeSource::new();
         let config = EventSchedulerConfig {
+            check_in: None,
             alert: Duration::from_secs(10),
             cleanup: Duration::from_secs(30),
+            message_linger: None,
         };
         let events = vec![
             test_event(&time_source, 1, 90),
@@ -539,14 +662,26 @@ mod test {
         assert_eq!(last.id.idx, 2);
         assert_eq!(last.action, EventAction::Alert);
 
+        // t == 31
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 2);
+        assert_eq!(last.action, EventAction::Start);
+
         // t == 41
-        tokio::time::sleep(Duration::from_secs(20)).await;
+        tokio::time::sleep(Duration::from_secs(10)).await;
         let last = test.take_last_action().unwrap().unwrap();
         assert_eq!(last.id.idx, 3);
         assert_eq!(last.action, EventAction::Alert);
 
+        // t == 51
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 3);
+        assert_eq!(last.action, EventAction::Start);
+
         // t == 61
-        tokio::time::sleep(Duration::from_secs(20)).await;
+        tokio::time::sleep(Duration::from_secs(10)).await;
         let last = test.take_last_action().unwrap().unwrap();
         assert_eq!(last.id.idx, 2);
         assert_eq!(last.action, EventAction::Cleanup);
@@ -561,8 +696,14 @@ mod test {
         assert_eq!(last[1].id.idx, 3);
         assert_eq!(last[1].action, EventAction::Cleanup);
 
+        // t == 91
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::Start);
+
         // t == 121
-        tokio::time::sleep(Duration::from_secs(40)).await;
+        tokio::time::sleep(Duration::from_secs(30)).await;
         let last = test.take_last_action().unwrap().unwrap();
         assert_eq!(last.id.idx, 1);
         assert_eq!(last.action, EventAction::Cleanup);
@@ -572,8 +713,10 @@ mod test {
     async fn test_scheduler_add_edit_delete() {
         let time_source = TestTimeSource::new();
         let config = EventSchedulerConfig {
+            check_in: None,
             alert: Duration::from_secs(10),
             cleanup: Duration::from_secs(30),
+            message_linger: None,
         };
         let events = vec![
             test_event(&time_source, 1, 200),
@@ -609,8 +752,15 @@ mod test {
         test.edit_event_non_time(1).await;
         test.edit_event_non_time(2).await;
 
+        // Event 2 still starts, though.
+        // t == 51
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 2);
+        assert_eq!(last.action, EventAction::Start);
+
         // t == 61
-        tokio::time::sleep(Duration::from_secs(20)).await;
+        tokio::time::sleep(Duration::from_secs(10)).await;
         let last = test.take_last_action().unwrap().unwrap();
         assert_eq!(last.id.idx, 1);
         assert_eq!(last.action, EventAction::Cleanup);
@@ -625,4 +775,281 @@ mod test {
         assert_eq!(last.id.idx, 4);
         assert_eq!(last.action, EventAction::Alert);
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_check_in() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: Some(Duration::from_secs(20)),
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: None,
+        };
+        let events = vec![test_event(&time_source, 1, 50)];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+
+        // t == 31
+        tokio::time::sleep(Duration::from_secs(31)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::CheckIn);
+
+        // Editing the event without changing its time doesn't reopen check-in.
+        test.edit_event_non_time(1).await;
+
+        // t == 41
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Alert);
+
+        // Moving the event later resets check-in, so it opens again.
+        test.edit_event_time(1, 100).await;
+
+        // t == 81
+        tokio::time::sleep(Duration::from_secs(40)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::CheckIn);
+
+        // Events that have already started don't open check-in.
+        test.add_event(test_event(&time_source, 2, 70)).await;
+
+        // t == 99
+        tokio::time::sleep(Duration::from_secs(18)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!((last.id.idx, last.action), (1, EventAction::Alert));
+
+        // t == 101
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let last = test
+            .take_last_actions()
+            .unwrap()
+            .into_iter()
+            .map(|a| (a.id.idx, a.action))
+            .sorted()
+            .collect::<Vec<_>>();
+        assert_eq!(
+            last,
+            vec![(1, EventAction::Start), (2, EventAction::Cleanup)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_message_linger() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: None,
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: Some(Duration::from_secs(60)),
+        };
+        let events = vec![test_event(&time_source, 1, 20)];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+
+        // t == 11
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Alert);
+
+        // t == 21
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Start);
+
+        // Cleanup only marks the event as ended, leaving it around.
+        // t == 51
+        tokio::time::sleep(Duration::from_secs(30)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::Cleanup);
+        let event = test.events.lock().get(&last.id).cloned().unwrap();
+        assert!(event.ended());
+
+        // Nothing happens until the linger period after cleanup has passed.
+        // t == 109
+        tokio::time::sleep(Duration::from_secs(58)).await;
+        assert!(test.take_last_action().unwrap().is_none());
+
+        // t == 111
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::Expire);
+        assert!(test.events.lock().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_snapshot() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: Some(Duration::from_secs(20)),
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: None,
+        };
+        let events = vec![
+            test_event(&time_source, 1, 100),
+            test_event(&time_source, 2, 50),
+        ];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+
+        let summary = |actions: Vec<ScheduledAction>| {
+            actions
+                .into_iter()
+                .map(|a| {
+                    let secs = a
+                        .action_datetime()
+                        .signed_duration_since(time_source.from_start(Duration::ZERO))
+                        .num_seconds();
+                    (secs, a.id.idx, a.action)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            summary(test.scheduler.snapshot().await),
+            vec![
+                (30, 2, EventAction::CheckIn),
+                (40, 2, EventAction::Alert),
+                (50, 2, EventAction::Start),
+                (80, 1, EventAction::CheckIn),
+                (80, 2, EventAction::Cleanup),
+                (90, 1, EventAction::Alert),
+                (100, 1, EventAction::Start),
+                (130, 1, EventAction::Cleanup),
+            ]
+        );
+
+        // Performed actions drop out of the snapshot.
+        // t == 45
+        tokio::time::sleep(Duration::from_secs(45)).await;
+        test.delete_event(1).await;
+        assert_eq!(
+            summary(test.scheduler.snapshot().await),
+            vec![(50, 2, EventAction::Start), (80, 2, EventAction::Cleanup)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_extend_cleanup() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: None,
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: None,
+        };
+        let mut event = test_event(&time_source, 1, 20);
+        Arc::make_mut(&mut event)
+            .extend_cleanup(SignedDuration::seconds(30), SignedDuration::seconds(40));
+        let events = vec![event];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+
+        // Extending cleanup doesn't touch the alert.
+        // t == 11
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Alert);
+
+        // t == 21
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Start);
+
+        // The extension survives edits that don't change the event's time.
+        test.edit_event_non_time(1).await;
+
+        // The original cleanup time passes without anything happening.
+        // t == 89
+        tokio::time::sleep(Duration::from_secs(68)).await;
+        assert!(test.take_last_action().unwrap().is_none());
+
+        // t == 91
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.id.idx, 1);
+        assert_eq!(last.action, EventAction::Cleanup);
+        assert!(test.events.lock().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_start() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: None,
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: None,
+        };
+        let events = vec![test_event(&time_source, 1, 20)];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+        let started = || test.events.lock()[&events[0].id].started();
+
+        // t == 11
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Alert);
+
+        // t == 19
+        tokio::time::sleep(Duration::from_secs(8)).await;
+        assert!(test.take_last_action().unwrap().is_none());
+        assert!(!started());
+
+        // The event starts right at its start time.
+        // t == 21
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!((last.id.idx, last.action), (1, EventAction::Start));
+        assert!(started());
+
+        // Editing the event without changing its time doesn't start it again.
+        test.edit_event_non_time(1).await;
+        // t == 49
+        tokio::time::sleep(Duration::from_secs(28)).await;
+        assert!(test.take_last_action().unwrap().is_none());
+
+        // Moving the event later makes it upcoming again, until its new start time.
+        test.edit_event_time(1, 80).await;
+        assert!(!started());
+
+        // t == 71
+        tokio::time::sleep(Duration::from_secs(22)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!(last.action, EventAction::Alert);
+
+        // t == 81
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!((last.id.idx, last.action), (1, EventAction::Start));
+        assert!(started());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_shutdown() {
+        let time_source = TestTimeSource::new();
+        let config = EventSchedulerConfig {
+            check_in: None,
+            alert: Duration::from_secs(10),
+            cleanup: Duration::from_secs(30),
+            message_linger: None,
+        };
+        let events = vec![
+            test_event(&time_source, 1, 20),
+            test_event(&time_source, 2, 40),
+        ];
+        let test = EventSchedulerTest::start(events.iter(), config, time_source.clone());
+
+        // t == 11
+        tokio::time::sleep(Duration::from_secs(11)).await;
+        let last = test.take_last_action().unwrap().unwrap();
+        assert_eq!((last.id.idx, last.action), (1, EventAction::Alert));
+
+        // Nothing else happens once the scheduler is shut down, even as events change.
+        test.scheduler.shutdown();
+        test.edit_event_non_time(2).await;
+        test.add_event(test_event(&time_source, 3, 30)).await;
+
+        // t == 111
+        tokio::time::sleep(Duration::from_secs(100)).await;
+        assert!(test.take_last_actions().is_none());
+    }
 }
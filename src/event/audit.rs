@@ -0,0 +1,86 @@
+use crate::store::{PersistentStore, PersistentStoreBuilder};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+
+const AUDIT_LOG_STORE_NAME: &str = "audit_log.json";
+
+// Only recent commands are of interest, so older entries are dropped past this many.
+const MAX_ENTRIES: usize = 500;
+
+/// A command that changed the guild's events, recorded when it was issued.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    /// The full command name with subcommands joined by dots, e.g. "lfg.edit.datetime".
+    pub command: String,
+    pub user: UserId,
+    /// The event ID the command was given, as typed, if it targets an existing event.
+    pub event_id: Option<String>,
+}
+
+/// Persistent log of commands that changed the guild's events, oldest first.
+#[derive(Debug)]
+pub struct AuditLog {
+    entries: Vec<AuditEntry>,
+    store: PersistentStore<Vec<AuditEntry>>,
+}
+
+impl AuditLog {
+    pub async fn load(store_builder: &PersistentStoreBuilder) -> Result<Self> {
+        let store = store_builder.build(AUDIT_LOG_STORE_NAME).await?;
+        let entries = store.load().await?;
+        Ok(AuditLog { entries, store })
+    }
+
+    pub async fn append(&mut self, entry: AuditEntry) -> Result<()> {
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            let excess = self.entries.len() - MAX_ENTRIES;
+            self.entries.drain(..excess);
+        }
+        self.store.store(&self.entries).await
+    }
+
+    /// Up to `count` of the most recent entries, most recent first.
+    pub fn recent(&self, count: usize) -> Vec<AuditEntry> {
+        self.entries.iter().rev().take(count).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    fn entry(user: u64) -> AuditEntry {
+        AuditEntry {
+            at: Utc::now(),
+            command: "lfg.join".to_owned(),
+            user: UserId(user),
+            event_id: Some("vog1".to_owned()),
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn audit_log_is_bounded_and_persisted() {
+        let tempdir = tempdir::TempDir::new("audit").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let users = |entries: Vec<AuditEntry>| entries.iter().map(|e| e.user.0).collect::<Vec<_>>();
+
+        let mut log = AuditLog::load(&store_builder).await.unwrap();
+        assert!(log.recent(10).is_empty());
+        for user in 1..=MAX_ENTRIES as u64 + 2 {
+            log.append(entry(user)).await.unwrap();
+        }
+        let last = MAX_ENTRIES as u64 + 2;
+        assert_eq!(users(log.recent(3)), vec![last, last - 1, last - 2]);
+        assert_eq!(log.recent(usize::MAX).len(), MAX_ENTRIES);
+        assert_eq!(log.recent(usize::MAX).last().unwrap().user, UserId(3));
+
+        // Entries survive a restart.
+        let log = AuditLog::load(&store_builder).await.unwrap();
+        assert_eq!(users(log.recent(2)), vec![last, last - 1]);
+    }
+}
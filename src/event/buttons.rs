@@ -0,0 +1,57 @@
+use serde::Deserialize;
+use serenity::{builder::CreateButton, model::channel::ReactionType};
+use tracing::warn;
+
+/// The label and optional emoji shown on a single event button.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ButtonLabel {
+    pub label: String,
+    /// Either a unicode emoji or a custom emoji, written like `<:name:id>`.
+    #[serde(default)]
+    pub emoji: Option<String>,
+}
+
+impl ButtonLabel {
+    fn new(label: &str) -> Self {
+        ButtonLabel {
+            label: label.to_owned(),
+            emoji: None,
+        }
+    }
+
+    pub fn apply(&self, button: &mut CreateButton) {
+        button.label(&self.label);
+        if let Some(emoji) = &self.emoji {
+            match ReactionType::try_from(emoji.as_str()) {
+                Ok(emoji) => {
+                    button.emoji(emoji);
+                }
+                Err(err) => warn!("Invalid button emoji '{}': {:?}", emoji, err),
+            }
+        }
+    }
+}
+
+/// What the buttons on event messages say. Only the wording changes; the buttons' actions stay
+/// the same.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ButtonLabels {
+    pub join: ButtonLabel,
+    pub leave: ButtonLabel,
+    pub alt: ButtonLabel,
+    pub maybe: ButtonLabel,
+    pub check_in: ButtonLabel,
+}
+
+impl Default for ButtonLabels {
+    fn default() -> Self {
+        ButtonLabels {
+            join: ButtonLabel::new("Join"),
+            leave: ButtonLabel::new("Leave"),
+            alt: ButtonLabel::new("Alt"),
+            maybe: ButtonLabel::new("Maybe"),
+            check_in: ButtonLabel::new("Check In"),
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use super::DEFAULT_MAX_LISTED_MEMBERS;
+use crate::activity::ActivityType;
+use serenity::utils::Color;
+use std::collections::HashMap;
+
+/// The colors of event embeds, by activity type. Guilds can give activity types their own colors,
+/// and the rest keep their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmbedColors(HashMap<ActivityType, Color>);
+
+impl EmbedColors {
+    pub fn new(overrides: HashMap<ActivityType, Color>) -> Self {
+        EmbedColors(overrides)
+    }
+
+    pub fn color(&self, activity_type: ActivityType) -> Color {
+        self.0
+            .get(&activity_type)
+            .copied()
+            .unwrap_or_else(|| activity_type.color())
+    }
+}
+
+/// How a guild's event embeds look.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmbedStyle {
+    pub colors: EmbedColors,
+    /// Whether embeds show their creator's avatar and when they were created.
+    pub show_creator: bool,
+    /// How many spare alts and maybes embeds list before cutting the lists short.
+    pub max_listed: usize,
+}
+
+impl Default for EmbedStyle {
+    fn default() -> Self {
+        EmbedStyle {
+            colors: Default::default(),
+            show_creator: false,
+            max_listed: DEFAULT_MAX_LISTED_MEMBERS,
+        }
+    }
+}
+
+/// Parses a color written as hex RGB, like "#e67e22" or "e67e22".
+pub fn parse_hex_color(s: &str) -> Option<Color> {
+    let hex = s.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(Color::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use enum_iterator::IntoEnumIterator;
+    use itertools::Itertools;
+    use test_env_log::test;
+
+    #[test]
+    fn activity_types_have_distinct_colors() {
+        let colors = ActivityType::into_enum_iter()
+            .map(|ty| ty.color().0)
+            .collect_vec();
+        assert_eq!(colors.iter().unique().count(), colors.len(), "{:?}", colors);
+        // Ended events are grey whatever their activity, so no activity type can be.
+        assert!(!colors.contains(&Color::DARK_GREY.0));
+    }
+
+    #[test]
+    fn guild_colors_override_defaults() {
+        let purple = parse_hex_color("#9b59b6").unwrap();
+        let colors = EmbedColors::new([(ActivityType::Raid, purple)].into_iter().collect());
+        assert_eq!(colors.color(ActivityType::Raid), purple);
+        assert_eq!(
+            colors.color(ActivityType::Crucible),
+            ActivityType::Crucible.color()
+        );
+        assert_eq!(
+            EmbedColors::default().color(ActivityType::Raid),
+            ActivityType::Raid.color()
+        );
+    }
+
+    #[test]
+    fn parses_hex_colors() {
+        assert_eq!(parse_hex_color("#E67E22"), Some(Color::new(0xe67e22)));
+        assert_eq!(parse_hex_color("e67e22"), Some(Color::new(0xe67e22)));
+        for invalid in ["", "#fff", "#e67e2g", "orange"] {
+            assert_eq!(parse_hex_color(invalid), None, "{}", invalid);
+        }
+    }
+}
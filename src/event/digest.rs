@@ -0,0 +1,185 @@
+use super::{Event, JoinKind};
+use crate::store::{PersistentStore, PersistentStoreBuilder};
+use anyhow::Result;
+use chrono::{DateTime, Duration, Timelike, Utc};
+use chrono_tz::Tz;
+use serenity::model::id::UserId;
+use std::{collections::BTreeSet, sync::Arc};
+
+const DIGEST_SUBSCRIBERS_STORE_NAME: &str = "digest_subscribers.json";
+
+/// The hour of the day, in each subscriber's own timezone, that their daily digest is sent.
+const DIGEST_LOCAL_HOUR: u32 = 9;
+
+/// The hour of the day, in UTC, that subscribers who haven't set a timezone get their digest. This
+/// is morning in the Americas and evening in Europe, ahead of most events either way.
+const FALLBACK_DIGEST_HOUR_UTC: u32 = 15;
+
+// Keeps digests well under Discord's 2000 character limit for messages.
+const MAX_DIGEST_EVENTS: usize = 20;
+
+/// Persistent set of users who've opted in to a daily DM listing their upcoming events.
+#[derive(Debug)]
+pub struct DigestSubscribers {
+    users: BTreeSet<UserId>,
+    store: PersistentStore<BTreeSet<UserId>>,
+}
+
+impl DigestSubscribers {
+    pub async fn load(store_builder: &PersistentStoreBuilder) -> Result<Self> {
+        let store = store_builder.build(DIGEST_SUBSCRIBERS_STORE_NAME).await?;
+        let users = store.load().await?;
+        Ok(DigestSubscribers { users, store })
+    }
+
+    pub fn users(&self) -> impl Iterator<Item = UserId> + '_ {
+        self.users.iter().copied()
+    }
+
+    /// Subscribes the user to daily digests, or unsubscribes them.
+    pub async fn set(&mut self, user: UserId, subscribed: bool) -> Result<()> {
+        let changed = if subscribed {
+            self.users.insert(user)
+        } else {
+            self.users.remove(&user)
+        };
+        if !changed {
+            return Ok(());
+        }
+        self.store.store(&self.users).await
+    }
+}
+
+/// A compact list of the given events, one line each with the event's time and the user's part in
+/// it. Times are Discord timestamps, so each reader sees them in their own timezone.
+pub fn digest_message(events: &[Arc<Event>], user: UserId) -> String {
+    let mut lines = vec![format!("Your {} upcoming events:", events.len())];
+    lines.extend(events.iter().take(MAX_DIGEST_EVENTS).map(|event| {
+        format!(
+            "• **{}** {} <t:{ts}:f> (<t:{ts}:R>), {}",
+            event.id,
+            event.activity,
+            role_label(event, user),
+            ts = event.datetime().timestamp(),
+        )
+    }));
+    if events.len() > MAX_DIGEST_EVENTS {
+        lines.push(format!("...and {} more", events.len() - MAX_DIGEST_EVENTS));
+    }
+    lines.join("\n")
+}
+
+/// The part the given user has in the event.
+pub fn role_label(event: &Event, user: UserId) -> &'static str {
+    match (event.is_creator(user), event.kind_of(user)) {
+        (true, Some(JoinKind::Confirmed)) => "Creator, confirmed",
+        (true, Some(JoinKind::Alternate)) => "Creator, alternate",
+        (true, Some(JoinKind::Maybe)) => "Creator, maybe",
+        (true, None) => "Creator, not joined",
+        (false, Some(JoinKind::Confirmed)) => "Confirmed",
+        (false, Some(JoinKind::Alternate)) => "Alternate",
+        (false, Some(JoinKind::Maybe)) => "Maybe",
+        (false, None) => "Not joined",
+    }
+}
+
+/// Whether a subscriber's daily digest is due in the hour that `now` is in, given the timezone
+/// they've set, if any.
+pub fn digest_due(now: DateTime<Utc>, timezone: Option<Tz>) -> bool {
+    match timezone {
+        Some(tz) => now.with_timezone(&tz).hour() == DIGEST_LOCAL_HOUR,
+        None => now.hour() == FALLBACK_DIGEST_HOUR_UTC,
+    }
+}
+
+/// How long from now until the start of the next hour, when the digests due then are sent.
+pub fn until_next_hour(now: DateTime<Utc>) -> Duration {
+    now.date().and_hms(now.hour(), 0, 0) + Duration::hours(1) - now
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{activity::Activity, event::EventId};
+    use chrono::TimeZone;
+    use chrono_tz::Tz;
+    use serenity::model::user::User;
+    use test_env_log::test;
+
+    #[test]
+    fn digest_lists_events_compactly() {
+        let mut joiner = User::default();
+        joiner.id = UserId(2);
+        let datetime = Tz::America__New_York.ymd(2021, 10, 18).and_hms(19, 0, 0);
+        let events = [Activity::VaultOfGlass, Activity::Prophecy]
+            .into_iter()
+            .enumerate()
+            .map(|(i, activity)| {
+                let mut event = Event {
+                    id: EventId { activity, idx: 1 },
+                    activity,
+                    ..Default::default()
+                };
+                event.set_datetime(datetime + Duration::hours(i as i64));
+                if i == 1 {
+                    event.join(&joiner, JoinKind::Alternate).unwrap();
+                }
+                Arc::new(event)
+            })
+            .collect::<Vec<_>>();
+
+        let creator = events[0].creator.id;
+        assert_eq!(
+            digest_message(&events, creator),
+            "Your 2 upcoming events:\n\
+             • **vog1** Vault of Glass <t:1634598000:f> (<t:1634598000:R>), Creator, not joined\n\
+             • **proph1** Prophecy <t:1634601600:f> (<t:1634601600:R>), Creator, not joined"
+        );
+        let digest = digest_message(&events, joiner.id);
+        assert!(
+            digest.ends_with("(<t:1634601600:R>), Alternate"),
+            "{}",
+            digest
+        );
+
+        let many = vec![events[0].clone(); MAX_DIGEST_EVENTS + 3];
+        let digest = digest_message(&many, creator);
+        assert_eq!(digest.lines().count(), MAX_DIGEST_EVENTS + 2);
+        assert!(digest.ends_with("...and 3 more"));
+    }
+
+    #[test]
+    fn digest_due_at_local_hour() {
+        // 13:00 UTC is 9am in New York, during daylight saving time.
+        let now = Utc.ymd(2021, 10, 18).and_hms(13, 0, 5);
+        assert!(digest_due(now, Some(Tz::America__New_York)));
+        assert!(!digest_due(now, Some(Tz::Europe__London)));
+        assert!(!digest_due(now, None));
+        let now = Utc
+            .ymd(2021, 10, 18)
+            .and_hms(FALLBACK_DIGEST_HOUR_UTC, 0, 5);
+        assert!(digest_due(now, None));
+        assert!(!digest_due(now, Some(Tz::America__New_York)));
+
+        let now = Utc.ymd(2021, 10, 18).and_hms(23, 30, 0);
+        assert_eq!(until_next_hour(now), Duration::minutes(30));
+    }
+
+    #[test(tokio::test)]
+    async fn digest_subscribers_round_trip() {
+        let tempdir = tempdir::TempDir::new("digest").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+
+        let mut subscribers = DigestSubscribers::load(&store_builder).await.unwrap();
+        assert_eq!(subscribers.users().count(), 0);
+        subscribers.set(UserId(1), true).await.unwrap();
+        subscribers.set(UserId(2), true).await.unwrap();
+        subscribers.set(UserId(2), false).await.unwrap();
+        subscribers.set(UserId(3), false).await.unwrap();
+        drop(subscribers);
+
+        // Subscriptions survive a restart.
+        let subscribers = DigestSubscribers::load(&store_builder).await.unwrap();
+        assert_eq(subscribers.users().collect::<Vec<_>>(), vec![UserId(1)]);
+    }
+}
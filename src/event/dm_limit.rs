@@ -0,0 +1,145 @@
+use anyhow::Result;
+use futures::future::join_all;
+use std::{cmp, fmt::Display, future::Future, time::Duration};
+use tokio::{
+    sync::{Mutex, Semaphore, SemaphorePermit},
+    time::{self, Instant},
+};
+use tracing::{error, info};
+
+/// How many DMs can be in flight at once.
+const MAX_CONCURRENT_DMS: usize = 2;
+/// The minimum time between starting one DM and the next.
+const DM_SPACING: Duration = Duration::from_millis(500);
+
+/// Spaces out DMs so that sending a lot of them at once, like an alert for a full raid or several
+/// alerts at the same time, doesn't trip Discord's rate limits.
+#[derive(Debug)]
+pub struct DmLimiter {
+    permits: Semaphore,
+    spacing: Duration,
+    next_send: Mutex<Instant>,
+}
+
+impl Default for DmLimiter {
+    fn default() -> Self {
+        DmLimiter::new(MAX_CONCURRENT_DMS, DM_SPACING)
+    }
+}
+
+impl DmLimiter {
+    pub fn new(max_concurrent: usize, spacing: Duration) -> Self {
+        DmLimiter {
+            permits: Semaphore::new(max_concurrent),
+            spacing,
+            next_send: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for a turn to send a DM. The returned permit should be held until it's sent.
+    async fn acquire(&self) -> SemaphorePermit<'_> {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .expect("DmLimiter semaphore is never closed");
+        let send_at = {
+            let mut next_send = self.next_send.lock().await;
+            let send_at = cmp::max(*next_send, Instant::now());
+            *next_send = send_at + self.spacing;
+            send_at
+        };
+        time::sleep_until(send_at).await;
+        permit
+    }
+
+    /// Sends a DM to each recipient with `send`, within the limits, logging how each one went. A
+    /// failure to DM one recipient doesn't stop the rest. Returns how many failed.
+    pub async fn send_all<T, F, Fut>(
+        &self,
+        what: &str,
+        recipients: impl IntoIterator<Item = T>,
+        send: F,
+    ) -> usize
+    where
+        T: Display + Copy,
+        F: Fn(T) -> Fut,
+        Fut: Future<Output = Result<()>>,
+    {
+        let sends = recipients.into_iter().map(|recipient| {
+            let send = &send;
+            async move {
+                let _permit = self.acquire().await;
+                match send(recipient).await {
+                    Ok(()) => {
+                        info!("Sent {} DM to {}", what, recipient);
+                        true
+                    }
+                    Err(err) => {
+                        error!("Failed to send {} DM to {}: {:?}", what, recipient, err);
+                        false
+                    }
+                }
+            }
+        });
+        join_all(sends)
+            .await
+            .into_iter()
+            .filter(|sent| !sent)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::format_err;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex as SyncMutex,
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_are_limited_and_failures_skipped() {
+        let limiter = DmLimiter::new(2, Duration::from_millis(500));
+        let start = Instant::now();
+        let attempts = SyncMutex::new(Vec::new());
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        let failed = limiter
+            .send_all("test", 1..=6, |recipient: u32| {
+                let (attempts, in_flight, max_in_flight) = (&attempts, &in_flight, &max_in_flight);
+                async move {
+                    attempts.lock().unwrap().push((recipient, start.elapsed()));
+                    let now_in_flight = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(now_in_flight, Ordering::SeqCst);
+                    time::sleep(Duration::from_secs(2)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    if recipient == 3 {
+                        return Err(format_err!("Cannot send messages to this user"));
+                    }
+                    Ok(())
+                }
+            })
+            .await;
+
+        // Every recipient is tried even though one fails.
+        assert_eq!(failed, 1);
+        let attempts = attempts.into_inner().unwrap();
+        assert_eq!(attempts.len(), 6);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+
+        // Sends are at least the spacing apart, and wait for earlier ones to finish.
+        let times = attempts.iter().map(|(_, at)| *at).collect::<Vec<_>>();
+        assert!(
+            times
+                .windows(2)
+                .all(|w| w[1] >= w[0] + Duration::from_millis(500)),
+            "{:?}",
+            times
+        );
+        assert_eq!(times[0], Duration::ZERO);
+        assert_eq!(times[2], Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,335 @@
+use super::{Event, EventId, RecurKind};
+use crate::store::{PersistentStore, PersistentStoreBuilder};
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use serenity::model::id::UserId;
+use std::collections::BTreeSet;
+use thiserror::Error;
+
+const HISTORY_STORE_NAME: &str = "history.json";
+const ATTENDANCE_STORE_NAME: &str = "attendance.json";
+
+// Only recently cleaned up events need to be kept around.
+const MAX_RECORDS: usize = 100;
+
+/// Record of an event that has been cleaned up after it occurred.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventRecord {
+    pub event: Event,
+    pub cleaned_up_at: DateTime<Utc>,
+}
+
+#[derive(Error, Debug)]
+pub enum RestoreError {
+    #[error("No record of event {0}")]
+    NotFound(EventId),
+    #[error("Event {0}'s original time has passed, so it needs a new one")]
+    NeedsDatetime(EventId),
+    #[error("New time for event {0} is in the past")]
+    DatetimeInPast(EventId),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl EventRecord {
+    /// Recreates the recorded event with a new ID, keeping its activity, description, and roster.
+    /// The event keeps its original datetime unless a new one is given, which is required if the
+    /// original has already passed.
+    pub fn restore(
+        &self,
+        id: EventId,
+        datetime: Option<DateTime<Tz>>,
+        now: DateTime<Utc>,
+    ) -> Result<Event, RestoreError> {
+        let old = &self.event;
+        let datetime = match datetime {
+            Some(datetime) if datetime <= now => Err(RestoreError::DatetimeInPast(old.id)),
+            Some(datetime) => Ok(datetime),
+            None if old.datetime <= now => Err(RestoreError::NeedsDatetime(old.id)),
+            None => Ok(old.datetime),
+        }?;
+        Ok(Event {
+            id,
+            datetime,
+            // A recurring event already had its next recurrence created when it was cleaned up.
+            recur: RecurKind::None,
+            // Whatever locked the roster before it was cleaned up, e.g. the event starting, is
+            // over, so the restored event starts out open.
+            locked: false,
+            locked_by_start: false,
+            started: false,
+            check_in_open: false,
+            checked_in: Default::default(),
+            cleanup_after: None,
+            alert_message: None,
+            ..old.clone()
+        })
+    }
+}
+
+/// The weeks in which a user attended at least one event, each identified by the date of its
+/// Monday. Unlike event records these are kept indefinitely, since they're small.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct UserAttendance {
+    user: UserId,
+    weeks: BTreeSet<NaiveDate>,
+}
+
+/// The Monday starting the week that the given date falls in.
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+/// Who attended an event: its confirmed members, or only those of them that checked in if anyone
+/// did.
+fn attendees(event: &Event) -> Vec<UserId> {
+    let confirmed = event.confirmed().into_iter().map(|m| m.id);
+    if event.checked_in.is_empty() {
+        confirmed.collect()
+    } else {
+        confirmed
+            .filter(|id| event.checked_in.contains(id))
+            .collect()
+    }
+}
+
+/// The number of consecutive weeks, up to the week containing `today`, with attendance. The
+/// current week still has time left, so the streak isn't broken until a whole week is missed.
+pub fn streak(weeks: &BTreeSet<NaiveDate>, today: NaiveDate) -> u32 {
+    let mut week = week_start(today);
+    if !weeks.contains(&week) {
+        week = week - Duration::weeks(1);
+    }
+    let mut count = 0;
+    while weeks.contains(&week) {
+        count += 1;
+        week = week - Duration::weeks(1);
+    }
+    count
+}
+
+/// Persistent log of events that have been cleaned up, most recent last, along with the weeks each
+/// member attended events in.
+#[derive(Debug)]
+pub struct EventHistory {
+    records: Vec<EventRecord>,
+    store: PersistentStore<Vec<EventRecord>>,
+    attendance: Vec<UserAttendance>,
+    attendance_store: PersistentStore<Vec<UserAttendance>>,
+}
+
+impl EventHistory {
+    pub async fn load(store_builder: &PersistentStoreBuilder) -> Result<Self> {
+        let store = store_builder.build(HISTORY_STORE_NAME).await?;
+        let records = store.load().await?;
+        let attendance_store = store_builder.build(ATTENDANCE_STORE_NAME).await?;
+        let attendance = attendance_store.load().await?;
+        Ok(EventHistory {
+            records,
+            store,
+            attendance,
+            attendance_store,
+        })
+    }
+
+    pub async fn record(&mut self, event: Event, now: DateTime<Utc>) -> Result<()> {
+        self.record_attendance(&event).await?;
+        self.records.push(EventRecord {
+            event,
+            cleaned_up_at: now,
+        });
+        if self.records.len() > MAX_RECORDS {
+            let excess = self.records.len() - MAX_RECORDS;
+            self.records.drain(..excess);
+        }
+        self.store.store(&self.records).await
+    }
+
+    async fn record_attendance(&mut self, event: &Event) -> Result<()> {
+        let attendees = attendees(event);
+        if attendees.is_empty() {
+            return Ok(());
+        }
+        let week = week_start(event.datetime().naive_local().date());
+        for user in attendees {
+            match self.attendance.iter_mut().find(|a| a.user == user) {
+                Some(attendance) => {
+                    attendance.weeks.insert(week);
+                }
+                None => self.attendance.push(UserAttendance {
+                    user,
+                    weeks: BTreeSet::from([week]),
+                }),
+            }
+        }
+        self.attendance_store.store(&self.attendance).await
+    }
+
+    /// The given user's current attendance streak in weeks, as of `today`.
+    pub fn streak(&self, user: UserId, today: NaiveDate) -> u32 {
+        self.attendance
+            .iter()
+            .find(|a| a.user == user)
+            .map_or(0, |a| streak(&a.weeks, today))
+    }
+
+    /// The most recent record for the given event ID, since IDs are reused.
+    pub fn find(&self, id: EventId) -> Option<&EventRecord> {
+        self.records.iter().rev().find(|r| r.event.id == id)
+    }
+
+    /// Removes the most recent record for the given event ID, e.g. once it has been restored.
+    pub async fn remove(&mut self, id: EventId) -> Result<()> {
+        if let Some(idx) = self.records.iter().rposition(|r| r.event.id == id) {
+            self.records.remove(idx);
+            self.store.store(&self.records).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        activity::Activity,
+        event::{EventMember, Roster},
+    };
+    use assert_matches::assert_matches;
+    use std::str::FromStr;
+    use test_env_log::test;
+
+    fn member(id: u64) -> EventMember {
+        EventMember {
+            id: UserId(id),
+            name: id.to_string(),
+            role: None,
+            joined_at: None,
+            note: None,
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::from_str(s).unwrap()
+    }
+
+    fn weeks(dates: &[&str]) -> BTreeSet<NaiveDate> {
+        dates.iter().map(|d| week_start(date(d))).collect()
+    }
+
+    #[test]
+    fn continuous_attendance_streak() {
+        // 2021-09-13 is a Monday.
+        assert_eq!(week_start(date("2021-09-19")), date("2021-09-13"));
+        assert_eq!(week_start(date("2021-09-13")), date("2021-09-13"));
+
+        let attended = weeks(&["2021-08-31", "2021-09-07", "2021-09-18", "2021-09-21"]);
+        assert_eq!(streak(&attended, date("2021-09-22")), 4);
+        // The current week doesn't count against the streak until it's over.
+        assert_eq!(streak(&attended, date("2021-09-27")), 4);
+        assert_eq!(streak(&attended, date("2021-10-04")), 0);
+        assert_eq!(streak(&BTreeSet::new(), date("2021-09-22")), 0);
+    }
+
+    #[test]
+    fn attendance_streak_with_gaps() {
+        // Missed the week of 2021-09-06, so only the two weeks since count.
+        let attended = weeks(&["2021-08-24", "2021-08-31", "2021-09-14", "2021-09-21"]);
+        assert_eq!(streak(&attended, date("2021-09-22")), 2);
+        // Multiple events in the same week only count once.
+        let attended = weeks(&["2021-09-13", "2021-09-15", "2021-09-19"]);
+        assert_eq!(streak(&attended, date("2021-09-20")), 1);
+    }
+
+    #[test(tokio::test)]
+    async fn attendance_recorded_with_history() {
+        let tempdir = tempdir::TempDir::new("history").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let datetime = DateTime::parse_from_rfc3339("2021-09-15T20:00:00-07:00")
+            .unwrap()
+            .with_timezone(&Tz::PST8PDT);
+        let mut event = Event {
+            datetime,
+            roster: Roster::from_lists(vec![member(1), member(2)], vec![member(3)], vec![]),
+            ..Default::default()
+        };
+
+        let mut history = EventHistory::load(&store_builder).await.unwrap();
+        history.record(event.clone(), Utc::now()).await.unwrap();
+        // Only members that checked in count once anyone has.
+        event.set_datetime(datetime + Duration::weeks(1));
+        event.open_check_in();
+        event.check_in(UserId(2)).unwrap();
+        history.record(event, Utc::now()).await.unwrap();
+
+        // Attendance persists across restarts.
+        let history = EventHistory::load(&store_builder).await.unwrap();
+        let today = date("2021-09-23");
+        assert_eq!(history.streak(UserId(1), today), 1);
+        assert_eq!(history.streak(UserId(2), today), 2);
+        assert_eq!(history.streak(UserId(3), today), 0);
+    }
+
+    #[test]
+    fn restore_reconstructs_event() {
+        let now = Utc::now();
+        let datetime = (now + chrono::Duration::hours(2)).with_timezone(&Tz::PST8PDT);
+        let mut event = Event {
+            id: EventId::from_str("vog3").unwrap(),
+            activity: Activity::VaultOfGlass,
+            datetime,
+            description: "Sherpa run".to_owned(),
+            group_size: 4,
+            recur: RecurKind::Weekly,
+            roster: Roster::from_lists(
+                vec![member(1), member(2)],
+                vec![member(3)],
+                vec![member(4)],
+            ),
+            ..Default::default()
+        };
+        event.start(true);
+        event.open_check_in();
+        event.check_in(UserId(1)).unwrap();
+        event.trigger_alert_protocol();
+        let record = EventRecord {
+            event: event.clone(),
+            cleaned_up_at: now,
+        };
+
+        let new_id = EventId::from_str("vog7").unwrap();
+        let restored = record.restore(new_id, None, now).unwrap();
+        assert_eq!(restored.id, new_id);
+        assert_eq!(restored.activity, event.activity);
+        assert_eq!(restored.datetime(), datetime);
+        assert_eq!(restored.description, event.description);
+        assert_eq!(restored.group_size, 4);
+        assert_eq!(restored.creator, event.creator);
+        assert_eq!(restored.confirmed(), event.confirmed());
+        assert_eq!(restored.alternates(), event.alternates());
+        assert_eq!(restored.maybe(), event.maybe());
+        assert!(!restored.locked);
+        assert!(!restored.locked_by_start);
+        assert!(!restored.started());
+        assert_eq!(restored.recur, RecurKind::None);
+        assert!(!restored.alerted());
+        assert!(!restored.check_in_open());
+        assert!(restored.checked_in.is_empty());
+
+        // Once the original time passes, a new (future) one is required.
+        let later = now + chrono::Duration::hours(3);
+        assert_matches!(
+            record.restore(new_id, None, later),
+            Err(RestoreError::NeedsDatetime(_))
+        );
+        assert_matches!(
+            record.restore(new_id, Some(datetime), later),
+            Err(RestoreError::DatetimeInPast(_))
+        );
+        let new_datetime = datetime + chrono::Duration::days(1);
+        let restored = record.restore(new_id, Some(new_datetime), later).unwrap();
+        assert_eq!(restored.datetime(), new_datetime);
+    }
+}
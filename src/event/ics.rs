@@ -0,0 +1,260 @@
+use crate::activity::Activity;
+use anyhow::{format_err, Context as _, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::{collections::HashMap, str::FromStr};
+
+/// An event read from somewhere else, such as an external calendar, to be created with
+/// EventManager::import_events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedEvent {
+    pub activity: Activity,
+    pub datetime: DateTime<Tz>,
+    pub description: String,
+}
+
+/// A property of a calendar component, e.g. `DTSTART;TZID=America/New_York:20211018T190000`.
+#[derive(Debug, Default)]
+struct Property {
+    params: HashMap<String, String>,
+    value: String,
+}
+
+/// Parses the VEVENTs in an iCalendar (.ics) file into events. This understands only as much of
+/// iCalendar as importing needs; anything else in the file, like recurrence rules, is ignored.
+///
+/// The activity is taken from the first of the VEVENT's CATEGORIES that names one, by name or ID
+/// prefix, and is otherwise Custom. DTSTART times without a timezone of their own are taken to be
+/// in the default timezone, if there is one.
+pub fn parse_ics(ics: &str, default_timezone: Option<Tz>) -> Result<Vec<ImportedEvent>> {
+    let mut events = Vec::new();
+    let mut vevent: Option<HashMap<String, Property>> = None;
+    for line in unfold_lines(ics) {
+        let (name, property) = match parse_property(&line) {
+            Some(parsed) => parsed,
+            None => continue,
+        };
+        match (name.as_str(), property.value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => vevent = Some(HashMap::new()),
+            ("END", "VEVENT") => {
+                let properties = vevent
+                    .take()
+                    .ok_or_else(|| format_err!("END:VEVENT without BEGIN:VEVENT"))?;
+                let event = parse_vevent(&properties, default_timezone)
+                    .with_context(|| format!("Couldn't import event {}", events.len() + 1))?;
+                events.push(event);
+            }
+            _ => {
+                // Only the first of a repeated property is used.
+                if let Some(properties) = &mut vevent {
+                    properties.entry(name).or_insert(property);
+                }
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Splits the file into its content lines, joining lines that were folded onto the next line.
+fn unfold_lines(ics: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in ics.lines() {
+        match (
+            line.strip_prefix(|c| c == ' ' || c == '\t'),
+            lines.last_mut(),
+        ) {
+            (Some(continued), Some(last)) => last.push_str(continued),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+    lines
+}
+
+/// Parses a content line into its uppercased name and the property, or None if it isn't one.
+fn parse_property(line: &str) -> Option<(String, Property)> {
+    // The value starts after the first colon that isn't in a quoted parameter value.
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(i),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+
+    let mut parts = head.split(';');
+    let name = parts.next()?.trim().to_ascii_uppercase();
+    if name.is_empty() {
+        return None;
+    }
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.to_ascii_uppercase(), value.trim_matches('"').to_owned()))
+        })
+        .collect();
+    let value = value.to_owned();
+    Some((name, Property { params, value }))
+}
+
+fn parse_vevent(
+    properties: &HashMap<String, Property>,
+    default_timezone: Option<Tz>,
+) -> Result<ImportedEvent> {
+    let dtstart = properties
+        .get("DTSTART")
+        .ok_or_else(|| format_err!("Missing DTSTART"))?;
+    let datetime = parse_dtstart(dtstart, default_timezone)?;
+
+    let text = |name: &str| properties.get(name).map(|p| unescape_text(&p.value));
+    let description = match (text("SUMMARY"), text("DESCRIPTION")) {
+        (Some(summary), Some(description)) => format!("{}\n{}", summary, description),
+        (Some(text), None) | (None, Some(text)) => text,
+        (None, None) => String::new(),
+    };
+
+    let activity = properties
+        .get("CATEGORIES")
+        .and_then(|p| {
+            p.value
+                .split(',')
+                .find_map(|category| Activity::activity_named(&unescape_text(category)))
+        })
+        .unwrap_or(Activity::Custom);
+
+    Ok(ImportedEvent {
+        activity,
+        datetime,
+        description: description.trim().to_owned(),
+    })
+}
+
+fn parse_dtstart(dtstart: &Property, default_timezone: Option<Tz>) -> Result<DateTime<Tz>> {
+    if dtstart.params.get("VALUE").map(String::as_str) == Some("DATE")
+        || NaiveDate::parse_from_str(&dtstart.value, "%Y%m%d").is_ok()
+    {
+        return Err(format_err!("All-day events can't be imported"));
+    }
+
+    let value = dtstart.value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")
+            .with_context(|| format!("Invalid DTSTART: {}", value))?;
+        let timezone = default_timezone.unwrap_or(Tz::UTC);
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&timezone));
+    }
+
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")
+        .with_context(|| format!("Invalid DTSTART: {}", value))?;
+    let timezone = match dtstart.params.get("TZID") {
+        Some(tzid) => Tz::from_str(tzid).map_err(|_| format_err!("Unknown timezone: {}", tzid))?,
+        None => default_timezone
+            .ok_or_else(|| format_err!("DTSTART has no timezone and no default is set"))?,
+    };
+    timezone
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format_err!("DTSTART doesn't exist in {}: {}", timezone.name(), value))
+}
+
+/// Undoes the escaping of TEXT values.
+fn unescape_text(value: &str) -> String {
+    let mut text = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n' | 'N') => text.push('\n'),
+                Some(escaped) => text.push(escaped),
+                None => text.push('\\'),
+            },
+            c => text.push(c),
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    const SAMPLE: &str = "BEGIN:VCALENDAR\r\n\
+        VERSION:2.0\r\n\
+        PRODID:-//Example//Calendar//EN\r\n\
+        BEGIN:VEVENT\r\n\
+        UID:1234@example.com\r\n\
+        DTSTART;TZID=America/New_York:20211018T190000\r\n\
+        SUMMARY:Weekly raid night\r\n\
+        DESCRIPTION:Bring your best loadout\\, and a snack.\\nSherpa ru\r\n\
+        \x20n for new folks.\r\n\
+        CATEGORIES:Gaming,Vault of Glass\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20211020T020000Z\r\n\
+        SUMMARY:Trials\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_sample_vevents() {
+        let events = parse_ics(SAMPLE, Some(Tz::America__Los_Angeles)).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                ImportedEvent {
+                    activity: Activity::VaultOfGlass,
+                    datetime: Tz::America__New_York.ymd(2021, 10, 18).and_hms(19, 0, 0),
+                    description: "Weekly raid night\nBring your best loadout, and a snack.\n\
+                                  Sherpa run for new folks."
+                        .to_owned(),
+                },
+                ImportedEvent {
+                    activity: Activity::Custom,
+                    datetime: Tz::America__Los_Angeles.ymd(2021, 10, 19).and_hms(19, 0, 0),
+                    description: "Trials".to_owned(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn activity_from_id_prefix_category() {
+        let ics = "BEGIN:VEVENT\nDTSTART:20211020T020000Z\nCATEGORIES:GOS\nEND:VEVENT\n";
+        let events = parse_ics(ics, None).unwrap();
+        assert_eq!(events[0].activity, Activity::GardenOfSalvation);
+        assert_eq!(events[0].datetime.timezone(), Tz::UTC);
+        assert_eq!(events[0].description, "");
+    }
+
+    #[test]
+    fn floating_times_use_default_timezone() {
+        let ics = "BEGIN:VEVENT\nDTSTART:20211018T190000\nEND:VEVENT\n";
+        let events = parse_ics(ics, Some(Tz::Europe__London)).unwrap();
+        assert_eq!(
+            events[0].datetime,
+            Tz::Europe__London.ymd(2021, 10, 18).and_hms(19, 0, 0)
+        );
+        assert!(parse_ics(ics, None).is_err());
+    }
+
+    #[test]
+    fn rejects_unusable_vevents() {
+        for vevent in [
+            "SUMMARY:No start",
+            "DTSTART;VALUE=DATE:20211018",
+            "DTSTART:not a time",
+            "DTSTART;TZID=Mars/Olympus_Mons:20211018T190000",
+        ] {
+            let ics = format!("BEGIN:VEVENT\n{}\nEND:VEVENT\n", vevent);
+            assert!(parse_ics(&ics, Some(Tz::UTC)).is_err(), "{}", vevent);
+        }
+        assert!(parse_ics("END:VEVENT\n", None).is_err());
+        assert_eq!(
+            parse_ics("BEGIN:VCALENDAR\nEND:VCALENDAR\n", None).unwrap(),
+            vec![]
+        );
+    }
+}
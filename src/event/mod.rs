@@ -1,17 +1,19 @@
 use crate::{
     activity::Activity,
+    custom_id::{CustomId, EventAction},
     embed::EmbedManager,
-    guild::GuildConfig,
+    guild::{GuildConfig, GuildDataGuard},
+    metrics,
     store::{PersistentStore, PersistentStoreBuilder},
     util::*,
 };
-use anyhow::{format_err, Context as _, Error, Result};
-use chrono::{DateTime, Utc};
+use anyhow::{ensure, format_err, Context as _, Error, Result};
+use chrono::{DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
 use chrono_tz::Tz;
 use derivative::Derivative;
 use itertools::Itertools;
 use lazy_static::lazy_static;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use serenity::{
     async_trait,
     builder::{CreateActionRow, CreateButton, CreateComponents, CreateEmbed},
@@ -21,22 +23,37 @@ use serenity::{
     utils::Color,
 };
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryFrom,
-    iter::successors,
+    iter::{self, successors},
     str::FromStr,
-    sync::{
-        atomic::{AtomicBool, Ordering},
-        Arc,
-    },
+    sync::{Arc, Weak},
     time::Duration,
 };
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use thiserror::Error;
+use tokio::sync::{oneshot, RwLock};
+use tracing::{error, info, warn};
 
 mod alert;
+mod audit;
+mod buttons;
+mod colors;
+mod digest;
+mod dm_limit;
+mod history;
+mod ics;
+mod role;
+mod timezones;
 
-pub use crate::embed::EventEmbedMessage;
+pub use crate::embed::{EventChannelInfo, EventEmbedMessage, FailedOp};
+pub use alert::{EventSchedulerConfig, ScheduledAction};
+pub use audit::AuditEntry;
+pub use buttons::{ButtonLabel, ButtonLabels};
+pub use colors::{parse_hex_color, EmbedColors, EmbedStyle};
+pub use digest::{digest_message, role_label};
+pub use history::RestoreError;
+pub use ics::{parse_ics, ImportedEvent};
+pub use role::{GroupRole, RoleRequirements};
 
 // Debugging features, enabled through environment variables.
 lazy_static! {
@@ -78,14 +95,32 @@ impl FromStr for EventId {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self> {
+        // Be forgiving of how people type IDs, e.g. " VOG 12", since prefixes are all lowercase.
+        let s = s.trim_start();
         if let Some((split_idx, _)) = s
             .char_indices()
             .skip_while(|(_, c)| c.is_ascii_alphabetic())
             .next()
         {
-            let (s1, s2) = s.split_at(split_idx);
-            let activity = Activity::activity_with_id_prefix(s1)
+            let (s1, rest) = s.split_at(split_idx);
+            let activity = Activity::activity_with_id_prefix(s1.to_ascii_lowercase())
                 .ok_or_else(|| format_err!("Unknown activity prefix"))?;
+            let rest = rest.trim_start();
+
+            // Ignore any trailing text (e.g. "vog12 (Vault of Glass)" copied from an embed), but
+            // only if it's clearly separate from the ID, so that something like "vog12abc" isn't
+            // silently treated as "vog12". A second number, like "vog 4 2", is ambiguous too.
+            let digits_end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or_else(|| rest.len());
+            let (s2, trailing) = rest.split_at(digits_end);
+            if trailing.starts_with(|c: char| c.is_alphanumeric())
+                || trailing
+                    .trim_start()
+                    .starts_with(|c: char| c.is_ascii_digit())
+            {
+                return Err(format_err!("Unexpected text after event ID"));
+            }
             let event_idx: u8 = s2.parse().context("Invalid number in event ID")?;
             Ok(event_id(activity, event_idx))
         } else {
@@ -102,12 +137,62 @@ impl TryFrom<String> for EventId {
     }
 }
 
-// TODO: We currently persist the last seen username. Need to make sure these stay up to date as we
-// get new user info.
+/// Identifies a recurring series of events. Unlike event IDs, which are reused once their events
+/// are gone, these are random, so a later event can never end up in an older series by accident.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "StoredSeriesId")]
+pub struct SeriesId(u64);
+
+impl SeriesId {
+    pub fn random() -> Self {
+        SeriesId(rand::random())
+    }
+}
+
+/// Serialized form of a SeriesId, which also accepts the ID of the series' first event that
+/// series used to be identified by.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredSeriesId {
+    Token(u64),
+    FirstEventId(EventId),
+}
+
+impl From<StoredSeriesId> for SeriesId {
+    fn from(stored: StoredSeriesId) -> Self {
+        match stored {
+            StoredSeriesId::Token(token) => SeriesId(token),
+            // Events of the same older series still end up together, including across restarts,
+            // so the hash has to be one that never changes.
+            StoredSeriesId::FirstEventId(id) => SeriesId(fnv1a(id.to_string().as_bytes())),
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of the bytes.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// A member of an event. The name is their display name when last seen, which is kept up to date as
+/// they change it.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventMember {
     pub id: UserId,
     pub name: String,
+    /// The role the member declared they'll fill in their group, if any.
+    #[serde(default)]
+    pub role: Option<GroupRole>,
+    /// When the member joined the event. Missing for members that joined before this was recorded.
+    #[serde(default)]
+    pub joined_at: Option<DateTime<Utc>>,
+    /// A short note the member left when joining, like "can only stay 1hr".
+    #[serde(default)]
+    pub note: Option<String>,
 }
 
 impl PartialEq for EventMember {
@@ -118,16 +203,31 @@ impl PartialEq for EventMember {
 
 impl Eq for EventMember {}
 
+impl EventMember {
+    /// Whether the two are the same member with exactly the same details, unlike `==`, which only
+    /// compares IDs.
+    fn identical(&self, other: &EventMember) -> bool {
+        self.id == other.id
+            && self.name == other.name
+            && self.role == other.role
+            && self.joined_at == other.joined_at
+            && self.note == other.note
+    }
+}
+
 impl From<&dyn MemberLike> for EventMember {
     fn from(member: &dyn MemberLike) -> Self {
         EventMember {
             id: member.user().id,
             name: member.display_name().to_owned(),
+            role: None,
+            joined_at: None,
+            note: None,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JoinKind {
     Confirmed,
     Alternate,
@@ -157,6 +257,550 @@ impl std::fmt::Display for JoinKind {
     }
 }
 
+// How many spare alts and maybes event embeds list before cutting the list short, so that long lists
+// don't bloat the embed, unless the guild configures otherwise.
+pub const DEFAULT_MAX_LISTED_MEMBERS: usize = 15;
+
+// The largest group size offered, for 12 player activities like 6v6 Crucible.
+pub const MAX_GROUP_SIZE: u8 = 12;
+
+// Events can have this many tags, each at most MAX_TAG_CHARS long. Tags are for categorizing, so
+// there's no need for long ones or many of them.
+pub const MAX_TAGS: usize = 5;
+const MAX_TAG_CHARS: usize = 20;
+
+/// Normalizes a tag to lowercase letters, digits, and dashes, with spaces turned into dashes and a
+/// leading '#' dropped. Returns None if nothing is left.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag: String = tag
+        .trim()
+        .trim_start_matches('#')
+        .split_whitespace()
+        .join("-")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-')
+        .flat_map(char::to_lowercase)
+        .take(MAX_TAG_CHARS)
+        .collect();
+    if tag.is_empty() {
+        None
+    } else {
+        Some(tag)
+    }
+}
+
+/// Parses comma-separated tags, normalizing each and dropping duplicates. Only the first MAX_TAGS
+/// are kept.
+pub fn normalize_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .filter_map(normalize_tag)
+        .unique()
+        .take(MAX_TAGS)
+        .collect()
+}
+
+// Member notes are cut off after this many characters, to keep rosters readable.
+const MAX_NOTE_CHARS: usize = 50;
+
+/// Cleans up a member's note, collapsing whitespace and cutting it down to MAX_NOTE_CHARS. Returns
+/// None if nothing's left.
+pub fn normalize_note(note: &str) -> Option<String> {
+    let note: String = note
+        .split_whitespace()
+        .join(" ")
+        .chars()
+        .take(MAX_NOTE_CHARS)
+        .collect();
+    let note = note.trim_end();
+    (!note.is_empty()).then(|| note.to_owned())
+}
+
+/// The member's note in italics, prefixed by a space, or nothing if they didn't leave one.
+fn note_suffix(member: &EventMember) -> String {
+    match &member.note {
+        Some(note) => {
+            let escaped: String = note
+                .chars()
+                .flat_map(|c| {
+                    let escape = matches!(c, '\\' | '*' | '_' | '`' | '~' | '|' | '>');
+                    escape.then(|| '\\').into_iter().chain(iter::once(c))
+                })
+                .collect();
+            format!(" _{}_", escaped)
+        }
+        None => String::new(),
+    }
+}
+
+/// Mentions the first max_listed members, followed by how many more were left out.
+fn listed_names(members: Vec<&EventMember>, max_listed: usize) -> String {
+    let mut names = members
+        .iter()
+        .take(max_listed)
+        .map(|user| format!("{}{}", user.id.mention(), note_suffix(user)))
+        .collect_vec();
+    if members.len() > max_listed {
+        names.push(format!("+{} more", members.len() - max_listed));
+    }
+    names
+        .into_iter()
+        .pad_using(1, |_| "None".to_owned())
+        .join(", ")
+}
+
+// Events without a cap on players can still only name this many groups.
+const MAX_GROUP_LABELS: usize = 10;
+const MAX_GROUP_LABEL_CHARS: usize = 32;
+
+/// Splits a comma-separated list of group labels, collapsing whitespace and cutting each label down
+/// to MAX_GROUP_LABEL_CHARS. Empty labels are dropped.
+pub fn parse_group_labels(labels: &str) -> Vec<String> {
+    labels
+        .split(',')
+        .filter_map(|label| {
+            let label: String = label
+                .split_whitespace()
+                .join(" ")
+                .chars()
+                .take(MAX_GROUP_LABEL_CHARS)
+                .collect();
+            let label = label.trim_end();
+            (!label.is_empty()).then(|| label.to_owned())
+        })
+        .collect()
+}
+
+/// Why Event::set_group_labels rejected the labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum GroupLabelsError {
+    #[error("{count} group labels given, but the event can only have {max} groups")]
+    TooMany { count: usize, max: usize },
+}
+
+/// Why Event::join turned a member away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum JoinError {
+    #[error("Event has ended")]
+    Ended,
+    #[error("Event is locked")]
+    Locked,
+    #[error("User already in event")]
+    AlreadyJoined,
+    /// Joining as confirmed would go over the event's max_players.
+    #[error("Event is full")]
+    Full,
+}
+
+/// Why EventManager::bump_event didn't repost an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum BumpError {
+    #[error("Event isn't posted in any event channels")]
+    NotPosted,
+    /// The event was bumped too recently, and can be bumped again at the given time.
+    #[error("Event was bumped too recently, can be bumped again at {0}")]
+    CoolingDown(DateTime<Utc>),
+}
+
+/// What to do when someone joins an event as confirmed while they're already confirmed for another
+/// event around the same time, configurable per guild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictCheck {
+    /// Don't check for conflicts.
+    Off,
+    /// Let them join, but point out the conflicting events.
+    Warn,
+    /// Turn them away, listing the conflicting events.
+    Block,
+}
+
+impl Default for ConflictCheck {
+    fn default() -> Self {
+        ConflictCheck::Off
+    }
+}
+
+/// Optional settings for a new event. Anything left out uses the event's default.
+#[derive(Debug, Clone, Default)]
+pub struct EventSettings {
+    pub recur: RecurKind,
+    pub platform: Option<Platform>,
+    pub max_players: Option<u16>,
+    /// Normalized with normalize_tags.
+    pub tags: Vec<String>,
+    /// Defaults to the activity's usual group size.
+    pub group_size: Option<u8>,
+}
+
+/// A new event to create with EventManager::create_event_from_draft, for creating events without
+/// going through a command.
+#[derive(Clone)]
+pub struct EventDraft<'a> {
+    pub creator: &'a dyn MemberLike,
+    pub activity: Activity,
+    pub datetime: DateTime<Tz>,
+    /// Uses the guild's default description for the activity if left out or blank.
+    pub description: Option<String>,
+    pub settings: EventSettings,
+}
+
+impl<'a> EventDraft<'a> {
+    /// A draft without a description, using the default settings.
+    pub fn new(creator: &'a dyn MemberLike, activity: Activity, datetime: DateTime<Tz>) -> Self {
+        EventDraft {
+            creator,
+            activity,
+            datetime,
+            description: None,
+            settings: Default::default(),
+        }
+    }
+}
+
+/// Everyone that has joined an event, along with how they joined, in the order they joined.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RosterLists", into = "RosterLists")]
+pub struct Roster(Vec<(EventMember, JoinKind)>);
+
+/// Serialized form of a Roster, which keeps the separate per-kind lists that events were
+/// originally stored with.
+#[derive(Serialize, Deserialize)]
+struct RosterLists {
+    confirmed: Vec<EventMember>,
+    alternates: Vec<EventMember>,
+    maybe: Vec<EventMember>,
+}
+
+impl From<RosterLists> for Roster {
+    fn from(lists: RosterLists) -> Self {
+        // The lists don't preserve how joins of different kinds were interleaved, so restore that
+        // from the join times. Members without one joined before join times were recorded and so
+        // sort first, in their list order.
+        let mut roster = Roster::from_lists(lists.confirmed, lists.alternates, lists.maybe);
+        roster.0.sort_by_key(|(m, _)| m.joined_at);
+        roster
+    }
+}
+
+impl From<Roster> for RosterLists {
+    fn from(roster: Roster) -> Self {
+        let list = |kind| {
+            roster
+                .of_kind(kind)
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        RosterLists {
+            confirmed: list(JoinKind::Confirmed),
+            alternates: list(JoinKind::Alternate),
+            maybe: list(JoinKind::Maybe),
+        }
+    }
+}
+
+impl Roster {
+    pub fn from_lists(
+        confirmed: Vec<EventMember>,
+        alternates: Vec<EventMember>,
+        maybe: Vec<EventMember>,
+    ) -> Self {
+        let with_kind = |list: Vec<EventMember>, kind| list.into_iter().map(move |m| (m, kind));
+        Roster(
+            with_kind(confirmed, JoinKind::Confirmed)
+                .chain(with_kind(alternates, JoinKind::Alternate))
+                .chain(with_kind(maybe, JoinKind::Maybe))
+                .collect(),
+        )
+    }
+
+    /// Members that joined as the given kind, in the order they joined.
+    pub fn of_kind(&self, kind: JoinKind) -> Vec<&EventMember> {
+        self.0
+            .iter()
+            .filter(|(_, k)| *k == kind)
+            .map(|(m, _)| m)
+            .collect()
+    }
+
+    /// All members along with how they joined, in the order they joined.
+    pub fn in_join_order(&self) -> impl Iterator<Item = (&EventMember, JoinKind)> {
+        self.0.iter().map(|(m, k)| (m, *k))
+    }
+
+    pub fn get(&self, id: UserId) -> Option<(&EventMember, JoinKind)> {
+        self.0
+            .iter()
+            .find(|(m, _)| m.id == id)
+            .map(|(m, k)| (m, *k))
+    }
+
+    fn get_mut(&mut self, id: UserId) -> Option<&mut EventMember> {
+        self.0.iter_mut().find(|(m, _)| m.id == id).map(|(m, _)| m)
+    }
+
+    fn push(&mut self, member: EventMember, kind: JoinKind) {
+        self.0.push((member, kind));
+    }
+
+    /// Changes how the member with the given ID joined, keeping their place in the join order.
+    fn set_kind(&mut self, id: UserId, kind: JoinKind) {
+        if let Some((_, k)) = self.0.iter_mut().find(|(m, _)| m.id == id) {
+            *k = kind;
+        }
+    }
+
+    /// Removes the member with the given ID, returning whether they were in the roster.
+    fn remove(&mut self, id: UserId) -> bool {
+        let len_before = self.0.len();
+        self.0.retain(|(m, _)| m.id != id);
+        self.0.len() != len_before
+    }
+
+    /// Whether the two rosters have exactly the same members, with the same details, joined the
+    /// same way and in the same order.
+    fn identical(&self, other: &Roster) -> bool {
+        self.0.len() == other.0.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|((m1, k1), (m2, k2))| k1 == k2 && m1.identical(m2))
+    }
+
+    /// A copy of the roster with only the members whose kind matches the predicate.
+    fn filtered(&self, pred: impl Fn(JoinKind) -> bool) -> Roster {
+        Roster(self.0.iter().filter(|(_, k)| pred(*k)).cloned().collect())
+    }
+}
+
+/// How often an event recurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecurKind {
+    None,
+    Weekly,
+    Biweekly,
+    /// On the same day of each month, or the last day of months too short for it.
+    Monthly,
+}
+
+impl Default for RecurKind {
+    fn default() -> Self {
+        RecurKind::None
+    }
+}
+
+impl FromStr for RecurKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(RecurKind::None),
+            "weekly" => Ok(RecurKind::Weekly),
+            "biweekly" => Ok(RecurKind::Biweekly),
+            "monthly" => Ok(RecurKind::Monthly),
+            _ => Err(format_err!("Unknown recur value: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for RecurKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecurKind::None => f.write_str("never"),
+            RecurKind::Weekly => f.write_str("weekly"),
+            RecurKind::Biweekly => f.write_str("every other week"),
+            RecurKind::Monthly => f.write_str("monthly"),
+        }
+    }
+}
+
+impl RecurKind {
+    pub fn is_recurring(&self) -> bool {
+        *self != RecurKind::None
+    }
+
+    /// How a recurring event is described in messages, e.g. "weekly". Events that don't recur are
+    /// described as weekly, since that's how next_recurrence() treats them.
+    fn adjective(&self) -> &'static str {
+        match self {
+            RecurKind::None | RecurKind::Weekly => "weekly",
+            RecurKind::Biweekly => "biweekly",
+            RecurKind::Monthly => "monthly",
+        }
+    }
+
+    /// How the upcoming recurrence of an event is referred to at the start of a sentence, e.g.
+    /// "Next week's".
+    fn next_one(&self) -> &'static str {
+        match self {
+            RecurKind::None | RecurKind::Weekly => "Next week's",
+            RecurKind::Biweekly => "The next",
+            RecurKind::Monthly => "Next month's",
+        }
+    }
+
+    /// How the previous recurrence of an event is referred to, e.g. "last week's".
+    fn previous_one(&self) -> &'static str {
+        match self {
+            RecurKind::None | RecurKind::Weekly => "last week's",
+            RecurKind::Biweekly => "the last",
+            RecurKind::Monthly => "last month's",
+        }
+    }
+
+    /// The first recurrence of an event at `datetime` that's after `now`, or None if it doesn't
+    /// recur. Monthly recurrences fall on `day` of the month, clamped to the month's last day.
+    ///
+    /// If the event is being cleaned up more than a period late, this skips ahead as many periods
+    /// as needed. Otherwise we'd end up creating many events, spamming event channels and so forth,
+    /// to do the same thing.
+    pub fn next_after(
+        &self,
+        datetime: DateTime<Tz>,
+        day: u32,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Tz>> {
+        self.occurrences(datetime, day).find(|next| *next > now)
+    }
+
+    /// The recurrences of an event at `datetime`, in order and starting with the one after it, or
+    /// nothing if it doesn't recur. Every recurrence is at the event's local time, even across DST
+    /// changes, and monthly recurrences fall on `day` of the month as with next_after.
+    pub fn occurrences(
+        &self,
+        datetime: DateTime<Tz>,
+        day: u32,
+    ) -> impl Iterator<Item = DateTime<Tz>> {
+        let recur = *self;
+        (1..).map_while(move |n| match recur {
+            RecurKind::None => None,
+            RecurKind::Weekly => Some(add_weeks(datetime, n)),
+            RecurKind::Biweekly => Some(add_weeks(datetime, 2 * n)),
+            RecurKind::Monthly => Some(add_months(datetime, day, n)),
+        })
+    }
+}
+
+/// The datetime `weeks` weeks after `datetime` at the same local time.
+fn add_weeks(datetime: DateTime<Tz>, weeks: u32) -> DateTime<Tz> {
+    let local = datetime.naive_local() + chrono::Duration::weeks(weeks.into());
+    at_local_time(datetime, local)
+}
+
+/// The datetime `months` months after `datetime` at the same local time, on `day` of that month
+/// or its last day if it's too short.
+fn add_months(datetime: DateTime<Tz>, day: u32, months: u32) -> DateTime<Tz> {
+    let local = datetime.naive_local();
+    let month0 = local.year() * 12 + local.month0() as i32 + months as i32;
+    let (year, month) = (month0.div_euclid(12), month0.rem_euclid(12) as u32 + 1);
+    let date = (1..=day)
+        .rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .expect("Every month has a first day");
+    at_local_time(datetime, date.and_time(local.time()))
+}
+
+/// The given local time in `datetime`'s timezone.
+fn at_local_time(datetime: DateTime<Tz>, local: NaiveDateTime) -> DateTime<Tz> {
+    let timezone = datetime.timezone();
+    match timezone.from_local_datetime(&local) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt,
+        // The time was skipped by a DST change, so use the original UTC offset, which lands just
+        // after the change.
+        LocalResult::None => {
+            let offset = datetime.offset().fix().local_minus_utc();
+            timezone.from_utc_datetime(&(local - chrono::Duration::seconds(offset.into())))
+        }
+    }
+}
+
+/// Events used to store `recur` as a bool, which meant weekly.
+fn deserialize_recur<'de, D: Deserializer<'de>>(deserializer: D) -> Result<RecurKind, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Recur {
+        Bool(bool),
+        Kind(RecurKind),
+    }
+    Ok(match Recur::deserialize(deserializer)? {
+        Recur::Bool(true) => RecurKind::Weekly,
+        Recur::Bool(false) => RecurKind::None,
+        Recur::Kind(kind) => kind,
+    })
+}
+
+/// Which members of a recurring event are carried over to its next recurrence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CarryRoster {
+    Nobody,
+    Confirmed,
+    ConfirmedAndAlternates,
+}
+
+impl Default for CarryRoster {
+    fn default() -> Self {
+        CarryRoster::Nobody
+    }
+}
+
+impl FromStr for CarryRoster {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "nobody" => Ok(CarryRoster::Nobody),
+            "confirmed" => Ok(CarryRoster::Confirmed),
+            "all" => Ok(CarryRoster::ConfirmedAndAlternates),
+            _ => Err(format_err!("Unknown carry roster value: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for CarryRoster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CarryRoster::Nobody => f.write_str("nobody"),
+            CarryRoster::Confirmed => f.write_str("confirmed members"),
+            CarryRoster::ConfirmedAndAlternates => f.write_str("confirmed members and alternates"),
+        }
+    }
+}
+
+/// Which platform an event's group plays on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Platform {
+    Pc,
+    Xbox,
+    PlayStation,
+    Crossplay,
+}
+
+impl FromStr for Platform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pc" => Ok(Platform::Pc),
+            "xbox" => Ok(Platform::Xbox),
+            "playstation" => Ok(Platform::PlayStation),
+            "crossplay" => Ok(Platform::Crossplay),
+            _ => Err(format_err!("Unknown platform value: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::Pc => f.write_str("PC"),
+            Platform::Xbox => f.write_str("Xbox"),
+            Platform::PlayStation => f.write_str("PlayStation"),
+            Platform::Crossplay => f.write_str("Crossplay"),
+        }
+    }
+}
+
 /// A single scheduled event.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Event {
@@ -165,23 +809,104 @@ pub struct Event {
     #[serde(with = "serialize_datetime_tz")]
     datetime: DateTime<Tz>,
     pub description: String,
+    /// Freeform, normalized tags for categorizing the event beyond its activity.
+    #[serde(default)]
+    pub tags: Vec<String>,
     pub group_size: u8,
-    pub recur: bool,
+    /// Names shown in place of "Group N" for the event's groups, in order. Groups past the end of
+    /// the list are numbered as usual.
+    #[serde(default)]
+    pub group_labels: Vec<String>,
+    #[serde(deserialize_with = "deserialize_recur")]
+    pub recur: RecurKind,
+    /// The day of the month a monthly event recurs on, when it's a later recurrence that had to be
+    /// moved earlier for a short month. Otherwise it's the day of the event's datetime.
+    #[serde(default)]
+    pub recur_day: Option<u32>,
     pub creator: EventMember,
-    pub confirmed: Vec<EventMember>,
-    pub alternates: Vec<EventMember>,
-    pub maybe: Vec<EventMember>,
+    /// URL of the creator's avatar, shown along with when the event was created at the top of its
+    /// embed if the guild shows event creators.
+    #[serde(default)]
+    pub creator_avatar: Option<String>,
+    /// When the event was created, if it was created after this started being recorded.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// The recurring series this event belongs to, which it shares with its recurrences. Events
+    /// stored before series had their own IDs start a new series when loaded.
+    #[serde(default = "SeriesId::random")]
+    series_id: SeriesId,
+    #[serde(flatten)]
+    roster: Roster,
+    /// Number of members of each role that every group should have, if any.
+    #[serde(default)]
+    pub role_requirements: RoleRequirements,
+    /// Who gets signed up for the next recurrence automatically, if the event recurs.
+    #[serde(default)]
+    pub carry_roster: CarryRoster,
+    /// Maximum number of confirmed members, if the event has a cap.
+    #[serde(default)]
+    pub max_players: Option<u16>,
+    /// Platform the group plays on, if the creator has a preference.
+    #[serde(default)]
+    pub platform: Option<Platform>,
+    /// Whether the roster is locked, i.e. no one new can join.
+    #[serde(default)]
+    pub locked: bool,
+    /// Whether the roster was locked because the event started, rather than by its creator. This
+    /// lock is lifted if the Event's time changes.
+    #[serde(default)]
+    locked_by_start: bool,
+    /// Whether the creator gets a DM when someone else joins or leaves.
+    #[serde(default)]
+    pub notify_creator: bool,
+    /// Whether the event is kept out of the channels, i.e. it's only shown to members via DMs and
+    /// command responses.
+    #[serde(default)]
+    pub unlisted: bool,
+    /// Whether the first alternate takes a confirmed member's spot when they leave.
+    #[serde(default = "default_auto_promote")]
+    pub auto_promote: bool,
+    /// Whether the check-in window before the event's start has opened. This gets reset if the
+    /// Event's time changes.
+    #[serde(default)]
+    check_in_open: bool,
+    /// Members that have checked in since the check-in window opened.
+    #[serde(default)]
+    pub checked_in: HashSet<UserId>,
+    /// Members that don't want the alert protocol DM for this event. They still count toward its
+    /// groups.
+    #[serde(default)]
+    pub alert_muted: HashSet<UserId>,
+    /// Whether the event has been cleaned up and its messages are only lingering until they're
+    /// removed.
+    #[serde(default)]
+    ended: bool,
+    /// Whether the event's start time has come, so it's in progress until it's cleaned up. This
+    /// gets reset if the Event's time changes.
+    #[serde(default)]
+    started: bool,
+    /// When the event gets cleaned up, if that was pushed back past the usual time after it starts.
+    /// This gets reset if the Event's time changes.
+    #[serde(default)]
+    cleanup_after: Option<DateTime<Utc>>,
     /// If alert_message is Some, alert protocol has been triggered for this event. This gets reset
     /// if the Event's time changes.
     alert_message: Option<String>,
 }
 
+fn default_auto_promote() -> bool {
+    true
+}
+
 #[cfg(test)]
 impl Default for Event {
     fn default() -> Self {
         let creator = EventMember {
             id: UserId(1),
             name: "default".into(),
+            role: None,
+            joined_at: None,
+            note: None,
         };
         let activity = Activity::Custom;
         Event {
@@ -189,12 +914,31 @@ impl Default for Event {
             activity,
             datetime: Utc::now().with_timezone(&Tz::PST8PDT),
             description: "".to_owned(),
+            tags: vec![],
             group_size: activity.default_group_size(),
-            recur: false,
+            group_labels: vec![],
+            recur: RecurKind::None,
+            recur_day: None,
             creator: creator.clone(),
-            confirmed: vec![creator],
-            alternates: vec![],
-            maybe: vec![],
+            creator_avatar: None,
+            created_at: None,
+            series_id: SeriesId::random(),
+            roster: Roster::from_lists(vec![creator], vec![], vec![]),
+            role_requirements: Default::default(),
+            carry_roster: Default::default(),
+            max_players: None,
+            platform: None,
+            locked: false,
+            locked_by_start: false,
+            notify_creator: false,
+            unlisted: false,
+            auto_promote: true,
+            check_in_open: false,
+            checked_in: Default::default(),
+            alert_muted: Default::default(),
+            ended: false,
+            started: false,
+            cleanup_after: None,
             alert_message: None,
         }
     }
@@ -223,134 +967,662 @@ impl Event {
 
     pub fn set_datetime(&mut self, new: DateTime<Tz>) {
         self.datetime = new;
+        self.recur_day = None;
         self.alert_message = None;
+        self.check_in_open = false;
+        self.checked_in.clear();
+        self.started = false;
+        self.cleanup_after = None;
+        if self.locked_by_start {
+            self.locked = false;
+            self.locked_by_start = false;
+        }
+    }
+
+    /// Whether the event is stale enough, as of now, that it shouldn't recur anymore. Catching up a
+    /// long-missed event would carry over a roster and description that are likely outdated.
+    pub fn lapsed(&self, now: DateTime<Utc>, threshold: chrono::Duration) -> bool {
+        now.signed_duration_since(self.datetime) > threshold
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// The recurring series this event belongs to. Events that don't recur are a series of one.
+    pub fn series_id(&self) -> SeriesId {
+        self.series_id
+    }
+
+    /// Creates a copy of this event with the given ID at a new time, keeping its whole roster. The
+    /// copy starts a new series, created now, with nobody's alerts muted.
+    pub fn clone_to(&self, id: EventId, datetime: DateTime<Tz>) -> Event {
+        let mut event = Event {
+            id,
+            series_id: SeriesId::random(),
+            created_at: Some(Utc::now()),
+            locked: false,
+            locked_by_start: false,
+            ended: false,
+            alert_muted: Default::default(),
+            ..self.clone()
+        };
+        event.set_datetime(datetime);
+        event
+    }
+
+    /// When the event's next `count` recurrences will be, which is none if it doesn't recur.
+    pub fn upcoming_recurrences(&self, count: usize) -> Vec<DateTime<Tz>> {
+        let day = self.recur_day.unwrap_or_else(|| self.datetime.day());
+        self.recur
+            .occurrences(self.datetime, day)
+            .take(count)
+            .collect()
     }
 
-    // TODO: Add a limit on how many people can join an event.
-    pub fn join(&mut self, member: &dyn MemberLike, kind: JoinKind) -> Result<()> {
-        let list = match kind {
-            JoinKind::Confirmed => &mut self.confirmed,
-            JoinKind::Alternate => &mut self.alternates,
-            JoinKind::Maybe => &mut self.maybe,
+    /// Creates the next recurrence of this event with the given ID, scheduled for the first
+    /// repeat after `now`, carrying over members per `carry_roster`. Events that don't recur are
+    /// treated as weekly.
+    pub fn next_recurrence(&self, id: EventId, now: DateTime<Utc>) -> Event {
+        let recur = match self.recur {
+            RecurKind::None => RecurKind::Weekly,
+            recur => recur,
         };
-        if !*ALLOW_DUPLICATE_JOIN && list.iter().any(|u| u.id == member.id()) {
-            return Err(format_err!("User already in event"));
+        let day = self.recur_day.unwrap_or_else(|| self.datetime.day());
+        let datetime = recur
+            .next_after(self.datetime, day, now)
+            .expect("Recurring events have a next recurrence");
+        let recur_day = if recur == RecurKind::Monthly && datetime.day() != day {
+            Some(day)
+        } else {
+            None
+        };
+
+        let roster = self.roster.filtered(|kind| match self.carry_roster {
+            CarryRoster::Nobody => false,
+            CarryRoster::Confirmed => kind == JoinKind::Confirmed,
+            CarryRoster::ConfirmedAndAlternates => kind != JoinKind::Maybe,
+        });
+        Event {
+            id,
+            activity: self.activity,
+            datetime,
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            group_size: self.group_size,
+            group_labels: self.group_labels.clone(),
+            recur,
+            recur_day,
+            creator: self.creator.clone(),
+            creator_avatar: self.creator_avatar.clone(),
+            created_at: Some(now),
+            series_id: self.series_id,
+            roster,
+            role_requirements: self.role_requirements.clone(),
+            carry_roster: self.carry_roster,
+            max_players: self.max_players,
+            platform: self.platform,
+            locked: false,
+            locked_by_start: false,
+            notify_creator: self.notify_creator,
+            unlisted: self.unlisted,
+            auto_promote: self.auto_promote,
+            check_in_open: false,
+            checked_in: Default::default(),
+            alert_muted: Default::default(),
+            ended: false,
+            started: false,
+            cleanup_after: None,
+            alert_message: None,
         }
+    }
 
-        // Remove user from any other lists so that they don't end up in multiple.
-        if !*ALLOW_DUPLICATE_JOIN {
-            self.leave(member).ok();
+    /// Adds the member to the event, replacing how they joined if they're already in it. Fails with a
+    /// JoinError if the event has ended or is locked, if they already joined this way, or if they're
+    /// joining as confirmed and the event already has max_players confirmed members.
+    pub fn join(&mut self, member: &dyn MemberLike, kind: JoinKind) -> Result<(), JoinError> {
+        if self.ended {
+            return Err(JoinError::Ended);
+        }
+        if self.locked {
+            return Err(JoinError::Locked);
+        }
+        if !*ALLOW_DUPLICATE_JOIN && self.kind_of(member.id()) == Some(kind) {
+            return Err(JoinError::AlreadyJoined);
+        }
+        if kind == JoinKind::Confirmed && self.is_full() {
+            return Err(JoinError::Full);
         }
 
-        match kind {
-            JoinKind::Confirmed => &mut self.confirmed,
-            JoinKind::Alternate => &mut self.alternates,
-            JoinKind::Maybe => &mut self.maybe,
+        // Remove user from the roster first so that they don't end up in it twice, keeping any role
+        // they previously declared and whether they muted the alert.
+        let mut new_member: EventMember = member.into();
+        if !*ALLOW_DUPLICATE_JOIN {
+            if let Some((existing, _)) = self.roster.get(member.id()) {
+                new_member.role = existing.role;
+                new_member.note = existing.note.clone();
+            }
+            let muted = self.alert_muted.contains(&member.id());
+            self.remove_member(member.id()).ok();
+            if muted {
+                self.alert_muted.insert(member.id());
+            }
         }
-        .push(member.into());
+
+        new_member.joined_at = Some(Utc::now());
+        self.roster.push(new_member, kind);
         Ok(())
     }
 
-    pub fn leave(&mut self, member: &dyn MemberLike) -> Result<()> {
-        let count_before = self.confirmed.len() + self.alternates.len() + self.maybe.len();
-        self.confirmed.retain(|u| u.id != member.id());
-        self.alternates.retain(|u| u.id != member.id());
-        self.maybe.retain(|u| u.id != member.id());
-        let count_after = self.confirmed.len() + self.alternates.len() + self.maybe.len();
-
-        if count_before == count_after {
-            return Err(format_err!("User wasn't in the event"));
+    /// Like join, but if the event is full and the member asked to join as confirmed, adds them as
+    /// an alternate instead. Returns how they ended up joining.
+    pub fn join_or_alternate(
+        &mut self,
+        member: &dyn MemberLike,
+        kind: JoinKind,
+    ) -> Result<JoinKind, JoinError> {
+        match self.join(member, kind) {
+            Ok(()) => Ok(kind),
+            Err(JoinError::Full) => {
+                self.join(member, JoinKind::Alternate)?;
+                Ok(JoinKind::Alternate)
+            }
+            Err(err) => Err(err),
         }
-        Ok(())
     }
 
-    pub fn timestamp(&self) -> String {
-        let timestamp = self.datetime.timestamp();
-        format!("<t:{0}:F> (<t:{0}:R>)", timestamp)
+    pub fn confirmed(&self) -> Vec<&EventMember> {
+        self.roster.of_kind(JoinKind::Confirmed)
     }
 
-    fn confirmed_groups(&self) -> Vec<Vec<(&EventMember, bool)>> {
-        let chunk_size = self.group_size as usize;
-        let combined = self
-            .confirmed
-            .iter()
-            .map(|u| (u, false))
-            .chain(self.alternates.iter().map(|u| (u, true)))
-            .collect_vec();
-        combined
-            .chunks(chunk_size)
-            .take_while(|group| group.len() == chunk_size || !group[0].1)
-            .map(Vec::from)
-            .collect()
+    pub fn alternates(&self) -> Vec<&EventMember> {
+        self.roster.of_kind(JoinKind::Alternate)
     }
 
-    fn extra_alts(&self) -> impl Iterator<Item = &EventMember> {
-        let total = self.confirmed.len() + self.alternates.len();
-        let partial_group = total % self.group_size as usize;
-        let skip = if self.alternates.len() >= partial_group {
-            self.alternates.len() - partial_group
-        } else {
-            self.alternates.len()
-        };
-        self.alternates.iter().skip(skip)
+    pub fn maybe(&self) -> Vec<&EventMember> {
+        self.roster.of_kind(JoinKind::Maybe)
     }
 
-    pub fn as_embed(&self) -> CreateEmbed {
-        let mut embed = CreateEmbed::default();
-        let mut start_time = self.timestamp();
-        if self.recur {
-            start_time.push_str("\nRecurs weekly");
-        }
-        embed
-            .field("Activity", self.activity, true)
-            .field("Start Time", start_time, true)
-            .field("Event ID", self.id, true)
-            .field("Description", self.description.clone(), false)
-            .color(Color::DARK_GOLD)
-            .footer(|f| f.text(format!("Creator | {}", self.creator.name)));
+    /// Everyone in the event along with how they joined, in the order they joined.
+    pub fn join_order(&self) -> Vec<(&EventMember, JoinKind)> {
+        self.roster.in_join_order().collect()
+    }
 
-        self.confirmed_groups()
+    /// How the given user joined the event, if they're in it.
+    pub fn kind_of(&self, id: UserId) -> Option<JoinKind> {
+        self.roster.get(id).map(|(_, kind)| kind)
+    }
+
+    pub fn is_creator(&self, id: UserId) -> bool {
+        self.creator.id == id
+    }
+
+    /// Set the role that the given user will fill in their group.
+    pub fn set_role(&mut self, id: UserId, role: Option<GroupRole>) -> Result<()> {
+        let member = self
+            .roster
+            .get_mut(id)
+            .ok_or_else(|| format_err!("User wasn't in the event"))?;
+        member.role = role;
+        Ok(())
+    }
+
+    /// Sets or clears the note of a member of the event. Notes should already be normalized.
+    pub fn set_note(&mut self, id: UserId, note: Option<String>) -> Result<()> {
+        let member = self
+            .roster
+            .get_mut(id)
+            .ok_or_else(|| format_err!("User wasn't in the event"))?;
+        member.note = note;
+        Ok(())
+    }
+
+    /// Updates the given user's stored name wherever they appear in the event.
+    pub fn rename_member(&mut self, id: UserId, name: &str) {
+        let creator = Some(&mut self.creator).filter(|c| c.id == id);
+        for member in self.roster.get_mut(id).into_iter().chain(creator) {
+            member.name = name.to_owned();
+        }
+    }
+
+    /// Whether the two events are exactly the same, including the details of their members, which
+    /// `==` ignores since members compare equal by ID alone.
+    fn identical(&self, other: &Event) -> bool {
+        self == other
+            && self.creator.identical(&other.creator)
+            && self.roster.identical(&other.roster)
+    }
+
+    /// Removes the member from the event. If they were confirmed and the event auto-promotes, the
+    /// first alternate takes their spot and is returned, so that they can be told.
+    pub fn leave(&mut self, member: &dyn MemberLike) -> Result<Option<EventMember>> {
+        let was_confirmed = self.kind_of(member.id()) == Some(JoinKind::Confirmed);
+        self.remove_member(member.id())?;
+        if was_confirmed && self.auto_promote {
+            Ok(self.promote_alternate())
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn remove_member(&mut self, id: UserId) -> Result<()> {
+        self.checked_in.remove(&id);
+        self.alert_muted.remove(&id);
+        if !self.roster.remove(id) {
+            return Err(format_err!("User wasn't in the event"));
+        }
+        Ok(())
+    }
+
+    /// Confirms the first alternate, if there is one and there's room for them.
+    fn promote_alternate(&mut self) -> Option<EventMember> {
+        if self.is_full() {
+            return None;
+        }
+        let promoted = self.alternates().first().map(|m| (*m).clone())?;
+        self.roster.set_kind(promoted.id, JoinKind::Confirmed);
+        Some(promoted)
+    }
+
+    /// Moves the given member from this event into the destination event as confirmed, keeping any
+    /// role they declared. Errors describe why the member couldn't be moved.
+    pub fn move_member_to(&mut self, dest: &mut Event, member: &dyn MemberLike) -> Result<()> {
+        let role = match self.roster.get(member.id()) {
+            Some((m, _)) => m.role,
+            None => return Err(format_err!("they aren't in {}", self.id)),
+        };
+        if dest.kind_of(member.id()) == Some(JoinKind::Confirmed) {
+            return Err(format_err!("they're already confirmed for {}", dest.id));
+        }
+        if dest.locked {
+            return Err(format_err!("{} is locked", dest.id));
+        }
+        if dest.is_full() {
+            return Err(format_err!("{} is full", dest.id));
+        }
+
+        dest.join(member, JoinKind::Confirmed)?;
+        if role.is_some() {
+            dest.set_role(member.id(), role)?;
+        }
+        self.remove_member(member.id())
+    }
+
+    /// Merges the other event's roster into this one, returning the members that were added or
+    /// whose kind changed, with how they're now joined. Members in both events keep whichever kind
+    /// ranks higher (confirmed, then alternate, then maybe). Confirmed members that don't fit under
+    /// max_players are added as alternates instead.
+    pub fn merge_from(&mut self, other: &Event) -> Vec<(EventMember, JoinKind)> {
+        let rank = |kind| match kind {
+            JoinKind::Confirmed => 0,
+            JoinKind::Alternate => 1,
+            JoinKind::Maybe => 2,
+        };
+        let mut merged = Vec::new();
+        for (member, kind) in other.roster.in_join_order() {
+            let existing = self.kind_of(member.id);
+            if existing.map_or(false, |existing| rank(existing) <= rank(kind)) {
+                continue;
+            }
+            let kind = if kind == JoinKind::Confirmed && self.is_full() {
+                JoinKind::Alternate
+            } else {
+                kind
+            };
+            match existing {
+                Some(existing) if existing == kind => continue,
+                Some(_) => self.roster.set_kind(member.id, kind),
+                None => self.roster.push(member.clone(), kind),
+            }
+            merged.push((member.clone(), kind));
+        }
+        merged
+    }
+
+    /// The DM to send the creator when the given member joins (with Some kind) or leaves (with
+    /// None), as the recipient and content. None if the creator didn't ask to be notified, or if
+    /// they're the one who joined or left.
+    pub fn creator_notification(
+        &self,
+        member: &dyn MemberLike,
+        joined: Option<JoinKind>,
+    ) -> Option<(UserId, String)> {
+        if !self.notify_creator || member.id() == self.creator.id {
+            return None;
+        }
+        let change = match joined {
+            Some(kind) => format!("joined as **{}**", kind),
+            None => "left".to_owned(),
+        };
+        let content = format!(
+            "{} {} your {} event **{}** at {}",
+            member.id().mention(),
+            change,
+            self.activity,
+            self.id,
+            self.timestamp()
+        );
+        Some((self.creator.id, content))
+    }
+
+    /// Whether members on the given platform can play in this event, i.e. it's on that platform,
+    /// crossplay, or has no platform preference.
+    pub fn open_to(&self, platform: Platform) -> bool {
+        match self.platform {
+            None | Some(Platform::Crossplay) => true,
+            Some(p) => p == platform,
+        }
+    }
+
+    /// Whether the event has reached its cap on confirmed members.
+    pub fn is_full(&self) -> bool {
+        self.max_players
+            .map_or(false, |max| self.confirmed().len() >= max as usize)
+    }
+
+    pub fn timestamp(&self) -> String {
+        let timestamp = self.datetime.timestamp();
+        format!("<t:{0}:F> (<t:{0}:R>)", timestamp)
+    }
+
+    fn confirmed_groups(&self) -> Vec<Vec<(&EventMember, bool)>> {
+        let chunk_size = self.group_size as usize;
+        let mut combined = self
+            .confirmed()
+            .into_iter()
+            .map(|u| (u, false))
+            .collect_vec();
+        // Only confirmed members are reordered, so that alternates never jump ahead of them.
+        if !self.role_requirements.is_empty() {
+            combined = role::order_for_requirements(combined, chunk_size, &self.role_requirements);
+        }
+        combined.extend(self.alternates().into_iter().map(|u| (u, true)));
+        combined
+            .chunks(chunk_size)
+            .take_while(|group| group.len() == chunk_size || !group[0].1)
+            .map(Vec::from)
+            .collect()
+    }
+
+    /// Members of the group with the given index, or None if the event doesn't have that many
+    /// groups.
+    pub fn group_members(&self, idx: usize) -> Option<Vec<EventMember>> {
+        self.confirmed_groups()
+            .get(idx)
+            .map(|group| group.iter().map(|(u, _)| (*u).clone()).collect())
+    }
+
+    /// The name of the group with the given index, from group_labels if it has one.
+    pub fn group_label(&self, idx: usize) -> String {
+        match self.group_labels.get(idx) {
+            Some(label) => label.clone(),
+            None => format!("Group {}", idx + 1),
+        }
+    }
+
+    /// How many groups the event can have, if its max_players caps that.
+    pub fn expected_groups(&self) -> Option<usize> {
+        let group_size = self.group_size.max(1) as usize;
+        self.max_players
+            .map(|max| (max as usize + group_size - 1) / group_size)
+    }
+
+    /// Names the event's groups, or numbers them again if labels is empty. There can't be more
+    /// labels than the event can have groups.
+    pub fn set_group_labels(&mut self, labels: Vec<String>) -> Result<(), GroupLabelsError> {
+        let max = self.expected_groups().unwrap_or(MAX_GROUP_LABELS);
+        if labels.len() > max {
+            return Err(GroupLabelsError::TooMany {
+                count: labels.len(),
+                max,
+            });
+        }
+        self.group_labels = labels;
+        Ok(())
+    }
+
+    /// Alternates that aren't needed to fill out a group.
+    fn extra_alts(&self) -> Vec<&EventMember> {
+        let groups = self.confirmed_groups();
+        self.alternates()
+            .into_iter()
+            .filter(|alt| !groups.iter().flatten().any(|(u, _)| u == alt))
+            .collect()
+    }
+
+    /// Returns the roles that each group (by index) is missing to meet the role requirements.
+    /// Groups that meet the requirements are omitted.
+    pub fn unmet_role_requirements(&self) -> Vec<(usize, Vec<(GroupRole, u8)>)> {
+        self.confirmed_groups()
+            .iter()
+            .enumerate()
+            .filter_map(|(i, group)| {
+                let unmet = role::unmet_requirements(
+                    group.iter().map(|(u, _)| *u),
+                    &self.role_requirements,
+                );
+                if unmet.is_empty() {
+                    None
+                } else {
+                    Some((i, unmet))
+                }
+            })
+            .collect()
+    }
+
+    /// The event's embed, in the guild's style. At most the style's max_listed spare alts and maybes
+    /// are listed, followed by how many more there are.
+    pub fn as_embed(&self, style: &EmbedStyle) -> CreateEmbed {
+        let mut embed = CreateEmbed::default();
+        let mut start_time = self.timestamp();
+        if self.recur.is_recurring() {
+            start_time.push_str(&format!("\nRecurs {}", self.recur));
+        }
+        if self.ended {
+            start_time.push_str("\n**Ended**");
+        } else if self.started {
+            start_time.push_str("\n**In Progress**");
+        }
+        let color = if self.ended {
+            Color::DARK_GREY
+        } else {
+            style.colors.color(self.activity.activity_type())
+        };
+        embed
+            .field("Activity", self.activity, true)
+            .field("Start Time", start_time, true)
+            .field("Event ID", self.id, true)
+            .field("Description", self.description.clone(), false)
+            .color(color)
+            .footer(|f| f.text(format!("Creator | {}", self.creator.name)));
+        if let Some(avatar) = self.creator_avatar.as_ref().filter(|_| style.show_creator) {
+            embed.author(|a| a.name(&self.creator.name).icon_url(avatar));
+            if let Some(created_at) = self.created_at {
+                embed.description(format!("Created <t:{}:R>", created_at.timestamp()));
+            }
+        }
+        if let Some(platform) = self.platform {
+            embed.field("Platform", platform, true);
+        }
+        if !self.tags.is_empty() {
+            let tags = self.tags.iter().map(|t| format!("#{}", t)).join(" ");
+            embed.field("Tags", tags, true);
+        }
+        if !self.role_requirements.is_empty() {
+            let requirements = self
+                .role_requirements
+                .iter()
+                .map(|(role, count)| format!("{} {}", count, role))
+                .join(", ");
+            embed.field("Roles Per Group", requirements, false);
+        }
+
+        let mut unmet = self.unmet_role_requirements().into_iter().peekable();
+        self.confirmed_groups()
             .into_iter()
             .pad_using(1, |_| vec![])
             .enumerate()
             .for_each(|(i, group)| {
-                let names: String = group
+                let mut names: String = group
                     .iter()
                     .map(|(user, alt)| {
-                        let name = user.id.mention();
+                        let mut name = match user.role {
+                            Some(role) => format!("{} {}", role.emoji(), user.id.mention()),
+                            None => user.id.mention().to_string(),
+                        };
+                        name.push_str(&note_suffix(user));
+                        if self.checked_in.contains(&user.id) {
+                            name.push_str(" ✅");
+                        }
                         if *alt {
                             return format!("*{} (alt)*", name);
                         }
-                        name.to_string()
+                        name
                     })
                     .pad_using(1, |_| "None".to_owned())
                     .join(", ");
+                if let Some((_, group_unmet)) = unmet.next_if(|(idx, _)| *idx == i) {
+                    let needs = group_unmet
+                        .iter()
+                        .map(|(role, count)| format!("{} {}", count, role))
+                        .join(", ");
+                    names.push_str(&format!("\n*Needs {}*", needs));
+                }
 
                 embed.field(
-                    format!("Group {} ({}/{})", i + 1, group.len(), self.group_size),
+                    format!(
+                        "{} ({}/{})",
+                        self.group_label(i),
+                        group.len(),
+                        self.group_size
+                    ),
                     names,
                     false,
                 );
             });
 
-        let alt_names = self
-            .extra_alts()
-            .map(|user| user.id.mention().to_string())
-            .pad_using(1, |_| "None".to_owned())
-            .join(", ");
-        let maybe_names = self
-            .maybe
-            .iter()
-            .map(|user| user.id.mention().to_string())
-            .pad_using(1, |_| "None".to_owned())
-            .join(", ");
         embed
-            .field("Spare Alts", alt_names, true)
-            .field("Maybe", maybe_names, true);
+            .field(
+                "Spare Alts",
+                listed_names(self.extra_alts(), style.max_listed),
+                true,
+            )
+            .field("Maybe", listed_names(self.maybe(), style.max_listed), true);
+
+        if self.check_in_open {
+            let waiting = self.awaiting_check_in();
+            let grouped = self.confirmed_groups().iter().map(Vec::len).sum::<usize>();
+            let mut check_in = format!("{}/{} checked in", grouped - waiting.len(), grouped);
+            if !waiting.is_empty() {
+                let names = waiting.iter().map(|user| user.id.mention()).join(", ");
+                check_in.push_str(&format!("\nWaiting on {}", names));
+            }
+            embed.field("Check-In", check_in, false);
+        }
 
         embed
     }
 
+    /// Opens the check-in window, returning the members that need to check in.
+    pub fn open_check_in(&mut self) -> Vec<EventMember> {
+        self.check_in_open = true;
+        self.awaiting_check_in().into_iter().cloned().collect()
+    }
+
+    pub fn check_in_open(&self) -> bool {
+        self.check_in_open
+    }
+
+    /// Marks the event as ended, so that its messages can linger for a while after cleanup.
+    pub fn end(&mut self) {
+        self.ended = true;
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended
+    }
+
+    /// Locks or unlocks the roster on the creator's behalf, so it stays that way if the event is
+    /// moved.
+    pub fn set_locked(&mut self, locked: bool) {
+        self.locked = locked;
+        self.locked_by_start = false;
+    }
+
+    /// Marks the event as in progress, once its start time has come, locking its roster too if
+    /// lock is set and it isn't locked already.
+    pub fn start(&mut self, lock: bool) {
+        self.started = true;
+        if lock && !self.locked {
+            self.locked = true;
+            self.locked_by_start = true;
+        }
+    }
+
+    pub fn started(&self) -> bool {
+        self.started
+    }
+
+    /// When the event gets cleaned up, given how long after their start events usually are.
+    pub fn cleanup_time(&self, usual_delay: chrono::Duration) -> DateTime<Tz> {
+        let usual = self.datetime + usual_delay;
+        match self.cleanup_after {
+            Some(after) => usual.max(after.with_timezone(&self.datetime.timezone())),
+            None => usual,
+        }
+    }
+
+    /// Pushes the event's cleanup back by delta without changing when it starts, on top of any
+    /// earlier extensions.
+    pub fn extend_cleanup(&mut self, usual_delay: chrono::Duration, delta: chrono::Duration) {
+        let cleanup = self.cleanup_time(usual_delay) + delta;
+        self.cleanup_after = Some(cleanup.with_timezone(&Utc));
+    }
+
+    /// Check in a member of one of the event's groups, once the check-in window is open.
+    pub fn check_in(&mut self, id: UserId) -> Result<()> {
+        if !self.check_in_open {
+            return Err(format_err!("Check-in isn't open yet"));
+        }
+        let in_group = self
+            .confirmed_groups()
+            .iter()
+            .flatten()
+            .any(|(u, _)| u.id == id);
+        if !in_group {
+            return Err(format_err!("User isn't in one of the event's groups"));
+        }
+        if !self.checked_in.insert(id) {
+            return Err(format_err!("User already checked in"));
+        }
+        Ok(())
+    }
+
+    /// Members of the event's groups that haven't checked in yet.
+    fn awaiting_check_in(&self) -> Vec<&EventMember> {
+        self.confirmed_groups()
+            .into_iter()
+            .flatten()
+            .map(|(u, _)| u)
+            .filter(|u| !self.checked_in.contains(&u.id))
+            .collect()
+    }
+
+    /// Mutes or unmutes the alert protocol DM for a member of the event.
+    pub fn set_alert_muted(&mut self, id: UserId, muted: bool) -> Result<()> {
+        if self.kind_of(id).is_none() {
+            return Err(format_err!("User isn't in the event"));
+        }
+        if muted {
+            self.alert_muted.insert(id);
+        } else {
+            self.alert_muted.remove(&id);
+        }
+        Ok(())
+    }
+
+    /// Triggers the alert protocol, returning the members to DM: those in full groups that haven't
+    /// muted the alert.
     pub fn trigger_alert_protocol(&mut self) -> Vec<EventMember> {
         // We generate and save the alert protocol message when it is triggered, which avoids it
         // changing if people join/leave after it is triggered.
@@ -364,8 +1636,8 @@ impl Event {
             .enumerate()
             .map(|(i, group)| {
                 format!(
-                    "Group {}: {}",
-                    i + 1,
+                    "{}: {}",
+                    self.group_label(i),
                     group.iter().map(|(user, _)| user.id.mention()).join(", "),
                 )
             })
@@ -373,6 +1645,7 @@ impl Event {
 
         let members = groups
             .flat_map(|group| group.into_iter().map(|(member, _)| member))
+            .filter(|member| !self.alert_muted.contains(&member.id))
             .cloned()
             .collect();
 
@@ -397,20 +1670,54 @@ impl Event {
         self.alert_message.clone()
     }
 
-    pub fn event_buttons(&self) -> CreateComponents {
+    /// The alert protocol message to DM members, which also says how long until the event starts
+    /// as of now. The timing isn't part of the saved message, since that's also shown in embeds
+    /// where it would go stale.
+    pub fn alert_dm_message(&self, now: DateTime<Utc>) -> Option<String> {
+        let message = self.alert_message.as_ref()?;
+        let until = self.datetime.signed_duration_since(now);
+        Some(format!("{}\nThe event {}.", message, starts_in(until)))
+    }
+
+    pub fn event_buttons(&self, labels: &ButtonLabels) -> CreateComponents {
+        self.buttons(labels, None)
+    }
+
+    /// Like event_buttons(), but for messages sent outside the guild (i.e. DMs), where component
+    /// interactions don't say which guild the event belongs to.
+    pub fn dm_event_buttons(&self, labels: &ButtonLabels, guild_id: GuildId) -> CreateComponents {
+        self.buttons(labels, Some(guild_id))
+    }
+
+    fn buttons(&self, labels: &ButtonLabels, guild_id: Option<GuildId>) -> CreateComponents {
         let mut components = CreateComponents::default();
         let mut row = CreateActionRow::default();
 
-        let buttons = [
-            ("Join", ButtonStyle::Success),
-            ("Leave", ButtonStyle::Danger),
-            ("Alt", ButtonStyle::Primary),
-            ("Maybe", ButtonStyle::Secondary),
+        let mut buttons = vec![
+            (&labels.join, EventAction::Join, ButtonStyle::Success),
+            (&labels.leave, EventAction::Leave, ButtonStyle::Danger),
+            (&labels.alt, EventAction::Alt, ButtonStyle::Primary),
+            (&labels.maybe, EventAction::Maybe, ButtonStyle::Secondary),
         ];
-        buttons.iter().for_each(|(label, style)| {
+        if self.check_in_open {
+            buttons.push((&labels.check_in, EventAction::CheckIn, ButtonStyle::Success));
+        }
+        buttons.iter().for_each(|(label, action, style)| {
             let mut button = CreateButton::default();
-            let id = format!("{}:{}", label.to_ascii_lowercase(), self.id);
-            button.style(*style).label(label).custom_id(id);
+            let id = CustomId::event(*action, self.id, guild_id);
+            // Leave stays enabled until the event ends, so members can drop out of a full or
+            // locked event.
+            let disabled = match action {
+                _ if self.ended => true,
+                EventAction::Join => self.locked || self.is_full(),
+                EventAction::Alt | EventAction::Maybe => self.locked,
+                _ => false,
+            };
+            label.apply(&mut button);
+            button
+                .style(*style)
+                .custom_id(id.to_string())
+                .disabled(disabled);
             row.add_button(button);
         });
 
@@ -419,6 +1726,55 @@ impl Event {
     }
 }
 
+/// Describes how long until an event starts, e.g. "starts in 10 minutes", rounded to the nearest
+/// minute.
+fn starts_in(until: chrono::Duration) -> String {
+    let minutes = (until.num_seconds() + 30).div_euclid(60);
+    let plural = |n: i64, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+    match (minutes / 60, minutes % 60) {
+        _ if minutes <= 0 => "is starting now".to_owned(),
+        (0, mins) => format!("starts in {}", plural(mins, "minute")),
+        (hours, 0) => format!("starts in {}", plural(hours, "hour")),
+        (hours, mins) => format!(
+            "starts in {} {}",
+            plural(hours, "hour"),
+            plural(mins, "minute")
+        ),
+    }
+}
+
+/// How a roster of a given size splits into groups, using the same chunking as an event's groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupLayout {
+    pub group_size: u8,
+    pub full_groups: usize,
+    /// Size of the final partial group, or 0 if every group is full.
+    pub remainder: usize,
+}
+
+impl GroupLayout {
+    pub fn new(group_size: u8, count: usize) -> Self {
+        let chunk_size = group_size.max(1) as usize;
+        GroupLayout {
+            group_size,
+            full_groups: count / chunk_size,
+            remainder: count % chunk_size,
+        }
+    }
+}
+
+impl std::fmt::Display for GroupLayout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = if self.full_groups == 1 { "" } else { "s" };
+        match (self.full_groups, self.remainder) {
+            (0, 0) => f.write_str("No groups"),
+            (0, rem) => write!(f, "1 partial group of {}", rem),
+            (full, 0) => write!(f, "{} full group{}", full, plural),
+            (full, rem) => write!(f, "{} full group{} + 1 of {}", full, plural, rem),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum EventChange {
     /// New event added.
@@ -429,60 +1785,187 @@ pub enum EventChange {
     Edited(Arc<Event>),
     /// Alert protocol initiated.
     Alert(Arc<Event>),
+    /// Check-in window opened.
+    CheckIn(Arc<Event>),
 }
 
-// TODO: Use a hardcoded config for now, but this should become per-guild config.
-lazy_static! {
-    static ref SCHEDULER_CONFIG: alert::EventSchedulerConfig = alert::EventSchedulerConfig {
-        alert: Duration::from_secs(10 * 60),
-        cleanup: Duration::from_secs(30 * 60),
-    };
+/// Replaces the event with its edited version, returning the change to emit. Edits that leave the
+/// event exactly as it was (e.g. toggling a setting back and forth) aren't a change, so nothing
+/// gets persisted or re-rendered for them.
+fn replace_edited(event: &mut Arc<Event>, edited: Event) -> Option<EventChange> {
+    if event.identical(&edited) {
+        return None;
+    }
+    *event = Arc::new(edited);
+    Some(EventChange::Edited(event.clone()))
+}
+
+// Recurring events that went unused for longer than this aren't recreated, unless the guild
+// configures a different threshold.
+const DEFAULT_RECURRENCE_LAPSE_WEEKS: u32 = 8;
+
+fn recurrence_lapse(weeks: Option<u32>) -> chrono::Duration {
+    chrono::Duration::weeks(weeks.unwrap_or(DEFAULT_RECURRENCE_LAPSE_WEEKS).into())
+}
+
+// Events starting less than this far apart conflict, unless the guild configures a different
+// window.
+const DEFAULT_CONFLICT_WINDOW_MINUTES: u32 = 120;
+
+fn conflict_window(minutes: Option<u32>) -> chrono::Duration {
+    chrono::Duration::minutes(minutes.unwrap_or(DEFAULT_CONFLICT_WINDOW_MINUTES).into())
 }
 
 type EventsCollection = BTreeMap<EventId, Arc<Event>>;
 
+/// The number of events that haven't ended yet.
+fn active_event_count(events: &EventsCollection) -> usize {
+    events.values().filter(|event| !event.ended()).count()
+}
+
+// How long after an event is bumped before it can be bumped again, so that its channel doesn't get
+// flooded with reposts.
+const BUMP_COOLDOWN_MINUTES: i64 = 30;
+
 const EVENTS_STORE_NAME: &str = "events.json";
 
+// Holds when the last startup announcement was posted, so that it survives restarts.
+const STARTUP_ANNOUNCEMENT_STORE_NAME: &str = "startup_announcement.json";
+
+// Startup announcements are skipped if one was posted more recently than this, so that a bot that's
+// restarting frequently doesn't spam the announcement channel.
+const STARTUP_ANNOUNCEMENT_INTERVAL_HOURS: i64 = 6;
+
+/// The notice posted to the guild's announcement channel once the bot is back up, summarizing the
+/// events that survived the restart.
+fn startup_message(events: &[Arc<Event>]) -> String {
+    let next = match events.iter().min() {
+        Some(next) => next,
+        None => return "I'm back online, Captain. There aren't any events scheduled.".to_owned(),
+    };
+    let recurring = events.iter().filter(|e| e.recur.is_recurring()).count();
+    format!(
+        "I'm back online, Captain. Events intact: {} ({} recurring), next up is **{}** {}.",
+        events.len(),
+        recurring,
+        next.id,
+        next.timestamp()
+    )
+}
+
 #[derive(Debug)]
 struct EventManagerState {
+    guild_id: GuildId,
     events: EventsCollection,
     events_store: PersistentStore<EventsCollection>,
     next_id: HashMap<Activity, u8>,
+    history: history::EventHistory,
+    audit_log: audit::AuditLog,
+    user_timezones: timezones::UserTimezones,
+    digest_subscribers: digest::DigestSubscribers,
     embed_manager: Option<EmbedManager>,
     event_scheduler: alert::EventScheduler,
+    recurrence_announcement_channel: Option<ChannelId>,
+    follow_up_maybes: bool,
+    message_linger: Option<Duration>,
+    recurrence_lapse: chrono::Duration,
+    // Set once the EventManager is shut down, after which events can no longer be changed.
+    shut_down: bool,
+    // Events that have been previewed to their creators but not yet confirmed, keyed by the IDs
+    // reserved for them. These aren't persisted, so a restart discards them.
+    pending_events: HashMap<EventId, PendingEvent>,
+    // When events were last bumped, kept until their cooldown is over. These aren't persisted
+    // either, since the cooldown is short.
+    bumped_at: HashMap<EventId, DateTime<Utc>>,
+}
+
+/// A previewed event waiting for its creator to confirm or cancel it.
+#[derive(Debug)]
+struct PendingEvent {
+    event: Event,
+    // Told the created event once confirmed, or None once discarded.
+    resolved: oneshot::Sender<Option<Arc<Event>>>,
 }
 
 impl EventManagerState {
     pub async fn load(
         ctx: Context,
+        guild_id: GuildId,
         store_builder: &PersistentStoreBuilder,
         config: GuildConfig,
     ) -> Result<Self> {
         let events_store = store_builder.build(EVENTS_STORE_NAME).await?;
         let events: EventsCollection = events_store.load().await?;
+        let history = history::EventHistory::load(store_builder).await?;
+        let audit_log = audit::AuditLog::load(store_builder).await?;
+        let user_timezones = timezones::UserTimezones::load(store_builder).await?;
+        let digest_subscribers = digest::DigestSubscribers::load(store_builder).await?;
 
         let embed_manager = Some(
-            EmbedManager::new(ctx, store_builder, config.embed_config, events.values()).await?,
+            EmbedManager::new(
+                ctx,
+                guild_id,
+                store_builder,
+                config.embed_config,
+                events.values(),
+            )
+            .await?,
         );
-        let event_scheduler = alert::EventScheduler::new(events.values(), *SCHEDULER_CONFIG);
+        let scheduler_config = alert::EventSchedulerConfig {
+            message_linger: config.message_linger,
+            ..config.scheduler_config
+        };
+        let event_scheduler = alert::EventScheduler::new(events.values(), scheduler_config);
+        metrics::set_active_events(guild_id, active_event_count(&events));
 
         Ok(EventManagerState {
+            guild_id,
             events,
             events_store,
             next_id: Default::default(),
+            history,
+            audit_log,
+            user_timezones,
+            digest_subscribers,
             embed_manager,
             event_scheduler,
+            recurrence_announcement_channel: config.recurrence_announcement_channel,
+            follow_up_maybes: config.follow_up_maybes,
+            message_linger: config.message_linger,
+            recurrence_lapse: recurrence_lapse(config.recurrence_lapse_weeks),
+            shut_down: false,
+            pending_events: Default::default(),
+            bumped_at: Default::default(),
         })
     }
 
     #[cfg(test)]
-    pub fn default(events_store: PersistentStore<EventsCollection>) -> Self {
+    pub fn default(
+        guild_id: GuildId,
+        events_store: PersistentStore<EventsCollection>,
+        history: history::EventHistory,
+        audit_log: audit::AuditLog,
+        user_timezones: timezones::UserTimezones,
+        digest_subscribers: digest::DigestSubscribers,
+    ) -> Self {
         EventManagerState {
+            guild_id,
             events: Default::default(),
             events_store,
             next_id: Default::default(),
+            history,
+            audit_log,
+            user_timezones,
+            digest_subscribers,
             embed_manager: None,
-            event_scheduler: alert::EventScheduler::new(std::iter::empty(), *SCHEDULER_CONFIG),
+            event_scheduler: alert::EventScheduler::new(std::iter::empty(), Default::default()),
+            recurrence_announcement_channel: None,
+            follow_up_maybes: false,
+            message_linger: None,
+            recurrence_lapse: recurrence_lapse(None),
+            shut_down: false,
+            pending_events: Default::default(),
+            bumped_at: Default::default(),
         }
     }
 
@@ -490,9 +1973,25 @@ impl EventManagerState {
     where
         F: FnOnce(&mut EventsCollection) -> Result<(Option<EventChange>, T)>,
     {
-        let (change, ret) = f(&mut self.events)?;
-        if let Some(change) = change {
+        self.modify_events(|events| {
+            let (change, ret) = f(events)?;
+            Ok((change.into_iter().collect(), ret))
+        })
+        .await
+    }
+
+    /// Like modify_event, but for any number of changes, which are persisted to the store together.
+    pub async fn modify_events<F, T>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut EventsCollection) -> Result<(Vec<EventChange>, T)>,
+    {
+        ensure!(!self.shut_down, "EventManager is shut down");
+        let (changes, ret) = f(&mut self.events)?;
+        if !changes.is_empty() {
             self.events_store.store(&self.events).await?;
+            metrics::set_active_events(self.guild_id, active_event_count(&self.events));
+        }
+        for change in changes {
             self.event_scheduler.event_changed(&change).await;
             if let Some(mgr) = &mut self.embed_manager {
                 mgr.event_changed(change).await?;
@@ -503,11 +2002,37 @@ impl EventManagerState {
 }
 
 impl EventManagerState {
+    /// The channel and message content to announce a new recurrence with, if the guild has
+    /// announcements enabled.
+    fn recurrence_announcement(&self, event: &Event) -> Option<(ChannelId, String)> {
+        let channel = self.recurrence_announcement_channel?;
+        let content = format!(
+            "{} {} is up: **{}**, {}",
+            event.recur.next_one(),
+            event.activity,
+            event.id,
+            event.timestamp()
+        );
+        Some((channel, content))
+    }
+
+    /// The maybes of a cleaned up recurring event to ask about joining the next recurrence, if the
+    /// guild has follow-ups enabled.
+    fn maybe_follow_ups<'a>(&self, old: &'a Event) -> Vec<&'a EventMember> {
+        if self.follow_up_maybes && old.recur.is_recurring() {
+            old.maybe()
+        } else {
+            vec![]
+        }
+    }
+
     pub fn next_id(&mut self, activity: Activity) -> Result<EventId> {
         // We don't need to find the lowest unused ID or anything fancy, just find the next unused
         // ID and wrap once maxed out. next_id can be inaccurate or uninitialized for a given
-        // activity type since we check the known events.
+        // activity type since we check the known events. IDs reserved by pending events are
+        // skipped too, so that confirming one keeps the ID it was previewed with.
         let events = &self.events;
+        let pending = &self.pending_events;
         let next = self.next_id.entry(activity).or_insert(1);
 
         let found_next = successors(Some(*next), |n| {
@@ -521,7 +2046,10 @@ impl EventManagerState {
                 None
             }
         })
-        .find(|&n| !events.contains_key(&event_id(activity, n)))
+        .find(|&n| {
+            let id = event_id(activity, n);
+            !events.contains_key(&id) && !pending.contains_key(&id)
+        })
         .ok_or_else(|| format_err!("Maximum number of {} events created", activity.name()))?;
 
         let next_id = event_id(activity, found_next);
@@ -536,24 +2064,60 @@ impl EventManagerState {
 pub struct EventManager<C: CacheHttp = Context> {
     #[derivative(Debug = "ignore")]
     ctx: C,
+    guild_id: GuildId,
+    date_format: DateFormat,
+    weekly_reset: WeeklyReset,
+    button_labels: ButtonLabels,
+    embed_style: EmbedStyle,
+    auto_alt: bool,
+    lock_started_events: bool,
+    conflict_check: ConflictCheck,
+    conflict_window: chrono::Duration,
+    // Per-activity description templates that replace the activities' built-in defaults.
+    default_descriptions: HashMap<Activity, String>,
+    // Shared by all of the guild's alerts, so that simultaneous ones are spaced out together.
+    dm_limiter: dm_limit::DmLimiter,
     store_builder: PersistentStoreBuilder,
     state: RwLock<EventManagerState>,
-    removed_from_guild: AtomicBool,
+    data_guard: Arc<GuildDataGuard>,
 }
 
 impl EventManager {
     pub async fn new(
         ctx: Context,
+        guild_id: GuildId,
         store_builder: PersistentStoreBuilder,
         config: GuildConfig,
+        data_guard: Arc<GuildDataGuard>,
     ) -> Result<Arc<Self>> {
-        let state =
-            RwLock::new(EventManagerState::load(ctx.clone(), &store_builder, config).await?);
+        let date_format = config.date_format;
+        let weekly_reset = config.weekly_reset;
+        let button_labels = config.button_labels.clone();
+        let embed_style = config.embed_config.embed_style.clone();
+        let auto_alt = config.auto_alt;
+        let lock_started_events = config.lock_started_events;
+        let conflict_check = config.join_conflicts;
+        let conflict_window = conflict_window(config.conflict_window_minutes);
+        let default_descriptions = config.default_descriptions.clone();
+        let state = RwLock::new(
+            EventManagerState::load(ctx.clone(), guild_id, &store_builder, config).await?,
+        );
         let mgr = Arc::new(EventManager {
             ctx,
+            guild_id,
+            date_format,
+            weekly_reset,
+            button_labels,
+            embed_style,
+            auto_alt,
+            lock_started_events,
+            conflict_check,
+            conflict_window,
+            default_descriptions,
+            dm_limiter: Default::default(),
             store_builder,
             state,
-            removed_from_guild: Default::default(),
+            data_guard,
         });
         // We should be able to acquire the state lock immediately, nothing else could have acquired
         // it yet. We can't Arc::get_mut + RwLock::get_mut because then we wouldn't be able to
@@ -564,10 +2128,34 @@ impl EventManager {
                 .expect("Should be able to acquire state lock")
                 .event_scheduler
                 .start(Arc::downgrade(&mgr));
+            Self::start_daily_digests(Arc::downgrade(&mgr));
         }
         Ok(mgr)
     }
 
+    /// Sends the daily digests due at the start of each hour, until the EventManager is shut down
+    /// or dropped.
+    fn start_daily_digests(mgr: Weak<Self>) {
+        tokio::spawn(async move {
+            loop {
+                let until = digest::until_next_hour(Utc::now());
+                tokio::time::sleep(until.to_std().unwrap_or_default()).await;
+                let mgr = match mgr.upgrade() {
+                    Some(mgr) => mgr,
+                    None => {
+                        warn!("EventManager gone, stopping daily digest loop");
+                        return;
+                    }
+                };
+                if mgr.state.read().await.shut_down {
+                    info!("EventManager shut down, stopping daily digest loop");
+                    return;
+                }
+                mgr.send_daily_digests().await;
+            }
+        });
+    }
+
     /// A test-only, async Default-like method.
     #[cfg(test)]
     pub async fn default() -> EventManager<serenity::CacheAndHttp> {
@@ -575,12 +2163,47 @@ impl EventManager {
         let store_builder = PersistentStoreBuilder::new(tempdir.into_path())
             .await
             .expect("Failed to create PersistentStoreBuilder");
+        Self::with_store(store_builder, Default::default()).await
+    }
+
+    /// Like default(), but using the given store and data guard.
+    #[cfg(test)]
+    pub async fn with_store(
+        store_builder: PersistentStoreBuilder,
+        data_guard: Arc<GuildDataGuard>,
+    ) -> EventManager<serenity::CacheAndHttp> {
         let events_store = store_builder.build(EVENTS_STORE_NAME).await.unwrap();
+        let history = history::EventHistory::load(&store_builder).await.unwrap();
+        let audit_log = audit::AuditLog::load(&store_builder).await.unwrap();
+        let user_timezones = timezones::UserTimezones::load(&store_builder)
+            .await
+            .unwrap();
+        let digest_subscribers = digest::DigestSubscribers::load(&store_builder)
+            .await
+            .unwrap();
         EventManager {
             ctx: Default::default(),
+            guild_id: GuildId(1),
+            date_format: Default::default(),
+            weekly_reset: Default::default(),
+            button_labels: Default::default(),
+            embed_style: Default::default(),
+            auto_alt: false,
+            lock_started_events: false,
+            conflict_check: Default::default(),
+            conflict_window: conflict_window(None),
+            default_descriptions: Default::default(),
+            dm_limiter: Default::default(),
             store_builder,
-            state: RwLock::new(EventManagerState::default(events_store)),
-            removed_from_guild: Default::default(),
+            state: RwLock::new(EventManagerState::default(
+                GuildId(1),
+                events_store,
+                history,
+                audit_log,
+                user_timezones,
+                digest_subscribers,
+            )),
+            data_guard,
         }
     }
 }
@@ -588,7 +2211,66 @@ impl EventManager {
 impl<C: CacheHttp> EventManager<C> {
     // Bot was removed from the guild for this EventManager, delete state.
     pub fn removed_from_guild(&self) {
-        self.removed_from_guild.store(true, Ordering::Relaxed)
+        self.data_guard.mark_removed()
+    }
+
+    /// Stops performing scheduled actions and stores the guild's events one last time, once any
+    /// change in progress is done being stored. Events can't be changed afterwards, so that the
+    /// process can exit without cutting a write short.
+    pub async fn shutdown(&self) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.shut_down = true;
+        state.event_scheduler.shutdown();
+        state.events_store.store(&state.events).await?;
+        if let Some(embed_manager) = &state.embed_manager {
+            embed_manager.flush().await?;
+        }
+        Ok(())
+    }
+
+    /// How the guild wants dates written out in messages.
+    pub fn date_format(&self) -> DateFormat {
+        self.date_format
+    }
+
+    /// When the guild's weekly reset is, which dates can be given relative to.
+    pub fn weekly_reset(&self) -> WeeklyReset {
+        self.weekly_reset
+    }
+
+    /// What the guild wants event buttons to say.
+    pub fn button_labels(&self) -> &ButtonLabels {
+        &self.button_labels
+    }
+
+    /// How the guild wants event embeds to look.
+    pub fn embed_style(&self) -> &EmbedStyle {
+        &self.embed_style
+    }
+
+    pub fn guild_id(&self) -> GuildId {
+        self.guild_id
+    }
+
+    /// Whether the guild wants members joining a full event as confirmed added as alternates.
+    pub fn auto_alt(&self) -> bool {
+        self.auto_alt
+    }
+
+    /// Whether and how the guild wants joins checked for conflicting events, and how close
+    /// together events have to start to conflict.
+    pub fn conflict_check(&self) -> (ConflictCheck, chrono::Duration) {
+        (self.conflict_check, self.conflict_window)
+    }
+
+    /// The description to use for a new event of the given activity when the creator doesn't give
+    /// one, from the guild's template if it has one and otherwise the activity's own.
+    pub fn default_description(&self, activity: Activity) -> Option<String> {
+        let template = match self.default_descriptions.get(&activity) {
+            Some(template) => template.as_str(),
+            None => activity.default_description()?,
+        };
+        Some(template.replace("{activity}", activity.name()))
     }
 
     pub async fn create_event(
@@ -597,25 +2279,23 @@ impl<C: CacheHttp> EventManager<C> {
         activity: Activity,
         datetime: DateTime<Tz>,
         description: impl Into<String>,
-        recur: bool,
+        settings: EventSettings,
     ) -> Result<Arc<Event>> {
-        let mut state = self.state.write().await;
-        let id = state.next_id(activity)?;
-        let description = description.into();
-        let creator: EventMember = creator.into();
-        let event = Arc::new(Event {
-            id,
+        self.create_event_from_draft(EventDraft {
+            creator,
             activity,
             datetime,
-            description,
-            group_size: activity.default_group_size(),
-            recur,
-            creator: creator.clone(),
-            confirmed: vec![creator],
-            alternates: vec![],
-            maybe: vec![],
-            alert_message: None,
-        });
+            description: Some(description.into()),
+            settings,
+        })
+        .await
+    }
+
+    /// Creates the drafted event, after checking that its settings are valid.
+    pub async fn create_event_from_draft(&self, draft: EventDraft<'_>) -> Result<Arc<Event>> {
+        let mut state = self.state.write().await;
+        let id = state.next_id(draft.activity)?;
+        let event = Arc::new(self.new_event(id, draft)?);
 
         state
             .modify_event(|events| {
@@ -623,11 +2303,201 @@ impl<C: CacheHttp> EventManager<C> {
                 Ok((Some(EventChange::Added(event)), ()))
             })
             .await?;
+        metrics::event_created();
 
         let event = state.events.get(&id).unwrap().clone();
         Ok(event)
     }
 
+    /// Builds an event like create_event would, but holds it back until its creator confirms it
+    /// with confirm_pending_event, so they can check it over first. The event's ID is reserved in
+    /// the meantime. The receiver is told the created event once it's confirmed, or None if it's
+    /// discarded instead.
+    pub async fn preview_event(
+        &self,
+        creator: &dyn MemberLike,
+        activity: Activity,
+        datetime: DateTime<Tz>,
+        description: impl Into<String>,
+        settings: EventSettings,
+    ) -> Result<(Event, oneshot::Receiver<Option<Arc<Event>>>)> {
+        let mut state = self.state.write().await;
+        let id = state.next_id(activity)?;
+        let draft = EventDraft {
+            creator,
+            activity,
+            datetime,
+            description: Some(description.into()),
+            settings,
+        };
+        let event = self.new_event(id, draft)?;
+        let (resolved, receiver) = oneshot::channel();
+        let pending = PendingEvent {
+            event: event.clone(),
+            resolved,
+        };
+        state.pending_events.insert(id, pending);
+        Ok((event, receiver))
+    }
+
+    /// Creates the pending event with the given ID, if there is one and it was previewed by the
+    /// given user.
+    pub async fn confirm_pending_event(
+        &self,
+        id: EventId,
+        user: UserId,
+    ) -> Result<Option<Arc<Event>>> {
+        let mut state = self.state.write().await;
+        let pending = match state.pending_events.remove(&id) {
+            Some(pending) if pending.event.creator.id == user => pending,
+            Some(pending) => {
+                state.pending_events.insert(id, pending);
+                return Ok(None);
+            }
+            None => return Ok(None),
+        };
+
+        let event = Arc::new(pending.event);
+        state
+            .modify_event(|events| {
+                events.insert(id, event.clone());
+                Ok((Some(EventChange::Added(event)), ()))
+            })
+            .await?;
+        metrics::event_created();
+
+        let event = state.events.get(&id).unwrap().clone();
+        // The preview may have been abandoned by now, which is fine.
+        let _ = pending.resolved.send(Some(event.clone()));
+        Ok(Some(event))
+    }
+
+    /// Discards the pending event with the given ID, releasing its ID, if there is one and it was
+    /// previewed by the given user. Returns whether it was discarded.
+    pub async fn discard_pending_event(&self, id: EventId, user: UserId) -> bool {
+        let mut state = self.state.write().await;
+        match state.pending_events.get(&id) {
+            Some(pending) if pending.event.creator.id == user => {
+                let pending = state.pending_events.remove(&id).unwrap();
+                let _ = pending.resolved.send(None);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Creates an event for each of the imported events, all created by the given member. Either
+    /// all of the events are created or, if any of them can't be, none are.
+    pub async fn import_events(
+        &self,
+        creator: &dyn MemberLike,
+        imported: Vec<ImportedEvent>,
+    ) -> Result<Vec<Arc<Event>>> {
+        let mut state = self.state.write().await;
+        let mut events = Vec::with_capacity(imported.len());
+        for ImportedEvent {
+            activity,
+            datetime,
+            description,
+        } in imported
+        {
+            let id = state.next_id(activity)?;
+            // Only possible if the IDs wrapped around while importing.
+            ensure!(
+                events.iter().all(|e: &Arc<Event>| e.id != id),
+                "Maximum number of {} events created",
+                activity.name()
+            );
+            let draft = EventDraft {
+                description: Some(description),
+                ..EventDraft::new(creator, activity, datetime)
+            };
+            events.push(Arc::new(self.new_event(id, draft)?));
+        }
+
+        state
+            .modify_events(|collection| {
+                let changes = events
+                    .iter()
+                    .map(|event| {
+                        collection.insert(event.id, event.clone());
+                        EventChange::Added(event.clone())
+                    })
+                    .collect();
+                Ok((changes, ()))
+            })
+            .await?;
+        for _ in &events {
+            metrics::event_created();
+        }
+        Ok(events)
+    }
+
+    /// Builds a new event with the given ID, filling in everything that isn't given with the
+    /// guild's defaults.
+    fn new_event(&self, id: EventId, draft: EventDraft) -> Result<Event> {
+        let EventDraft {
+            creator,
+            activity,
+            datetime,
+            description,
+            settings,
+        } = draft;
+        let mut description = description.unwrap_or_default();
+        if description.trim().is_empty() {
+            if let Some(default) = self.default_description(activity) {
+                description = default;
+            }
+        }
+        let group_size = settings
+            .group_size
+            .unwrap_or_else(|| activity.default_group_size());
+        ensure!(
+            (1..=MAX_GROUP_SIZE).contains(&group_size),
+            "Invalid group size: {}",
+            group_size
+        );
+        ensure!(
+            settings.tags.len() <= MAX_TAGS,
+            "Too many tags: {}",
+            settings.tags.len()
+        );
+        let creator_avatar = Some(creator.user().face());
+        let creator: EventMember = creator.into();
+        Ok(Event {
+            id,
+            activity,
+            datetime,
+            description,
+            tags: settings.tags,
+            group_size,
+            group_labels: vec![],
+            recur: settings.recur,
+            recur_day: None,
+            creator: creator.clone(),
+            creator_avatar,
+            created_at: Some(Utc::now()),
+            series_id: SeriesId::random(),
+            roster: Roster::from_lists(vec![creator], vec![], vec![]),
+            role_requirements: Default::default(),
+            carry_roster: Default::default(),
+            max_players: settings.max_players,
+            platform: settings.platform,
+            locked: false,
+            locked_by_start: false,
+            notify_creator: false,
+            unlisted: false,
+            auto_promote: true,
+            check_in_open: false,
+            checked_in: Default::default(),
+            alert_muted: Default::default(),
+            ended: false,
+            started: false,
+            cleanup_after: None,
+            alert_message: None,
+        })
+    }
+
     #[cfg(test)]
     async fn add_test_event(&self, event: Event) -> Result<()> {
         let mut state = self.state.write().await;
@@ -653,6 +2523,91 @@ impl<C: CacheHttp> EventManager<C> {
         events.get(&id).map(|e| e.clone())
     }
 
+    /// Find events whose description or activity name contains the query, ignoring case. Events are
+    /// returned in datetime order.
+    pub async fn search(&self, query: &str) -> Vec<Arc<Event>> {
+        let query = query.to_lowercase();
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .filter(|e| !e.ended())
+            .filter(|e| {
+                e.description.to_lowercase().contains(&query)
+                    || e.activity.name().to_lowercase().contains(&query)
+            })
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
+    /// All events that haven't ended in datetime order, limited to those open to the given platform
+    /// if there is one.
+    pub async fn list(&self, platform: Option<Platform>) -> Vec<Arc<Event>> {
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .filter(|e| !e.ended() && platform.map_or(true, |p| e.open_to(p)))
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
+    /// Events that haven't ended which the user created or joined in any way, in datetime order.
+    pub async fn events_for_user(&self, user: UserId) -> Vec<Arc<Event>> {
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .filter(|e| !e.ended() && (e.is_creator(user) || e.kind_of(user).is_some()))
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
+    /// Events the user is confirmed for that start less than `window` before or after the given
+    /// datetime, in order. Events that have ended don't conflict with anything.
+    pub async fn conflicts_for(
+        &self,
+        user: UserId,
+        datetime: DateTime<Tz>,
+        window: chrono::Duration,
+    ) -> Vec<Arc<Event>> {
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .filter(|e| !e.ended() && e.kind_of(user) == Some(JoinKind::Confirmed))
+            .filter(|e| (e.datetime - datetime).abs() < window)
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
+    /// Events starting within the given window from now, inclusive of both ends, in datetime order.
+    pub async fn events_within(&self, window: Duration) -> Vec<Arc<Event>> {
+        self.events_within_at(Utc::now(), window).await
+    }
+
+    async fn events_within_at(&self, now: DateTime<Utc>, window: Duration) -> Vec<Arc<Event>> {
+        // A window too large to represent just means there's no end.
+        let end = chrono::Duration::from_std(window)
+            .ok()
+            .and_then(|window| now.checked_add_signed(window));
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .filter(|e| {
+                let datetime = e.datetime().with_timezone(&Utc);
+                now <= datetime && end.map_or(true, |end| datetime <= end)
+            })
+            .cloned()
+            .sorted()
+            .collect()
+    }
+
     /// Run the provided closure with a mutable reference to the event with the given ID, if one
     /// exists. State is persisted to the store before this returns, and an async task started to
     /// update event embeds.
@@ -669,15 +2624,37 @@ impl<C: CacheHttp> EventManager<C> {
                 Some(event) => {
                     let mut modified = (**event).clone();
                     let ret = edit_fn(Some(&mut modified));
-                    *event = Arc::new(modified);
-
-                    Ok((Some(EventChange::Edited(event.clone())), ret))
+                    Ok((replace_edited(event, modified), ret))
                 }
                 None => Ok((None, edit_fn(None))),
             })
             .await
     }
 
+    /// Like edit_event, for the given member joining or leaving the event. Their join or leave is
+    /// counted in metrics once the edit is saved, so edits that don't go through aren't counted.
+    pub async fn edit_membership<T>(
+        &self,
+        id: &EventId,
+        member: UserId,
+        edit_fn: impl FnOnce(Option<&mut Event>) -> T,
+    ) -> Result<T> {
+        let (before, after, ret) = self
+            .edit_event(id, |mut event| {
+                let before = event.as_ref().and_then(|event| event.kind_of(member));
+                let ret = edit_fn(event.as_deref_mut());
+                let after = event.as_ref().and_then(|event| event.kind_of(member));
+                (before, after, ret)
+            })
+            .await?;
+        match (before, after) {
+            (_, Some(after)) if before != Some(after) => metrics::event_joined(),
+            (Some(_), None) => metrics::event_left(),
+            _ => {}
+        }
+        Ok(ret)
+    }
+
     pub async fn delete_event(&self, id: &EventId) -> Result<()> {
         let mut state = self.state.write().await;
         state
@@ -706,133 +2683,789 @@ impl<C: CacheHttp> EventManager<C> {
                 None => Err(format_err!("Event {} didn't exist to alert", id)),
             })
             .await?;
+        drop(state);
+        metrics::event_alerted();
 
         let message = event
-            .alert_protocol_message()
+            .alert_dm_message(Utc::now())
             .ok_or(format_err!("Missing alert message??"))?;
+        let recipients = members.iter().map(|member| member.id);
+        let failed = self
+            .dm_limiter
+            .send_all("alert", recipients, |user| {
+                let message = &message;
+                async move {
+                    user.create_dm_channel(&self.ctx)
+                        .await?
+                        .send_message(&self.ctx.http(), |msg| msg.content(message))
+                        .await?;
+                    Ok(())
+                }
+            })
+            .await;
+        if failed > 0 {
+            warn!(
+                "Couldn't DM {} of {} members the alert for {}",
+                failed,
+                members.len(),
+                id
+            );
+        }
+        Ok(())
+    }
+
+    async fn open_check_in(&self, id: EventId) -> Result<()> {
+        info!("Opening check-in for {}", id);
+
+        let mut state = self.state.write().await;
+        let (event, members) = state
+            .modify_event(|events| match events.get_mut(&id) {
+                Some(mut event) => {
+                    let members = Arc::make_mut(&mut event).open_check_in();
+                    Ok((
+                        Some(EventChange::CheckIn(event.clone())),
+                        (event.clone(), members),
+                    ))
+                }
+                None => Err(format_err!("Event {} didn't exist to open check-in", id)),
+            })
+            .await?;
+        drop(state);
+
+        let content = format!(
+            "Check-in is open for the {} starting {}! Let your fireteam know you're coming.",
+            event.activity,
+            event.timestamp()
+        );
         for member in members {
-            member
-                .id
-                .create_dm_channel(&self.ctx)
-                .await?
-                .send_message(&self.ctx.http(), |msg| msg.content(message.clone()))
-                .await?;
+            if let Err(err) = self.dm_event(member.id, &event, &content).await {
+                error!(
+                    "Failed to send {} check-in prompt for event {}: {:?}",
+                    member.id, event.id, err
+                );
+            }
         }
         Ok(())
     }
 
+    /// Marks the event as in progress, locking its roster too if the guild wants started events
+    /// closed to new members.
+    async fn start_event(&self, id: EventId) -> Result<()> {
+        info!("Starting event {}", id);
+
+        let lock = self.lock_started_events;
+        let mut state = self.state.write().await;
+        state
+            .modify_event(|events| {
+                let event = events
+                    .get_mut(&id)
+                    .ok_or(format_err!("Event {} does not exist", id))?;
+                Arc::make_mut(event).start(lock);
+                Ok((Some(EventChange::Edited(event.clone())), ()))
+            })
+            .await
+    }
+
     async fn cleanup_event(&self, id: EventId) -> Result<()> {
         info!("Cleaning up event {}", id);
 
         let mut state = self.state.write().await;
+        let linger = state.message_linger.is_some();
         let old = state
             .modify_event(|events| {
+                // If messages linger after cleanup, the event stays around (marked as ended) until
+                // the Expire action removes it.
+                if linger {
+                    let event = events
+                        .get_mut(&id)
+                        .ok_or(format_err!("Event {} does not exist", id))?;
+                    let old = event.clone();
+                    Arc::make_mut(event).end();
+                    return Ok((Some(EventChange::Edited(event.clone())), old));
+                }
                 let event = events
                     .remove(&id)
                     .ok_or(format_err!("Event {} does not exist", id))?;
                 Ok((Some(EventChange::Deleted(event.clone())), event))
             })
             .await?;
+        metrics::event_cleaned_up();
+        if let Err(err) = state.history.record((*old).clone(), Utc::now()).await {
+            error!("Failed to record event {} in history: {:?}", id, err);
+        }
 
-        if old.recur {
+        if old.recur.is_recurring() && old.lapsed(Utc::now(), state.recurrence_lapse) {
+            warn!(
+                "Not recreating recurring event {}, its series lapsed after going unused since {}",
+                id,
+                old.datetime()
+            );
+            drop(state);
+            self.notify_series_lapsed(&old).await;
+        } else if old.recur.is_recurring() {
             info!("Creating event recurrence from {}", id);
 
-            // Check whether we're cleaning up an event that's >1 week old and increase date by
-            // multiple weeks as needed. Otherwise we'll end up creating many events, spamming event
-            // channels and so forth, to do the same thing.
-            let weeks_to_add = Utc::now().signed_duration_since(old.datetime).num_weeks() + 1;
-
             let id = state.next_id(old.activity)?;
-            let new = Arc::new(Event {
-                id,
-                activity: old.activity,
-                datetime: old.datetime + chrono::Duration::weeks(weeks_to_add),
-                description: old.description.clone(),
-                group_size: old.group_size,
-                recur: true,
-                creator: old.creator.clone(),
-                confirmed: vec![],
-                alternates: vec![],
-                maybe: vec![],
-                alert_message: None,
-            });
+            let new = Arc::new(old.next_recurrence(id, Utc::now()));
             state
                 .modify_event(|events| {
                     events.insert(id, new.clone());
-                    Ok((Some(EventChange::Added(new)), ()))
+                    Ok((Some(EventChange::Added(new.clone())), ()))
                 })
                 .await?;
+            let announcement = state.recurrence_announcement(&new);
+            let maybes = state
+                .maybe_follow_ups(&old)
+                .into_iter()
+                .cloned()
+                .collect_vec();
+            drop(state);
+
+            if let Some((channel, content)) = announcement {
+                if let Err(err) = self.announce_recurrence(channel, content, &new).await {
+                    error!("Failed to announce recurrence {}: {:?}", new.id, err);
+                }
+            }
+            self.notify_carried_members(&new).await;
+            self.follow_up_maybes(&maybes, &new).await;
         }
         Ok(())
     }
 
-    /// Adds a new message that contains this event's embed and which should be kept up to date as
-    /// the event is modified.
-    pub async fn keep_embed_updated(
-        &self,
-        event_id: EventId,
-        msg: EventEmbedMessage,
-    ) -> Result<()> {
-        let state = self.state.read().await;
+    /// DMs the creator of a recurring event that it won't be recreated, since it was too stale.
+    async fn notify_series_lapsed(&self, event: &Event) {
+        let content = format!(
+            "Your {} {} ({}) hasn't happened since {}, so I've stopped recreating it. \
+            Create a new event if you want to pick it back up.",
+            event.recur.adjective(),
+            event.activity,
+            event.id,
+            event.timestamp()
+        );
+        let result = async {
+            event
+                .creator
+                .id
+                .create_dm_channel(&self.ctx)
+                .await?
+                .say(&self.ctx.http(), content)
+                .await
+        };
+        if let Err(err) = result.await {
+            error!(
+                "Failed to notify creator that event {} lapsed: {:?}",
+                event.id, err
+            );
+        }
+    }
+
+    /// Deletes an ended event whose messages have lingered long enough, removing its messages.
+    async fn expire_event(&self, id: EventId) -> Result<()> {
+        info!("Removing lingering messages for event {}", id);
+
+        let mut state = self.state.write().await;
         state
-            .embed_manager
-            .as_ref()
-            .expect("EmbedManager None, bad test")
-            .keep_embed_updated(event_id, msg)
+            .modify_event(|events| {
+                let event = events
+                    .remove(&id)
+                    .ok_or(format_err!("Event {} does not exist", id))?;
+                Ok((Some(EventChange::Deleted(event)), ()))
+            })
             .await
     }
 
-    #[cfg(test)]
-    pub async fn next_id(&self, activity: Activity) -> Result<EventId> {
+    /// The number of consecutive weeks, up to this one, that the user attended an event in.
+    pub async fn attendance_streak(&self, user: UserId) -> u32 {
+        let state = self.state.read().await;
+        state.history.streak(user, Utc::now().naive_utc().date())
+    }
+
+    /// Recreates a cleaned up event from its history record, optionally at a new datetime.
+    pub async fn restore_event(
+        &self,
+        id: EventId,
+        datetime: Option<DateTime<Tz>>,
+    ) -> Result<Arc<Event>, RestoreError> {
         let mut state = self.state.write().await;
-        state.next_id(activity)
+        let record = state
+            .history
+            .find(id)
+            .ok_or(RestoreError::NotFound(id))?
+            .clone();
+        let new_id = state.next_id(record.event.activity)?;
+        let event = Arc::new(record.restore(new_id, datetime, Utc::now())?);
+
+        state
+            .modify_event(|events| {
+                events.insert(new_id, event.clone());
+                Ok((Some(EventChange::Added(event.clone())), ()))
+            })
+            .await?;
+        state.history.remove(id).await?;
+        Ok(event)
     }
-}
 
-#[async_trait]
-impl alert::ScheduledActionHandler for EventManager {
-    async fn with_event_for_id<F, T>(&self, id: EventId, func: F) -> Option<T>
-    where
-        F: FnOnce(&Event) -> T + Send,
-    {
+    /// Records a command that changed the guild's events in its audit log.
+    pub async fn record_audit_entry(&self, entry: AuditEntry) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.audit_log.append(entry).await
+    }
+
+    /// Up to `count` of the most recent audit log entries, most recent first.
+    pub async fn audit_log(&self, count: usize) -> Vec<AuditEntry> {
         let state = self.state.read().await;
-        state.events.get(&id).map(|event| func(event))
+        state.audit_log.recent(count)
     }
 
-    async fn perform_action(&self, action: &alert::ScheduledAction) -> Result<()> {
-        match action.action {
-            alert::EventAction::Alert => self.alert_event(action.id).await,
-            alert::EventAction::Cleanup => self.cleanup_event(action.id).await,
-        }
+    /// The default timezone the user has set for datetimes they give, if any.
+    pub async fn user_timezone(&self, user: UserId) -> Option<Tz> {
+        let state = self.state.read().await;
+        state.user_timezones.get(user)
     }
-}
 
-impl<C: CacheHttp> Drop for EventManager<C> {
-    fn drop(&mut self) {
-        if self.removed_from_guild.load(Ordering::Relaxed) {
-            let store_builder = self.store_builder.clone();
-            tokio::spawn(async move {
-                if let Err(err) = store_builder.delete().await {
-                    error!("Failed to delete guild data after removal: {:?}", err);
-                }
-            });
-        }
+    /// Sets the user's default timezone, or clears it with None.
+    pub async fn set_user_timezone(&self, user: UserId, timezone: Option<Tz>) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.user_timezones.set(user, timezone).await
     }
-}
 
-impl TypeMapKey for EventManager {
-    type Value = Arc<EventManager>;
-}
+    /// Opts the user in to or out of a daily DM listing their upcoming events.
+    pub async fn set_digest_subscribed(&self, user: UserId, subscribed: bool) -> Result<()> {
+        let mut state = self.state.write().await;
+        state.digest_subscribers.set(user, subscribed).await
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::{Event, *};
-    use std::iter;
-    use test_env_log::test;
+    /// DMs the user a compact list of their upcoming events. Returns false without sending
+    /// anything if they don't have any.
+    pub async fn send_digest(&self, user: UserId) -> Result<bool> {
+        let events = self.events_for_user(user).await;
+        if events.is_empty() {
+            return Ok(false);
+        }
+        user.create_dm_channel(&self.ctx)
+            .await?
+            .say(&self.ctx.http(), digest::digest_message(&events, user))
+            .await?;
+        Ok(true)
+    }
 
-    async fn add_events_to_manager<C: CacheHttp>(
-        manager: &EventManager<C>,
-        activity: Activity,
+    /// Sends each user who's opted in to daily digests their digest, if it's due this hour in their
+    /// timezone, skipping those without any upcoming events.
+    async fn send_daily_digests(&self) {
+        let now = Utc::now();
+        let users = {
+            let state = self.state.read().await;
+            state
+                .digest_subscribers
+                .users()
+                .filter(|user| digest::digest_due(now, state.user_timezones.get(*user)))
+                .collect_vec()
+        };
+        for user in users {
+            if let Err(err) = self.send_digest(user).await {
+                error!("Failed to send daily digest to user {}: {:?}", user, err);
+            }
+        }
+    }
+
+    /// Clones the event with the given ID to a new time, keeping its roster, and optionally deletes
+    /// the original. Returns None if there's no such event. A clone that keeps the original around
+    /// doesn't recur, so that the event doesn't end up with two series of recurrences.
+    pub async fn clone_event(
+        &self,
+        id: &EventId,
+        datetime: DateTime<Tz>,
+        delete_original: bool,
+    ) -> Result<Option<Arc<Event>>> {
+        let mut state = self.state.write().await;
+        let original = match state.events.get(id) {
+            Some(event) => event.clone(),
+            None => return Ok(None),
+        };
+        let new_id = state.next_id(original.activity)?;
+        let mut clone = original.clone_to(new_id, datetime);
+        if !delete_original {
+            clone.recur = RecurKind::None;
+        }
+        // Moving an event keeps it in the same series, along with whose alerts are muted.
+        if delete_original {
+            clone.series_id = original.series_id;
+            clone.created_at = original.created_at;
+            clone.alert_muted = original.alert_muted.clone();
+        }
+        let clone = Arc::new(clone);
+
+        state
+            .modify_event(|events| {
+                events.insert(new_id, clone.clone());
+                Ok((Some(EventChange::Added(clone.clone())), ()))
+            })
+            .await?;
+        if delete_original {
+            state
+                .modify_event(|events| {
+                    let event = events
+                        .remove(id)
+                        .ok_or_else(|| format_err!("Event {} does not exist", id))?;
+                    Ok((Some(EventChange::Deleted(event)), ()))
+                })
+                .await?;
+        }
+        Ok(Some(clone))
+    }
+
+    /// Makes the given member the creator of every current event in the series that the given event
+    /// belongs to, which future recurrences carry forward. Returns the IDs of the transferred events,
+    /// or None if the event doesn't exist.
+    pub async fn transfer_series(
+        &self,
+        id: &EventId,
+        new_creator: EventMember,
+    ) -> Result<Option<Vec<EventId>>> {
+        let mut state = self.state.write().await;
+        let series_id = match state.events.get(id) {
+            Some(event) => event.series_id(),
+            None => return Ok(None),
+        };
+        let ids = state
+            .events
+            .values()
+            .filter(|e| !e.ended() && e.series_id() == series_id)
+            .map(|e| e.id)
+            .collect_vec();
+        for id in &ids {
+            state
+                .modify_event(|events| {
+                    let event = events
+                        .get_mut(id)
+                        .ok_or_else(|| format_err!("Event {} does not exist", id))?;
+                    let mut edited = (**event).clone();
+                    edited.creator = new_creator.clone();
+                    Ok((replace_edited(event, edited), ()))
+                })
+                .await?;
+        }
+        Ok(Some(ids))
+    }
+
+    /// The IDs of everyone that created or joined any of the events.
+    pub async fn member_ids(&self) -> BTreeSet<UserId> {
+        let state = self.state.read().await;
+        state
+            .events
+            .values()
+            .flat_map(|e| {
+                iter::once(e.creator.id).chain(e.join_order().into_iter().map(|(m, _)| m.id))
+            })
+            .collect()
+    }
+
+    /// Updates the member's name in every event they're in, returning the IDs of the events that
+    /// changed.
+    pub async fn rename_member(&self, member: &dyn MemberLike) -> Result<Vec<EventId>> {
+        let names = HashMap::from([(member.id(), member.display_name().to_owned())]);
+        self.refresh_member_names(&names).await
+    }
+
+    /// Updates the stored names of every member of every event to their current display names, for
+    /// those users in `names`, so that names changed while offline aren't left stale. Returns the
+    /// IDs of events that changed, which are persisted together.
+    pub async fn refresh_member_names(
+        &self,
+        names: &HashMap<UserId, String>,
+    ) -> Result<Vec<EventId>> {
+        let mut state = self.state.write().await;
+        state
+            .modify_events(|events| {
+                let mut changes = Vec::new();
+                let mut ids = Vec::new();
+                for event in events.values_mut() {
+                    let mut renamed = (**event).clone();
+                    let users = iter::once(event.creator.id)
+                        .chain(event.join_order().into_iter().map(|(m, _)| m.id))
+                        .collect_vec();
+                    for user in users {
+                        if let Some(name) = names.get(&user) {
+                            renamed.rename_member(user, name);
+                        }
+                    }
+                    if let Some(change) = replace_edited(event, renamed) {
+                        ids.push(event.id);
+                        changes.push(change);
+                    }
+                }
+                Ok((changes, ids))
+            })
+            .await
+    }
+
+    /// Moves the given members from one event to another as confirmed members, returning the
+    /// result of each move. Both events are updated together so that nobody is ever in both.
+    pub async fn move_members(
+        &self,
+        from: &EventId,
+        to: &EventId,
+        members: &[&dyn MemberLike],
+    ) -> Result<Vec<(UserId, Result<()>)>> {
+        if from == to {
+            return Err(format_err!("Can't move members within event {}", from));
+        }
+        let mut state = self.state.write().await;
+        let get = |id| {
+            state
+                .events
+                .get(id)
+                .map(|e| (**e).clone())
+                .ok_or_else(|| format_err!("Event {} does not exist", id))
+        };
+        let (mut source, mut dest) = (get(from)?, get(to)?);
+
+        let results: Vec<_> = members
+            .iter()
+            .map(|member| (member.id(), source.move_member_to(&mut dest, *member)))
+            .collect();
+        if results.iter().all(|(_, result)| result.is_err()) {
+            return Ok(results);
+        }
+
+        state
+            .modify_events(|events| {
+                let changes = [dest, source]
+                    .into_iter()
+                    .map(|event| {
+                        let event = Arc::new(event);
+                        events.insert(event.id, event.clone());
+                        EventChange::Edited(event)
+                    })
+                    .collect();
+                Ok((changes, ()))
+            })
+            .await?;
+        Ok(results)
+    }
+
+    /// Merges the roster of one event into another and deletes it, returning the merged event and
+    /// the members that were added to or changed in it.
+    pub async fn merge_events(
+        &self,
+        into: &EventId,
+        from: &EventId,
+    ) -> Result<(Arc<Event>, Vec<(EventMember, JoinKind)>)> {
+        if into == from {
+            return Err(format_err!("Can't merge event {} into itself", into));
+        }
+        let mut state = self.state.write().await;
+        let get = |id| {
+            state
+                .events
+                .get(id)
+                .cloned()
+                .ok_or_else(|| format_err!("Event {} does not exist", id))
+        };
+        let (mut dest, source) = (get(into)?, get(from)?);
+        let merged = Arc::make_mut(&mut dest).merge_from(&source);
+
+        // Both changes are persisted together, so that the merged members are never stored in both.
+        state
+            .modify_events(|events| {
+                let source = events
+                    .remove(from)
+                    .ok_or_else(|| format_err!("Event {} does not exist", from))?;
+                events.insert(dest.id, dest.clone());
+                Ok((
+                    vec![
+                        EventChange::Edited(dest.clone()),
+                        EventChange::Deleted(source),
+                    ],
+                    (),
+                ))
+            })
+            .await?;
+        Ok((dest, merged))
+    }
+
+    async fn announce_recurrence(
+        &self,
+        channel: ChannelId,
+        content: String,
+        event: &Event,
+    ) -> Result<()> {
+        let msg = channel
+            .send_message(&self.ctx.http(), |msg| {
+                msg.content(content)
+                    .set_embed(event.as_embed(&self.embed_style))
+                    .components(|c| {
+                        *c = event.event_buttons(&self.button_labels);
+                        c
+                    })
+            })
+            .await?;
+        self.keep_embed_updated(event.id, EventEmbedMessage::Normal(msg.channel_id, msg.id))
+            .await
+    }
+
+    /// Posts a notice that the bot is back up to the given channel, unless one was already posted
+    /// within the last STARTUP_ANNOUNCEMENT_INTERVAL_HOURS.
+    pub async fn announce_startup(&self, channel: ChannelId) -> Result<()> {
+        let store: PersistentStore<Option<DateTime<Utc>>> = self
+            .store_builder
+            .build(STARTUP_ANNOUNCEMENT_STORE_NAME)
+            .await?;
+        let now = Utc::now();
+        if let Some(last) = store.load().await? {
+            if now - last < chrono::Duration::hours(STARTUP_ANNOUNCEMENT_INTERVAL_HOURS) {
+                info!(
+                    "Skipping startup announcement for guild {}, last one was at {}",
+                    self.guild_id, last
+                );
+                return Ok(());
+            }
+        }
+
+        let content = startup_message(&self.list(None).await);
+        channel.say(&self.ctx.http(), content).await?;
+        store.store(&Some(now)).await
+    }
+
+    /// DMs a user the given content along with the event's embed and buttons, keeping the embed
+    /// updated as the event changes.
+    pub async fn dm_event(&self, user: UserId, event: &Event, content: &str) -> Result<()> {
+        let msg = user
+            .create_dm_channel(&self.ctx)
+            .await?
+            .send_message(&self.ctx.http(), |msg| {
+                msg.content(content)
+                    .set_embed(event.as_embed(&self.embed_style))
+                    .components(|c| {
+                        *c = event.dm_event_buttons(&self.button_labels, self.guild_id);
+                        c
+                    })
+            })
+            .await?;
+        self.keep_embed_updated(event.id, EventEmbedMessage::Dm(msg.channel_id, msg.id))
+            .await
+    }
+
+    /// DMs members that were carried over into a new recurrence, so they can opt out.
+    async fn notify_carried_members(&self, event: &Event) {
+        let content = format!(
+            "Heads up, Guardian! I've signed you up for the next {} at {}, same as last time. \
+            Use the buttons below if you can't make it.",
+            event.activity,
+            event.timestamp()
+        );
+        for member in event.confirmed().into_iter().chain(event.alternates()) {
+            if let Err(err) = self.dm_event(member.id, event, &content).await {
+                error!(
+                    "Failed to notify {} about carried over event {}: {:?}",
+                    member.id, event.id, err
+                );
+            }
+        }
+    }
+
+    /// DMs the maybes from the previous recurrence, asking if they want to join the new one.
+    async fn follow_up_maybes(&self, maybes: &[EventMember], event: &Event) {
+        let content = format!(
+            "You were a maybe for {} {}. Want in on the next one at {}? \
+            Use the buttons below to sign up.",
+            event.recur.previous_one(),
+            event.activity,
+            event.timestamp()
+        );
+        for member in maybes {
+            if let Err(err) = self.dm_event(member.id, event, &content).await {
+                error!(
+                    "Failed to follow up with maybe {} about event {}: {:?}",
+                    member.id, event.id, err
+                );
+            }
+        }
+    }
+
+    /// Adds a new message that contains this event's embed and which should be kept up to date as
+    /// the event is modified.
+    pub async fn keep_embed_updated(
+        &self,
+        event_id: EventId,
+        msg: EventEmbedMessage,
+    ) -> Result<()> {
+        let state = self.state.read().await;
+        state
+            .embed_manager
+            .as_ref()
+            .expect("EmbedManager None, bad test")
+            .keep_embed_updated(event_id, msg)
+            .await
+    }
+
+    /// A snapshot of the scheduler's pending actions, soonest first.
+    pub async fn scheduled_actions(&self) -> Vec<ScheduledAction> {
+        let state = self.state.read().await;
+        state.event_scheduler.snapshot().await
+    }
+
+    /// How the guild's event channels are configured, ordered by channel ID.
+    pub async fn event_channels(&self) -> Vec<EventChannelInfo> {
+        let state = self.state.read().await;
+        state
+            .embed_manager
+            .as_ref()
+            .map_or_else(Vec::new, |mgr| mgr.event_channels())
+    }
+
+    /// Reposts the event's message at the bottom of each event channel that shows it, in those
+    /// where others' messages have buried it. Each event can be bumped once per cooldown.
+    pub async fn bump_event(&self, id: EventId) -> Result<(), BumpError> {
+        let mut state = self.state.write().await;
+        let now = Utc::now();
+        let cooldown = chrono::Duration::minutes(BUMP_COOLDOWN_MINUTES);
+        state
+            .bumped_at
+            .retain(|_, bumped_at| now < *bumped_at + cooldown);
+        if let Some(bumped_at) = state.bumped_at.get(&id) {
+            return Err(BumpError::CoolingDown(*bumped_at + cooldown));
+        }
+
+        let channels = match &state.embed_manager {
+            Some(embed_manager) => embed_manager.bump_event(id).await,
+            None => 0,
+        };
+        if channels == 0 {
+            return Err(BumpError::NotPosted);
+        }
+        state.bumped_at.insert(id, now);
+        Ok(())
+    }
+
+    /// Pushes the event's cleanup back by delta without changing when it starts, e.g. when an
+    /// activity runs long. Returns false if the event doesn't exist or was already cleaned up.
+    pub async fn extend_cleanup(&self, id: EventId, delta: chrono::Duration) -> Result<bool> {
+        let mut state = self.state.write().await;
+        let usual_delay = state.event_scheduler.cleanup_delay();
+        state
+            .modify_event(|events| match events.get_mut(&id) {
+                Some(event) if !event.ended() => {
+                    let mut edited = (**event).clone();
+                    edited.extend_cleanup(usual_delay, delta);
+                    Ok((replace_edited(event, edited), true))
+                }
+                _ => Ok((None, false)),
+            })
+            .await
+    }
+
+    /// Sets the role mentioned when new events are posted to the given event channel, or clears it
+    /// with None. Returns false if it isn't one of the guild's event channels.
+    pub async fn set_new_event_role(
+        &self,
+        channel_id: ChannelId,
+        role: Option<RoleId>,
+    ) -> Result<bool> {
+        let mut state = self.state.write().await;
+        match &mut state.embed_manager {
+            Some(embed_manager) => embed_manager.set_new_event_role(channel_id, role).await,
+            None => Ok(false),
+        }
+    }
+
+    /// The events the given event channel currently shows, in order, or None if it isn't one of the
+    /// guild's event channels.
+    pub async fn channel_events(&self, channel_id: ChannelId) -> Option<Vec<Arc<Event>>> {
+        let state = self.state.read().await;
+        state.embed_manager.as_ref()?.channel_events(channel_id)
+    }
+
+    /// Embed operations that failed and haven't been retried yet.
+    pub async fn failed_embed_ops(&self) -> Vec<FailedOp> {
+        let state = self.state.read().await;
+        state
+            .embed_manager
+            .as_ref()
+            .expect("EmbedManager None, bad test")
+            .failed_ops()
+            .await
+    }
+
+    /// Retries failed embed operations, returning the number that succeeded and failed.
+    pub async fn retry_failed_embed_ops(&self) -> Result<(usize, usize)> {
+        let state = self.state.read().await;
+        let events = &state.events;
+        state
+            .embed_manager
+            .as_ref()
+            .expect("EmbedManager None, bad test")
+            .retry_failed_ops(|id| events.get(&id).cloned())
+            .await
+    }
+
+    #[cfg(test)]
+    pub async fn next_id(&self, activity: Activity) -> Result<EventId> {
+        let mut state = self.state.write().await;
+        state.next_id(activity)
+    }
+}
+
+#[async_trait]
+impl alert::ScheduledActionHandler for EventManager {
+    async fn with_event_for_id<F, T>(&self, id: EventId, func: F) -> Option<T>
+    where
+        F: FnOnce(&Event) -> T + Send,
+    {
+        let state = self.state.read().await;
+        state.events.get(&id).map(|event| func(event))
+    }
+
+    async fn perform_action(&self, action: &alert::ScheduledAction) -> Result<()> {
+        match action.action {
+            alert::EventAction::CheckIn => self.open_check_in(action.id).await,
+            alert::EventAction::Alert => self.alert_event(action.id).await,
+            alert::EventAction::Start => self.start_event(action.id).await,
+            alert::EventAction::Cleanup => self.cleanup_event(action.id).await,
+            alert::EventAction::Expire => self.expire_event(action.id).await,
+        }
+    }
+}
+
+impl<C: CacheHttp> Drop for EventManager<C> {
+    fn drop(&mut self) {
+        if !self.data_guard.is_removed() {
+            return;
+        }
+        // If the lock is held, the guild has been re-added and its data is being loaded again.
+        let lock = match self.data_guard.try_lock_for_deletion() {
+            Some(lock) => lock,
+            None => return,
+        };
+        let data_guard = self.data_guard.clone();
+        let store_builder = self.store_builder.clone();
+        tokio::spawn(async move {
+            // Check again in case the guild was re-added after this EventManager was dropped.
+            if data_guard.is_removed() {
+                if let Err(err) = store_builder.delete().await {
+                    error!("Failed to delete guild data after removal: {:?}", err);
+                }
+            }
+            drop(lock);
+        });
+    }
+}
+
+impl TypeMapKey for EventManager {
+    type Value = Arc<EventManager>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Event, *};
+    use crate::activity::ActivityType;
+    use assert_matches::assert_matches;
+    use std::iter;
+    use test_env_log::test;
+
+    async fn add_events_to_manager<C: CacheHttp>(
+        manager: &EventManager<C>,
+        activity: Activity,
         indexes: impl IntoIterator<Item = u8>,
     ) {
         for index in indexes {
@@ -847,21 +3480,6 @@ mod tests {
         }
     }
 
-    // Helper for tests since Member doesn't implement Default
-    impl MemberLike for User {
-        fn user(&self) -> &User {
-            &self
-        }
-
-        fn id(&self) -> UserId {
-            self.id
-        }
-
-        fn display_name(&self) -> &str {
-            &self.name
-        }
-    }
-
     const VOG: Activity = Activity::VaultOfGlass;
     const GOS: Activity = Activity::GardenOfSalvation;
 
@@ -919,7 +3537,7 @@ mod tests {
         let user = User::default();
         assert_eq!(
             manager
-                .create_event(&user, VOG, t, "", false)
+                .create_event(&user, VOG, t, "", Default::default())
                 .await
                 .unwrap()
                 .id,
@@ -927,7 +3545,7 @@ mod tests {
         );
         assert_eq!(
             manager
-                .create_event(&user, VOG, t, "", false)
+                .create_event(&user, VOG, t, "", Default::default())
                 .await
                 .unwrap()
                 .id,
@@ -935,11 +3553,2756 @@ mod tests {
         );
         assert_eq!(
             manager
-                .create_event(&user, GOS, t, "", false)
+                .create_event(&user, GOS, t, "", Default::default())
                 .await
                 .unwrap()
                 .id,
             event_id(GOS, 1)
         );
     }
+
+    #[test(tokio::test)]
+    async fn test_import_events() {
+        let manager = EventManager::default().await;
+        manager
+            .create_event(
+                &User::default(),
+                VOG,
+                Utc::now().with_timezone(&Tz::UTC),
+                "",
+                Default::default(),
+            )
+            .await
+            .unwrap();
+        let t = (Utc::now() + chrono::Duration::days(1)).with_timezone(&Tz::PST8PDT);
+        let imported = |activity, description: &str| ImportedEvent {
+            activity,
+            datetime: t,
+            description: description.to_owned(),
+        };
+        let events = manager
+            .import_events(
+                &User::default(),
+                vec![
+                    imported(VOG, "Raid night"),
+                    imported(VOG, ""),
+                    imported(GOS, "Garden"),
+                ],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.id).collect_vec(),
+            vec![event_id(VOG, 2), event_id(VOG, 3), event_id(GOS, 1)]
+        );
+        assert_eq!(events[0].description, "Raid night");
+        assert_eq!(events[0].datetime, t);
+        assert!(events[0].is_creator(User::default().id));
+        for event in &events {
+            assert_eq!(manager.get_event(&event.id).await.as_ref(), Some(event));
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_create_event_with_group_size() {
+        let manager = EventManager::default().await;
+        let t = Utc::now().with_timezone(&Tz::PST8PDT) + chrono::Duration::days(1);
+        let settings = |group_size| EventSettings {
+            group_size,
+            ..Default::default()
+        };
+        let event = manager
+            .create_event(&User::default(), VOG, t, "", settings(Some(3)))
+            .await
+            .unwrap();
+        assert_eq!(event.group_size, 3);
+
+        let mut event = (*event).clone();
+        for id in 1..=6 {
+            let mut user = User::default();
+            user.id = UserId(id);
+            let kind = if id <= 4 {
+                JoinKind::Confirmed
+            } else {
+                JoinKind::Alternate
+            };
+            event.join(&user, kind).unwrap();
+        }
+        let group_sizes = event
+            .confirmed_groups()
+            .iter()
+            .map(|g| g.len())
+            .collect_vec();
+        assert_eq!(group_sizes, vec![3, 3]);
+        assert_eq!(
+            event.extra_alts().iter().map(|m| m.id.0).collect_vec(),
+            vec![6]
+        );
+
+        for size in [0, MAX_GROUP_SIZE + 1] {
+            assert!(manager
+                .create_event(&User::default(), VOG, t, "", settings(Some(size)))
+                .await
+                .is_err());
+        }
+        let event = manager
+            .create_event(&User::default(), VOG, t, "", settings(None))
+            .await
+            .unwrap();
+        assert_eq!(event.group_size, VOG.default_group_size());
+    }
+
+    #[test(tokio::test)]
+    async fn test_create_event_from_draft() {
+        let manager = EventManager::default().await;
+        let t = Utc::now().with_timezone(&Tz::PST8PDT) + chrono::Duration::days(1);
+        let mut creator = User::default();
+        creator.id = UserId(3);
+
+        // Anything left out of the draft uses the defaults.
+        let event = manager
+            .create_event_from_draft(EventDraft::new(&creator, VOG, t))
+            .await
+            .unwrap();
+        assert_eq!(event.id, event_id(VOG, 1));
+        assert_eq!(event.datetime(), t);
+        assert_eq!(
+            Some(event.description.clone()),
+            manager.default_description(VOG)
+        );
+        assert_eq!(event.group_size, VOG.default_group_size());
+        assert_eq!(event.recur, RecurKind::None);
+        assert!(event.is_creator(UserId(3)));
+        assert_eq!(event.kind_of(UserId(3)), Some(JoinKind::Confirmed));
+        assert_eq!(manager.get_event(&event.id).await, Some(event));
+
+        let draft = EventDraft {
+            description: Some("Weekly clear".to_owned()),
+            settings: EventSettings {
+                recur: RecurKind::Weekly,
+                group_size: Some(3),
+                max_players: Some(6),
+                tags: normalize_tags("sherpa"),
+                ..Default::default()
+            },
+            ..EventDraft::new(&creator, VOG, t)
+        };
+        let event = manager.create_event_from_draft(draft).await.unwrap();
+        assert_eq!(event.id, event_id(VOG, 2));
+        assert_eq!(event.description, "Weekly clear");
+        assert_eq!(event.recur, RecurKind::Weekly);
+        assert_eq!(event.group_size, 3);
+        assert_eq!(event.max_players, Some(6));
+        assert_eq!(event.tags, vec!["sherpa"]);
+
+        // Invalid drafts are rejected without creating anything.
+        let invalid_drafts = [
+            EventSettings {
+                group_size: Some(0),
+                ..Default::default()
+            },
+            EventSettings {
+                group_size: Some(MAX_GROUP_SIZE + 1),
+                ..Default::default()
+            },
+            EventSettings {
+                tags: (0..=MAX_TAGS).map(|i| format!("tag{}", i)).collect(),
+                ..Default::default()
+            },
+        ];
+        for settings in invalid_drafts {
+            let draft = EventDraft {
+                settings,
+                ..EventDraft::new(&creator, VOG, t)
+            };
+            assert!(manager.create_event_from_draft(draft).await.is_err());
+        }
+        assert_eq!(manager.list(None).await.len(), 2);
+    }
+
+    #[test(tokio::test)]
+    async fn test_default_description() {
+        async fn create(
+            manager: &EventManager<serenity::CacheAndHttp>,
+            activity: Activity,
+            description: &str,
+        ) -> String {
+            let t = Utc::now().with_timezone(&Tz::PST8PDT) + chrono::Duration::days(1);
+            manager
+                .create_event(
+                    &User::default(),
+                    activity,
+                    t,
+                    description,
+                    Default::default(),
+                )
+                .await
+                .unwrap()
+                .description
+                .clone()
+        }
+        let mut manager = EventManager::default().await;
+
+        // Empty descriptions fall back to the activity's default, if it has one.
+        assert_eq!(
+            create(&manager, VOG, "").await,
+            "Full Vault of Glass clear, all encounters."
+        );
+        assert_eq!(create(&manager, VOG, "Flawless").await, "Flawless");
+        assert_eq!(create(&manager, Activity::Custom, " ").await, " ");
+
+        // Guild templates take precedence.
+        manager
+            .default_descriptions
+            .insert(VOG, "{activity}, bring Vex Mythoclast".to_owned());
+        manager
+            .default_descriptions
+            .insert(Activity::Custom, "Anything goes".to_owned());
+        assert_eq!(
+            create(&manager, VOG, "").await,
+            "Vault of Glass, bring Vex Mythoclast"
+        );
+        assert_eq!(
+            create(&manager, Activity::Custom, "").await,
+            "Anything goes"
+        );
+    }
+
+    fn test_member(id: u64, role: Option<GroupRole>) -> EventMember {
+        EventMember {
+            id: UserId(id),
+            name: id.to_string(),
+            role,
+            joined_at: None,
+            note: None,
+        }
+    }
+
+    fn group_ids(event: &Event) -> Vec<Vec<u64>> {
+        event
+            .confirmed_groups()
+            .iter()
+            .map(|group| group.iter().map(|(u, _)| u.id.0).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_groups_honor_role_requirements() {
+        let dps = Some(GroupRole::Dps);
+        let support = Some(GroupRole::Support);
+        let mut event = Event {
+            group_size: 3,
+            roster: Roster::from_lists(
+                vec![
+                    test_member(1, dps),
+                    test_member(2, dps),
+                    test_member(3, dps),
+                    test_member(4, support),
+                    test_member(5, dps),
+                    test_member(6, support),
+                ],
+                vec![],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        assert_eq!(group_ids(&event), vec![vec![1, 2, 3], vec![4, 5, 6]]);
+
+        event.role_requirements = vec![(GroupRole::Support, 1)].into_iter().collect();
+        assert_eq!(group_ids(&event), vec![vec![1, 2, 4], vec![3, 5, 6]]);
+        assert!(event.unmet_role_requirements().is_empty());
+    }
+
+    #[test]
+    fn test_role_requirements_keep_alternates_last() {
+        let event = Event {
+            group_size: 2,
+            roster: Roster::from_lists(
+                vec![
+                    test_member(1, None),
+                    test_member(2, None),
+                    test_member(3, None),
+                    test_member(4, None),
+                ],
+                vec![test_member(5, Some(GroupRole::Support))],
+                vec![],
+            ),
+            role_requirements: vec![(GroupRole::Support, 1)].into_iter().collect(),
+            ..Default::default()
+        };
+        // The alternate has the required role, but confirmed members still fill the groups first.
+        assert_eq!(group_ids(&event), vec![vec![1, 2], vec![3, 4]]);
+    }
+
+    #[test]
+    fn test_unmet_role_requirements() {
+        let dps = Some(GroupRole::Dps);
+        let support = Some(GroupRole::Support);
+        let event = Event {
+            group_size: 3,
+            roster: Roster::from_lists(
+                vec![
+                    test_member(1, dps),
+                    test_member(2, support),
+                    test_member(3, dps),
+                    test_member(4, dps),
+                    test_member(5, None),
+                ],
+                vec![],
+                vec![],
+            ),
+            role_requirements: vec![(GroupRole::Dps, 1), (GroupRole::Support, 1)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        assert_eq!(group_ids(&event), vec![vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(
+            event.unmet_role_requirements(),
+            vec![(1, vec![(GroupRole::Support, 1)])]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_search() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let events = [
+            (event_id(VOG, 1), 2, "Sherpa run, bring snacks"),
+            (event_id(VOG, 2), 1, "Chill clear"),
+            (event_id(GOS, 1), 3, "Flawless SHERPA attempt"),
+        ];
+        for (id, hours, description) in events {
+            manager
+                .add_test_event(Event {
+                    id,
+                    activity: id.activity,
+                    datetime: now + chrono::Duration::hours(hours),
+                    description: description.to_owned(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let ids = |events: Vec<Arc<Event>>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(
+            ids(manager.search("sherpa").await),
+            vec![event_id(VOG, 1), event_id(GOS, 1)]
+        );
+        assert_eq!(
+            ids(manager.search("vault").await),
+            vec![event_id(VOG, 2), event_id(VOG, 1)]
+        );
+        assert_eq!(ids(manager.search("Garden").await), vec![event_id(GOS, 1)]);
+        assert!(manager.search("crota").await.is_empty());
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags("Sherpa, #chill ,no mic"),
+            vec!["sherpa", "chill", "no-mic"]
+        );
+        assert_eq!(
+            normalize_tags("flawless,FLAWLESS, #Flawless"),
+            vec!["flawless"]
+        );
+        assert_eq!(normalize_tags(" , #, !!"), Vec::<String>::new());
+        assert_eq!(
+            normalize_tags("a,b,c,d,e,f,g"),
+            vec!["a", "b", "c", "d", "e"]
+        );
+        assert_eq!(
+            normalize_tag("Day-1 (emblem)!"),
+            Some("day-1-emblem".to_owned())
+        );
+        assert_eq!(
+            normalize_tag("averyveryverylongtagthatkeepsgoing"),
+            Some("averyveryverylongtag".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_embed_color() {
+        let event = Event {
+            activity: Activity::TrialsOfOsiris,
+            ..Default::default()
+        };
+        let color = |event: &Event, colors: &EmbedColors| {
+            let style = EmbedStyle {
+                colors: colors.clone(),
+                ..Default::default()
+            };
+            event.as_embed(&style).0["color"].clone()
+        };
+        assert_eq!(
+            color(&event, &Default::default()),
+            ActivityType::Crucible.color().0
+        );
+
+        let pink = Color::new(0xff69b4);
+        let colors = EmbedColors::new([(ActivityType::Crucible, pink)].into_iter().collect());
+        assert_eq!(color(&event, &colors), pink.0);
+
+        let ended = Event {
+            ended: true,
+            ..event
+        };
+        assert_eq!(color(&ended, &colors), Color::DARK_GREY.0);
+    }
+
+    #[test]
+    fn test_embed_shows_creator() {
+        let mut event = Event::default();
+        event.created_at = Some(Utc.ymd(2021, 9, 1).and_hms(12, 0, 0));
+        let embed = event.as_embed(&Default::default()).0;
+        assert!(embed.get("author").is_none());
+        assert!(embed.get("description").is_none());
+        assert_eq!(embed["footer"]["text"], "Creator | default");
+
+        let avatar = "https://cdn.discordapp.com/avatars/1/abc.webp?size=1024";
+        event.creator_avatar = Some(avatar.to_owned());
+        // The avatar is always recorded, but only shown if the guild shows creators.
+        let embed = event.as_embed(&Default::default()).0;
+        assert!(embed.get("author").is_none());
+        assert!(embed.get("description").is_none());
+
+        let style = EmbedStyle {
+            show_creator: true,
+            ..Default::default()
+        };
+        let embed = event.as_embed(&style).0;
+        assert_eq!(embed["author"]["name"], "default");
+        assert_eq!(embed["author"]["icon_url"], avatar);
+        assert_eq!(embed["description"], "Created <t:1630497600:R>");
+        assert_eq!(embed["footer"]["text"], "Creator | default");
+
+        // Events from before creation times were recorded still show the creator.
+        event.created_at = None;
+        let embed = event.as_embed(&style).0;
+        assert_eq!(embed["author"]["icon_url"], avatar);
+        assert!(embed.get("description").is_none());
+    }
+
+    #[test]
+    fn test_member_notes() {
+        assert_eq!(
+            normalize_note("  can only\nstay   1hr "),
+            Some("can only stay 1hr".to_owned())
+        );
+        assert_eq!(normalize_note(" \n "), None);
+        assert_eq!(
+            normalize_note(&"x".repeat(80)).unwrap().len(),
+            MAX_NOTE_CHARS
+        );
+
+        let mut user = User::default();
+        user.id = UserId(2);
+        let mut event = Event::default();
+        event.join(&user, JoinKind::Confirmed).unwrap();
+        event
+            .set_note(UserId(2), Some("bringing a *sherpa*".to_owned()))
+            .unwrap();
+        assert!(event.set_note(UserId(3), None).is_err());
+        let fields = |event: &Event| event.as_embed(&Default::default()).0["fields"].clone();
+        let group = fields(&event)
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["name"].as_str().unwrap().starts_with("Group 1"))
+            .unwrap()["value"]
+            .clone();
+        assert_eq!(group, "<@1>, <@2> _bringing a \\*sherpa\\*_");
+
+        // Notes survive switching how the member is joined, and show in the other lists too.
+        event.join(&user, JoinKind::Maybe).unwrap();
+        assert_eq!(
+            event.maybe()[0].note.as_deref(),
+            Some("bringing a *sherpa*")
+        );
+        let maybe = fields(&event)
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|f| f["name"] == "Maybe")
+            .unwrap()["value"]
+            .clone();
+        assert_eq!(maybe, "<@2> _bringing a \\*sherpa\\*_");
+
+        // Members are still equal by ID alone, whatever their notes.
+        let mut noted = test_member(2, None);
+        noted.note = Some("can only stay 1hr".to_owned());
+        assert_eq!(noted, test_member(2, None));
+
+        // Members stored before notes existed have none.
+        let mut json = serde_json::to_value(&noted).unwrap();
+        json.as_object_mut().unwrap().remove("note");
+        let loaded: EventMember = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.note, None);
+    }
+
+    #[test]
+    fn test_platform_display() {
+        let platforms = ["pc", "xbox", "playstation", "crossplay"]
+            .iter()
+            .map(|s| Platform::from_str(s).unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(platforms, vec!["PC", "Xbox", "PlayStation", "Crossplay"]);
+        assert!(Platform::from_str("stadia").is_err());
+
+        let mut event = Event::default();
+        let has_platform_field = |event: &Event| {
+            event.as_embed(&Default::default()).0["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .any(|f| f["name"] == "Platform" && f["value"] == "Xbox")
+        };
+        assert!(!has_platform_field(&event));
+        event.platform = Some(Platform::Xbox);
+        assert!(has_platform_field(&event));
+    }
+
+    #[test(tokio::test)]
+    async fn test_list_by_platform() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let events = [
+            (event_id(VOG, 1), 1, Some(Platform::Pc)),
+            (event_id(VOG, 2), 2, Some(Platform::Xbox)),
+            (event_id(GOS, 1), 3, Some(Platform::Crossplay)),
+            (event_id(GOS, 2), 4, None),
+        ];
+        for (id, hours, platform) in events {
+            manager
+                .add_test_event(Event {
+                    id,
+                    activity: id.activity,
+                    datetime: now + chrono::Duration::hours(hours),
+                    platform,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let ids = |events: Vec<Arc<Event>>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        assert_eq!(manager.list(None).await.len(), 4);
+        // Crossplay events and those without a preference are open to everyone.
+        assert_eq!(
+            ids(manager.list(Some(Platform::Pc)).await),
+            vec![event_id(VOG, 1), event_id(GOS, 1), event_id(GOS, 2)]
+        );
+        assert_eq!(
+            ids(manager.list(Some(Platform::PlayStation)).await),
+            vec![event_id(GOS, 1), event_id(GOS, 2)]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_events_for_user() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let roster = |confirmed: &[u64], alternates: &[u64], maybe: &[u64]| {
+            let members = |ids: &[u64]| ids.iter().map(|&id| test_member(id, None)).collect();
+            Roster::from_lists(members(confirmed), members(alternates), members(maybe))
+        };
+        let events = [
+            (event_id(VOG, 1), 3, 2, roster(&[2], &[], &[])),
+            (event_id(VOG, 2), 1, 1, roster(&[2, 3], &[], &[])),
+            (event_id(GOS, 1), 2, 2, roster(&[2], &[1], &[])),
+            (event_id(GOS, 2), 4, 2, roster(&[2], &[], &[1])),
+            (event_id(VOG, 3), 5, 3, roster(&[3], &[], &[])),
+        ];
+        for (id, hours, creator, roster) in events {
+            manager
+                .add_test_event(Event {
+                    id,
+                    activity: id.activity,
+                    datetime: now + chrono::Duration::hours(hours),
+                    creator: test_member(creator, None),
+                    roster,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let ids = |events: Vec<Arc<Event>>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        // Creators count even if they've left their own event.
+        assert_eq!(
+            ids(manager.events_for_user(UserId(1)).await),
+            vec![event_id(VOG, 2), event_id(GOS, 1), event_id(GOS, 2)]
+        );
+        assert_eq!(
+            ids(manager.events_for_user(UserId(3)).await),
+            vec![event_id(VOG, 2), event_id(VOG, 3)]
+        );
+        assert!(manager.events_for_user(UserId(4)).await.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_conflicts_for() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let at = |minutes| now + chrono::Duration::minutes(minutes);
+        let events = [
+            (event_id(VOG, 1), 0, JoinKind::Confirmed, false),
+            (event_id(VOG, 2), 90, JoinKind::Confirmed, false),
+            (event_id(GOS, 1), 240, JoinKind::Confirmed, false),
+            (event_id(GOS, 2), 30, JoinKind::Alternate, false),
+            (event_id(VOG, 3), 60, JoinKind::Confirmed, true),
+        ];
+        for (id, minutes, kind, ended) in events {
+            let members = vec![test_member(2, None)];
+            let roster = match kind {
+                JoinKind::Confirmed => Roster::from_lists(members, vec![], vec![]),
+                _ => Roster::from_lists(vec![], members, vec![]),
+            };
+            manager
+                .add_test_event(Event {
+                    id,
+                    activity: id.activity,
+                    datetime: at(minutes),
+                    roster,
+                    ended,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let ids = |events: Vec<Arc<Event>>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        let window = chrono::Duration::hours(2);
+        // Only confirmed events that haven't ended and start within the window, before or after.
+        assert_eq!(
+            ids(manager.conflicts_for(UserId(2), at(60), window).await),
+            vec![event_id(VOG, 1), event_id(VOG, 2)]
+        );
+        assert_eq!(
+            ids(manager.conflicts_for(UserId(2), at(300), window).await),
+            vec![event_id(GOS, 1)]
+        );
+        // Events exactly a window apart don't overlap.
+        assert_eq!(
+            ids(manager.conflicts_for(UserId(2), at(-120), window).await),
+            Vec::<EventId>::new()
+        );
+        assert!(manager
+            .conflicts_for(UserId(2), at(600), window)
+            .await
+            .is_empty());
+        assert!(manager
+            .conflicts_for(UserId(3), at(0), window)
+            .await
+            .is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_events_within() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let events = [
+            (event_id(VOG, 1), -1),
+            (event_id(VOG, 2), 0),
+            (event_id(GOS, 1), 12 * 60),
+            (event_id(GOS, 2), 12 * 60 + 1),
+            (event_id(VOG, 3), 3 * 60),
+        ];
+        for (id, minutes) in events {
+            manager
+                .add_test_event(Event {
+                    id,
+                    activity: id.activity,
+                    datetime: now + chrono::Duration::minutes(minutes),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let ids = |events: Vec<Arc<Event>>| events.iter().map(|e| e.id).collect::<Vec<_>>();
+        let now = now.with_timezone(&Utc);
+        let hours = |h| Duration::from_secs(h * 60 * 60);
+        // Both ends of the window are inclusive.
+        assert_eq!(
+            ids(manager.events_within_at(now, hours(12)).await),
+            vec![event_id(VOG, 2), event_id(VOG, 3), event_id(GOS, 1)]
+        );
+        assert_eq!(
+            ids(manager.events_within_at(now, hours(1)).await),
+            vec![event_id(VOG, 2)]
+        );
+        assert_eq!(ids(manager.events_within_at(now, hours(24)).await).len(), 4);
+    }
+
+    #[test]
+    fn test_group_layout() {
+        let layout = |size, count| GroupLayout::new(size, count).to_string();
+        assert_eq!(layout(6, 0), "No groups");
+        assert_eq!(layout(6, 2), "1 partial group of 2");
+        assert_eq!(layout(6, 6), "1 full group");
+        assert_eq!(layout(6, 24), "4 full groups");
+        assert_eq!(layout(6, 26), "4 full groups + 1 of 2");
+        assert_eq!(layout(4, 7), "1 full group + 1 of 3");
+        assert_eq!(layout(1, 3), "3 full groups");
+        assert_eq!(layout(12, 30), "2 full groups + 1 of 6");
+
+        assert_eq!(
+            GroupLayout::new(3, 10),
+            GroupLayout {
+                group_size: 3,
+                full_groups: 3,
+                remainder: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_group_layout_matches_confirmed_groups() {
+        for (size, count) in [(6, 13), (4, 8), (3, 2)] {
+            let event = Event {
+                group_size: size,
+                roster: Roster::from_lists(
+                    (0..count).map(|i| test_member(i, None)).collect(),
+                    vec![],
+                    vec![],
+                ),
+                ..Default::default()
+            };
+            let groups = event.confirmed_groups();
+            let layout = GroupLayout::new(size, count as usize);
+            let full = groups.iter().filter(|g| g.len() == size as usize).count();
+            let partial = groups
+                .iter()
+                .find(|g| g.len() < size as usize)
+                .map_or(0, |g| g.len());
+            assert_eq!((layout.full_groups, layout.remainder), (full, partial));
+        }
+    }
+
+    #[test]
+    fn test_next_recurrence_carry_roster() {
+        let now = Utc::now();
+        let old = Event {
+            datetime: (now - chrono::Duration::hours(1)).with_timezone(&Tz::PST8PDT),
+            recur: RecurKind::Weekly,
+            roster: Roster::from_lists(
+                vec![test_member(1, Some(GroupRole::Dps)), test_member(2, None)],
+                vec![test_member(3, None)],
+                vec![test_member(4, None)],
+            ),
+            ..Default::default()
+        };
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        let next_id = event_id(old.activity, 2);
+
+        let new = old.next_recurrence(next_id, now);
+        assert_eq!(new.id, next_id);
+        assert_eq!(new.datetime(), old.datetime() + chrono::Duration::weeks(1));
+        assert!(new.confirmed().is_empty());
+        assert!(new.alternates().is_empty());
+        assert!(new.maybe().is_empty());
+
+        let old = Event {
+            carry_roster: CarryRoster::Confirmed,
+            ..old
+        };
+        let new = old.next_recurrence(next_id, now);
+        assert_eq!(new.carry_roster, CarryRoster::Confirmed);
+        assert_eq!(ids(new.confirmed()), vec![1, 2]);
+        assert_eq!(new.confirmed()[0].role, Some(GroupRole::Dps));
+        assert!(new.alternates().is_empty());
+        assert!(new.maybe().is_empty());
+
+        let old = Event {
+            carry_roster: CarryRoster::ConfirmedAndAlternates,
+            ..old
+        };
+        let new = old.next_recurrence(next_id, now);
+        assert_eq!(ids(new.confirmed()), vec![1, 2]);
+        assert_eq!(ids(new.alternates()), vec![3]);
+        assert!(new.maybe().is_empty());
+    }
+
+    #[test]
+    fn test_recurrence_catches_up_late_cleanup() {
+        let datetime = Tz::PST8PDT.ymd(2021, 9, 7).and_hms(19, 0, 0);
+        let at = |days: i64, hours: i64| {
+            (datetime + chrono::Duration::days(days) + chrono::Duration::hours(hours))
+                .with_timezone(&Utc)
+        };
+        let next = |recur: RecurKind, now| recur.next_after(datetime, 7, now).unwrap();
+
+        assert_eq!(RecurKind::None.next_after(datetime, 7, at(0, 1)), None);
+
+        // Cleaned up on time, and then 3 weeks late.
+        assert_eq!(
+            next(RecurKind::Weekly, at(0, 1)),
+            datetime + chrono::Duration::weeks(1)
+        );
+        assert_eq!(
+            next(RecurKind::Weekly, at(21, 1)),
+            datetime + chrono::Duration::weeks(4)
+        );
+
+        assert_eq!(
+            next(RecurKind::Biweekly, at(0, 1)),
+            datetime + chrono::Duration::weeks(2)
+        );
+        assert_eq!(
+            next(RecurKind::Biweekly, at(14, 1)),
+            datetime + chrono::Duration::weeks(4)
+        );
+        assert_eq!(
+            next(RecurKind::Biweekly, at(21, 1)),
+            datetime + chrono::Duration::weeks(4)
+        );
+        assert_eq!(
+            next(RecurKind::Biweekly, at(29, 1)),
+            datetime + chrono::Duration::weeks(6)
+        );
+
+        // Monthly recurrences keep the local time across the DST change in November.
+        let monthly = |months: u32, day| Tz::PST8PDT.ymd(2021, 9 + months, day).and_hms(19, 0, 0);
+        assert_eq!(next(RecurKind::Monthly, at(0, 1)), monthly(1, 7));
+        assert_eq!(next(RecurKind::Monthly, at(40, 1)), monthly(2, 7));
+        assert_eq!(next(RecurKind::Monthly, at(61, -1)), monthly(2, 7));
+        assert_eq!(next(RecurKind::Monthly, at(61, 3)), monthly(3, 7));
+    }
+
+    #[test]
+    fn test_monthly_recurrence_keeps_day_of_month() {
+        let datetime = Tz::PST8PDT.ymd(2022, 1, 31).and_hms(18, 30, 0);
+        let event = Event {
+            datetime,
+            recur: RecurKind::Monthly,
+            ..Default::default()
+        };
+        let now = datetime.with_timezone(&Utc) + chrono::Duration::hours(3);
+
+        // February is too short, but March goes back to the 31st.
+        let feb = event.next_recurrence(event_id(event.activity, 2), now);
+        assert_eq!(
+            feb.datetime(),
+            Tz::PST8PDT.ymd(2022, 2, 28).and_hms(18, 30, 0)
+        );
+        assert_eq!(feb.recur, RecurKind::Monthly);
+        let now = feb.datetime().with_timezone(&Utc) + chrono::Duration::hours(3);
+        let mar = feb.next_recurrence(event_id(event.activity, 3), now);
+        assert_eq!(
+            mar.datetime(),
+            Tz::PST8PDT.ymd(2022, 3, 31).and_hms(18, 30, 0)
+        );
+        let now = mar.datetime().with_timezone(&Utc) + chrono::Duration::hours(3);
+        let apr = mar.next_recurrence(event_id(event.activity, 4), now);
+        assert_eq!(
+            apr.datetime(),
+            Tz::PST8PDT.ymd(2022, 4, 30).and_hms(18, 30, 0)
+        );
+
+        // Moving the event picks a new day.
+        let mut moved = feb.clone();
+        moved.set_datetime(Tz::PST8PDT.ymd(2022, 3, 15).and_hms(18, 30, 0));
+        let now = moved.datetime().with_timezone(&Utc) + chrono::Duration::hours(3);
+        let next = moved.next_recurrence(event_id(event.activity, 5), now);
+        assert_eq!(
+            next.datetime(),
+            Tz::PST8PDT.ymd(2022, 4, 15).and_hms(18, 30, 0)
+        );
+
+        // A time skipped by the DST change lands just after it.
+        let datetime = Tz::PST8PDT.ymd(2021, 2, 14).and_hms(2, 30, 0);
+        let now = datetime.with_timezone(&Utc) + chrono::Duration::hours(3);
+        assert_eq!(
+            RecurKind::Monthly.next_after(datetime, 14, now),
+            Some(Tz::PST8PDT.ymd(2021, 3, 14).and_hms(3, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_upcoming_recurrences() {
+        // Weekly and biweekly recurrences keep the local time across the DST change in November.
+        let datetime = Tz::PST8PDT.ymd(2021, 10, 26).and_hms(19, 0, 0);
+        let mut event = Event {
+            datetime,
+            recur: RecurKind::Weekly,
+            ..Default::default()
+        };
+        let local = |month, day| Tz::PST8PDT.ymd(2021, month, day).and_hms(19, 0, 0);
+        assert_eq!(
+            event.upcoming_recurrences(3),
+            vec![local(11, 2), local(11, 9), local(11, 16)]
+        );
+        event.recur = RecurKind::Biweekly;
+        assert_eq!(
+            event.upcoming_recurrences(2),
+            vec![local(11, 9), local(11, 23)]
+        );
+
+        // Monthly recurrences go back to the original day when a short month moved them earlier.
+        event.datetime = Tz::PST8PDT.ymd(2022, 2, 28).and_hms(19, 0, 0);
+        event.recur_day = Some(31);
+        event.recur = RecurKind::Monthly;
+        let local = |month, day| Tz::PST8PDT.ymd(2022, month, day).and_hms(19, 0, 0);
+        assert_eq!(
+            event.upcoming_recurrences(3),
+            vec![local(3, 31), local(4, 30), local(5, 31)]
+        );
+
+        event.recur = RecurKind::None;
+        assert!(event.upcoming_recurrences(3).is_empty());
+    }
+
+    #[test]
+    fn test_weekly_recurrence_keeps_local_time() {
+        let datetime = Tz::PST8PDT.ymd(2021, 10, 26).and_hms(19, 0, 0);
+        let now = Tz::PST8PDT
+            .ymd(2021, 11, 10)
+            .and_hms(12, 0, 0)
+            .with_timezone(&Utc);
+        assert_eq!(
+            RecurKind::Weekly.next_after(datetime, 26, now),
+            Some(Tz::PST8PDT.ymd(2021, 11, 16).and_hms(19, 0, 0))
+        );
+
+        // A time skipped by the DST change lands just after it.
+        let datetime = Tz::PST8PDT.ymd(2021, 3, 7).and_hms(2, 30, 0);
+        assert_eq!(
+            RecurKind::Weekly.occurrences(datetime, 7).next(),
+            Some(Tz::PST8PDT.ymd(2021, 3, 14).and_hms(3, 30, 0))
+        );
+    }
+
+    #[test]
+    fn test_recur_bool_deserializes() {
+        let event = Event {
+            recur: RecurKind::Biweekly,
+            ..Default::default()
+        };
+        let json = serde_json::to_value(&event).unwrap();
+        let loaded: Event = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(loaded.recur, RecurKind::Biweekly);
+
+        // Events stored when recurrence could only be weekly have a bool.
+        for (recur, kind) in [(true, RecurKind::Weekly), (false, RecurKind::None)] {
+            let mut json = json.clone();
+            json["recur"] = serde_json::Value::Bool(recur);
+            let loaded: Event = serde_json::from_value(json).unwrap();
+            assert_eq!(loaded.recur, kind);
+        }
+    }
+
+    #[test(tokio::test)]
+    async fn test_no_op_edit() {
+        let mut event = Arc::new(Event::default());
+        let original = event.clone();
+        assert!(replace_edited(&mut event, (*original).clone()).is_none());
+        assert!(Arc::ptr_eq(&event, &original));
+
+        let mut edited = (*original).clone();
+        edited.recur = RecurKind::Weekly;
+        match replace_edited(&mut event, edited) {
+            Some(EventChange::Edited(changed)) => assert_eq!(changed.recur, RecurKind::Weekly),
+            change => panic!("Unexpected change {:?}", change),
+        }
+        assert_eq!(event.recur, RecurKind::Weekly);
+
+        // Members compare equal by ID alone, but changes to their details are still edits.
+        let mut event = Arc::new(Event {
+            roster: Roster::from_lists(vec![test_member(2, None)], vec![], vec![]),
+            ..Default::default()
+        });
+        let mut edited = (*event).clone();
+        edited.set_note(UserId(2), Some("late".to_owned())).unwrap();
+        assert!(replace_edited(&mut event, edited).is_some());
+        assert_eq!(event.confirmed()[0].note.as_deref(), Some("late"));
+        let mut edited = (*event).clone();
+        edited.roster.get_mut(UserId(2)).unwrap().role = Some(GroupRole::Support);
+        assert!(replace_edited(&mut event, edited).is_some());
+        assert_eq!(event.confirmed()[0].role, Some(GroupRole::Support));
+        let mut edited = (*event).clone();
+        edited.creator.name = "renamed".to_owned();
+        assert!(replace_edited(&mut event, edited).is_some());
+        assert_eq!(event.creator.name, "renamed");
+
+        // Toggling a setting back and forth within one edit leaves the stored event untouched.
+        let manager = EventManager::default().await;
+        manager.add_test_event(Event::default()).await.unwrap();
+        let id = Event::default().id;
+        let before = manager.get_event(&id).await.unwrap();
+        manager
+            .edit_event(&id, |event| {
+                let event = event.unwrap();
+                event.locked = true;
+                event.locked = false;
+            })
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&before, &manager.get_event(&id).await.unwrap()));
+    }
+
+    #[test(tokio::test)]
+    async fn test_edit_member_note_and_role() {
+        let tempdir = tempdir::TempDir::new("edit_member_note").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let manager = EventManager::with_store(store_builder, Default::default()).await;
+        let event = Event {
+            roster: Roster::from_lists(vec![test_member(2, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        let id = event.id;
+        manager.add_test_event(event).await.unwrap();
+        let events_path = tempdir.path().join(EVENTS_STORE_NAME);
+        std::fs::remove_file(&events_path).unwrap();
+
+        // Like re-joining with a note and role, which changes only the member's details.
+        let before = manager.get_event(&id).await.unwrap();
+        manager
+            .edit_event(&id, |event| {
+                let event = event.unwrap();
+                event.set_note(UserId(2), Some("late".to_owned())).unwrap();
+                event.set_role(UserId(2), Some(GroupRole::Support)).unwrap();
+            })
+            .await
+            .unwrap();
+
+        // The edit was emitted as a change, so the edited event replaced the old one and was stored.
+        let after = manager.get_event(&id).await.unwrap();
+        assert!(!Arc::ptr_eq(&before, &after));
+        assert_eq!(after.confirmed()[0].note.as_deref(), Some("late"));
+        assert_eq!(after.confirmed()[0].role, Some(GroupRole::Support));
+        assert!(events_path.exists());
+    }
+
+    #[test(tokio::test)]
+    async fn test_bump_cooldown() {
+        let manager = EventManager::default().await;
+        let id = event_id(VOG, 1);
+        assert_eq!(manager.bump_event(id).await, Err(BumpError::NotPosted));
+
+        let cooldown = chrono::Duration::minutes(BUMP_COOLDOWN_MINUTES);
+        let bumped_at = Utc::now() - chrono::Duration::minutes(5);
+        manager.state.write().await.bumped_at.insert(id, bumped_at);
+        assert_eq!(
+            manager.bump_event(id).await,
+            Err(BumpError::CoolingDown(bumped_at + cooldown))
+        );
+        // The cooldown is per event.
+        assert_eq!(
+            manager.bump_event(event_id(VOG, 2)).await,
+            Err(BumpError::NotPosted)
+        );
+
+        // Once it's over, the event can be bumped again.
+        let bumped_at = Utc::now() - cooldown - chrono::Duration::minutes(1);
+        manager.state.write().await.bumped_at.insert(id, bumped_at);
+        assert_eq!(manager.bump_event(id).await, Err(BumpError::NotPosted));
+        assert!(manager.state.read().await.bumped_at.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_extend_cleanup() {
+        let tempdir = tempdir::TempDir::new("extend_cleanup").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let manager = EventManager::with_store(store_builder, Default::default()).await;
+        let event = Event::default();
+        let (id, datetime) = (event.id, event.datetime());
+        manager.add_test_event(event).await.unwrap();
+        let events_path = tempdir.path().join(EVENTS_STORE_NAME);
+        std::fs::remove_file(&events_path).unwrap();
+
+        // Extensions add up, and are stored with the event so they survive a restart.
+        let usual_delay = chrono::Duration::minutes(30);
+        assert!(manager
+            .extend_cleanup(id, chrono::Duration::minutes(20))
+            .await
+            .unwrap());
+        assert!(events_path.exists());
+        assert!(manager
+            .extend_cleanup(id, chrono::Duration::minutes(10))
+            .await
+            .unwrap());
+        let event = manager.get_event(&id).await.unwrap();
+        assert_eq!(
+            event.cleanup_time(usual_delay),
+            datetime + chrono::Duration::minutes(60)
+        );
+
+        // Rescheduling the event goes back to the usual cleanup time.
+        let rescheduled = datetime + chrono::Duration::days(1);
+        manager
+            .edit_event(&id, |event| event.unwrap().set_datetime(rescheduled))
+            .await
+            .unwrap();
+        let event = manager.get_event(&id).await.unwrap();
+        assert_eq!(event.cleanup_time(usual_delay), rescheduled + usual_delay);
+
+        assert!(!manager
+            .extend_cleanup(event_id(Activity::Custom, 2), chrono::Duration::minutes(10))
+            .await
+            .unwrap());
+    }
+
+    #[test]
+    fn test_creator_notification() {
+        let mut event = Event::default();
+        let mut user = User::default();
+        user.id = UserId(2);
+        let mut creator = User::default();
+        creator.id = event.creator.id;
+
+        // Off by default.
+        assert_eq!(
+            event.creator_notification(&user, Some(JoinKind::Confirmed)),
+            None
+        );
+
+        event.notify_creator = true;
+        let (to, content) = event
+            .creator_notification(&user, Some(JoinKind::Alternate))
+            .unwrap();
+        assert_eq!(to, event.creator.id);
+        assert!(
+            content.contains("joined as **a confirmed alt**"),
+            "{}",
+            content
+        );
+        let (to, content) = event.creator_notification(&user, None).unwrap();
+        assert_eq!(to, event.creator.id);
+        assert!(content.contains("left"), "{}", content);
+
+        // The creator doesn't need to hear about their own changes.
+        assert_eq!(
+            event.creator_notification(&creator, Some(JoinKind::Confirmed)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_event_buttons_disabled() {
+        let mut event = Event {
+            max_players: Some(2),
+            ..Default::default()
+        };
+        let id = event.id;
+        let states = |event: &Event| button_states(&event.event_buttons(&ButtonLabels::default()));
+        let expected = |join, alt, maybe| {
+            vec![
+                (
+                    CustomId::event(EventAction::Join, id, None).to_string(),
+                    join,
+                ),
+                (
+                    CustomId::event(EventAction::Leave, id, None).to_string(),
+                    false,
+                ),
+                (CustomId::event(EventAction::Alt, id, None).to_string(), alt),
+                (
+                    CustomId::event(EventAction::Maybe, id, None).to_string(),
+                    maybe,
+                ),
+            ]
+        };
+        assert!(!event.is_full());
+        assert_eq!(states(&event), expected(false, false, false));
+
+        event.roster.push(test_member(2, None), JoinKind::Confirmed);
+        assert!(event.is_full());
+        assert_eq!(states(&event), expected(true, false, false));
+
+        event.max_players = None;
+        event.locked = true;
+        assert_eq!(states(&event), expected(true, true, true));
+        assert_eq!(
+            button_states(&event.dm_event_buttons(&ButtonLabels::default(), GuildId(5)))[1],
+            (
+                CustomId::event(EventAction::Leave, id, Some(GuildId(5))).to_string(),
+                false
+            )
+        );
+    }
+
+    #[test]
+    fn test_locked_event_rejects_joins() {
+        let mut event = Event {
+            locked: true,
+            ..Default::default()
+        };
+        let user = User::default();
+        assert!(event.join(&user, JoinKind::Confirmed).is_err());
+        assert!(event.join(&user, JoinKind::Maybe).is_err());
+
+        event.locked = false;
+        assert!(event.join(&user, JoinKind::Confirmed).is_ok());
+    }
+
+    #[test]
+    fn test_join_errors() {
+        let mut user = User::default();
+        user.id = UserId(2);
+
+        let mut event = Event {
+            max_players: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(event.join(&user, JoinKind::Confirmed), Err(JoinError::Full));
+        event.join(&user, JoinKind::Alternate).unwrap();
+        assert_eq!(
+            event.join(&user, JoinKind::Alternate),
+            Err(JoinError::AlreadyJoined)
+        );
+        // Switching how they're joined isn't joining twice.
+        event.join(&user, JoinKind::Maybe).unwrap();
+        assert_eq!(
+            event.join_or_alternate(&user, JoinKind::Confirmed),
+            Ok(JoinKind::Alternate)
+        );
+        assert_eq!(
+            event.join_or_alternate(&user, JoinKind::Confirmed),
+            Err(JoinError::AlreadyJoined)
+        );
+
+        event.locked = true;
+        assert_eq!(event.join(&user, JoinKind::Maybe), Err(JoinError::Locked));
+        event.locked = false;
+        event.ended = true;
+        assert_eq!(event.join(&user, JoinKind::Maybe), Err(JoinError::Ended));
+    }
+
+    #[test]
+    fn test_full_event_rejects_confirmed_joins() {
+        let user = |id| {
+            let mut user = User::default();
+            user.id = UserId(id);
+            user
+        };
+        let is_full_err = |result: Result<(), JoinError>| result == Err(JoinError::Full);
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+
+        // The creator is already confirmed, so this leaves a single spot. Alternates have to take
+        // an open spot themselves here.
+        let mut event = Event {
+            max_players: Some(2),
+            auto_promote: false,
+            ..Default::default()
+        };
+        event.join(&user(2), JoinKind::Confirmed).unwrap();
+        assert!(is_full_err(event.join(&user(3), JoinKind::Confirmed)));
+
+        // Alternates and maybes can still join once the event is at its cap.
+        event.join(&user(3), JoinKind::Alternate).unwrap();
+        event.join(&user(4), JoinKind::Maybe).unwrap();
+        assert!(is_full_err(event.join(&user(3), JoinKind::Confirmed)));
+        assert_eq!(event.kind_of(UserId(3)), Some(JoinKind::Alternate));
+
+        event.leave(&user(2)).unwrap();
+        event.join(&user(3), JoinKind::Confirmed).unwrap();
+        assert_eq!(ids(event.confirmed()), vec![1, 3]);
+
+        // A cap of zero never has room for anyone to confirm.
+        let mut event = Event {
+            max_players: Some(0),
+            ..Default::default()
+        };
+        event.leave(&user(1)).unwrap();
+        assert!(is_full_err(event.join(&user(1), JoinKind::Confirmed)));
+        event.join(&user(1), JoinKind::Alternate).unwrap();
+
+        // Events stored before there was a cap have none.
+        let mut json = serde_json::to_value(&event).unwrap();
+        json.as_object_mut().unwrap().remove("max_players");
+        let loaded: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded.max_players, None);
+    }
+
+    #[test]
+    fn test_join_or_alternate_when_full() {
+        let user = |id| {
+            let mut user = User::default();
+            user.id = UserId(id);
+            user
+        };
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+
+        let mut event = Event {
+            max_players: Some(2),
+            auto_promote: false,
+            ..Default::default()
+        };
+        let join = |event: &mut Event, id, kind| event.join_or_alternate(&user(id), kind).unwrap();
+        assert_eq!(
+            join(&mut event, 2, JoinKind::Confirmed),
+            JoinKind::Confirmed
+        );
+        assert_eq!(
+            join(&mut event, 3, JoinKind::Confirmed),
+            JoinKind::Alternate
+        );
+        assert_eq!(join(&mut event, 4, JoinKind::Maybe), JoinKind::Maybe);
+        assert_eq!(ids(event.confirmed()), vec![1, 2]);
+        assert_eq!(ids(event.alternates()), vec![3]);
+
+        // Someone who's already an alternate is told they're already in the event.
+        assert!(event
+            .join_or_alternate(&user(3), JoinKind::Confirmed)
+            .is_err());
+
+        // Once there's room again, confirmed joins go through as asked.
+        event.leave(&user(2)).unwrap();
+        assert_eq!(
+            join(&mut event, 3, JoinKind::Confirmed),
+            JoinKind::Confirmed
+        );
+        assert_eq!(ids(event.confirmed()), vec![1, 3]);
+        assert!(event.alternates().is_empty());
+    }
+
+    #[test]
+    fn test_leave_promotes_first_alternate() {
+        let user = |id| {
+            let mut user = User::default();
+            user.id = UserId(id);
+            user
+        };
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        let group_ids = |event: &Event| {
+            event
+                .confirmed_groups()
+                .iter()
+                .map(|g| g.iter().map(|(m, alt)| (m.id.0, *alt)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        let members = |ids: &[u64]| ids.iter().map(|id| test_member(*id, None)).collect();
+
+        // Two groups of three, with the second group filled out by alternates.
+        let mut event = Event {
+            group_size: 3,
+            roster: Roster::from_lists(members(&[1, 2, 3, 4]), members(&[5, 6, 7]), vec![]),
+            ..Default::default()
+        };
+        assert_eq!(
+            group_ids(&event),
+            vec![
+                vec![(1, false), (2, false), (3, false)],
+                vec![(4, false), (5, true), (6, true)],
+            ]
+        );
+        assert_eq!(ids(event.extra_alts()), vec![7]);
+
+        // A spot in the first group opens up, so the first alternate is confirmed and everyone
+        // after shifts up a group.
+        let promoted = event.leave(&user(2)).unwrap();
+        assert_eq!(promoted.map(|m| m.id), Some(UserId(5)));
+        assert_eq!(ids(event.confirmed()), vec![1, 3, 4, 5]);
+        assert_eq!(ids(event.alternates()), vec![6, 7]);
+        assert_eq!(
+            group_ids(&event),
+            vec![
+                vec![(1, false), (3, false), (4, false)],
+                vec![(5, false), (6, true), (7, true)],
+            ]
+        );
+        assert!(event.extra_alts().is_empty());
+
+        // Alternates and maybes leaving doesn't open a confirmed spot.
+        assert_eq!(event.leave(&user(7)).unwrap(), None);
+        assert_eq!(ids(event.confirmed()), vec![1, 3, 4, 5]);
+
+        // Without alternates, the spot stays open.
+        assert_eq!(event.leave(&user(6)).unwrap(), None);
+        assert_eq!(event.leave(&user(4)).unwrap(), None);
+        assert_eq!(ids(event.confirmed()), vec![1, 3, 5]);
+        assert_eq!(group_ids(&event).len(), 1);
+
+        // Switching to alternate isn't leaving, so it doesn't promote anyone else.
+        event.join(&user(8), JoinKind::Alternate).unwrap();
+        event.join(&user(3), JoinKind::Alternate).unwrap();
+        assert_eq!(ids(event.confirmed()), vec![1, 5]);
+
+        // Events can opt out of promotion.
+        event.auto_promote = false;
+        assert_eq!(event.leave(&user(5)).unwrap(), None);
+        assert_eq!(ids(event.alternates()), vec![8, 3]);
+    }
+
+    #[test]
+    fn test_promotion_respects_max_players() {
+        let user = |id| {
+            let mut user = User::default();
+            user.id = UserId(id);
+            user
+        };
+        let members = |ids: &[u64]| ids.iter().map(|id| test_member(*id, None)).collect();
+        let mut event = Event {
+            roster: Roster::from_lists(members(&[1, 2, 3]), members(&[4]), vec![]),
+            ..Default::default()
+        };
+
+        // Lowering the cap below the confirmed count leaves no room even after someone leaves.
+        event.max_players = Some(1);
+        assert_eq!(event.leave(&user(2)).unwrap(), None);
+        event.max_players = Some(2);
+        assert_eq!(
+            event.leave(&user(3)).unwrap().map(|m| m.id),
+            Some(UserId(4))
+        );
+
+        // Events stored before auto-promotion existed get it.
+        let mut json = serde_json::to_value(&event).unwrap();
+        json.as_object_mut().unwrap().remove("auto_promote");
+        let loaded: Event = serde_json::from_value(json).unwrap();
+        assert!(loaded.auto_promote);
+    }
+
+    #[test(tokio::test)]
+    async fn test_recurrence_announcement() {
+        let manager = EventManager::default().await;
+        let event = Event {
+            id: event_id(VOG, 7),
+            activity: VOG,
+            recur: RecurKind::Weekly,
+            ..Default::default()
+        };
+        let new = event.next_recurrence(event_id(VOG, 8), Utc::now());
+
+        let mut state = manager.state.write().await;
+        assert_eq!(state.recurrence_announcement(&new), None);
+
+        state.recurrence_announcement_channel = Some(ChannelId(5));
+        let (channel, content) = state
+            .recurrence_announcement(&new)
+            .expect("Announcements enabled");
+        assert_eq!(channel, ChannelId(5));
+        assert!(content.starts_with("Next week's Vault of Glass is up: **vog8**"));
+        assert!(content.contains(&new.timestamp()));
+
+        // The wording follows how often the event recurs.
+        let mut monthly = new.clone();
+        monthly.recur = RecurKind::Monthly;
+        let (_, content) = state.recurrence_announcement(&monthly).unwrap();
+        assert!(content.starts_with("Next month's Vault of Glass is up"));
+        let mut biweekly = new.clone();
+        biweekly.recur = RecurKind::Biweekly;
+        let (_, content) = state.recurrence_announcement(&biweekly).unwrap();
+        assert!(content.starts_with("The next Vault of Glass is up"));
+    }
+
+    #[test]
+    fn test_check_in() {
+        let mut event = Event {
+            group_size: 2,
+            roster: Roster::from_lists(
+                vec![test_member(1, None), test_member(2, None)],
+                vec![test_member(3, None)],
+                vec![test_member(4, None)],
+            ),
+            ..Default::default()
+        };
+        assert!(event.check_in(UserId(1)).is_err());
+
+        let awaiting = event.open_check_in();
+        assert_eq!(awaiting, vec![test_member(1, None), test_member(2, None)]);
+        assert!(event.check_in_open());
+        assert!(event.check_in(UserId(1)).is_ok());
+        assert!(event.check_in(UserId(1)).is_err());
+        // Only members of the event's groups can check in.
+        assert!(event.check_in(UserId(3)).is_err());
+        assert!(event.check_in(UserId(4)).is_err());
+        assert_eq!(event.awaiting_check_in(), vec![&test_member(2, None)]);
+
+        // Leaving the event clears a member's check-in.
+        let mut user = User::default();
+        user.id = UserId(1);
+        event.leave(&user).unwrap();
+        assert!(!event.checked_in.contains(&UserId(1)));
+
+        // Rescheduling the event closes check-in and starts over.
+        event.check_in(UserId(2)).unwrap();
+        event.set_datetime(event.datetime() + chrono::Duration::hours(1));
+        assert!(!event.check_in_open());
+        assert!(event.checked_in.is_empty());
+    }
+
+    #[test]
+    fn test_check_in_embed() {
+        let mut event = Event {
+            group_size: 2,
+            roster: Roster::from_lists(
+                vec![test_member(1, None), test_member(2, None)],
+                vec![],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        let field = |event: &Event, name: &str| {
+            event.as_embed(&Default::default()).0["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|f| f["name"] == name)
+                .map(|f| f["value"].as_str().unwrap().to_owned())
+        };
+        let check_in_button = CustomId::event(EventAction::CheckIn, event.id, None).to_string();
+        let has_button = |event: &Event| {
+            button_states(&event.event_buttons(&ButtonLabels::default()))
+                .iter()
+                .any(|(id, _)| *id == check_in_button)
+        };
+        assert_eq!(field(&event, "Check-In"), None);
+        assert!(!has_button(&event));
+
+        event.open_check_in();
+        assert!(has_button(&event));
+        assert_eq!(
+            field(&event, "Check-In").unwrap(),
+            "0/2 checked in\nWaiting on <@1>, <@2>"
+        );
+
+        event.check_in(UserId(2)).unwrap();
+        assert_eq!(
+            field(&event, "Check-In").unwrap(),
+            "1/2 checked in\nWaiting on <@1>"
+        );
+        assert_eq!(field(&event, "Group 1 (2/2)").unwrap(), "<@1>, <@2> ✅");
+
+        event.check_in(UserId(1)).unwrap();
+        assert_eq!(field(&event, "Check-In").unwrap(), "2/2 checked in");
+    }
+
+    #[test]
+    fn test_group_labels_embed() {
+        let mut event = Event {
+            group_size: 2,
+            roster: Roster::from_lists(
+                (1..6).map(|id| test_member(id, None)).collect(),
+                vec![],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        let group_fields = |event: &Event| {
+            event.as_embed(&Default::default()).0["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| f["name"].as_str().unwrap().to_owned())
+                .filter(|name| name.ends_with("/2)"))
+                .collect_vec()
+        };
+        assert_eq!(
+            group_fields(&event),
+            vec!["Group 1 (2/2)", "Group 2 (2/2)", "Group 3 (1/2)"]
+        );
+
+        // Groups without a label of their own fall back to numbers.
+        event
+            .set_group_labels(parse_group_labels(" Atheon   team,Relic team ,, "))
+            .unwrap();
+        assert_eq!(event.group_labels, vec!["Atheon team", "Relic team"]);
+        assert_eq!(
+            group_fields(&event),
+            vec!["Atheon team (2/2)", "Relic team (2/2)", "Group 3 (1/2)"]
+        );
+        event.trigger_alert_protocol();
+        assert!(event
+            .alert_message
+            .as_deref()
+            .unwrap()
+            .contains("Atheon team: <@1>, <@2>\nRelic team: <@3>, <@4>"));
+
+        event.set_group_labels(vec![]).unwrap();
+        assert_eq!(group_fields(&event)[0], "Group 1 (2/2)");
+    }
+
+    #[test]
+    fn test_group_labels_count() {
+        let mut event = Event {
+            group_size: 6,
+            max_players: Some(12),
+            ..Default::default()
+        };
+        assert_eq!(event.expected_groups(), Some(2));
+        let labels = |count| (1..=count).map(|i| format!("Team {}", i)).collect_vec();
+        assert_eq!(
+            event.set_group_labels(labels(3)),
+            Err(GroupLabelsError::TooMany { count: 3, max: 2 })
+        );
+        assert!(event.group_labels.is_empty());
+        event.set_group_labels(labels(2)).unwrap();
+
+        // A partial last group still counts.
+        event.max_players = Some(13);
+        assert_eq!(event.expected_groups(), Some(3));
+        event.set_group_labels(labels(3)).unwrap();
+
+        // Without a cap, there's only the overall limit.
+        event.max_players = None;
+        assert_eq!(event.expected_groups(), None);
+        event.set_group_labels(labels(MAX_GROUP_LABELS)).unwrap();
+        assert!(event
+            .set_group_labels(labels(MAX_GROUP_LABELS + 1))
+            .is_err());
+    }
+
+    #[test]
+    fn test_embed_truncates_long_lists() {
+        let event = Event {
+            group_size: 6,
+            roster: Roster::from_lists(
+                (1..7).map(|id| test_member(id, None)).collect(),
+                (7..10).map(|id| test_member(id, None)).collect(),
+                (10..30).map(|id| test_member(id, None)).collect(),
+            ),
+            ..Default::default()
+        };
+        let field = |max_listed, name: &str| {
+            let style = EmbedStyle {
+                max_listed,
+                ..Default::default()
+            };
+            event.as_embed(&style).0["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|f| f["name"] == name)
+                .map(|f| f["value"].as_str().unwrap().to_owned())
+                .unwrap()
+        };
+        assert_eq!(field(3, "Spare Alts"), "<@7>, <@8>, <@9>");
+        assert_eq!(field(2, "Spare Alts"), "<@7>, <@8>, +1 more");
+        assert_eq!(field(2, "Maybe"), "<@10>, <@11>, +18 more");
+        assert_eq!(field(0, "Maybe"), "+20 more");
+        assert_eq!(
+            field(DEFAULT_MAX_LISTED_MEMBERS, "Maybe"),
+            format!(
+                "{}, +5 more",
+                (10..25).map(|id| format!("<@{}>", id)).join(", ")
+            )
+        );
+
+        // The full lists are kept.
+        assert_eq!(event.maybe().len(), 20);
+    }
+
+    #[test]
+    fn test_group_size_edits_regroup_embed() {
+        let mut event = Event {
+            group_size: 6,
+            roster: Roster::from_lists(
+                (1..6).map(|id| test_member(id, None)).collect(),
+                (6..9).map(|id| test_member(id, None)).collect(),
+                vec![test_member(9, None)],
+            ),
+            ..Default::default()
+        };
+        let fields = |event: &Event| {
+            event.as_embed(&Default::default()).0["fields"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|f| {
+                    let name = f["name"].as_str().unwrap().to_owned();
+                    (name, f["value"].as_str().unwrap().to_owned())
+                })
+                .filter(|(name, _)| name.starts_with("Group ") || name == "Spare Alts")
+                .collect_vec()
+        };
+        let field = |name: &str, value: &str| (name.to_owned(), value.to_owned());
+
+        // Confirmed members come first, then alternates fill out groups as long as they join a
+        // group with someone confirmed or fill one of their own. The rest are spare.
+        let expected = [
+            (
+                1,
+                vec![
+                    field("Group 1 (1/1)", "<@1>"),
+                    field("Group 2 (1/1)", "<@2>"),
+                    field("Group 3 (1/1)", "<@3>"),
+                    field("Group 4 (1/1)", "<@4>"),
+                    field("Group 5 (1/1)", "<@5>"),
+                    field("Group 6 (1/1)", "*<@6> (alt)*"),
+                    field("Group 7 (1/1)", "*<@7> (alt)*"),
+                    field("Group 8 (1/1)", "*<@8> (alt)*"),
+                    field("Spare Alts", "None"),
+                ],
+            ),
+            (
+                2,
+                vec![
+                    field("Group 1 (2/2)", "<@1>, <@2>"),
+                    field("Group 2 (2/2)", "<@3>, <@4>"),
+                    field("Group 3 (2/2)", "<@5>, *<@6> (alt)*"),
+                    field("Group 4 (2/2)", "*<@7> (alt)*, *<@8> (alt)*"),
+                    field("Spare Alts", "None"),
+                ],
+            ),
+            (
+                3,
+                vec![
+                    field("Group 1 (3/3)", "<@1>, <@2>, <@3>"),
+                    field("Group 2 (3/3)", "<@4>, <@5>, *<@6> (alt)*"),
+                    field("Spare Alts", "<@7>, <@8>"),
+                ],
+            ),
+            (
+                4,
+                vec![
+                    field("Group 1 (4/4)", "<@1>, <@2>, <@3>, <@4>"),
+                    field(
+                        "Group 2 (4/4)",
+                        "<@5>, *<@6> (alt)*, *<@7> (alt)*, *<@8> (alt)*",
+                    ),
+                    field("Spare Alts", "None"),
+                ],
+            ),
+            (
+                6,
+                vec![
+                    field(
+                        "Group 1 (6/6)",
+                        "<@1>, <@2>, <@3>, <@4>, <@5>, *<@6> (alt)*",
+                    ),
+                    field("Spare Alts", "<@7>, <@8>"),
+                ],
+            ),
+            (
+                12,
+                vec![
+                    field(
+                        "Group 1 (8/12)",
+                        "<@1>, <@2>, <@3>, <@4>, <@5>, *<@6> (alt)*, *<@7> (alt)*, \
+                         *<@8> (alt)*",
+                    ),
+                    field("Spare Alts", "None"),
+                ],
+            ),
+        ];
+
+        // Editing the size back and forth regroups from scratch each time, so the order of the
+        // edits doesn't matter.
+        for size in [6, 3, 12, 1, 4, 2, 6, 1, 3] {
+            event.group_size = size;
+            let (_, expected) = expected.iter().find(|(s, _)| *s == size).unwrap();
+            assert_eq!(&fields(&event), expected, "group size {}", size);
+
+            // Every alternate is listed exactly once, either in a group or as a spare.
+            let grouped_alts = event
+                .confirmed_groups()
+                .into_iter()
+                .flatten()
+                .filter(|(_, alt)| *alt)
+                .map(|(member, _)| member.id.0)
+                .collect_vec();
+            let spare_alts = event.extra_alts().iter().map(|m| m.id.0).collect_vec();
+            assert_eq!(
+                grouped_alts.into_iter().chain(spare_alts).collect_vec(),
+                vec![6, 7, 8],
+                "group size {}",
+                size
+            );
+        }
+
+        // After shrinking the groups, a confirmed member leaving promotes the first alternate,
+        // which shows them as confirmed in their group and the next spare joining a group.
+        event.group_size = 3;
+        let mut leaver = User::default();
+        leaver.id = UserId(2);
+        assert_eq!(event.leave(&leaver).unwrap().map(|m| m.id), Some(UserId(6)));
+        assert_eq!(
+            fields(&event),
+            vec![
+                field("Group 1 (3/3)", "<@1>, <@3>, <@4>"),
+                field("Group 2 (3/3)", "<@5>, <@6>, *<@7> (alt)*"),
+                field("Spare Alts", "<@8>"),
+            ]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_start_event() {
+        let start_time = |event: &Event| {
+            event.as_embed(&Default::default()).0["fields"][1]["value"]
+                .as_str()
+                .unwrap()
+                .to_owned()
+        };
+        let mut manager = EventManager::default().await;
+        let event = Event::default();
+        manager.add_test_event(event.clone()).await.unwrap();
+        manager.start_event(event.id).await.unwrap();
+        let started = manager.get_event(&event.id).await.unwrap();
+        assert!(started.started());
+        assert!(!started.locked);
+        assert!(start_time(&started).ends_with("**In Progress**"));
+
+        // Moving the event makes it upcoming again.
+        let mut moved = (*started).clone();
+        moved.set_datetime(moved.datetime() + chrono::Duration::hours(1));
+        assert!(!moved.started());
+        assert!(!start_time(&moved).contains("In Progress"));
+
+        // Guilds can have started events locked.
+        manager.lock_started_events = true;
+        let other = Event {
+            id: event_id(VOG, 2),
+            ..Default::default()
+        };
+        manager.add_test_event(other.clone()).await.unwrap();
+        manager.start_event(other.id).await.unwrap();
+        let started = manager.get_event(&other.id).await.unwrap();
+        assert!(started.locked);
+
+        // That lock is lifted if the event is moved, but a lock set by the creator isn't.
+        let mut moved = (*started).clone();
+        moved.set_datetime(moved.datetime() + chrono::Duration::hours(1));
+        assert!(!moved.locked);
+        let mut locked = Event {
+            locked: true,
+            ..Default::default()
+        };
+        locked.start(true);
+        locked.set_datetime(locked.datetime() + chrono::Duration::hours(1));
+        assert!(locked.locked);
+    }
+
+    #[test(tokio::test)]
+    async fn test_restore_cleaned_up_event() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let original = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            datetime: now - chrono::Duration::hours(1),
+            description: "Flawless run".to_owned(),
+            roster: Roster::from_lists(
+                vec![test_member(1, Some(GroupRole::Dps)), test_member(2, None)],
+                vec![],
+                vec![test_member(3, None)],
+            ),
+            ..Default::default()
+        };
+        manager.add_test_event(original.clone()).await.unwrap();
+        manager.cleanup_event(original.id).await.unwrap();
+        assert!(manager.get_event(&original.id).await.is_none());
+
+        // The original time has passed, so a new one is needed.
+        assert_matches!(
+            manager.restore_event(original.id, None).await,
+            Err(RestoreError::NeedsDatetime(_))
+        );
+
+        let new_datetime = now + chrono::Duration::days(1);
+        let restored = manager
+            .restore_event(original.id, Some(new_datetime))
+            .await
+            .unwrap();
+        assert_eq!(restored.datetime(), new_datetime);
+        assert_eq!(restored.activity, VOG);
+        assert_eq!(restored.description, "Flawless run");
+        assert_eq!(restored.confirmed(), original.confirmed());
+        assert_eq!(restored.confirmed()[0].role, Some(GroupRole::Dps));
+        assert_eq!(restored.maybe(), original.maybe());
+        assert_eq!(
+            manager.get_event(&restored.id).await,
+            Some(restored.clone())
+        );
+
+        // Each record can only be restored once.
+        assert_matches!(
+            manager.restore_event(original.id, Some(new_datetime)).await,
+            Err(RestoreError::NotFound(_))
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_stale_recurrence_lapses() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let recent = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            datetime: now - chrono::Duration::weeks(3),
+            recur: RecurKind::Weekly,
+            ..Default::default()
+        };
+        let stale = Event {
+            id: event_id(GOS, 1),
+            activity: GOS,
+            datetime: now - chrono::Duration::weeks(DEFAULT_RECURRENCE_LAPSE_WEEKS as i64 + 1),
+            recur: RecurKind::Weekly,
+            ..Default::default()
+        };
+        assert!(!recent.lapsed(Utc::now(), recurrence_lapse(None)));
+        assert!(stale.lapsed(Utc::now(), recurrence_lapse(None)));
+        assert!(!stale.lapsed(Utc::now(), recurrence_lapse(Some(52))));
+
+        manager.add_test_event(recent.clone()).await.unwrap();
+        manager.add_test_event(stale.clone()).await.unwrap();
+        manager.cleanup_event(recent.id).await.unwrap();
+        manager.cleanup_event(stale.id).await.unwrap();
+
+        // Only the recently missed event is caught up, to its next future occurrence.
+        let events = manager.list(None).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].activity, VOG);
+        assert!(events[0].datetime() > now);
+    }
+
+    #[test(tokio::test)]
+    async fn test_maybe_follow_ups() {
+        let manager = EventManager::default().await;
+        let event = Event {
+            recur: RecurKind::Weekly,
+            roster: Roster::from_lists(
+                vec![],
+                vec![],
+                vec![test_member(2, None), test_member(3, None)],
+            ),
+            ..Default::default()
+        };
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+
+        let mut state = manager.state.write().await;
+        assert!(state.maybe_follow_ups(&event).is_empty());
+
+        state.follow_up_maybes = true;
+        assert_eq!(ids(state.maybe_follow_ups(&event)), vec![2, 3]);
+
+        // Only recurring events have a next recurrence to join.
+        let one_off = Event {
+            recur: RecurKind::None,
+            ..event.clone()
+        };
+        assert!(state.maybe_follow_ups(&one_off).is_empty());
+    }
+
+    #[test]
+    fn test_group_members() {
+        let support = Some(GroupRole::Support);
+        let event = Event {
+            group_size: 3,
+            roster: Roster::from_lists(
+                vec![
+                    test_member(1, None),
+                    test_member(2, None),
+                    test_member(3, None),
+                    test_member(4, None),
+                    test_member(5, support),
+                ],
+                vec![test_member(6, None), test_member(7, None)],
+                vec![],
+            ),
+            role_requirements: vec![(GroupRole::Support, 1)].into_iter().collect(),
+            ..Default::default()
+        };
+        let ids = |idx| {
+            event
+                .group_members(idx)
+                .map(|members| members.iter().map(|m| m.id.0).collect::<Vec<_>>())
+        };
+        // Groups follow the same ordering as the embed, including role requirements and alts.
+        assert_eq!(ids(0), Some(vec![1, 2, 5]));
+        assert_eq!(ids(1), Some(vec![3, 4, 6]));
+        assert_eq!(ids(2), None);
+    }
+
+    #[test]
+    fn test_roster_views() {
+        let mut event = Event {
+            roster: Roster::from_lists(
+                vec![test_member(1, None), test_member(2, None)],
+                vec![test_member(3, None)],
+                vec![test_member(4, None)],
+            ),
+            ..Default::default()
+        };
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        let mut user = User::default();
+        user.id = UserId(4);
+
+        // Changing kind moves the member between views, without duplicating them.
+        event.join(&user, JoinKind::Confirmed).unwrap();
+        assert_eq!(event.kind_of(UserId(4)), Some(JoinKind::Confirmed));
+        assert_eq!(ids(event.confirmed()), vec![1, 2, 4]);
+        assert!(event.maybe().is_empty());
+
+        event.join(&user, JoinKind::Alternate).unwrap();
+        assert_eq!(ids(event.confirmed()), vec![1, 2]);
+        assert_eq!(ids(event.alternates()), vec![3, 4]);
+        assert_eq!(event.roster.0.len(), 4);
+
+        event.leave(&user).unwrap();
+        assert_eq!(event.kind_of(UserId(4)), None);
+        assert_eq!(ids(event.alternates()), vec![3]);
+    }
+
+    #[test]
+    fn test_roster_serialization_compatible() {
+        let event = Event {
+            roster: Roster::from_lists(
+                vec![test_member(1, Some(GroupRole::Dps)), test_member(2, None)],
+                vec![test_member(3, None)],
+                vec![test_member(4, None)],
+            ),
+            ..Default::default()
+        };
+        let ids = |value: &serde_json::Value| {
+            value
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|m| serde_json::from_value::<UserId>(m["id"].clone()).unwrap().0)
+                .collect::<Vec<_>>()
+        };
+
+        // The roster is still stored as the separate lists, so existing stores keep loading.
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(ids(&json["confirmed"]), vec![1, 2]);
+        assert!(!json["confirmed"][0]["role"].is_null());
+        assert_eq!(ids(&json["alternates"]), vec![3]);
+        assert_eq!(ids(&json["maybe"]), vec![4]);
+        assert!(json.get("roster").is_none());
+
+        let loaded: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(loaded, event);
+        assert_eq!(loaded.roster, event.roster);
+        assert_eq!(loaded.kind_of(UserId(3)), Some(JoinKind::Alternate));
+    }
+
+    #[test]
+    fn test_join_order() {
+        let mut event = Event {
+            roster: Roster::from_lists(vec![test_member(1, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        let join = |event: &mut Event, id, kind| {
+            let mut user = User::default();
+            user.id = UserId(id);
+            event.join(&user, kind).unwrap();
+        };
+        join(&mut event, 2, JoinKind::Maybe);
+        join(&mut event, 3, JoinKind::Alternate);
+        join(&mut event, 4, JoinKind::Confirmed);
+
+        // Join times are recorded, except for members that joined before they were.
+        let order = event.join_order();
+        assert_eq!(
+            order.iter().map(|(m, _)| m.id.0).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
+        assert!(order[0].0.joined_at.is_none());
+        assert!(order[1..].iter().all(|(m, _)| m.joined_at.is_some()));
+        assert!(order
+            .windows(2)
+            .all(|w| w[0].0.joined_at <= w[1].0.joined_at));
+
+        // The stored lists are split up by kind, but the join order is restored when loading.
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["confirmed"].as_array().unwrap().len(), 2);
+        let loaded: Event = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            loaded
+                .join_order()
+                .iter()
+                .map(|(m, kind)| (m.id.0, *kind))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, JoinKind::Confirmed),
+                (2, JoinKind::Maybe),
+                (3, JoinKind::Alternate),
+                (4, JoinKind::Confirmed),
+            ]
+        );
+
+        // Members from older stores have no join time, so they sort first.
+        let mut json = serde_json::to_value(&event).unwrap();
+        json["confirmed"][1]
+            .as_object_mut()
+            .unwrap()
+            .remove("joined_at");
+        let loaded: Event = serde_json::from_value(json).unwrap();
+        let order = loaded.join_order();
+        assert_eq!(
+            order.iter().map(|(m, _)| m.id.0).collect::<Vec<_>>(),
+            vec![1, 4, 2, 3]
+        );
+        assert!(order[1].0.joined_at.is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn test_shutdown() {
+        let manager = EventManager::default().await;
+        add_events_to_manager(&manager, VOG, [1, 2]).await;
+        manager.shutdown().await.unwrap();
+
+        // Nothing can be changed afterwards, and the events are left as they were stored.
+        let user = User::default();
+        let t = Utc::now().with_timezone(&Tz::PST8PDT);
+        assert!(manager
+            .create_event(&user, VOG, t, "", Default::default())
+            .await
+            .is_err());
+        assert!(manager.delete_event(&event_id(VOG, 1)).await.is_err());
+        let state = manager.state.read().await;
+        let stored = state.events_store.load().await.unwrap();
+        assert_eq!(
+            stored.keys().copied().collect_vec(),
+            vec![event_id(VOG, 1), event_id(VOG, 2)]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_guild_removed_then_readded() {
+        let tempdir = tempdir::TempDir::new("EventManager").unwrap();
+        let builder = &PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let guild_dir = tempdir.path().join("1");
+        let stored_events = move || async move {
+            let store = builder.new_scoped("1").await.unwrap();
+            let events: EventsCollection = store
+                .build(EVENTS_STORE_NAME)
+                .await
+                .unwrap()
+                .load()
+                .await
+                .unwrap();
+            events.keys().copied().collect::<Vec<_>>()
+        };
+        let guard = &Arc::new(GuildDataGuard::default());
+        let new_manager = move || async move {
+            let store = builder.new_scoped("1").await.unwrap();
+            EventManager::with_store(store, guard.clone()).await
+        };
+
+        // Re-added while the old manager is still alive.
+        let manager = new_manager().await;
+        add_events_to_manager(&manager, VOG, [1]).await;
+        manager.removed_from_guild();
+        guard.cancel_removal();
+        drop(manager);
+        drop(guard.lock().await);
+        assert_eq!(stored_events().await, vec![event_id(VOG, 1)]);
+
+        // Re-added right after the old manager was dropped, before its data was deleted.
+        let manager = new_manager().await;
+        manager.removed_from_guild();
+        drop(manager);
+        guard.cancel_removal();
+        drop(guard.lock().await);
+        assert_eq!(stored_events().await, vec![event_id(VOG, 1)]);
+
+        // Not re-added, so the data is deleted.
+        let manager = new_manager().await;
+        manager.removed_from_guild();
+        drop(manager);
+        drop(guard.lock().await);
+        assert!(!guild_dir.exists());
+    }
+
+    #[test(tokio::test)]
+    async fn test_clone_event_to_new_time() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let mut original = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            datetime: now + chrono::Duration::hours(1),
+            description: "Flawless run".to_owned(),
+            recur: RecurKind::Weekly,
+            roster: Roster::from_lists(
+                vec![test_member(1, Some(GroupRole::Dps)), test_member(2, None)],
+                vec![test_member(3, None)],
+                vec![test_member(4, None)],
+            ),
+            created_at: Some(Utc::now() - chrono::Duration::days(7)),
+            ..Default::default()
+        };
+        original.open_check_in();
+        original.set_alert_muted(UserId(1), true).unwrap();
+        manager.add_test_event(original.clone()).await.unwrap();
+
+        let new_datetime = now + chrono::Duration::days(1);
+        let clone = manager
+            .clone_event(&original.id, new_datetime, false)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(clone.id, original.id);
+        assert_eq!(clone.activity, VOG);
+        assert_eq!(clone.datetime(), new_datetime);
+        assert_eq!(clone.description, "Flawless run");
+        assert_eq!(clone.confirmed(), original.confirmed());
+        assert_eq!(clone.confirmed()[0].role, Some(GroupRole::Dps));
+        assert_eq!(clone.alternates(), original.alternates());
+        assert_eq!(clone.maybe(), original.maybe());
+        assert!(!clone.check_in_open());
+        assert!(clone.created_at > original.created_at);
+        assert!(clone.alert_muted.is_empty());
+        // The original is kept, so only it recurs.
+        assert_eq!(clone.recur, RecurKind::None);
+        assert!(manager.get_event(&original.id).await.is_some());
+        assert_eq!(manager.get_event(&clone.id).await, Some(clone.clone()));
+
+        let moved = manager
+            .clone_event(&original.id, new_datetime, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_ne!(moved.id, original.id);
+        assert_ne!(moved.id, clone.id);
+        assert_eq!(moved.recur, RecurKind::Weekly);
+        assert_eq!(moved.confirmed(), original.confirmed());
+        assert_eq!(moved.created_at, original.created_at);
+        assert_eq!(moved.alert_muted, original.alert_muted);
+        assert!(manager.get_event(&original.id).await.is_none());
+
+        assert!(manager
+            .clone_event(&original.id, new_datetime, false)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test(tokio::test)]
+    async fn test_transfer_series() {
+        let manager = EventManager::default().await;
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let original = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            datetime: now - chrono::Duration::hours(1),
+            recur: RecurKind::Weekly,
+            ..Default::default()
+        };
+        let unrelated = Event {
+            id: event_id(VOG, 2),
+            activity: VOG,
+            ..Default::default()
+        };
+        manager.add_test_event(original.clone()).await.unwrap();
+        manager.add_test_event(unrelated.clone()).await.unwrap();
+
+        let new_creator = test_member(5, None);
+        let transferred = manager
+            .transfer_series(&original.id, new_creator.clone())
+            .await
+            .unwrap();
+        assert_eq!(transferred, Some(vec![original.id]));
+        assert_eq!(
+            manager.get_event(&unrelated.id).await.unwrap().creator,
+            unrelated.creator
+        );
+
+        // The next recurrence keeps both the series and its new creator.
+        manager.cleanup_event(original.id).await.unwrap();
+        let next = manager
+            .list(None)
+            .await
+            .into_iter()
+            .find(|e| e.id != unrelated.id)
+            .unwrap();
+        assert_ne!(next.id, original.id);
+        assert_eq!(next.series_id(), original.series_id());
+        assert_eq!(next.creator.id, new_creator.id);
+
+        // Transferring via any event in the series works.
+        let transferred = manager
+            .transfer_series(&next.id, test_member(6, None))
+            .await
+            .unwrap();
+        assert_eq!(transferred, Some(vec![next.id]));
+        assert_eq!(
+            manager
+                .transfer_series(&original.id, new_creator.clone())
+                .await
+                .unwrap(),
+            None
+        );
+
+        // A new event that reuses the original's ID isn't part of its series.
+        let reused = Event {
+            id: original.id,
+            activity: VOG,
+            ..Default::default()
+        };
+        manager.add_test_event(reused.clone()).await.unwrap();
+        assert_ne!(reused.series_id(), next.series_id());
+        let transferred = manager
+            .transfer_series(&reused.id, new_creator)
+            .await
+            .unwrap();
+        assert_eq!(transferred, Some(vec![reused.id]));
+        assert_eq!(
+            manager.get_event(&next.id).await.unwrap().creator.id,
+            UserId(6)
+        );
+    }
+
+    #[test]
+    fn test_move_members() {
+        let mut source = Event {
+            roster: Roster::from_lists(
+                vec![
+                    test_member(1, Some(GroupRole::Dps)),
+                    test_member(2, None),
+                    test_member(3, None),
+                ],
+                vec![test_member(4, None)],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        let mut dest = Event {
+            id: event_id(VOG, 2),
+            roster: Roster::from_lists(vec![test_member(5, None)], vec![], vec![]),
+            max_players: Some(3),
+            ..Default::default()
+        };
+        let users: Vec<_> = [1, 4, 2, 3, 9]
+            .into_iter()
+            .map(|id| {
+                let mut user = User::default();
+                user.id = UserId(id);
+                user
+            })
+            .collect();
+
+        let results: Vec<_> = users
+            .iter()
+            .map(|user| {
+                source
+                    .move_member_to(&mut dest, user)
+                    .map_err(|e| e.to_string())
+            })
+            .collect();
+        assert_eq!(
+            results,
+            vec![
+                Ok(()),
+                Ok(()),
+                // The destination is full after moving two members.
+                Err(format!("{} is full", dest.id)),
+                Err(format!("{} is full", dest.id)),
+                Err(format!("they aren't in {}", source.id)),
+            ]
+        );
+
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        assert_eq!(ids(source.confirmed()), vec![2, 3]);
+        assert!(source.alternates().is_empty());
+        assert_eq!(ids(dest.confirmed()), vec![5, 1, 4]);
+        assert_eq!(
+            dest.roster.get(UserId(1)).unwrap().0.role,
+            Some(GroupRole::Dps)
+        );
+
+        // Members can't be moved into a locked event, and stay where they were.
+        dest.max_players = None;
+        dest.locked = true;
+        assert_eq!(
+            source
+                .move_member_to(&mut dest, &users[2])
+                .unwrap_err()
+                .to_string(),
+            format!("{} is locked", dest.id)
+        );
+        assert_eq!(ids(source.confirmed()), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_starts_in() {
+        let at = |secs| starts_in(chrono::Duration::seconds(secs));
+        // Actions fire just after their scheduled time, so round to the nearest minute.
+        assert_eq!(at(599), "starts in 10 minutes");
+        assert_eq!(at(600), "starts in 10 minutes");
+        assert_eq!(at(60), "starts in 1 minute");
+        assert_eq!(at(3600), "starts in 1 hour");
+        assert_eq!(at(2 * 3600 + 5 * 60), "starts in 2 hours 5 minutes");
+        assert_eq!(at(20), "is starting now");
+        assert_eq!(at(-120), "is starting now");
+    }
+
+    #[test(tokio::test)]
+    async fn test_rename_member() {
+        let manager = EventManager::default().await;
+        let created = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            ..Default::default()
+        };
+        let joined = Event {
+            id: event_id(VOG, 2),
+            activity: VOG,
+            creator: test_member(2, None),
+            roster: Roster::from_lists(
+                vec![test_member(2, None)],
+                vec![],
+                vec![test_member(1, None)],
+            ),
+            ..Default::default()
+        };
+        let other = Event {
+            id: event_id(GOS, 1),
+            activity: GOS,
+            creator: test_member(2, None),
+            roster: Roster::from_lists(vec![test_member(2, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        for event in [&created, &joined, &other] {
+            manager.add_test_event(event.clone()).await.unwrap();
+        }
+        assert_eq!(
+            manager.member_ids().await,
+            BTreeSet::from([UserId(1), UserId(2)])
+        );
+
+        let mut user = User::default();
+        user.id = UserId(1);
+        user.name = "renamed".to_owned();
+        let renamed = manager.rename_member(&user).await.unwrap();
+        assert_eq!(renamed, vec![created.id, joined.id]);
+
+        let created = manager.get_event(&created.id).await.unwrap();
+        assert_eq!(created.creator.name, "renamed");
+        assert_eq!(created.confirmed()[0].name, "renamed");
+        let joined = manager.get_event(&joined.id).await.unwrap();
+        assert_eq!(joined.creator.name, "2");
+        assert_eq!(joined.maybe()[0].name, "renamed");
+
+        // Nothing changes when the name is already up to date.
+        assert!(manager.rename_member(&user).await.unwrap().is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_refresh_member_names() {
+        let tempdir = tempdir::TempDir::new("refresh_member_names").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let manager = EventManager::with_store(store_builder, Default::default()).await;
+        let joined = Event {
+            id: event_id(VOG, 1),
+            activity: VOG,
+            creator: test_member(2, None),
+            roster: Roster::from_lists(
+                vec![test_member(2, None), test_member(3, None)],
+                vec![],
+                vec![test_member(1, None)],
+            ),
+            ..Default::default()
+        };
+        let other = Event {
+            id: event_id(GOS, 1),
+            activity: GOS,
+            creator: test_member(4, None),
+            roster: Roster::from_lists(vec![test_member(4, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        for event in [&joined, &other] {
+            manager.add_test_event(event.clone()).await.unwrap();
+        }
+        let events_path = tempdir.path().join(EVENTS_STORE_NAME);
+        std::fs::remove_file(&events_path).unwrap();
+
+        // Nothing is stored when every name is already up to date.
+        let names = |names: &[(u64, &str)]| {
+            names
+                .iter()
+                .map(|(id, name)| (UserId(*id), name.to_string()))
+                .collect::<HashMap<_, _>>()
+        };
+        let current = names(&[(1, "1"), (2, "2"), (4, "4"), (5, "5")]);
+        assert!(manager
+            .refresh_member_names(&current)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!events_path.exists());
+
+        // Users that aren't in the map, like 3 here, keep their names.
+        let renamed = names(&[(1, "one"), (2, "two"), (4, "4")]);
+        assert_eq!(
+            manager.refresh_member_names(&renamed).await.unwrap(),
+            vec![joined.id]
+        );
+        assert!(events_path.exists());
+        let joined = manager.get_event(&joined.id).await.unwrap();
+        assert_eq!(joined.creator.name, "two");
+        assert_eq!(
+            joined.confirmed().iter().map(|m| &m.name).collect_vec(),
+            vec!["two", "3"]
+        );
+        assert_eq!(joined.maybe()[0].name, "one");
+        assert_eq!(manager.get_event(&other.id).await.unwrap(), Arc::new(other));
+    }
+
+    #[test]
+    fn test_startup_message() {
+        assert_eq!(
+            startup_message(&[]),
+            "I'm back online, Captain. There aren't any events scheduled."
+        );
+
+        let now = Utc::now().with_timezone(&Tz::PST8PDT);
+        let event = |activity, idx, hours, recur| {
+            Arc::new(Event {
+                id: event_id(activity, idx),
+                activity,
+                datetime: now + chrono::Duration::hours(hours),
+                recur,
+                ..Default::default()
+            })
+        };
+        let later = event(VOG, 1, 5, RecurKind::Weekly);
+        let sooner = event(GOS, 2, 1, RecurKind::None);
+        assert_eq!(
+            startup_message(&[later.clone()]),
+            format!(
+                "I'm back online, Captain. Events intact: 1 (1 recurring), next up is **{}** {}.",
+                later.id,
+                later.timestamp()
+            )
+        );
+        assert_eq!(
+            startup_message(&[later, sooner.clone()]),
+            format!(
+                "I'm back online, Captain. Events intact: 2 (1 recurring), next up is **{}** {}.",
+                sooner.id,
+                sooner.timestamp()
+            )
+        );
+    }
+
+    #[test]
+    fn test_alert_dm_includes_start_timing() {
+        let now = Utc::now();
+        let lead = alert::EventSchedulerConfig::default().alert;
+        let mut event = Event {
+            datetime: (now + chrono::Duration::from_std(lead).unwrap()).with_timezone(&Tz::PST8PDT),
+            ..Default::default()
+        };
+        assert_eq!(event.alert_dm_message(now), None);
+
+        event.trigger_alert_protocol();
+        let message = event.alert_dm_message(now).unwrap();
+        assert!(message.starts_with(&event.alert_protocol_message().unwrap()));
+        assert!(message.ends_with("\nThe event starts in 10 minutes."));
+    }
+
+    #[test]
+    fn test_muted_members_not_alerted() {
+        let mut event = Event {
+            group_size: 2,
+            roster: Roster::from_lists(
+                (1..=5).map(|id| test_member(id, None)).collect(),
+                vec![],
+                vec![],
+            ),
+            ..Default::default()
+        };
+        event.set_alert_muted(UserId(2), true).unwrap();
+        event.set_alert_muted(UserId(3), true).unwrap();
+        event.set_alert_muted(UserId(3), false).unwrap();
+        event.set_alert_muted(UserId(5), true).unwrap();
+        assert!(event.set_alert_muted(UserId(6), true).is_err());
+
+        let alerted = event.trigger_alert_protocol();
+        let ids = alerted.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        assert_eq!(ids, vec![1, 3, 4]);
+        // Muted members are still in their groups.
+        assert!(event
+            .alert_protocol_message()
+            .unwrap()
+            .contains(&UserId(2).mention().to_string()));
+    }
+
+    #[test]
+    fn test_leaving_clears_alert_mute() {
+        let mut event = Event::default();
+        let mut user = User::default();
+        user.id = UserId(2);
+        event.join(&user, JoinKind::Confirmed).unwrap();
+        event.set_alert_muted(user.id, true).unwrap();
+        event.leave(&user).unwrap();
+        assert!(event.alert_muted.is_empty());
+    }
+
+    #[test]
+    fn test_switching_join_kind_keeps_alert_mute() {
+        let mut event = Event::default();
+        let mut user = User::default();
+        user.id = UserId(2);
+        event.join(&user, JoinKind::Confirmed).unwrap();
+        event.set_alert_muted(user.id, true).unwrap();
+        event.join(&user, JoinKind::Alternate).unwrap();
+        assert!(event.alert_muted.contains(&user.id));
+        event.join(&user, JoinKind::Confirmed).unwrap();
+        assert!(event.alert_muted.contains(&user.id));
+    }
+
+    #[test]
+    fn test_merge_dedups_by_join_kind() {
+        let mut dest = Event {
+            id: event_id(VOG, 2),
+            roster: Roster::from_lists(
+                vec![test_member(1, None)],
+                vec![test_member(2, None)],
+                vec![test_member(3, None)],
+            ),
+            ..Default::default()
+        };
+        let source = Event {
+            roster: Roster::from_lists(
+                vec![test_member(3, Some(GroupRole::Dps)), test_member(4, None)],
+                vec![test_member(1, None)],
+                vec![test_member(2, None), test_member(5, None)],
+            ),
+            ..Default::default()
+        };
+
+        let merged = dest.merge_from(&source);
+        let summary = |members: Vec<(EventMember, JoinKind)>| {
+            members.into_iter().map(|(m, k)| (m.id.0, k)).collect_vec()
+        };
+        // 1 and 2 were already in the destination with a higher ranked kind, and 3 is upgraded.
+        assert_eq!(
+            summary(merged),
+            vec![
+                (3, JoinKind::Confirmed),
+                (4, JoinKind::Confirmed),
+                (5, JoinKind::Maybe),
+            ]
+        );
+        let ids = |members: Vec<&EventMember>| members.iter().map(|m| m.id.0).collect::<Vec<_>>();
+        assert_eq!(ids(dest.confirmed()), vec![1, 3, 4]);
+        assert_eq!(ids(dest.alternates()), vec![2]);
+        assert_eq!(ids(dest.maybe()), vec![5]);
+        // Upgraded members keep their own details from the destination event.
+        assert_eq!(dest.roster.get(UserId(3)).unwrap().0.role, None);
+    }
+
+    #[test(tokio::test)]
+    async fn test_merge_respects_capacity() {
+        let manager = EventManager::default().await;
+        let dest = Event {
+            id: event_id(VOG, 1),
+            roster: Roster::from_lists(vec![test_member(1, None)], vec![], vec![]),
+            max_players: Some(2),
+            ..Default::default()
+        };
+        let source = Event {
+            id: event_id(VOG, 2),
+            roster: Roster::from_lists(
+                vec![test_member(2, None), test_member(3, None)],
+                vec![],
+                vec![test_member(4, None)],
+            ),
+            ..Default::default()
+        };
+        manager.add_test_event(dest.clone()).await.unwrap();
+        manager.add_test_event(source.clone()).await.unwrap();
+
+        let (merged_event, merged) = manager.merge_events(&dest.id, &source.id).await.unwrap();
+        // Only one confirmed member fits, so the other becomes an alternate.
+        let summary = merged.into_iter().map(|(m, k)| (m.id.0, k)).collect_vec();
+        assert_eq!(
+            summary,
+            vec![
+                (2, JoinKind::Confirmed),
+                (3, JoinKind::Alternate),
+                (4, JoinKind::Maybe),
+            ]
+        );
+        assert!(merged_event.is_full());
+        assert_eq!(manager.get_event(&dest.id).await, Some(merged_event));
+        assert!(manager.get_event(&source.id).await.is_none());
+
+        assert!(manager.merge_events(&dest.id, &dest.id).await.is_err());
+        assert!(manager.merge_events(&dest.id, &source.id).await.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn test_merge_persisted_in_one_write() {
+        let manager = EventManager::default().await;
+        let dest = Event {
+            id: event_id(VOG, 1),
+            roster: Roster::from_lists(vec![test_member(1, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        let source = Event {
+            id: event_id(VOG, 2),
+            roster: Roster::from_lists(vec![test_member(2, None)], vec![], vec![]),
+            ..Default::default()
+        };
+        manager.add_test_event(dest.clone()).await.unwrap();
+        manager.add_test_event(source.clone()).await.unwrap();
+
+        // A second write would fail, leaving member 2 stored in both events.
+        manager.state.write().await.events_store = PersistentStore::failing_after(1);
+        let (merged_event, _) = manager.merge_events(&dest.id, &source.id).await.unwrap();
+
+        let state = manager.state.read().await;
+        let stored = state.events_store.load().await.unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored.get(&dest.id), Some(&merged_event));
+        assert!(merged_event.roster.get(UserId(2)).is_some());
+    }
+
+    #[test]
+    fn test_event_id_from_str() {
+        let parse = |s: &str| EventId::from_str(s).ok();
+        assert_eq!(parse("vog12"), Some(event_id(VOG, 12)));
+        assert_eq!(parse("vog12 (Vault of Glass)"), Some(event_id(VOG, 12)));
+        assert_eq!(parse("vog12, see you there"), Some(event_id(VOG, 12)));
+        assert_eq!(parse("gos3\n"), Some(event_id(GOS, 3)));
+
+        assert_eq!(parse("vog12abc"), None);
+        assert_eq!(parse("vog1a"), None);
+        assert_eq!(parse("vog"), None);
+        assert_eq!(parse("vog (Vault of Glass)"), None);
+        assert_eq!(parse("vog256"), None);
+        assert_eq!(parse("xyz12"), None);
+    }
+
+    #[test]
+    fn test_event_id_from_str_lenient() {
+        let parse = |s: &str| EventId::from_str(s).ok();
+        assert_eq!(parse("VOG42"), Some(event_id(VOG, 42)));
+        assert_eq!(parse("VoG42"), Some(event_id(VOG, 42)));
+        assert_eq!(parse(" vog 42"), Some(event_id(VOG, 42)));
+        assert_eq!(parse("\tGoS  3 "), Some(event_id(GOS, 3)));
+
+        assert_eq!(parse("V OG42"), None);
+        assert_eq!(parse("vog 4 2"), None);
+        assert_eq!(parse("VOG 12abc"), None);
+        assert_eq!(parse("  "), None);
+    }
+
+    #[test]
+    fn test_series_id_from_first_event_id() {
+        let token = SeriesId::random();
+        let json = serde_json::to_value(token).unwrap();
+        assert_eq!(serde_json::from_value::<SeriesId>(json).unwrap(), token);
+
+        // Series that were stored as the ID of their first event stay together.
+        let legacy = |id: &str| serde_json::from_value::<SeriesId>(serde_json::json!(id)).unwrap();
+        assert_eq!(legacy("vog1"), legacy("vog1"));
+        assert_ne!(legacy("vog1"), legacy("vog2"));
+        // The derived ID is stable, so it's the same however many times the event is reloaded.
+        assert_eq!(legacy("vog1"), SeriesId(0x315fb000e36a0e5a));
+    }
+
+    #[test]
+    fn test_event_id_canonical_form_unchanged() {
+        for id in ["vog42", "gos3", "lw255"] {
+            let event_id = EventId::from_str(id).unwrap();
+            assert_eq!(event_id.to_string(), id);
+            let json = serde_json::to_string(&event_id).unwrap();
+            assert_eq!(json, format!("\"{}\"", id));
+            assert_eq!(serde_json::from_str::<EventId>(&json).unwrap(), event_id);
+        }
+        assert_eq!(
+            EventId::from_str("VOG42").unwrap().to_string(),
+            "vog42".to_owned()
+        );
+    }
 }
@@ -0,0 +1,126 @@
+use super::EventMember;
+use anyhow::{format_err, Error, Result};
+use enum_iterator::IntoEnumIterator;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// A role that a member can declare they'll fill within their group.
+#[derive(
+    IntoEnumIterator,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+)]
+pub enum GroupRole {
+    Dps,
+    AddClear,
+    Support,
+}
+
+impl GroupRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GroupRole::Dps => "DPS",
+            GroupRole::AddClear => "Add Clear",
+            GroupRole::Support => "Support",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            GroupRole::Dps => "🎯",
+            GroupRole::AddClear => "💥",
+            GroupRole::Support => "🛡️",
+        }
+    }
+
+    /// The value used for this role in command options.
+    pub fn option_value(&self) -> &'static str {
+        match self {
+            GroupRole::Dps => "dps",
+            GroupRole::AddClear => "adds",
+            GroupRole::Support => "support",
+        }
+    }
+}
+
+impl FromStr for GroupRole {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::into_enum_iter()
+            .find(|r| r.option_value() == s)
+            .ok_or_else(|| format_err!("Unknown group role: {}", s))
+    }
+}
+
+impl std::fmt::Display for GroupRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{} {}", self.emoji(), self.name()))
+    }
+}
+
+/// Number of members of each role that every group should have.
+pub type RoleRequirements = BTreeMap<GroupRole, u8>;
+
+/// Orders members such that, when chunked into groups of `group_size`, each group satisfies the
+/// role requirements where possible. Members are otherwise kept in their original order, both
+/// within each group and across groups.
+pub fn order_for_requirements<'a, T>(
+    members: Vec<(&'a EventMember, T)>,
+    group_size: usize,
+    requirements: &RoleRequirements,
+) -> Vec<(&'a EventMember, T)> {
+    let mut remaining: Vec<_> = members.into_iter().enumerate().collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        let mut group = Vec::with_capacity(group_size);
+        for (role, count) in requirements {
+            for _ in 0..*count {
+                if group.len() == group_size {
+                    break;
+                }
+                match remaining
+                    .iter()
+                    .position(|(_, (member, _))| member.role == Some(*role))
+                {
+                    Some(idx) => group.push(remaining.remove(idx)),
+                    None => break,
+                }
+            }
+        }
+
+        // Fill out the rest of the group in the original order.
+        let fill = (group_size - group.len()).min(remaining.len());
+        group.extend(remaining.drain(..fill));
+        group.sort_by_key(|(idx, _)| *idx);
+        ordered.extend(group.into_iter().map(|(_, member)| member));
+    }
+    ordered
+}
+
+/// Returns the roles (and how many of each) that a group is missing to meet the requirements.
+pub fn unmet_requirements<'a>(
+    group: impl Iterator<Item = &'a EventMember> + Clone,
+    requirements: &RoleRequirements,
+) -> Vec<(GroupRole, u8)> {
+    requirements
+        .iter()
+        .filter_map(|(role, count)| {
+            let have = group.clone().filter(|m| m.role == Some(*role)).count();
+            let missing = (*count as usize).saturating_sub(have);
+            if missing > 0 {
+                Some((*role, missing as u8))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,108 @@
+use crate::store::{PersistentStore, PersistentStoreBuilder};
+use anyhow::Result;
+use chrono_tz::Tz;
+use itertools::Itertools;
+use serenity::model::id::UserId;
+use std::{collections::HashMap, str::FromStr};
+use tracing::warn;
+
+const USER_TIMEZONES_STORE_NAME: &str = "user_timezones.json";
+
+/// Persistent default timezones that users have set for themselves, used when they give a datetime
+/// without a timezone.
+#[derive(Debug)]
+pub struct UserTimezones {
+    timezones: HashMap<UserId, Tz>,
+    // Timezones are stored by their tz database name, e.g. "America/New_York".
+    store: PersistentStore<Vec<(UserId, String)>>,
+}
+
+impl UserTimezones {
+    pub async fn load(store_builder: &PersistentStoreBuilder) -> Result<Self> {
+        let store = store_builder.build(USER_TIMEZONES_STORE_NAME).await?;
+        let timezones = store
+            .load()
+            .await?
+            .into_iter()
+            .filter_map(|(user, name)| match Tz::from_str(&name) {
+                Ok(tz) => Some((user, tz)),
+                Err(_) => {
+                    // Skip it rather than losing everyone else's timezone, e.g. if the tz database
+                    // dropped the name.
+                    warn!("Skipping unknown timezone {} for user {}", name, user);
+                    None
+                }
+            })
+            .collect();
+        Ok(UserTimezones { timezones, store })
+    }
+
+    pub fn get(&self, user: UserId) -> Option<Tz> {
+        self.timezones.get(&user).copied()
+    }
+
+    /// Sets the user's default timezone, or clears it with None. The timezone is only changed if it's
+    /// saved successfully.
+    pub async fn set(&mut self, user: UserId, timezone: Option<Tz>) -> Result<()> {
+        let mut timezones = self.timezones.clone();
+        match timezone {
+            Some(tz) => timezones.insert(user, tz),
+            None => timezones.remove(&user),
+        };
+        let names: Vec<_> = timezones
+            .iter()
+            .map(|(user, tz)| (*user, tz.name().to_owned()))
+            .sorted()
+            .collect();
+        self.store.store(&names).await?;
+        self.timezones = timezones;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test(tokio::test)]
+    async fn user_timezones_round_trip() {
+        let tempdir = tempdir::TempDir::new("timezones").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+
+        let mut timezones = UserTimezones::load(&store_builder).await.unwrap();
+        assert_eq!(timezones.get(UserId(1)), None);
+        timezones.set(UserId(1), Some(Tz::EST5EDT)).await.unwrap();
+        timezones.set(UserId(2), Some(Tz::PST8PDT)).await.unwrap();
+        timezones.set(UserId(3), Some(Tz::MST7MDT)).await.unwrap();
+        timezones.set(UserId(2), Some(Tz::CST6CDT)).await.unwrap();
+        timezones.set(UserId(3), None).await.unwrap();
+        drop(timezones);
+
+        // Timezones survive a restart.
+        let timezones = UserTimezones::load(&store_builder).await.unwrap();
+        assert_eq!(timezones.get(UserId(1)), Some(Tz::EST5EDT));
+        assert_eq!(timezones.get(UserId(2)), Some(Tz::CST6CDT));
+        assert_eq!(timezones.get(UserId(3)), None);
+    }
+
+    #[test(tokio::test)]
+    async fn unknown_stored_timezones_skipped() {
+        let tempdir = tempdir::TempDir::new("timezones").unwrap();
+        let store_builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let store: PersistentStore<Vec<(UserId, String)>> = store_builder
+            .build(USER_TIMEZONES_STORE_NAME)
+            .await
+            .unwrap();
+        let names = vec![
+            (UserId(1), "America/New_York".to_owned()),
+            (UserId(2), "Mars/Olympus_Mons".to_owned()),
+        ];
+        store.store(&names).await.unwrap();
+        drop(store);
+
+        let timezones = UserTimezones::load(&store_builder).await.unwrap();
+        assert_eq!(timezones.get(UserId(1)), Some(Tz::America__New_York));
+        assert_eq!(timezones.get(UserId(2)), None);
+    }
+}
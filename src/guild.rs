@@ -1,28 +1,109 @@
 use crate::{
-    activity::ActivityType,
-    command::CommandManager,
-    embed::{EmbedManagerConfig, EventChannelFilterFn},
-    event::{Event, EventManager},
+    activity::{Activity, ActivityType},
+    command::{CommandManager, Shortcut},
+    embed::{EmbedManagerConfig, EventChannelConfig, EventChannelFilterFn},
+    event::{
+        parse_hex_color, ButtonLabels, ConflictCheck, EmbedColors, EmbedStyle, Event, EventManager,
+        EventSchedulerConfig, DEFAULT_MAX_LISTED_MEMBERS,
+    },
     store::PersistentStoreBuilder,
+    util::{DateFormat, WeeklyReset},
 };
-use anyhow::{format_err, Context as _, Result};
+use anyhow::{ensure, format_err, Context as _, Result};
 use derivative::Derivative;
+use enum_iterator::IntoEnumIterator;
 use itertools::Itertools;
-use serde::Deserialize;
+use serde::{de::Error as _, Deserialize, Deserializer};
 use serenity::{
     model::{
-        id::{ChannelId, GuildId},
+        guild::Member,
+        id::{ChannelId, GuildId, RoleId, UserId},
         interactions::Interaction,
     },
     prelude::*,
 };
-use std::{collections::HashMap, path::Path, sync::Arc};
-use tokio::sync::RwLock;
-use tracing::{error, info};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Weak,
+    },
+    time::Duration,
+};
+use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
+use tracing::{error, info, warn};
 
 #[derive(Debug, Default)]
 pub struct GuildConfig {
     pub embed_config: EmbedManagerConfig,
+    /// Channel to announce newly created event recurrences in, if any.
+    pub recurrence_announcement_channel: Option<ChannelId>,
+    /// Whether to ask an event's maybes if they want to join its next recurrence at cleanup.
+    pub follow_up_maybes: bool,
+    /// How dates are written out in messages.
+    pub date_format: DateFormat,
+    /// When the weekly reset is, which dates can be given relative to.
+    pub weekly_reset: WeeklyReset,
+    /// What the buttons on event messages say.
+    pub button_labels: ButtonLabels,
+    /// Top-level commands that create events for a single activity.
+    pub shortcuts: Vec<Shortcut>,
+    /// How long ended events' messages stay up after cleanup, if they should stay up at all.
+    pub message_linger: Option<Duration>,
+    /// Weeks a recurring event can go unused before it stops recurring, if not the default.
+    pub recurrence_lapse_weeks: Option<u32>,
+    /// Description templates for new events when the creator doesn't give one, replacing the
+    /// activities' own defaults.
+    pub default_descriptions: HashMap<Activity, String>,
+    /// Channel for operational notices from the bot, like it coming back online, if any.
+    pub announcement_channel: Option<ChannelId>,
+    /// Whether joining a full event as confirmed adds the member as an alternate instead.
+    pub auto_alt: bool,
+    /// Whether events' rosters are locked once they start.
+    pub lock_started_events: bool,
+    /// What to do when someone joins an event as confirmed while confirmed for another around then.
+    pub join_conflicts: ConflictCheck,
+    /// Minutes apart events have to start within to conflict, if not the default.
+    pub conflict_window_minutes: Option<u32>,
+    /// When to open check-in, alert, and clean up around each event.
+    pub scheduler_config: EventSchedulerConfig,
+}
+
+/// Coordinates deleting a guild's stored data after the bot is removed from it. This is shared by
+/// every EventManager created for the same guild, so that re-adding a guild before its old
+/// EventManager is dropped cancels the deletion, and so that a new EventManager never loads the
+/// guild's data while it's being deleted.
+#[derive(Debug, Default)]
+pub struct GuildDataGuard {
+    removed: AtomicBool,
+    lock: Arc<Mutex<()>>,
+}
+
+impl GuildDataGuard {
+    pub fn mark_removed(&self) {
+        self.removed.store(true, Ordering::SeqCst);
+    }
+
+    pub fn cancel_removal(&self) {
+        self.removed.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.removed.load(Ordering::SeqCst)
+    }
+
+    /// Waits for any in-progress deletion of the guild's data to finish. The data can be loaded
+    /// while the returned guard is held.
+    pub async fn lock(&self) -> OwnedMutexGuard<()> {
+        self.lock.clone().lock_owned().await
+    }
+
+    /// Acquires the lock for deleting the guild's data, unless the guild's data is being loaded
+    /// right now, in which case it's been re-added and the data shouldn't be deleted.
+    pub fn try_lock_for_deletion(&self) -> Option<OwnedMutexGuard<()>> {
+        self.lock.clone().try_lock_owned().ok()
+    }
 }
 
 #[derive(Derivative)]
@@ -32,7 +113,16 @@ pub struct GuildManager {
     config: GuildConfigToml,
     #[derivative(Debug = "ignore")]
     event_managers: RwLock<HashMap<GuildId, Arc<EventManager>>>,
+    /// EventManagers for guilds the bot was removed from, which may still be alive if they're in
+    /// use elsewhere. Re-adding the guild reinstates the same EventManager in that case, since a
+    /// second one couldn't open the guild's stores anyway.
+    #[derivative(Debug = "ignore")]
+    removed_managers: Mutex<HashMap<GuildId, Weak<EventManager>>>,
+    data_guards: Mutex<HashMap<GuildId, Arc<GuildDataGuard>>>,
     command_manager: CommandManager,
+    /// Whether orphaned guild data has been cleaned up, which only needs to happen once per start
+    /// rather than every time the cache becomes ready (e.g. after reconnecting).
+    orphans_deleted: AtomicBool,
 }
 
 impl GuildManager {
@@ -47,19 +137,38 @@ impl GuildManager {
                 config_file.display()
             )
         })?;
-        let config = toml::from_str(&config).context("Failed to deserialize guild config")?;
+        let config: GuildConfigToml =
+            toml::from_str(&config).context("Failed to deserialize guild config")?;
+        // Catch invalid guild configs on startup rather than once the bot's added to the guild.
+        for &guild_id in config.guilds.keys() {
+            config.config_for_guild(guild_id)?;
+        }
         Ok(GuildManager {
             store_builder,
             config,
             event_managers: Default::default(),
+            removed_managers: Default::default(),
+            data_guards: Default::default(),
             command_manager: CommandManager::new(),
+            orphans_deleted: AtomicBool::new(false),
         })
     }
 
-    pub async fn add_guilds(&self, ctx: &Context, guild_ids: Vec<GuildId>) -> Result<()> {
+    /// Adds the guilds that don't have an EventManager yet, returning the IDs of those that were
+    /// added. Guilds that fail to be added are logged and left out.
+    pub async fn add_guilds(&self, ctx: &Context, guild_ids: Vec<GuildId>) -> Vec<GuildId> {
         let mut managers = self.event_managers.write().await;
 
-        let mut errors = Vec::new();
+        if !self.orphans_deleted.swap(true, Ordering::SeqCst) {
+            let current_guilds: HashSet<_> =
+                guild_ids.iter().chain(managers.keys()).copied().collect();
+            if let Err(err) = delete_orphaned_guild_data(&self.store_builder, &current_guilds).await
+            {
+                error!("Error deleting orphaned guild data: {:?}", err);
+            }
+        }
+
+        let mut added = Vec::new();
         for guild_id in guild_ids {
             // This is expected; existing guild IDs will be passed in each time.
             if managers.contains_key(&guild_id) {
@@ -70,33 +179,65 @@ impl GuildManager {
             match self.add_guild(ctx.clone(), guild_id).await {
                 Ok(mgr) => {
                     managers.insert(guild_id, mgr);
+                    added.push(guild_id);
                 }
-                Err(err) => errors.push(err),
+                Err(err) => error!("Error adding guild {}: {:?}", guild_id, err),
             }
         }
-
-        if !errors.is_empty() {
-            return Err(format_err!(
-                "Errors occurred adding guilds: {}",
-                errors.iter().map(|e| format!("{:?}", e)).join(", ")
-            ));
-        }
-        Ok(())
+        added
     }
 
     async fn add_guild(&self, ctx: Context, guild_id: GuildId) -> Result<Arc<EventManager>> {
         let http = ctx.http.clone();
+        let data_guard = self
+            .data_guards
+            .lock()
+            .await
+            .entry(guild_id)
+            .or_default()
+            .clone();
+        // Cancel any pending deletion from a recent removal before anything else, so that an old
+        // EventManager that's dropped from here on leaves the data alone.
+        data_guard.cancel_removal();
+
+        let removed = self.removed_managers.lock().await.remove(&guild_id);
+        if let Some(mgr) = removed.and_then(|weak| weak.upgrade()) {
+            info!("Reinstating EventManager for re-added guild {}", guild_id);
+            let shortcuts = self.config.config_for_guild(guild_id)?.shortcuts;
+            self.command_manager
+                .add_guild(&http, &guild_id, shortcuts)
+                .await?;
+            return Ok(mgr);
+        }
+
+        // Wait for the data to finish being deleted if the old EventManager was already dropped.
+        let _data_lock = data_guard.lock().await;
         let guild_store = self
             .store_builder
             .new_scoped(guild_id.as_u64().to_string())
             .await
             .with_context(|| format!("Failed to create guild {} store", guild_id))?;
+        let config = self.config.config_for_guild(guild_id)?;
+        let shortcuts = config.shortcuts.clone();
+        let announcement_channel = config.announcement_channel;
         let event_manager =
-            EventManager::new(ctx, guild_store, self.config.config_for_guild(guild_id))
+            EventManager::new(ctx, guild_id, guild_store, config, data_guard.clone())
                 .await
                 .with_context(|| format!("Failed to create EventManager for guild {}", guild_id))?;
 
-        self.command_manager.add_guild(&http, &guild_id).await?;
+        self.command_manager
+            .add_guild(&http, &guild_id, shortcuts)
+            .await?;
+
+        // The guild's stored events are loaded by now, and its event channels are being synced.
+        if let Some(channel) = announcement_channel {
+            if let Err(err) = event_manager.announce_startup(channel).await {
+                error!(
+                    "Failed to post startup announcement for guild {}: {:?}",
+                    guild_id, err
+                );
+            }
+        }
         Ok(event_manager)
     }
 
@@ -104,11 +245,32 @@ impl GuildManager {
         let mut managers = self.event_managers.write().await;
         info!("Removed from guild {}", guild_id);
         match managers.remove(&guild_id) {
-            Some(mgr) => mgr.removed_from_guild(),
+            Some(mgr) => {
+                mgr.removed_from_guild();
+                self.removed_managers
+                    .lock()
+                    .await
+                    .insert(guild_id, Arc::downgrade(&mgr));
+            }
             None => error!("No EventManager exists for removed guild {}", guild_id),
         }
     }
 
+    /// Shuts down every guild's EventManager, so that nothing is left half-written when the
+    /// process exits.
+    pub async fn shutdown(&self) {
+        let managers = self.event_managers.read().await;
+        for (guild_id, mgr) in managers.iter() {
+            if let Err(err) = mgr.shutdown().await {
+                error!(
+                    "Error shutting down EventManager for guild {}: {:?}",
+                    guild_id, err
+                );
+            }
+        }
+        info!("Shut down {} EventManagers", managers.len());
+    }
+
     pub async fn get_event_manager(&self, guild_id: GuildId) -> Result<Arc<EventManager>> {
         let managers = self.event_managers.read().await;
         if let Some(mgr) = managers.get(&guild_id) {
@@ -117,6 +279,59 @@ impl GuildManager {
         Err(format_err!("No EventManager exists for guild {}", guild_id))
     }
 
+    /// Updates a guild member's name in the guild's events after it changes.
+    pub async fn member_updated(&self, member: &Member) -> Result<()> {
+        let event_manager = self.get_event_manager(member.guild_id).await?;
+        let renamed = event_manager.rename_member(member).await?;
+        if !renamed.is_empty() {
+            info!(
+                "Renamed {} to '{}' in {}",
+                member.user.id,
+                member.display_name(),
+                renamed.iter().join(", ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Brings the names stored in the guild's events up to date with the given display names, e.g.
+    /// those of the guild's cached members on startup.
+    pub async fn refresh_member_names(
+        &self,
+        guild_id: GuildId,
+        names: &HashMap<UserId, String>,
+    ) -> Result<()> {
+        let event_manager = self.get_event_manager(guild_id).await?;
+        let renamed = event_manager.refresh_member_names(names).await?;
+        if !renamed.is_empty() {
+            info!(
+                "Refreshed member names in {} for guild {}",
+                renamed.iter().join(", "),
+                guild_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Like refresh_member_names(), but looking up the current name of each member of the guild's
+    /// events, for when the guild's members aren't cached.
+    pub async fn fetch_member_names(&self, ctx: &Context, guild_id: GuildId) -> Result<()> {
+        let event_manager = self.get_event_manager(guild_id).await?;
+        let mut names = HashMap::new();
+        for user in event_manager.member_ids().await {
+            match guild_id.member(ctx, user).await {
+                Ok(member) => {
+                    names.insert(user, member.display_name().into_owned());
+                }
+                Err(err) => warn!(
+                    "Failed to fetch member {} of guild {}: {:?}",
+                    user, guild_id, err
+                ),
+            }
+        }
+        self.refresh_member_names(guild_id, &names).await
+    }
+
     pub async fn dispatch_interaction(
         &self,
         ctx: &Context,
@@ -128,6 +343,34 @@ impl GuildManager {
     }
 }
 
+/// Deletes the stored data for any guild that the bot is no longer in. Data is normally deleted when
+/// the bot is removed from a guild, but that happens in a task spawned when the guild's EventManager
+/// is dropped and so may never run if the process exits soon after.
+async fn delete_orphaned_guild_data(
+    store_builder: &PersistentStoreBuilder,
+    current_guilds: &HashSet<GuildId>,
+) -> Result<()> {
+    for name in store_builder.scoped_names().await? {
+        // Leave alone anything that isn't a guild store.
+        let guild_id = match name.parse::<u64>() {
+            Ok(id) => GuildId(id),
+            Err(_) => continue,
+        };
+        if current_guilds.contains(&guild_id) {
+            continue;
+        }
+
+        info!("Deleting orphaned data for guild {}", guild_id);
+        store_builder
+            .new_scoped(&name)
+            .await?
+            .delete()
+            .await
+            .with_context(|| format!("Failed to delete orphaned data for guild {}", guild_id))?;
+    }
+    Ok(())
+}
+
 impl TypeMapKey for GuildManager {
     type Value = Arc<GuildManager>;
 }
@@ -138,6 +381,31 @@ struct GuildConfigToml {
     guilds: HashMap<GuildId, SingleGuildConfigToml>,
 }
 
+/// A shortcut command, e.g. `{ name = "raid", activity = "vog" }`, where the activity is given by
+/// its event ID prefix.
+#[derive(Debug, Deserialize)]
+struct ShortcutToml {
+    #[serde(deserialize_with = "deserialize_shortcut_name")]
+    name: String,
+    activity: String,
+}
+
+/// Discord only accepts command names matching `^[a-z0-9_-]{1,32}$`, so shortcuts that don't would
+/// fail to register with the rest of the guild's commands.
+fn deserialize_shortcut_name<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    let valid_char = |c: char| matches!(c, 'a'..='z' | '0'..='9' | '_' | '-');
+    if name.is_empty() || name.len() > 32 || !name.chars().all(valid_char) {
+        return Err(D::Error::custom(format!(
+            "invalid shortcut name '{}', must be 1-32 lowercase letters, digits, '_' or '-'",
+            name
+        )));
+    }
+    Ok(name)
+}
+
 #[derive(Debug, Deserialize)]
 struct SingleGuildConfigToml {
     raid_lfg: ChannelId,
@@ -145,47 +413,665 @@ struct SingleGuildConfigToml {
     pvp_lfg: ChannelId,
     special_lfg: ChannelId,
     all_lfg: ChannelId,
+    /// Event channels that are also used for chat, so messages from users shouldn't be deleted.
+    #[serde(default)]
+    keep_user_messages: Vec<ChannelId>,
+    /// Event channels that keep a summary of their soonest events at the top of the channel.
+    #[serde(default)]
+    summary_channels: Vec<ChannelId>,
+    /// Event channels where a discussion thread is started off each new event message.
+    #[serde(default)]
+    thread_channels: Vec<ChannelId>,
+    /// Announce new recurrences of recurring events in this channel. This can't be one of the
+    /// event channels above, since the bot's own messages there are all managed event embeds.
+    #[serde(default)]
+    recurrence_announcement_channel: Option<ChannelId>,
+    /// DM the maybes of a recurring event when it's cleaned up, asking if they want to join the
+    /// next recurrence.
+    #[serde(default)]
+    follow_up_maybes: bool,
+    /// How dates are written out in messages: "us" (4/20), "iso" (2021-04-20), or "day_month"
+    /// (20/4).
+    #[serde(default)]
+    date_format: DateFormat,
+    /// When the game's weekly reset is, which dates like "reset+30m" are relative to, as a weekday
+    /// and a time of day in UTC, e.g. `weekly_reset = { weekday = "fri", time = "09:00:00" }`.
+    /// Defaults to Tuesdays at 17:00 UTC.
+    #[serde(default)]
+    weekly_reset: WeeklyReset,
+    /// Labels and emojis for the buttons on event messages, e.g. `join = { label = "I'm in",
+    /// emoji = "👍" }`. Any that aren't given keep their default.
+    #[serde(default)]
+    button_labels: ButtonLabels,
+    /// Colors for event embeds, keyed by activity type (the name used in /lfg create's
+    /// subcommands), e.g. `raid = "#f1c40f"`. Activity types that aren't given keep their default.
+    #[serde(default)]
+    embed_colors: HashMap<String, String>,
+    /// Top-level commands that create events for a single activity.
+    #[serde(default)]
+    shortcuts: Vec<ShortcutToml>,
+    /// Keep event messages up, marked as ended, for this many minutes after the event is cleaned
+    /// up instead of deleting them right away.
+    #[serde(default)]
+    message_linger_minutes: Option<u64>,
+    /// Stop recreating a recurring event once it's been this many weeks since it last happened,
+    /// e.g. because the bot was down, rather than catching it up with an outdated roster.
+    #[serde(default)]
+    recurrence_lapse_weeks: Option<u32>,
+    /// Description templates for new events whose creator doesn't give one, keyed by activity ID
+    /// prefix, e.g. `vog = "{activity} weekly clear, bring a sword"`. "{activity}" is replaced with
+    /// the activity's name.
+    #[serde(default)]
+    default_descriptions: HashMap<String, String>,
+    /// Post operational notices in this channel, e.g. when the bot comes back online after a
+    /// restart. Like recurrence_announcement_channel, this can't be one of the event channels.
+    #[serde(default)]
+    announcement_channel: Option<ChannelId>,
+    /// When someone tries to join an event that's already at its max_players as confirmed, add
+    /// them as an alternate instead of turning them away.
+    #[serde(default)]
+    auto_alt: bool,
+    /// Show the creator of each new event, with their avatar, at the top of its embed along with
+    /// how long ago it was created.
+    #[serde(default)]
+    show_creator: bool,
+    /// How many spare alternates and maybes event embeds list before cutting the list short with
+    /// how many more there are. Defaults to 15.
+    #[serde(default)]
+    max_listed_members: Option<usize>,
+    /// Lock each event's roster when it starts, so that no one can join it while it's in
+    /// progress. The creator or an admin can still unlock it.
+    #[serde(default)]
+    lock_started_events: bool,
+    /// What to do when someone joins an event as confirmed while they're already confirmed for
+    /// another event starting within conflict_window_minutes of it: "off", "warn" to let them join
+    /// but point out the conflict, or "block" to turn them away.
+    #[serde(default)]
+    join_conflicts: ConflictCheck,
+    /// How many minutes apart two events have to start within to conflict. Defaults to 120.
+    #[serde(default)]
+    conflict_window_minutes: Option<u32>,
+    /// When to open check-in, alert, and clean up around each event, e.g. `scheduler = {
+    /// alert_minutes = 15 }`. Any that aren't given keep their default.
+    #[serde(default, deserialize_with = "deserialize_scheduler_config")]
+    scheduler: EventSchedulerConfig,
+    /// Mention this role when an event's alert protocol fires, in a message posted to the event's
+    /// activity type channel (not all_lfg, so that the role is only pinged once). Guilds whose
+    /// activity type channels are all the all_lfg channel get pinged there.
+    #[serde(default)]
+    lfg_role: Option<RoleId>,
+    /// Roles to mention when a new event is posted to an event channel, keyed by channel, e.g.
+    /// `[new_event_roles]` with `1234 = 5678` to ping a raid role in the raid channel. The role is
+    /// only mentioned in the event's first message, not when it's edited or reposted.
+    #[serde(default)]
+    new_event_roles: HashMap<ChannelId, RoleId>,
+    /// Hold new events back from the event channels until they have at least this many confirmed
+    /// members, counting the creator, to keep the channels clear of empty events. Creators still
+    /// get the event's embed right away.
+    #[serde(default)]
+    min_confirmed_to_post: Option<u16>,
+}
+
+// The most minutes any timing in the config can be, a year. That's far beyond any useful timing, but
+// keeps the times it's added to from overflowing.
+const MAX_CONFIG_MINUTES: u64 = 60 * 24 * 365;
+
+/// The duration of a timing from the config given in minutes, which can't be more than
+/// MAX_CONFIG_MINUTES.
+fn config_minutes(name: &str, minutes: u64) -> Result<Duration> {
+    minutes
+        .checked_mul(60)
+        .filter(|_| minutes <= MAX_CONFIG_MINUTES)
+        .map(Duration::from_secs)
+        .ok_or_else(|| {
+            format_err!(
+                "{} must be at most {} minutes (a year)",
+                name,
+                MAX_CONFIG_MINUTES
+            )
+        })
+}
+
+/// Scheduler timings in minutes, relative to each event's start time.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct SchedulerConfigToml {
+    /// Minutes before an event to open check-in, which has to be before the alert. Check-in is off
+    /// if this is missing or 0.
+    check_in_minutes: Option<i64>,
+    /// Minutes before an event to alert its members.
+    alert_minutes: i64,
+    /// Minutes after an event to clean it up.
+    cleanup_minutes: i64,
+}
+
+impl Default for SchedulerConfigToml {
+    fn default() -> Self {
+        let defaults = EventSchedulerConfig::default();
+        let minutes = |d: Duration| d.as_secs() as i64 / 60;
+        SchedulerConfigToml {
+            check_in_minutes: defaults.check_in.map(minutes),
+            alert_minutes: minutes(defaults.alert),
+            cleanup_minutes: minutes(defaults.cleanup),
+        }
+    }
+}
+
+impl SchedulerConfigToml {
+    fn to_config(&self) -> Result<EventSchedulerConfig> {
+        let check_in_minutes = self.check_in_minutes.filter(|&minutes| minutes != 0);
+        if let Some(check_in_minutes) = check_in_minutes {
+            ensure!(
+                check_in_minutes > self.alert_minutes,
+                "check_in_minutes must be before the alert, i.e. more than alert_minutes"
+            );
+        }
+        ensure!(
+            self.alert_minutes > 0,
+            "alert_minutes must be before the event, i.e. positive"
+        );
+        ensure!(
+            self.cleanup_minutes > 0,
+            "cleanup_minutes must be after the event, i.e. positive"
+        );
+        // All of the timings are positive by now.
+        Ok(EventSchedulerConfig {
+            check_in: check_in_minutes
+                .map(|minutes| config_minutes("check_in_minutes", minutes as u64))
+                .transpose()?,
+            alert: config_minutes("alert_minutes", self.alert_minutes as u64)?,
+            cleanup: config_minutes("cleanup_minutes", self.cleanup_minutes as u64)?,
+            message_linger: None,
+        })
+    }
+}
+
+fn deserialize_scheduler_config<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<EventSchedulerConfig, D::Error> {
+    SchedulerConfigToml::deserialize(deserializer)?
+        .to_config()
+        .map_err(D::Error::custom)
 }
 
 impl GuildConfigToml {
-    pub fn config_for_guild(&self, guild_id: GuildId) -> GuildConfig {
-        self.guilds
-            .get(&guild_id)
-            .map(GuildConfig::from)
-            .unwrap_or_default()
+    pub fn config_for_guild(&self, guild_id: GuildId) -> Result<GuildConfig> {
+        match self.guilds.get(&guild_id) {
+            Some(cfg) => GuildConfig::try_from(cfg)
+                .with_context(|| format!("Invalid config for guild {}", guild_id)),
+            None => Ok(Default::default()),
+        }
     }
 }
 
-impl From<&SingleGuildConfigToml> for GuildConfig {
-    fn from(cfg: &SingleGuildConfigToml) -> Self {
-        let v: Vec<(_, EventChannelFilterFn)> = vec![
-            (
+/// An event channel filter for events of the given activity types, along with a description of it
+/// for admins.
+fn activity_type_filter(types: &'static [ActivityType]) -> (String, EventChannelFilterFn) {
+    let label = format!("{} events", types.iter().map(|t| t.name()).join(", "));
+    let filter = Box::new(move |e: &Event| types.contains(&e.activity.activity_type()));
+    (label, filter)
+}
+
+impl TryFrom<&SingleGuildConfigToml> for GuildConfig {
+    type Error = anyhow::Error;
+
+    fn try_from(cfg: &SingleGuildConfigToml) -> Result<Self> {
+        use ActivityType::*;
+        let is_event_channel = |channel: ChannelId| {
+            [
                 cfg.raid_lfg,
-                Box::new(|e: &Event| e.activity.activity_type() == ActivityType::Raid),
-            ),
-            (
                 cfg.pve_lfg,
-                Box::new(|e: &Event| match e.activity.activity_type() {
-                    ActivityType::Dungeon
-                    | ActivityType::Gambit
-                    | ActivityType::PvE
-                    | ActivityType::Seasonal => true,
-                    _ => false,
-                }),
-            ),
-            (
                 cfg.pvp_lfg,
-                Box::new(|e: &Event| e.activity.activity_type() == ActivityType::Crucible),
+                cfg.special_lfg,
+                cfg.all_lfg,
+            ]
+            .contains(&channel)
+        };
+        // The bot's own messages in event channels are all taken to be managed event embeds.
+        ensure!(
+            !cfg.announcement_channel.map_or(false, is_event_channel),
+            "announcement_channel can't be one of the event channels"
+        );
+        ensure!(
+            !cfg.recurrence_announcement_channel
+                .map_or(false, is_event_channel),
+            "recurrence_announcement_channel can't be one of the event channels"
+        );
+        // Whether the guild only has the one channel, with every activity type channel set to it.
+        let only_all_lfg = [cfg.raid_lfg, cfg.pve_lfg, cfg.pvp_lfg, cfg.special_lfg]
+            .iter()
+            .all(|&channel| channel == cfg.all_lfg);
+        let v: Vec<(_, (String, EventChannelFilterFn))> = vec![
+            (cfg.raid_lfg, activity_type_filter(&[Raid])),
+            (
+                cfg.pve_lfg,
+                activity_type_filter(&[Dungeon, Gambit, PvE, Seasonal]),
             ),
+            (cfg.pvp_lfg, activity_type_filter(&[Crucible])),
+            (cfg.special_lfg, activity_type_filter(&[Custom])),
             (
-                cfg.special_lfg,
-                Box::new(|e: &Event| e.activity.activity_type() == ActivityType::Custom),
+                cfg.all_lfg,
+                ("All events".to_owned(), Box::new(|_: &Event| true)),
             ),
-            (cfg.all_lfg, Box::new(|_: &Event| true)),
         ];
-        let event_channels = v.into_iter().collect();
-        GuildConfig {
-            embed_config: EmbedManagerConfig { event_channels },
+        let embed_colors = EmbedColors::new(
+            cfg.embed_colors
+                .iter()
+                .filter_map(|(name, color)| {
+                    let activity_type = ActivityType::into_enum_iter()
+                        .find(|ty| ty.command_name().eq_ignore_ascii_case(name));
+                    match (activity_type, parse_hex_color(color)) {
+                        (Some(activity_type), Some(color)) => Some((activity_type, color)),
+                        (None, _) => {
+                            error!("Unknown activity type '{}' for embed color", name);
+                            None
+                        }
+                        (_, None) => {
+                            error!("Invalid embed color '{}' for '{}'", color, name);
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        );
+        let embed_style = EmbedStyle {
+            colors: embed_colors,
+            show_creator: cfg.show_creator,
+            max_listed: cfg.max_listed_members.unwrap_or(DEFAULT_MAX_LISTED_MEMBERS),
+        };
+        let event_channels = v
+            .into_iter()
+            .map(|(channel, (filter_label, filter))| {
+                let config = EventChannelConfig {
+                    filter,
+                    filter_label,
+                    delete_user_messages: !cfg.keep_user_messages.contains(&channel),
+                    show_summary: cfg.summary_channels.contains(&channel),
+                    button_labels: cfg.button_labels.clone(),
+                    embed_style: embed_style.clone(),
+                    create_threads: cfg.thread_channels.contains(&channel),
+                    alert_role: cfg
+                        .lfg_role
+                        .filter(|_| channel != cfg.all_lfg || only_all_lfg),
+                    new_event_role: cfg.new_event_roles.get(&channel).copied(),
+                    min_confirmed: cfg.min_confirmed_to_post.unwrap_or(0) as usize,
+                };
+                (channel, config)
+            })
+            .collect();
+        let shortcuts = cfg
+            .shortcuts
+            .iter()
+            .filter_map(|s| match Activity::activity_with_id_prefix(&s.activity) {
+                Some(activity) => Some(Shortcut {
+                    name: s.name.clone(),
+                    activity,
+                }),
+                None => {
+                    error!(
+                        "Unknown activity '{}' for shortcut '{}'",
+                        s.activity, s.name
+                    );
+                    None
+                }
+            })
+            .collect();
+        let default_descriptions = cfg
+            .default_descriptions
+            .iter()
+            .filter_map(
+                |(prefix, template)| match Activity::activity_with_id_prefix(prefix) {
+                    Some(activity) => Some((activity, template.clone())),
+                    None => {
+                        error!("Unknown activity '{}' for default description", prefix);
+                        None
+                    }
+                },
+            )
+            .collect();
+        Ok(GuildConfig {
+            embed_config: EmbedManagerConfig {
+                event_channels,
+                embed_style,
+                button_labels: cfg.button_labels.clone(),
+            },
+            recurrence_announcement_channel: cfg.recurrence_announcement_channel,
+            follow_up_maybes: cfg.follow_up_maybes,
+            date_format: cfg.date_format,
+            weekly_reset: cfg.weekly_reset,
+            button_labels: cfg.button_labels.clone(),
+            shortcuts,
+            message_linger: cfg
+                .message_linger_minutes
+                .map(|minutes| config_minutes("message_linger_minutes", minutes))
+                .transpose()?,
+            recurrence_lapse_weeks: cfg.recurrence_lapse_weeks,
+            default_descriptions,
+            announcement_channel: cfg.announcement_channel,
+            auto_alt: cfg.auto_alt,
+            lock_started_events: cfg.lock_started_events,
+            join_conflicts: cfg.join_conflicts,
+            conflict_window_minutes: cfg.conflict_window_minutes,
+            scheduler_config: cfg.scheduler,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveTime, Weekday};
+    use serenity::utils::Color;
+    use tempdir::TempDir;
+    use test_env_log::test;
+
+    fn parse_guild_config(extra: &str) -> Result<SingleGuildConfigToml, toml::de::Error> {
+        let config = format!(
+            "raid_lfg = 1\npve_lfg = 2\npvp_lfg = 3\nspecial_lfg = 4\nall_lfg = 5\n{}",
+            extra
+        );
+        toml::from_str(&config)
+    }
+
+    #[test]
+    fn event_channel_labels_match_filters() {
+        let cfg = GuildConfig::try_from(&parse_guild_config("").unwrap()).unwrap();
+        let channels = &cfg.embed_config.event_channels;
+        let expected = [
+            (1, "Raid events"),
+            (2, "Dungeon, Gambit, PvE, Seasonal events"),
+            (3, "Crucible events"),
+            (4, "Custom events"),
+            (5, "All events"),
+        ];
+        for (channel, label) in expected {
+            let config = &channels[&ChannelId(channel)];
+            assert_eq!(config.filter_label, label);
+            assert_eq!(config.alert_role, None);
+
+            // Each channel shows exactly the activity types its label names.
+            for activity in Activity::into_enum_iter() {
+                let event = Event {
+                    activity,
+                    ..Default::default()
+                };
+                let activity_type = activity.activity_type().name();
+                assert_eq!(
+                    (config.filter)(&event),
+                    label == "All events" || label.contains(activity_type),
+                    "{} in channel {}",
+                    activity,
+                    channel
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn lfg_role_pinged_in_activity_type_channels() {
+        let cfg = GuildConfig::try_from(&parse_guild_config("lfg_role = 9").unwrap()).unwrap();
+        let channels = &cfg.embed_config.event_channels;
+        for channel in 1..=4 {
+            assert_eq!(channels[&ChannelId(channel)].alert_role, Some(RoleId(9)));
+        }
+        assert_eq!(channels[&ChannelId(5)].alert_role, None);
+    }
+
+    #[test]
+    fn lfg_role_pinged_in_all_lfg_when_only_channel() {
+        let config =
+            "raid_lfg = 5\npve_lfg = 5\npvp_lfg = 5\nspecial_lfg = 5\nall_lfg = 5\nlfg_role = 9";
+        let cfg = GuildConfig::try_from(&toml::from_str::<SingleGuildConfigToml>(config).unwrap())
+            .unwrap();
+        let channels = &cfg.embed_config.event_channels;
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[&ChannelId(5)].alert_role, Some(RoleId(9)));
+        assert_eq!(channels[&ChannelId(5)].filter_label, "All events");
+    }
+
+    #[test]
+    fn new_event_roles_per_channel() {
+        let cfg = parse_guild_config(
+            "[new_event_roles]
+1 = 7
+5 = 8",
+        )
+        .unwrap();
+        let cfg = GuildConfig::try_from(&cfg).unwrap();
+        let channels = &cfg.embed_config.event_channels;
+        assert_eq!(channels[&ChannelId(1)].new_event_role, Some(RoleId(7)));
+        assert_eq!(channels[&ChannelId(5)].new_event_role, Some(RoleId(8)));
+        for channel in 2..=4 {
+            assert_eq!(channels[&ChannelId(channel)].new_event_role, None);
+        }
+    }
+
+    #[test]
+    fn embed_colors_from_toml() {
+        let cfg = parse_guild_config(
+            "[embed_colors]\nraid = \"#9b59b6\"\npvp = \"not a color\"\nstrikes = \"#ffffff\"",
+        );
+        let cfg = GuildConfig::try_from(&cfg.unwrap()).unwrap();
+        let colors = &cfg.embed_config.embed_style.colors;
+        assert_eq!(colors.color(ActivityType::Raid), Color::new(0x9b59b6));
+        assert_eq!(
+            colors.color(ActivityType::Crucible),
+            ActivityType::Crucible.color()
+        );
+        for channel in cfg.embed_config.event_channels.values() {
+            assert_eq!(&channel.embed_style.colors, colors);
+        }
+    }
+
+    #[test]
+    fn button_labels_from_toml() {
+        let cfg = parse_guild_config("[button_labels]\njoin = { label = \"I'm in\" }").unwrap();
+        let cfg = GuildConfig::try_from(&cfg).unwrap();
+        assert_eq!(cfg.button_labels.join.label, "I'm in");
+        assert_eq!(cfg.button_labels.leave, ButtonLabels::default().leave);
+        // Tracked messages outside of the event channels are refreshed with the same labels.
+        assert_eq!(cfg.embed_config.button_labels, cfg.button_labels);
+        for channel in cfg.embed_config.event_channels.values() {
+            assert_eq!(channel.button_labels, cfg.button_labels);
+        }
+    }
+
+    #[test]
+    fn max_listed_members_from_toml() {
+        let cfg = GuildConfig::try_from(&parse_guild_config("").unwrap()).unwrap();
+        assert_eq!(
+            cfg.embed_config.embed_style.max_listed,
+            DEFAULT_MAX_LISTED_MEMBERS
+        );
+
+        let cfg =
+            GuildConfig::try_from(&parse_guild_config("max_listed_members = 5").unwrap()).unwrap();
+        assert_eq!(cfg.embed_config.embed_style.max_listed, 5);
+        for channel in cfg.embed_config.event_channels.values() {
+            assert_eq!(channel.embed_style.max_listed, 5);
+        }
+    }
+
+    #[test]
+    fn join_conflicts_from_toml() {
+        let cfg = GuildConfig::try_from(&parse_guild_config("").unwrap()).unwrap();
+        assert_eq!(cfg.join_conflicts, ConflictCheck::Off);
+        assert_eq!(cfg.conflict_window_minutes, None);
+
+        let cfg = parse_guild_config("join_conflicts = \"block\"\nconflict_window_minutes = 90");
+        let cfg = GuildConfig::try_from(&cfg.unwrap()).unwrap();
+        assert_eq!(cfg.join_conflicts, ConflictCheck::Block);
+        assert_eq!(cfg.conflict_window_minutes, Some(90));
+
+        assert!(parse_guild_config("join_conflicts = \"sometimes\"").is_err());
+    }
+
+    #[test]
+    fn weekly_reset_from_toml() {
+        let cfg = GuildConfig::try_from(&parse_guild_config("").unwrap()).unwrap();
+        assert_eq!(cfg.weekly_reset, WeeklyReset::default());
+
+        let cfg = parse_guild_config("weekly_reset = { weekday = \"fri\", time = \"09:00:00\" }");
+        let cfg = GuildConfig::try_from(&cfg.unwrap()).unwrap();
+        assert_eq!(cfg.weekly_reset.weekday, Weekday::Fri);
+        assert_eq!(cfg.weekly_reset.time, NaiveTime::from_hms(9, 0, 0));
+
+        // Anything left out keeps its default.
+        let cfg = parse_guild_config("weekly_reset = { weekday = \"wed\" }");
+        let cfg = GuildConfig::try_from(&cfg.unwrap()).unwrap();
+        assert_eq!(cfg.weekly_reset.time, WeeklyReset::default().time);
+
+        assert!(parse_guild_config("weekly_reset = { weekday = \"someday\" }").is_err());
+    }
+
+    #[test]
+    fn shortcut_names_validated() {
+        let shortcut = |name: &str| {
+            format!(
+                "shortcuts = [{{ name = \"{}\", activity = \"vog\" }}]",
+                name
+            )
+        };
+        let cfg = parse_guild_config(&shortcut("vog_hard-mode2")).unwrap();
+        assert_eq!(
+            GuildConfig::try_from(&cfg).unwrap().shortcuts[0].name,
+            "vog_hard-mode2"
+        );
+
+        let too_long = "a".repeat(33);
+        for name in ["", "Raid", "raid night", "raid!", too_long.as_str()] {
+            let err = parse_guild_config(&shortcut(name)).unwrap_err();
+            assert!(
+                err.to_string().contains("invalid shortcut name"),
+                "{}: {}",
+                name,
+                err
+            );
         }
     }
+
+    #[test]
+    fn recurrence_announcement_channel_not_event_channel() {
+        let cfg = parse_guild_config("recurrence_announcement_channel = 6").unwrap();
+        let cfg = GuildConfig::try_from(&cfg).unwrap();
+        assert_eq!(cfg.recurrence_announcement_channel, Some(ChannelId(6)));
+
+        for channel in 1..=5 {
+            let cfg = parse_guild_config(&format!("recurrence_announcement_channel = {}", channel))
+                .unwrap();
+            let err = GuildConfig::try_from(&cfg).unwrap_err();
+            assert!(
+                err.to_string().contains("recurrence_announcement_channel"),
+                "{}: {}",
+                channel,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn announcement_channel_not_event_channel() {
+        let cfg = parse_guild_config("announcement_channel = 6").unwrap();
+        let cfg = GuildConfig::try_from(&cfg).unwrap();
+        assert_eq!(cfg.announcement_channel, Some(ChannelId(6)));
+
+        for channel in 1..=5 {
+            let cfg = parse_guild_config(&format!("announcement_channel = {}", channel)).unwrap();
+            let err = GuildConfig::try_from(&cfg).unwrap_err();
+            assert!(
+                err.to_string().contains("announcement_channel"),
+                "{}: {}",
+                channel,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn scheduler_config_defaults_when_absent() {
+        let cfg = parse_guild_config("").unwrap();
+        let defaults = EventSchedulerConfig::default();
+        assert_eq!(cfg.scheduler.check_in, defaults.check_in);
+        assert_eq!(cfg.scheduler.alert, defaults.alert);
+        assert_eq!(cfg.scheduler.cleanup, defaults.cleanup);
+    }
+
+    #[test]
+    fn scheduler_config_from_toml() {
+        let cfg =
+            parse_guild_config("scheduler = { alert_minutes = 15, cleanup_minutes = 90 }").unwrap();
+        let config = GuildConfig::try_from(&cfg).unwrap().scheduler_config;
+        assert_eq!(config.alert, Duration::from_secs(15 * 60));
+        assert_eq!(config.cleanup, Duration::from_secs(90 * 60));
+        // Timings that aren't given keep their default.
+        assert_eq!(config.check_in, EventSchedulerConfig::default().check_in);
+
+        let cfg = parse_guild_config("[scheduler]\ncheck_in_minutes = 60").unwrap();
+        assert_eq!(cfg.scheduler.check_in, Some(Duration::from_secs(60 * 60)));
+    }
+
+    #[test]
+    fn scheduler_check_in_off_unless_configured() {
+        assert_eq!(parse_guild_config("").unwrap().scheduler.check_in, None);
+        let cfg = parse_guild_config("scheduler = { check_in_minutes = 0 }").unwrap();
+        assert_eq!(cfg.scheduler.check_in, None);
+    }
+
+    #[test]
+    fn scheduler_config_rejects_timings_on_wrong_side_of_event() {
+        for extra in [
+            "scheduler = { alert_minutes = 0 }",
+            "scheduler = { alert_minutes = -5 }",
+            "scheduler = { cleanup_minutes = -30 }",
+            "scheduler = { check_in_minutes = -10 }",
+            "scheduler = { check_in_minutes = 5, alert_minutes = 10 }",
+            "scheduler = { check_in_minutes = 10, alert_minutes = 10 }",
+        ] {
+            let err = parse_guild_config(extra).unwrap_err();
+            assert!(err.to_string().contains("must be"), "{}: {}", extra, err);
+        }
+    }
+
+    #[test]
+    fn timings_past_a_year_rejected() {
+        let year = MAX_CONFIG_MINUTES;
+        let cfg = parse_guild_config(&format!("scheduler = {{ cleanup_minutes = {} }}", year));
+        assert_eq!(
+            cfg.unwrap().scheduler.cleanup,
+            Duration::from_secs(year * 60)
+        );
+        for extra in [
+            format!("scheduler = {{ cleanup_minutes = {} }}", year + 1),
+            format!("scheduler = {{ check_in_minutes = {} }}", i64::MAX),
+        ] {
+            let err = parse_guild_config(&extra).unwrap_err();
+            assert!(err.to_string().contains("at most"), "{}: {}", extra, err);
+        }
+
+        let cfg = parse_guild_config(&format!("message_linger_minutes = {}", i64::MAX)).unwrap();
+        let err = GuildConfig::try_from(&cfg).unwrap_err();
+        assert!(
+            err.to_string().contains("message_linger_minutes"),
+            "{}",
+            err
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_delete_orphaned_guild_data() {
+        let tempdir = TempDir::new("GuildManager_test").unwrap();
+        let builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        builder.new_scoped("1").await.unwrap();
+        builder.new_scoped("2").await.unwrap();
+        builder.new_scoped("not_a_guild").await.unwrap();
+
+        let current_guilds = vec![GuildId(1)].into_iter().collect();
+        delete_orphaned_guild_data(&builder, &current_guilds)
+            .await
+            .unwrap();
+
+        assert!(tempdir.path().join("1").is_dir());
+        assert!(!tempdir.path().join("2").exists());
+        assert!(tempdir.path().join("not_a_guild").is_dir());
+    }
 }
@@ -1,15 +1,16 @@
 use crate::{guild::GuildManager, store::PersistentStoreBuilder};
 use serenity::{
     async_trait,
+    client::bridge::gateway::GatewayIntents,
     model::{
         gateway::Ready,
-        guild::{Guild, GuildUnavailable},
+        guild::{Guild, GuildUnavailable, Member},
         id::GuildId,
         interactions::Interaction,
     },
     prelude::*,
 };
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tracing::{debug, error, info};
 use tracing_subscriber::{prelude::*, EnvFilter};
 
@@ -17,14 +18,19 @@ use tracing_subscriber::{prelude::*, EnvFilter};
 mod activity;
 
 mod command;
+mod custom_id;
 mod embed;
 mod event;
 mod guild;
+mod metrics;
 mod store;
 mod util;
 
-#[derive(Default)]
-struct Handler;
+struct Handler {
+    /// Whether the privileged GUILD_MEMBERS intent is enabled, which keeps the guilds' members
+    /// cached.
+    guild_members_intent: bool,
+}
 
 #[async_trait]
 impl EventHandler for Handler {
@@ -42,8 +48,41 @@ impl EventHandler for Handler {
         let guild_manager = typemap
             .get::<GuildManager>()
             .expect("GuildManager uninitialized");
-        if let Err(err) = guild_manager.add_guilds(&ctx, guilds).await {
-            error!("Error adding new guilds: {:?}", err);
+        let added = guild_manager.add_guilds(&ctx, guilds).await;
+
+        // Members may have changed their names while we were offline. This fires again with every
+        // guild on each later GuildCreate, so only newly added guilds are refreshed. Without the
+        // guilds' members cached, only the members of events are looked up.
+        for guild_id in added {
+            if !self.guild_members_intent {
+                if let Err(err) = guild_manager.fetch_member_names(&ctx, guild_id).await {
+                    error!(
+                        "Error fetching member names for guild {}: {:?}",
+                        guild_id, err
+                    );
+                }
+                continue;
+            }
+            let names = ctx
+                .cache
+                .guild_field(guild_id, |guild| {
+                    guild
+                        .members
+                        .iter()
+                        .map(|(id, member)| (*id, member.display_name().into_owned()))
+                        .collect::<HashMap<_, _>>()
+                })
+                .await;
+            let names = match names {
+                Some(names) => names,
+                None => continue,
+            };
+            if let Err(err) = guild_manager.refresh_member_names(guild_id, &names).await {
+                error!(
+                    "Error refreshing member names for guild {}: {:?}",
+                    guild_id, err
+                );
+            }
         }
     }
 
@@ -60,6 +99,17 @@ impl EventHandler for Handler {
         guild_manager.removed_from_guild(guild.id).await;
     }
 
+    // Also sent when a member's username changes, not just their guild nickname.
+    async fn guild_member_update(&self, ctx: Context, _old: Option<Member>, new: Member) {
+        let typemap = ctx.data.read().await;
+        let guild_manager = typemap
+            .get::<GuildManager>()
+            .expect("GuildManager uninitialized");
+        if let Err(err) = guild_manager.member_updated(&new).await {
+            error!("Error updating member {}: {:?}", new.user.id, err);
+        }
+    }
+
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
         let typemap = ctx.data.read().await;
         let guild_manager = typemap
@@ -108,15 +158,72 @@ async fn main() {
         .await
         .expect("Failed to create PersistentStoreBuilder");
     let guild_config_file = std::env::var("GUILD_CONFIG_FILE").expect("Missing $GUILD_CONFIG_FILE");
-    let guild_manager =
-        GuildManager::new(store_builder, guild_config_file).expect("Failed to create GuildManager");
+    let guild_manager = Arc::new(
+        GuildManager::new(store_builder, guild_config_file).expect("Failed to create GuildManager"),
+    );
 
+    // GUILD_MEMBERS is privileged and has to be enabled for the bot in the developer portal, or
+    // Discord refuses the connection, so it's opt-in. It's needed to hear about members changing
+    // their names as it happens.
+    let guild_members_intent = std::env::var("GUILD_MEMBERS_INTENT").map_or(false, |v| v == "1");
+    let mut intents = GatewayIntents::non_privileged();
+    if guild_members_intent {
+        intents |= GatewayIntents::GUILD_MEMBERS;
+    }
     let mut client = Client::builder(&token)
         .application_id(app_id)
-        .event_handler(Handler::default())
-        .type_map_insert::<GuildManager>(Arc::new(guild_manager))
+        .intents(intents)
+        .event_handler(Handler {
+            guild_members_intent,
+        })
+        .type_map_insert::<GuildManager>(guild_manager.clone())
         .await
         .expect("Error creating client");
 
+    // Stop the schedulers and finish writing to the stores before disconnecting, which makes
+    // client.start() below return.
+    let shard_manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        shutdown_signal().await;
+        info!("Shutting down");
+        guild_manager.shutdown().await;
+        shard_manager.lock().await.shutdown_all().await;
+    });
+
     client.start().await.expect("Client error");
+
+    // Drop the client first, since it holds onto the GuildManager through its data TypeMap.
+    drop(client);
+    info!("Shut down cleanly");
+}
+
+/// Waits for ctrl-c or, on Unix, SIGTERM.
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            error!("Failed to listen for ctrl-c: {:?}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(err) => {
+                error!("Failed to listen for SIGTERM: {:?}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
@@ -0,0 +1,146 @@
+//! Counters and gauges for keeping an eye on the bot, recorded through the `metrics` crate's facade
+//! when the `metrics` feature is enabled so that any exporter (e.g. Prometheus) can be hooked up to
+//! them. With the feature disabled, these functions do nothing and compile away entirely.
+
+use serenity::model::id::GuildId;
+
+pub const EVENTS_CREATED: &str = "failsafe_events_created_total";
+pub const EVENT_JOINS: &str = "failsafe_event_joins_total";
+pub const EVENT_LEAVES: &str = "failsafe_event_leaves_total";
+pub const EVENTS_ALERTED: &str = "failsafe_events_alerted_total";
+pub const EVENTS_CLEANED_UP: &str = "failsafe_events_cleaned_up_total";
+pub const ACTIVE_EVENTS: &str = "failsafe_active_events";
+
+#[inline]
+pub fn event_created() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(EVENTS_CREATED).increment(1);
+}
+
+#[inline]
+pub fn event_joined() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(EVENT_JOINS).increment(1);
+}
+
+#[inline]
+pub fn event_left() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(EVENT_LEAVES).increment(1);
+}
+
+#[inline]
+pub fn event_alerted() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(EVENTS_ALERTED).increment(1);
+}
+
+#[inline]
+pub fn event_cleaned_up() {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(EVENTS_CLEANED_UP).increment(1);
+}
+
+/// Records how many events a guild currently has. Each guild's count is labeled with its ID.
+#[inline]
+#[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+pub fn set_active_events(guild: GuildId, count: usize) {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!(ACTIVE_EVENTS, "guild" => guild.to_string()).set(count as f64);
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+    use crate::{
+        activity::Activity,
+        event::{Event, EventManager, JoinKind},
+    };
+    use chrono::Utc;
+    use chrono_tz::Tz;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use serenity::model::user::User;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_event_metrics() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // The recorder is only installed for this thread, so this runs on a current-thread
+        // runtime to keep other tests' events out of the counts.
+        ::metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                let manager = EventManager::default().await;
+                let t = Utc::now().with_timezone(&Tz::PST8PDT);
+                let creator = User::default();
+                let mut events = vec![];
+                for _ in 0..2 {
+                    let event = manager
+                        .create_event(&creator, Activity::Custom, t, "", Default::default())
+                        .await
+                        .unwrap();
+                    events.push(event.id);
+                }
+
+                let mut joiner = User::default();
+                joiner.id = 2.into();
+                let join = |kind| {
+                    let joiner = &joiner;
+                    move |event: Option<&mut Event>| event.unwrap().join(joiner, kind).unwrap()
+                };
+                manager
+                    .edit_membership(&events[0], joiner.id, join(JoinKind::Confirmed))
+                    .await
+                    .unwrap();
+                // Switching how they joined counts as joining again.
+                manager
+                    .edit_membership(&events[0], joiner.id, join(JoinKind::Maybe))
+                    .await
+                    .unwrap();
+                manager
+                    .edit_membership(&events[0], joiner.id, |event| {
+                        event.unwrap().leave(&joiner).unwrap();
+                    })
+                    .await
+                    .unwrap();
+                // Edits that aren't the member joining or leaving, like kicks, aren't counted.
+                manager
+                    .edit_event(&events[0], |event| {
+                        let event = event.unwrap();
+                        event.join(&joiner, JoinKind::Confirmed).unwrap();
+                        event.leave(&joiner).unwrap();
+                    })
+                    .await
+                    .unwrap();
+
+                // Ended events aren't active.
+                let ended = manager
+                    .create_event(&creator, Activity::Custom, t, "", Default::default())
+                    .await
+                    .unwrap();
+                manager
+                    .edit_event(&ended.id, |event| event.unwrap().end())
+                    .await
+                    .unwrap();
+                manager.delete_event(&events[1]).await.unwrap();
+            })
+        });
+
+        let values: HashMap<_, _> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_owned(), value))
+            .collect();
+        assert_eq!(values[EVENTS_CREATED], DebugValue::Counter(3));
+        assert_eq!(values[EVENT_JOINS], DebugValue::Counter(2));
+        assert_eq!(values[EVENT_LEAVES], DebugValue::Counter(1));
+        assert_eq!(values[ACTIVE_EVENTS], DebugValue::Gauge(1.0.into()));
+        assert!(!values.contains_key(EVENTS_ALERTED));
+    }
+}
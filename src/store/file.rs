@@ -0,0 +1,96 @@
+use super::Store;
+use crate::util::*;
+use anyhow::{Context as _, Result};
+use fs2::FileExt;
+use serenity::async_trait;
+use std::{
+    io::SeekFrom,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    sync::Mutex,
+};
+
+async fn open_read_append(path: impl AsRef<Path>) -> Result<File> {
+    Ok(OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(&path)
+        .await?)
+}
+
+/// A Store that keeps its value in a file of its own, which is replaced whole on each store.
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl FileStore {
+    pub async fn open(path: &Path) -> Result<Self> {
+        let file = open_read_append(path)
+            .await
+            .with_context(|| format!("Failed to open store file: {}", path.display()))?;
+
+        let std_file = file
+            .try_into_std()
+            .expect("No operations should be in-flight");
+        std_file.try_lock_exclusive().with_context(|| format!("Failed to lock store file ({}) exclusively; was a store with this name already created?", path.display()))?;
+
+        Ok(FileStore {
+            path: path.to_owned(),
+            file: Mutex::new(File::from_std(std_file)),
+        })
+    }
+}
+
+#[async_trait]
+impl Store for FileStore {
+    async fn load(&self) -> Result<Vec<u8>> {
+        let mut file = self.file.lock().await;
+        file.seek(SeekFrom::Start(0))
+            .await
+            .context("Couldn't seek to start of file")?;
+
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .await
+            .context("Failed to read store file")?;
+        Ok(bytes)
+    }
+
+    async fn store(&self, bytes: Vec<u8>) -> Result<()> {
+        // Lock the file before doing the atomic write.
+        let mut file = self.file.lock().await;
+
+        // Atomically write to the store file through a tempfile.
+        let (temppath, mut tempfile) = tempfile().await.context("Unable to create tempfile")?;
+        tempfile
+            .write_all(&bytes)
+            .await
+            .context("Failed to write store file")?;
+        tempfile
+            .flush()
+            .await
+            .context("Failed to flush store file")?;
+        std::mem::drop(tempfile);
+
+        // Copy is used instead of rename in case the paths are on different devices/mounts
+        fs::copy(&temppath, &self.path)
+            .await
+            .context("Failed to atomically replace event store")?;
+        fs::remove_file(temppath)
+            .await
+            .context("Failed to delete temp file")?;
+
+        // Reopen the file now that its been replaced.
+        *file = open_read_append(&self.path)
+            .await
+            .with_context(|| format!("Failed to reopen store file: {}", self.path.display()))?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,309 @@
+use anyhow::{format_err, Context as _, Result};
+use chrono::Utc;
+use serde::{de::DeserializeOwned, Serialize};
+use serenity::async_trait;
+use std::{
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+use tokio::fs;
+use tracing::error;
+
+mod file;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+/// Where PersistentStores keep their data, chosen with $PERSISTENT_STORE_BACKEND.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StoreBackend {
+    /// A JSON file per store, rewritten whole on each store. This is the default.
+    File,
+    /// A row per store in a SQLite database in the store directory.
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+impl FromStr for StoreBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "file" => Ok(StoreBackend::File),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(StoreBackend::Sqlite),
+            #[cfg(not(feature = "sqlite"))]
+            "sqlite" => Err(format_err!(
+                "The sqlite store backend requires building with the sqlite feature"
+            )),
+            _ => Err(format_err!("Unknown store backend: {}", s)),
+        }
+    }
+}
+
+/// The raw storage behind a single PersistentStore, which holds its serialized value.
+#[async_trait]
+pub trait Store: std::fmt::Debug + Send + Sync {
+    /// Returns the stored bytes, which are empty if nothing has been stored yet.
+    async fn load(&self) -> Result<Vec<u8>>;
+
+    /// Replaces the stored bytes.
+    async fn store(&self, bytes: Vec<u8>) -> Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct PersistentStoreBuilder {
+    store_dir: PathBuf,
+    backend: StoreBackend,
+}
+
+impl PersistentStoreBuilder {
+    /// Create a new PersistentStoreBuilder that will create PersistentStores in the given
+    /// directory, using the backend named by $PERSISTENT_STORE_BACKEND (or files if it's unset).
+    pub async fn new(dir: impl Into<PathBuf>) -> Result<Self> {
+        let backend = match std::env::var("PERSISTENT_STORE_BACKEND") {
+            Ok(backend) => StoreBackend::from_str(&backend)?,
+            Err(_) => StoreBackend::File,
+        };
+        Self::with_backend(dir, backend).await
+    }
+
+    /// Create a new PersistentStoreBuilder that will create PersistentStores in the given
+    /// directory using the given backend.
+    pub async fn with_backend(dir: impl Into<PathBuf>, backend: StoreBackend) -> Result<Self> {
+        let store_dir = dir.into();
+        if fs::create_dir(&store_dir).await.is_err() {
+            if !fs::metadata(&store_dir)
+                .await
+                .with_context(|| format!("Failed to get dir metadata: {}", store_dir.display()))?
+                .is_dir()
+            {
+                return Err(format_err!(
+                    "File already exists, can't create directory: {}",
+                    store_dir.display()
+                ));
+            }
+        }
+        Ok(PersistentStoreBuilder { store_dir, backend })
+    }
+
+    /// Create a new PersistentStoreBuilder for the given subdirectory.
+    pub async fn new_scoped(&self, dir: impl AsRef<Path>) -> Result<Self> {
+        Self::with_backend(self.store_dir.join(dir.as_ref()), self.backend).await
+    }
+
+    /// List the names of all subdirectories, i.e. those that scoped builders could be created for.
+    pub async fn scoped_names(&self) -> Result<Vec<String>> {
+        let mut entries = fs::read_dir(&self.store_dir)
+            .await
+            .with_context(|| format!("Failed to read dir: {}", self.store_dir.display()))?;
+
+        let mut names = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str() {
+                names.push(name.to_owned());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Delete the directory that this PersistentStoreBuilder represents, along with all contents.
+    pub async fn delete(self) -> Result<()> {
+        Ok(fs::remove_dir_all(&self.store_dir).await?)
+    }
+
+    pub async fn build<T, P: AsRef<Path>>(&self, name: P) -> Result<PersistentStore<T>> {
+        let path = self.store_dir.join(name.as_ref());
+        let store: Box<dyn Store> = match self.backend {
+            StoreBackend::File => Box::new(file::FileStore::open(&path).await?),
+            #[cfg(feature = "sqlite")]
+            StoreBackend::Sqlite => {
+                let name = name.as_ref().to_string_lossy();
+                Box::new(sqlite::SqliteStore::open(&self.store_dir, &name).await?)
+            }
+        };
+
+        Ok(PersistentStore {
+            path,
+            store,
+            data_type: Default::default(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct PersistentStore<T> {
+    /// Where the store's file is, or would be for the file backend. Corrupt data is backed up
+    /// next to it.
+    path: PathBuf,
+    store: Box<dyn Store>,
+    data_type: PhantomData<T>,
+}
+
+impl<T> PersistentStore<T>
+where
+    T: Default + Serialize + DeserializeOwned,
+{
+    pub async fn load(&self) -> Result<T> {
+        let bytes = self.store.load().await?;
+
+        // Nothing might have been stored yet, in which case we return the default.
+        if bytes.is_empty() {
+            return Ok(T::default());
+        }
+
+        match serde_json::from_slice(&bytes) {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                // Rather than failing (and taking down everything that depends on this store),
+                // move the corrupt data aside where it can be inspected and start fresh.
+                let backup = self.corrupt_backup_path();
+                error!(
+                    "Failed to deserialize store file {} as {}, moving it to {} and starting \
+                     fresh: {}",
+                    self.path.display(),
+                    std::any::type_name::<T>(),
+                    backup.display(),
+                    err
+                );
+                fs::write(&backup, bytes).await.with_context(|| {
+                    format!("Failed to back up corrupt store file: {}", backup.display())
+                })?;
+                self.store
+                    .store(Vec::new())
+                    .await
+                    .context("Failed to clear corrupt store")?;
+                Ok(T::default())
+            }
+        }
+    }
+
+    fn corrupt_backup_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_owned();
+        name.push(format!(
+            ".corrupt-{}",
+            Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+        ));
+        self.path.with_file_name(name)
+    }
+
+    pub async fn store(&self, value: &T) -> Result<()> {
+        let json = serde_json::to_vec(value)
+            .with_context(|| format!("Failed to serialize {}", std::any::type_name::<T>()))?;
+        self.store.store(json).await
+    }
+}
+
+#[cfg(test)]
+impl<T> PersistentStore<T> {
+    /// A store that keeps its value in memory and fails every store after the first `ok_stores`,
+    /// for testing how failed writes are handled.
+    pub fn failing_after(ok_stores: usize) -> Self {
+        PersistentStore {
+            path: PathBuf::new(),
+            store: Box::new(FailingStore {
+                bytes: Default::default(),
+                ok_stores: std::sync::atomic::AtomicUsize::new(ok_stores),
+            }),
+            data_type: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Debug)]
+struct FailingStore {
+    bytes: std::sync::Mutex<Vec<u8>>,
+    ok_stores: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl Store for FailingStore {
+    async fn load(&self) -> Result<Vec<u8>> {
+        Ok(self.bytes.lock().unwrap().clone())
+    }
+
+    async fn store(&self, bytes: Vec<u8>) -> Result<()> {
+        use std::sync::atomic::Ordering;
+        let ok = self
+            .ok_stores
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok();
+        if !ok {
+            return Err(format_err!("Simulated store failure"));
+        }
+        *self.bytes.lock().unwrap() = bytes;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Event;
+    use tempdir::TempDir;
+    use test_env_log::test;
+
+    const TEMPDIR_PREFIX: &'static str = "PersistentStore_test";
+
+    #[test(tokio::test)]
+    async fn test_store_name_collisions() {
+        let tempdir = TempDir::new(TEMPDIR_PREFIX).unwrap();
+        let builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+
+        let _store = builder.build::<String, _>("foo").await.unwrap();
+        assert!(builder.build::<String, _>("foo").await.is_err());
+        assert!(builder.new_scoped("foo").await.is_err());
+
+        let builder = builder.new_scoped("bar").await.unwrap();
+        let _store = builder.build::<String, _>("bar").await.unwrap();
+        assert!(builder.build::<String, _>("bar").await.is_err());
+        assert!(builder.new_scoped("bar").await.is_err());
+    }
+
+    #[test(tokio::test)]
+    async fn test_store_load() {
+        let tempdir = TempDir::new(TEMPDIR_PREFIX).unwrap();
+        let builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let store = builder.build::<Event, _>("foo").await.unwrap();
+
+        let mut event = Event::default();
+        event.description = "foobar".to_owned();
+
+        store.store(&event).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), event);
+    }
+
+    #[test(tokio::test)]
+    async fn test_corrupt_store_backed_up() {
+        let tempdir = TempDir::new(TEMPDIR_PREFIX).unwrap();
+        let builder = PersistentStoreBuilder::new(tempdir.path()).await.unwrap();
+        let corrupt = br#"[{"id":"vog1","#;
+        std::fs::write(tempdir.path().join("events.json"), corrupt).unwrap();
+
+        let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+        assert!(store.load().await.unwrap().is_empty());
+
+        // The corrupt contents were moved aside.
+        let backups: Vec<_> = std::fs::read_dir(tempdir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                let name = path.file_name().unwrap().to_string_lossy();
+                name.starts_with("events.json.corrupt-")
+            })
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(std::fs::read(&backups[0]).unwrap(), corrupt);
+
+        // The store starts fresh and is usable again.
+        assert!(store.load().await.unwrap().is_empty());
+        let events = vec![Event::default()];
+        store.store(&events).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), events);
+    }
+}
@@ -0,0 +1,201 @@
+use super::Store;
+use anyhow::{Context as _, Result};
+use fs2::FileExt;
+use rusqlite::{params, Connection, OptionalExtension};
+use serenity::async_trait;
+use std::{
+    fs::{self, File},
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::task::spawn_blocking;
+use tracing::info;
+
+// Each store directory gets one database, with a row for each store in it.
+const DATABASE_NAME: &str = "store.sqlite3";
+
+/// A Store that keeps its value in a row of a SQLite database, so that storing a value doesn't
+/// rewrite a whole file and is protected against crashes by SQLite's transactions.
+#[derive(Debug)]
+pub struct SqliteStore {
+    name: String,
+    conn: Arc<Mutex<Connection>>,
+    // Held for as long as the store exists, so that a store can only be built once per name, just
+    // like with FileStore.
+    _lock: File,
+}
+
+impl SqliteStore {
+    /// Opens the store with the given name in the directory's database, importing the store's JSON
+    /// file if it was previously kept in one.
+    pub async fn open(dir: &Path, name: &str) -> Result<Self> {
+        let lock_path = dir.join(format!("{}.lock", name));
+        let lock = File::create(&lock_path)
+            .with_context(|| format!("Failed to create lock file: {}", lock_path.display()))?;
+        lock.try_lock_exclusive().with_context(|| {
+            format!(
+                "Failed to lock store '{}' exclusively; was a store with this name already created?",
+                name
+            )
+        })?;
+
+        let db_path = dir.join(DATABASE_NAME);
+        let json_path = dir.join(name);
+        let owned_name = name.to_owned();
+        let conn = spawn_blocking(move || -> Result<Connection> {
+            let conn = Connection::open(&db_path)
+                .with_context(|| format!("Failed to open database: {}", db_path.display()))?;
+            conn.busy_timeout(Duration::from_secs(5))?;
+            conn.execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE IF NOT EXISTS stores (name TEXT PRIMARY KEY, value BLOB NOT NULL);",
+            )
+            .context("Failed to create stores table")?;
+            import_json_file(&conn, &owned_name, &json_path)?;
+            Ok(conn)
+        })
+        .await??;
+
+        Ok(SqliteStore {
+            name: name.to_owned(),
+            conn: Arc::new(Mutex::new(conn)),
+            _lock: lock,
+        })
+    }
+}
+
+/// Imports the JSON file a store was kept in under the file backend, if there is one and the
+/// database doesn't have the store yet. The file is renamed afterwards rather than deleted, in case
+/// it's needed to go back to the file backend.
+fn import_json_file(conn: &Connection, name: &str, json_path: &Path) -> Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM stores WHERE name = ?1)",
+        params![name],
+        |row| row.get(0),
+    )?;
+    if exists || !json_path.is_file() {
+        return Ok(());
+    }
+
+    let bytes = fs::read(json_path)
+        .with_context(|| format!("Failed to read store file: {}", json_path.display()))?;
+    conn.execute(
+        "INSERT INTO stores (name, value) VALUES (?1, ?2)",
+        params![name, bytes],
+    )
+    .with_context(|| format!("Failed to import store file: {}", json_path.display()))?;
+
+    let mut migrated = json_path.as_os_str().to_owned();
+    migrated.push(".migrated");
+    fs::rename(json_path, &migrated)
+        .with_context(|| format!("Failed to rename store file: {}", json_path.display()))?;
+    info!(
+        "Imported store file {} into the database",
+        json_path.display()
+    );
+    Ok(())
+}
+
+#[async_trait]
+impl Store for SqliteStore {
+    async fn load(&self) -> Result<Vec<u8>> {
+        let (conn, name) = (self.conn.clone(), self.name.clone());
+        let value = spawn_blocking(move || {
+            conn.lock()
+                .unwrap()
+                .query_row(
+                    "SELECT value FROM stores WHERE name = ?1",
+                    params![name],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+        .await?
+        .with_context(|| format!("Failed to load store '{}'", self.name))?;
+        Ok(value.unwrap_or_default())
+    }
+
+    async fn store(&self, bytes: Vec<u8>) -> Result<()> {
+        let (conn, name) = (self.conn.clone(), self.name.clone());
+        spawn_blocking(move || {
+            conn.lock().unwrap().execute(
+                "INSERT OR REPLACE INTO stores (name, value) VALUES (?1, ?2)",
+                params![name, bytes],
+            )
+        })
+        .await?
+        .with_context(|| format!("Failed to store '{}'", self.name))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        event::Event,
+        store::{PersistentStoreBuilder, StoreBackend},
+    };
+    use tempdir::TempDir;
+    use test_env_log::test;
+
+    async fn sqlite_builder(tempdir: &TempDir) -> PersistentStoreBuilder {
+        PersistentStoreBuilder::with_backend(tempdir.path(), StoreBackend::Sqlite)
+            .await
+            .unwrap()
+    }
+
+    #[test(tokio::test)]
+    async fn test_sqlite_store_load() {
+        let tempdir = TempDir::new("SqliteStore_test").unwrap();
+        let builder = sqlite_builder(&tempdir).await;
+        let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+        let other = builder.build::<String, _>("other").await.unwrap();
+        assert!(builder.build::<String, _>("other").await.is_err());
+
+        assert!(store.load().await.unwrap().is_empty());
+        let mut event = Event::default();
+        event.description = "foobar".to_owned();
+        store.store(&vec![event.clone()]).await.unwrap();
+        other.store(&"unrelated".to_owned()).await.unwrap();
+        assert_eq!(store.load().await.unwrap(), vec![event.clone()]);
+
+        // Values survive reopening the database.
+        std::mem::drop((store, other));
+        let builder = sqlite_builder(&tempdir).await;
+        let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+        assert_eq!(store.load().await.unwrap(), vec![event]);
+        assert!(!tempdir.path().join("events.json").exists());
+    }
+
+    #[test(tokio::test)]
+    async fn test_sqlite_store_imports_json_file() {
+        let tempdir = TempDir::new("SqliteStore_test").unwrap();
+        let mut event = Event::default();
+        event.description = "from the file backend".to_owned();
+        {
+            let builder = PersistentStoreBuilder::with_backend(tempdir.path(), StoreBackend::File)
+                .await
+                .unwrap();
+            let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+            store.store(&vec![event.clone()]).await.unwrap();
+        }
+
+        let builder = sqlite_builder(&tempdir).await;
+        let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+        assert_eq!(store.load().await.unwrap(), vec![event.clone()]);
+        assert!(!tempdir.path().join("events.json").exists());
+        assert!(tempdir.path().join("events.json.migrated").exists());
+
+        // Once imported, the database is what's used, even if the file reappears.
+        store.store(&vec![]).await.unwrap();
+        std::mem::drop(store);
+        std::fs::copy(
+            tempdir.path().join("events.json.migrated"),
+            tempdir.path().join("events.json"),
+        )
+        .unwrap();
+        let store = builder.build::<Vec<Event>, _>("events.json").await.unwrap();
+        assert!(store.load().await.unwrap().is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{event::EventManager, guild::GuildManager};
 use anyhow::{format_err, Result};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, TimeZone, Utc, Weekday};
 use rand::{distributions::Alphanumeric, prelude::*};
+use serde::Deserialize;
 use serenity::{
     async_trait,
     builder::{CreateComponents, CreateEmbed},
@@ -296,6 +298,21 @@ impl MemberLike for Member {
     }
 }
 
+// Used when there's no guild member, e.g. for interactions from DMs.
+impl MemberLike for User {
+    fn user(&self) -> &User {
+        self
+    }
+
+    fn id(&self) -> UserId {
+        self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.name
+    }
+}
+
 impl MemberLike for (&User, &PartialMember) {
     fn user(&self) -> &User {
         &self.0
@@ -315,6 +332,7 @@ impl MemberLike for (&User, &PartialMember) {
 
 // From https://discord.com/developers/docs/topics/opcodes-and-status-codes#json
 pub enum DiscordJsonErrorCode {
+    UnknownChannel = 10003,
     UnknownMessage = 10008,
 }
 
@@ -333,6 +351,90 @@ impl SerenityErrorExt for SerenityError {
     }
 }
 
+/// How calendar dates are written out in messages, configurable per guild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DateFormat {
+    /// Month before day, e.g. "4/20/2021".
+    Us,
+    /// Year, month, then day, e.g. "2021-04-20".
+    Iso,
+    /// Day before month, e.g. "20/4/2021".
+    DayMonth,
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        DateFormat::Us
+    }
+}
+
+impl DateFormat {
+    /// The format for dates typed into commands, which leave out the year. ISO guilds still type
+    /// month before day, just with a slash.
+    pub fn input_format(self) -> &'static str {
+        match self {
+            DateFormat::Us | DateFormat::Iso => "%m/%d",
+            DateFormat::DayMonth => "%d/%m",
+        }
+    }
+
+    /// Formats the given date, leaving out the year if it isn't given.
+    pub fn format_date(self, year: Option<i32>, month: u32, day: u32) -> String {
+        match (self, year) {
+            (DateFormat::Us, Some(year)) => format!("{}/{}/{}", month, day, year),
+            (DateFormat::Us, None) => format!("{}/{}", month, day),
+            (DateFormat::Iso, Some(year)) => format!("{}-{:02}-{:02}", year, month, day),
+            (DateFormat::Iso, None) => format!("{:02}-{:02}", month, day),
+            (DateFormat::DayMonth, Some(year)) => format!("{}/{}/{}", day, month, year),
+            (DateFormat::DayMonth, None) => format!("{}/{}", day, month),
+        }
+    }
+}
+
+/// When the game's weekly reset happens, which dates can be given relative to (e.g. "reset+30m"),
+/// configurable per guild.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct WeeklyReset {
+    pub weekday: Weekday,
+    /// The time of day of the reset, in UTC.
+    pub time: NaiveTime,
+}
+
+impl Default for WeeklyReset {
+    /// Destiny's weekly reset, Tuesdays at 17:00 UTC.
+    fn default() -> Self {
+        WeeklyReset {
+            weekday: Weekday::Tue,
+            time: NaiveTime::from_hms(17, 0, 0),
+        }
+    }
+}
+
+impl WeeklyReset {
+    /// The first reset strictly after `now`.
+    pub fn next_after(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let today = now.naive_utc().date();
+        let days_ahead =
+            (self.weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+        let reset =
+            Utc.from_utc_datetime(&(today + Duration::days(days_ahead.into())).and_time(self.time));
+        if reset > now {
+            reset
+        } else {
+            reset + Duration::weeks(1)
+        }
+    }
+
+    /// The first time after `now` that is `offset` from a reset. For a positive offset that's just
+    /// after a reset, this is still relative to the reset that just happened, so "reset+30m" ten
+    /// minutes after reset is twenty minutes from now.
+    pub fn resolve_offset(&self, offset: Duration, now: DateTime<Utc>) -> DateTime<Utc> {
+        self.next_after(now - offset) + offset
+    }
+}
+
 /// Intended to be used with the #[serde(with = "module")] annotation on DateTime<Tz> fields
 pub mod serialize_datetime_tz {
     use super::*;
@@ -367,3 +469,73 @@ pub mod serialize_datetime_tz {
         Ok(value.0.with_timezone(&tz))
     }
 }
+
+/// The (custom_id, disabled) state of each button in the given components, e.g. to compare against
+/// the buttons on a posted message.
+pub fn button_states(components: &CreateComponents) -> Vec<(String, bool)> {
+    components
+        .0
+        .iter()
+        .filter_map(|row| row.get("components")?.as_array())
+        .flatten()
+        .filter_map(|button| {
+            let id = button.get("custom_id")?.as_str()?.to_owned();
+            let disabled = button.get("disabled").and_then(|d| d.as_bool());
+            Some((id, disabled.unwrap_or(false)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn date_formats() {
+        let cases = [
+            (DateFormat::Us, "4/20/2021", "4/20"),
+            (DateFormat::Iso, "2021-04-20", "04-20"),
+            (DateFormat::DayMonth, "20/4/2021", "20/4"),
+        ];
+        for (format, with_year, without_year) in cases {
+            assert_eq!(format.format_date(Some(2021), 4, 20), with_year);
+            assert_eq!(format.format_date(None, 4, 20), without_year);
+        }
+        assert_eq!(DateFormat::default(), DateFormat::Us);
+    }
+
+    #[test]
+    fn next_weekly_reset() {
+        let utc = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let cases = [
+            ("2021-04-19T12:00:00Z", "2021-04-20T17:00:00Z"),
+            ("2021-04-20T16:59:00Z", "2021-04-20T17:00:00Z"),
+            ("2021-04-20T17:00:00Z", "2021-04-27T17:00:00Z"),
+            ("2021-04-21T09:00:00Z", "2021-04-27T17:00:00Z"),
+            // 4/20 in Pacific time, but already past reset in UTC.
+            ("2021-04-20T12:00:00-07:00", "2021-04-27T17:00:00Z"),
+            ("2021-12-29T00:00:00Z", "2022-01-04T17:00:00Z"),
+        ];
+        for (now, expected) in cases {
+            assert_eq!(
+                WeeklyReset::default().next_after(utc(now)),
+                utc(expected),
+                "{}",
+                now
+            );
+        }
+
+        let friday_morning = WeeklyReset {
+            weekday: Weekday::Fri,
+            time: NaiveTime::from_hms(9, 0, 0),
+        };
+        assert_eq!(
+            friday_morning.next_after(utc("2021-04-20T00:00:00Z")),
+            utc("2021-04-23T09:00:00Z")
+        );
+        assert_eq!(
+            friday_morning.next_after(utc("2021-04-23T10:00:00Z")),
+            utc("2021-04-30T09:00:00Z")
+        );
+    }
+}